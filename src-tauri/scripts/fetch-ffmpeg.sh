@@ -0,0 +1,39 @@
+#!/usr/bin/env bash
+# Download the static ffmpeg build bundled with the app (tauri.conf.json externalBin)
+# for a Rust target triple, defaulting to the host's. Tauri looks for it at
+# src-tauri/binaries/ffmpeg-<target>[.exe] and installs it next to the executable.
+set -euo pipefail
+
+FFMPEG_STATIC_RELEASE="b6.0"
+TARGET="${1:-$(rustc -vV | sed -n 's/^host: //p')}"
+
+case "$TARGET" in
+  aarch64-apple-darwin) ASSET="ffmpeg-darwin-arm64" ;;
+  x86_64-apple-darwin) ASSET="ffmpeg-darwin-x64" ;;
+  x86_64-unknown-linux-gnu) ASSET="ffmpeg-linux-x64" ;;
+  aarch64-unknown-linux-gnu) ASSET="ffmpeg-linux-arm64" ;;
+  x86_64-pc-windows-msvc) ASSET="ffmpeg-win32-x64" ;;
+  *)
+    echo "No bundled ffmpeg build for $TARGET" >&2
+    exit 1
+    ;;
+esac
+
+EXT=""
+if [[ "$TARGET" == *windows* ]]; then
+  EXT=".exe"
+fi
+
+DIR="$(cd "$(dirname "$0")/.." && pwd)/binaries"
+DEST="$DIR/ffmpeg-$TARGET$EXT"
+if [[ -f "$DEST" ]]; then
+  echo "$DEST already present"
+  exit 0
+fi
+
+mkdir -p "$DIR"
+curl -fsSL "https://github.com/eugeneware/ffmpeg-static/releases/download/$FFMPEG_STATIC_RELEASE/$ASSET.gz" \
+  | gunzip > "$DEST.partial"
+chmod +x "$DEST.partial"
+mv "$DEST.partial" "$DEST"
+echo "Downloaded $DEST"
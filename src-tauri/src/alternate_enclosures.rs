@@ -0,0 +1,115 @@
+// Alternate enclosures: extra encodings of a track (e.g. FLAC or Opus next to the MP3
+// enclosure) uploaded to the feed's storage and listed as <podcast:alternateEnclosure>
+// with their type, length, bitrate, and an SRI hash of the uploaded file.
+
+use crate::audio::read_audio_metadata;
+use crate::feed_model::{alternate_enclosure_node, AlternateEnclosureModel};
+use crate::feed_xml::{parse_rss, render_document};
+use crate::storage::{self, StorageTarget};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::{AppHandle, State};
+
+#[derive(Serialize, Deserialize)]
+pub struct AlternateEnclosureResult {
+    pub feed_id: String,
+    pub item_guid: String,
+    pub alternates: Vec<AlternateEnclosureModel>,
+}
+
+/// Short label for an encoding, e.g. "FLAC" or "Opus 160 kbps"
+fn encoding_title(path: &Path, bitrate_kbps: Option<u32>, lossless: bool) -> String {
+    let format = match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+        Some("opus") => "Opus".to_string(),
+        Some("ogg") => "Ogg Vorbis".to_string(),
+        Some("m4a") | Some("aac") => "AAC".to_string(),
+        Some(ext) => ext.to_uppercase(),
+        None => "Audio".to_string(),
+    };
+    match bitrate_kbps {
+        Some(kbps) if !lossless => format!("{} {} kbps", format, kbps),
+        _ => format,
+    }
+}
+
+/// Upload extra encodings of a track and list them as alternate enclosures, replacing
+/// any existing alternate of the same type. Uploads go to the given Blossom server,
+/// else the feed's storage target, else the user's preferred Blossom server.
+#[tauri::command]
+pub async fn alternate_enclosures_attach(
+    feed_id: String,
+    item_guid: String,
+    file_paths: Vec<String>,
+    server_url: Option<String>,
+    app: AppHandle,
+    state: State<'_, crate::NostrState>,
+) -> Result<AlternateEnclosureResult, String> {
+    if file_paths.is_empty() {
+        return Err("No files to attach".to_string());
+    }
+    let target = match server_url {
+        Some(server_url) => StorageTarget::Blossom { server_url },
+        None => storage::resolve_target(Some(&feed_id), &state)?,
+    };
+    let keys = state.signing_keys().ok();
+
+    let feed = crate::load_feed_local(feed_id)?;
+    let mut doc = parse_rss(&feed.xml)?;
+    if !doc.items().iter().any(|item| item.child_text("guid") == Some(item_guid.as_str())) {
+        return Err(format!("Track not found: {}", item_guid));
+    }
+
+    // Read every file before uploading so a bad one fails fast
+    let encodings = tokio::task::spawn_blocking(move || {
+        let mut encodings = Vec::new();
+        for file_path in &file_paths {
+            let metadata = read_audio_metadata(Path::new(file_path))?;
+            if !metadata.mime_type.starts_with("audio/") {
+                return Err(format!("{} is not a supported audio file", file_path));
+            }
+            encodings.push(metadata);
+        }
+        Ok(encodings)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let mut alternates = Vec::new();
+    for metadata in encodings {
+        let stored = storage::upload_file(&target, &metadata.file_path, keys.clone(), Some(app.clone()))
+            .await
+            .map_err(|e| format!("Upload of {} failed: {}", metadata.file_path, e))?;
+        let lossless = matches!(metadata.mime_type.as_str(), "audio/flac" | "audio/wav" | "audio/aiff");
+        alternates.push(AlternateEnclosureModel {
+            url: stored.url,
+            title: Some(encoding_title(Path::new(&metadata.file_path), metadata.bitrate_kbps, lossless)),
+            mime_type: metadata.mime_type,
+            length: stored.size,
+            bitrate: metadata.bitrate_kbps.map(|kbps| kbps * 1000),
+            default: false,
+            sha256: Some(stored.sha256),
+        });
+    }
+
+    let item = doc
+        .root
+        .child_mut("channel")
+        .and_then(|c| {
+            c.children
+                .iter_mut()
+                .find(|c| c.name == "item" && c.child_text("guid") == Some(item_guid.as_str()))
+        })
+        .ok_or_else(|| format!("Track not found: {}", item_guid))?;
+    item.children.retain(|c| {
+        c.name != "podcast:alternateEnclosure"
+            || !alternates.iter().any(|a| c.attr("type") == Some(a.mime_type.as_str()))
+    });
+    item.children.extend(alternates.iter().map(alternate_enclosure_node));
+
+    let saved = crate::save_feed_local(Some(feed.id), feed.title, feed.feed_type, render_document(&doc.root), None)?;
+    Ok(AlternateEnclosureResult {
+        feed_id: saved.id,
+        item_guid,
+        alternates,
+    })
+}
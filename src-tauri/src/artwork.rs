@@ -0,0 +1,245 @@
+// Cover art helpers: pull embedded cover pictures out of audio files into the app's
+// artwork folder (one file per distinct picture, so tracks sharing a cover share the
+// file), report size and format, warning when art is below what directories expect,
+// and produce square, upright, metadata-free upload variants named by content hash.
+
+use crate::audio::read_embedded_picture;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{CompressionType, FilterType as PngFilterType, PngEncoder};
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageDecoder, ImageFormat};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+// Smallest cover Apple Podcasts and most directories accept
+const MIN_ARTWORK_PX: u32 = 1400;
+// Largest cover directories accept, and the default output size
+const MAX_ARTWORK_PX: u32 = 3000;
+// Recommended upper bound on artwork file size; larger art slows feed readers
+const TARGET_ARTWORK_BYTES: usize = 512 * 1024;
+// JPEG qualities tried in order until the output fits TARGET_ARTWORK_BYTES
+const JPEG_QUALITIES: [u8; 6] = [90, 85, 80, 75, 70, 60];
+// Each step down in output size when an encoding still exceeds TARGET_ARTWORK_BYTES
+const DOWNSCALE_PERCENT: u32 = 85;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ArtworkInfo {
+    pub path: String,
+    pub mime_type: String,
+    pub width: u32,
+    pub height: u32,
+    pub size: u64,
+    pub warnings: Vec<String>,
+}
+
+/// Warnings for artwork that directories will reject or players will show poorly
+fn artwork_warnings(width: u32, height: u32) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if width < MIN_ARTWORK_PX || height < MIN_ARTWORK_PX {
+        warnings.push(format!(
+            "Artwork is {}x{}; directories expect at least {}x{}, and upscaling it will look soft",
+            width, height, MIN_ARTWORK_PX, MIN_ARTWORK_PX
+        ));
+    }
+    if width != height {
+        warnings.push(format!("Artwork is {}x{}, not square; apps will crop it", width, height));
+    }
+    warnings
+}
+
+/// Pixel size of image bytes, read from the header
+pub fn dimensions(data: &[u8]) -> Result<(u32, u32), String> {
+    image::ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|e| e.to_string())?
+        .into_dimensions()
+        .map_err(|e| e.to_string())
+}
+
+/// Describe image bytes already on disk at `path`
+fn describe(path: &Path, data: &[u8]) -> Result<ArtworkInfo, String> {
+    let reader = image::ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|e| e.to_string())?;
+    let mime_type = reader
+        .format()
+        .map(|f| f.to_mime_type().to_string())
+        .ok_or_else(|| format!("{} is not a supported image", path.display()))?;
+    let (width, height) = reader
+        .into_dimensions()
+        .map_err(|e| format!("Failed to read image {}: {}", path.display(), e))?;
+    Ok(ArtworkInfo {
+        path: path.to_string_lossy().to_string(),
+        mime_type,
+        width,
+        height,
+        size: data.len() as u64,
+        warnings: artwork_warnings(width, height),
+    })
+}
+
+/// Size, format, and warnings for an image file
+pub fn artwork_info(path: &Path) -> Result<ArtworkInfo, String> {
+    let data = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    describe(path, &data)
+}
+
+/// Write an audio file's embedded cover into the artwork folder. None when the file
+/// has no embedded picture.
+pub fn extract_embedded(audio_path: &Path) -> Result<Option<ArtworkInfo>, String> {
+    let Some((_, data)) = read_embedded_picture(audio_path)? else {
+        return Ok(None);
+    };
+    let extension = match image::guess_format(&data) {
+        Ok(image::ImageFormat::Png) => "png",
+        Ok(image::ImageFormat::WebP) => "webp",
+        Ok(image::ImageFormat::Jpeg) => "jpg",
+        _ => return Err(format!("{} has embedded art in an unsupported format", audio_path.display())),
+    };
+
+    let dir = crate::import::get_artwork_dir()?.join("embedded");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}.{}", hex::encode(Sha256::digest(&data)), extension));
+    if !path.exists() {
+        crate::write_atomic(&path, &data)?;
+    }
+    describe(&path, &data).map(Some)
+}
+
+/// Extract the embedded cover art of an audio file to an image file, reporting its
+/// dimensions and format so it can be offered as track or album art
+#[tauri::command]
+pub fn artwork_extract_embedded(path: String) -> Result<Option<ArtworkInfo>, String> {
+    extract_embedded(Path::new(&path))
+}
+
+/// Report an image's dimensions and format, with warnings for undersized art
+#[tauri::command]
+pub fn artwork_inspect(path: String) -> Result<ArtworkInfo, String> {
+    artwork_info(Path::new(&path))
+}
+
+/// Encode an image, stepping JPEG quality down until it fits the size guidance.
+/// Re-encoding from pixels drops EXIF and any other embedded metadata.
+fn encode(image: &DynamicImage, format: ImageFormat) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    match format {
+        ImageFormat::Jpeg => {
+            let rgb = DynamicImage::ImageRgb8(image.to_rgb8());
+            for quality in JPEG_QUALITIES {
+                out.clear();
+                rgb.write_with_encoder(JpegEncoder::new_with_quality(&mut out, quality))
+                    .map_err(|e| e.to_string())?;
+                if out.len() <= TARGET_ARTWORK_BYTES {
+                    break;
+                }
+            }
+        }
+        ImageFormat::Png => {
+            let rgba = DynamicImage::ImageRgba8(image.to_rgba8());
+            rgba.write_with_encoder(PngEncoder::new_with_quality(
+                &mut out,
+                CompressionType::Best,
+                PngFilterType::Adaptive,
+            ))
+            .map_err(|e| e.to_string())?;
+        }
+        _ => {
+            let rgba = DynamicImage::ImageRgba8(image.to_rgba8());
+            rgba.write_to(&mut Cursor::new(&mut out), format)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(out)
+}
+
+/// Decode an image upright, applying its EXIF orientation
+fn decode_upright(source: &Path) -> Result<DynamicImage, String> {
+    let mut decoder = image::ImageReader::open(source)
+        .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?
+        .with_guessed_format()
+        .map_err(|e| e.to_string())?
+        .into_decoder()
+        .map_err(|e| format!("Failed to decode {}: {}", source.display(), e))?;
+    let orientation = decoder.orientation().map_err(|e| e.to_string())?;
+    let mut image =
+        DynamicImage::from_decoder(decoder).map_err(|e| format!("Failed to decode {}: {}", source.display(), e))?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+fn process(path: String, size: Option<u32>, format: Option<String>) -> Result<ArtworkInfo, String> {
+    let size = size.unwrap_or(MAX_ARTWORK_PX).clamp(1, MAX_ARTWORK_PX);
+    let (format, extension) = match format.as_deref().unwrap_or("jpeg").to_lowercase().as_str() {
+        "jpeg" | "jpg" => (ImageFormat::Jpeg, "jpg"),
+        "png" => (ImageFormat::Png, "png"),
+        "webp" => (ImageFormat::WebP, "webp"),
+        other => return Err(format!("Unsupported artwork format \"{}\" - use jpeg, png, or webp", other)),
+    };
+
+    let source = Path::new(&path);
+    let image = decode_upright(source)?;
+    let (width, height) = (image.width(), image.height());
+    let side = width.min(height);
+    let square = image.crop_imm((width - side) / 2, (height - side) / 2, side, side);
+
+    // Shrink until the encoding fits, but never below what directories accept
+    // (or the requested size, when that is already smaller)
+    let floor = size.min(MIN_ARTWORK_PX);
+    let mut output_size = size;
+    let data = loop {
+        let data = encode(&square.resize_exact(output_size, output_size, FilterType::Lanczos3), format)?;
+        if data.len() <= TARGET_ARTWORK_BYTES {
+            break data;
+        }
+        if output_size == floor {
+            return Err(format!(
+                "Artwork is {} KB as {} at {}x{}; it must be under {} KB - use JPEG or a simpler image",
+                data.len() / 1024,
+                extension.to_uppercase(),
+                output_size,
+                output_size,
+                TARGET_ARTWORK_BYTES / 1024
+            ));
+        }
+        output_size = (output_size * DOWNSCALE_PERCENT / 100).max(floor);
+    };
+
+    let dir = crate::import::get_artwork_dir()?.join("processed");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let output = dir.join(format!("{}.{}", hex::encode(Sha256::digest(&data)), extension));
+    if !output.exists() {
+        crate::write_atomic(&output, &data)?;
+    }
+
+    let mut info = describe(&output, &data)?;
+    if side < output_size {
+        info.warnings.push(format!(
+            "Source artwork is {}x{}; upscaling to {}x{} will look soft",
+            width, height, output_size, output_size
+        ));
+    }
+    if output_size < size {
+        info.warnings.push(format!(
+            "Artwork was reduced to {}x{} to stay under {} KB",
+            output_size,
+            output_size,
+            TARGET_ARTWORK_BYTES / 1024
+        ));
+    }
+    Ok(info)
+}
+
+/// Straighten artwork per its EXIF orientation, center-crop it to a square, resize it,
+/// and re-encode it as JPEG, PNG, or WebP without metadata. `size` defaults to 3000 and
+/// `format` to "jpeg"; the size steps down (not below 1400) until the file is under
+/// ~512KB. The result lands in the artwork folder, named by its content hash.
+#[tauri::command]
+pub async fn process_artwork(path: String, size: Option<u32>, format: Option<String>) -> Result<ArtworkInfo, String> {
+    tokio::task::spawn_blocking(move || process(path, size, format))
+        .await
+        .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,336 @@
+// Feed asset audit: check that every hosted file a feed points at (enclosures,
+// alternate enclosures, artwork, chapters, transcripts) still resolves, and that it
+// is still the file the feed describes. Length is compared against the feed's
+// declared length; content hashes are compared where the feed records one
+// (podcast:integrity) and the URL is content-addressed (Blossom). Missing files can
+// be repaired by uploading the local copy the upload ledger remembers and rewriting
+// the feed to the new URLs.
+
+use crate::feed_model::parse_alternate_enclosure;
+use crate::feed_xml::{parse_rss, parse_xml, render_document, XmlNode};
+use crate::storage::{self, StorageTarget};
+use futures_util::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use tauri::{AppHandle, State};
+
+// Requests in flight at once; hosts rate-limit bursts of HEAD requests
+const MAX_AUDIT_CONCURRENCY: usize = 6;
+const AUDIT_TIMEOUT_SECS: u64 = 15;
+
+/// A hosted file referenced by a feed
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FeedAsset {
+    pub kind: String, // "enclosure", "alternateEnclosure", "image", "chapters", or "transcript"
+    pub url: String,
+    pub item_guid: Option<String>,
+    pub expected_length: Option<u64>,
+    pub expected_sha256: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AssetCheck {
+    #[serde(flatten)]
+    pub asset: FeedAsset,
+    pub status: String, // "ok", "missing", "changed", or "error"
+    pub http_status: Option<u16>,
+    pub actual_length: Option<u64>,
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AssetAuditReport {
+    pub feed_id: String,
+    pub checked: usize,
+    pub dead: usize,
+    pub changed: usize,
+    pub assets: Vec<AssetCheck>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AssetRepair {
+    pub url: String,
+    pub new_url: Option<String>,
+    pub local_path: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AssetRepairReport {
+    pub feed_id: String,
+    pub repaired: usize,
+    pub failed: usize,
+    pub assets: Vec<AssetRepair>,
+}
+
+/// The sha256 in a content-addressed (Blossom) URL: a 64-hex last path segment,
+/// optionally with a file extension
+pub fn url_sha256(url: &str) -> Option<String> {
+    let path = url.split(['?', '#']).next()?;
+    let segment = path.rsplit('/').next()?;
+    let stem = segment.split('.').next()?;
+    (stem.len() == 64 && stem.chars().all(|c| c.is_ascii_hexdigit())).then(|| stem.to_lowercase())
+}
+
+fn push_asset(assets: &mut Vec<FeedAsset>, kind: &str, url: Option<&str>, item_guid: Option<&str>, expected_length: Option<u64>) {
+    let Some(url) = url.map(str::trim).filter(|u| u.starts_with("http://") || u.starts_with("https://")) else {
+        return;
+    };
+    if assets.iter().any(|a| a.url == url) {
+        return;
+    }
+    assets.push(FeedAsset {
+        kind: kind.to_string(),
+        url: url.to_string(),
+        item_guid: item_guid.map(str::to_string),
+        expected_length: expected_length.filter(|l| *l > 0),
+        expected_sha256: None,
+    });
+}
+
+/// Artwork URLs on a channel or item
+fn push_images(assets: &mut Vec<FeedAsset>, node: &XmlNode, item_guid: Option<&str>) {
+    push_asset(assets, "image", node.child("itunes:image").and_then(|i| i.attr("href")), item_guid, None);
+    push_asset(assets, "image", node.child("podcast:image").and_then(|i| i.attr("href")), item_guid, None);
+    push_asset(assets, "image", node.child("image").and_then(|i| i.child_text("url")), item_guid, None);
+}
+
+/// List the distinct hosted files a feed references. Local file:// references are
+/// left out; they are uploaded at publish time.
+pub fn feed_assets(xml: &str) -> Result<Vec<FeedAsset>, String> {
+    let doc = parse_rss(xml)?;
+    let channel = doc.channel().ok_or("Missing <channel> element")?;
+    let mut assets = Vec::new();
+    push_images(&mut assets, channel, None);
+
+    for item in doc.items() {
+        let guid = item.child_text("guid");
+        let enclosure = item.child("enclosure");
+        push_asset(
+            &mut assets,
+            "enclosure",
+            enclosure.and_then(|e| e.attr("url")),
+            guid,
+            enclosure.and_then(|e| e.attr("length")?.trim().parse().ok()),
+        );
+        for alternate in item.children_named("podcast:alternateEnclosure").map(parse_alternate_enclosure) {
+            push_asset(&mut assets, "alternateEnclosure", Some(alternate.url.as_str()), guid, Some(alternate.length));
+            if let Some(asset) = assets.iter_mut().find(|a| a.url == alternate.url.trim()) {
+                asset.expected_sha256 = asset.expected_sha256.take().or(alternate.sha256);
+            }
+        }
+        push_images(&mut assets, item, guid);
+        push_asset(&mut assets, "chapters", item.child("podcast:chapters").and_then(|c| c.attr("url")), guid, None);
+        for transcript in item.children_named("podcast:transcript") {
+            push_asset(&mut assets, "transcript", transcript.attr("url"), guid, None);
+        }
+    }
+    Ok(assets)
+}
+
+/// Total size from a "bytes 0-0/12345" Content-Range header
+fn content_range_total(response: &reqwest::Response) -> Option<u64> {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .rsplit('/')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// HEAD an asset (falling back to a one-byte ranged GET for hosts that reject HEAD)
+/// and compare what comes back with what the feed declares
+async fn check_asset(client: &reqwest::Client, asset: FeedAsset) -> AssetCheck {
+    let mut check = AssetCheck {
+        asset,
+        status: "ok".to_string(),
+        http_status: None,
+        actual_length: None,
+        detail: None,
+    };
+
+    let mut response = client.head(&check.asset.url).send().await;
+    if matches!(&response, Ok(r) if r.status() == reqwest::StatusCode::METHOD_NOT_ALLOWED) {
+        response = client.get(&check.asset.url).header("Range", "bytes=0-0").send().await;
+    }
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => {
+            check.status = "error".to_string();
+            check.detail = Some(e.to_string());
+            return check;
+        }
+    };
+
+    let status = response.status();
+    check.http_status = Some(status.as_u16());
+    if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::GONE {
+        check.status = "missing".to_string();
+        return check;
+    }
+    if !status.is_success() {
+        check.status = "error".to_string();
+        check.detail = Some(format!("Server returned {}", status));
+        return check;
+    }
+
+    check.actual_length = if status == reqwest::StatusCode::PARTIAL_CONTENT {
+        content_range_total(&response)
+    } else {
+        response.content_length().filter(|l| *l > 0)
+    };
+
+    let url_hash = url_sha256(&check.asset.url);
+    if let (Some(expected), Some(actual)) = (&check.asset.expected_sha256, &url_hash) {
+        if expected != actual {
+            check.status = "changed".to_string();
+            check.detail = Some(format!("Feed integrity is sha256 {}, but the URL serves {}", expected, actual));
+            return check;
+        }
+    }
+    if let (Some(expected), Some(actual)) = (check.asset.expected_length, check.actual_length) {
+        if expected != actual {
+            check.status = "changed".to_string();
+            check.detail = Some(format!("Feed declares {} bytes, server reports {}", expected, actual));
+        }
+    }
+    check
+}
+
+/// Check every hosted asset of a library feed
+pub async fn audit(feed_id: &str) -> Result<AssetAuditReport, String> {
+    let feed = crate::load_feed_local(feed_id.to_string())?;
+    let assets = feed_assets(&feed.xml)?;
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(AUDIT_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut checks: Vec<AssetCheck> = stream::iter(assets)
+        .map(|asset| check_asset(&client, asset))
+        .buffer_unordered(MAX_AUDIT_CONCURRENCY)
+        .collect()
+        .await;
+
+    // Problems first, in a stable order
+    checks.sort_by_key(|c| (c.status == "ok", c.asset.kind.clone(), c.asset.url.clone()));
+    Ok(AssetAuditReport {
+        feed_id: feed.id,
+        checked: checks.len(),
+        dead: checks.iter().filter(|c| c.status == "missing" || c.status == "error").count(),
+        changed: checks.iter().filter(|c| c.status == "changed").count(),
+        assets: checks,
+    })
+}
+
+/// Check that every enclosure, artwork, chapters, and transcript URL in a feed still
+/// resolves and matches the length (and hash, where known) the feed declares
+#[tauri::command]
+pub async fn audit_feed_assets(feed_id: String) -> Result<AssetAuditReport, String> {
+    audit(&feed_id).await
+}
+
+/// Find a local file with the same content as a hosted asset, using the upload
+/// ledger's record of where it was uploaded from
+fn local_copy(asset: &FeedAsset) -> Result<String, String> {
+    let sha256 = asset
+        .expected_sha256
+        .clone()
+        .or_else(|| url_sha256(&asset.url))
+        .or_else(|| {
+            crate::upload_ledger::lookup_url(&asset.url)
+                .ok()?
+                .into_iter()
+                .next()
+                .map(|b| b.sha256)
+        })
+        .ok_or("No record of which file was uploaded to this URL")?;
+
+    crate::upload_ledger::lookup(&sha256)?
+        .into_iter()
+        .filter_map(|blob| blob.local_path)
+        .find(|path| crate::hash_file_cached(Path::new(path)).is_ok_and(|(hash, _)| hash == sha256))
+        .ok_or_else(|| "No local copy of this file was found".to_string())
+}
+
+/// Replace every attribute or text value equal to `old_url`
+fn rewrite_url(node: &mut XmlNode, old_url: &str, new_url: &str) {
+    for (_, value) in node.attrs.iter_mut() {
+        if value.trim() == old_url {
+            *value = new_url.to_string();
+        }
+    }
+    if node.text.trim() == old_url {
+        node.text = new_url.to_string();
+    }
+    for child in node.children.iter_mut() {
+        rewrite_url(child, old_url, new_url);
+    }
+}
+
+/// Audit a feed, upload the local copy of every missing file to a Blossom server,
+/// and rewrite the feed to the new URLs in a single save
+#[tauri::command]
+pub async fn repair_feed_assets(
+    feed_id: String,
+    server_url: String,
+    app: AppHandle,
+    state: State<'_, crate::NostrState>,
+) -> Result<AssetRepairReport, String> {
+    let keys = state.signing_keys()?;
+    let target = StorageTarget::Blossom { server_url };
+    let report = audit(&feed_id).await?;
+
+    let mut repairs = Vec::new();
+    for check in report.assets.into_iter().filter(|c| c.status == "missing") {
+        let url = check.asset.url.clone();
+        let mut repair = AssetRepair {
+            url: url.clone(),
+            new_url: None,
+            local_path: None,
+            error: None,
+        };
+        let asset = check.asset.clone();
+        let found = tokio::task::spawn_blocking(move || local_copy(&asset))
+            .await
+            .map_err(|e| e.to_string())?;
+        match found {
+            Ok(path) => {
+                // The dead URL must not satisfy the ledger check in upload_file
+                let _ = crate::upload_ledger::forget_url(&url);
+                match storage::upload_file(&target, &path, Some(keys.clone()), Some(app.clone())).await {
+                    Ok(stored) => repair.new_url = Some(stored.url),
+                    Err(e) => repair.error = Some(e.to_string()),
+                }
+                repair.local_path = Some(path);
+            }
+            Err(e) => repair.error = Some(e),
+        }
+        repairs.push(repair);
+    }
+
+    let feed = crate::load_feed_local(report.feed_id)?;
+    let mut root = parse_xml(&feed.xml)?;
+    for repair in &repairs {
+        if let Some(new_url) = &repair.new_url {
+            rewrite_url(&mut root, &repair.url, new_url);
+        }
+    }
+    let repaired = repairs.iter().filter(|r| r.new_url.is_some()).count();
+    let feed_id = if repaired > 0 {
+        crate::save_feed_local(Some(feed.id), feed.title, feed.feed_type, render_document(&root), None)?.id
+    } else {
+        feed.id
+    };
+
+    Ok(AssetRepairReport {
+        feed_id,
+        repaired,
+        failed: repairs.len() - repaired,
+        assets: repairs,
+    })
+}
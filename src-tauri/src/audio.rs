@@ -0,0 +1,269 @@
+// Audio file inspection (tags, duration, format)
+
+use crate::formatting::format_itunes_duration;
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use lofty::picture::PictureType;
+use lofty::prelude::*;
+use lofty::probe::Probe;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+// Extensions treated as audio when scanning imports
+pub const AUDIO_EXTENSIONS: &[&str] = &["mp3", "flac", "wav", "ogg", "opus", "m4a", "aac", "aiff", "aif"];
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct AudioMetadata {
+    pub file_path: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub track_number: Option<u32>,
+    #[serde(default)]
+    pub disc_number: Option<u32>,
+    pub duration_secs: f64,
+    pub sample_rate: Option<u32>,
+    pub bitrate_kbps: Option<u32>,
+    pub file_size: u64,
+    pub mime_type: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AudioArtwork {
+    pub mime_type: String,
+    pub data_base64: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AudioFileInfo {
+    #[serde(flatten)]
+    pub metadata: AudioMetadata,
+    pub artwork: Option<AudioArtwork>,
+}
+
+/// Whether a path looks like an audio file
+pub fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| AUDIO_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Read tags and stream properties from an audio file
+pub fn read_audio_metadata(path: &Path) -> Result<AudioMetadata, String> {
+    let file_path = path.to_string_lossy().to_string();
+    let file_size = std::fs::metadata(path).map_err(|e| e.to_string())?.len();
+
+    let tagged = Probe::open(path)
+        .map_err(|e| format!("Failed to open audio file: {}", e))?
+        .read()
+        .map_err(|e| format!("Failed to read audio file: {}", e))?;
+
+    let properties = tagged.properties();
+    let tag = tagged.primary_tag().or_else(|| tagged.first_tag());
+
+    Ok(AudioMetadata {
+        mime_type: crate::guess_mime_type(&file_path).to_string(),
+        file_path,
+        title: tag.and_then(|t| t.title()).map(|s| s.to_string()),
+        artist: tag.and_then(|t| t.artist()).map(|s| s.to_string()),
+        album: tag.and_then(|t| t.album()).map(|s| s.to_string()),
+        track_number: tag.and_then(|t| t.track()),
+        disc_number: tag.and_then(|t| t.disk()),
+        duration_secs: mp3_frame_duration(path).unwrap_or_else(|| properties.duration().as_secs_f64()),
+        sample_rate: properties.sample_rate(),
+        bitrate_kbps: properties.audio_bitrate(),
+        file_size,
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EnclosureInfo {
+    pub file_path: String,
+    pub length: u64,
+    pub duration_secs: f64,
+    pub duration: String,
+    pub mime_type: String,
+}
+
+// MPEG audio bitrates in kbps, indexed by [table][bitrate index]
+const MP3_BITRATES: [[u32; 15]; 5] = [
+    [0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448], // MPEG1 Layer I
+    [0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384],    // MPEG1 Layer II
+    [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320],     // MPEG1 Layer III
+    [0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256],    // MPEG2/2.5 Layer I
+    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],         // MPEG2/2.5 Layer II/III
+];
+
+/// Parse an MPEG audio frame header, returning (frame length, samples, sample rate)
+fn parse_mp3_frame_header(header: [u8; 4]) -> Option<(usize, u32, u32)> {
+    if header[0] != 0xFF || header[1] & 0xE0 != 0xE0 {
+        return None;
+    }
+
+    let version = (header[1] >> 3) & 0x03; // 0 = 2.5, 2 = 2, 3 = 1
+    let layer = (header[1] >> 1) & 0x03; // 1 = III, 2 = II, 3 = I
+    let bitrate_index = (header[2] >> 4) as usize;
+    let rate_index = ((header[2] >> 2) & 0x03) as usize;
+    let padding = ((header[2] >> 1) & 0x01) as u32;
+    if version == 1 || layer == 0 || bitrate_index == 0 || bitrate_index == 15 || rate_index == 3 {
+        return None;
+    }
+
+    let mpeg1 = version == 3;
+    let table = match (mpeg1, layer) {
+        (true, 3) => 0,
+        (true, 2) => 1,
+        (true, _) => 2,
+        (false, 3) => 3,
+        (false, _) => 4,
+    };
+    let bitrate = MP3_BITRATES[table][bitrate_index] * 1000;
+    let sample_rate = [44100, 48000, 32000][rate_index] / match version {
+        3 => 1,
+        2 => 2,
+        _ => 4,
+    };
+
+    let (samples, length) = match layer {
+        3 => (384, (12 * bitrate / sample_rate + padding) * 4),
+        2 => (1152, 144 * bitrate / sample_rate + padding),
+        _ if mpeg1 => (1152, 144 * bitrate / sample_rate + padding),
+        _ => (576, 72 * bitrate / sample_rate + padding),
+    };
+    Some((length as usize, samples, sample_rate))
+}
+
+/// Compute an MP3's exact duration by walking every frame header, so VBR files without
+/// (or with wrong) Xing/VBRI headers are measured correctly. Only the first frame is
+/// read in full (to spot the Xing/Info frame); the audio data of the rest is skipped.
+/// None for non-MP3 files.
+fn mp3_frame_duration(path: &Path) -> Option<f64> {
+    let is_mp3 = path
+        .extension()
+        .map(|e| e.to_string_lossy().eq_ignore_ascii_case("mp3"))
+        .unwrap_or(false);
+    if !is_mp3 {
+        return None;
+    }
+
+    let file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let mut reader = BufReader::new(file);
+    let mut pos = 0u64;
+
+    // Skip an ID3v2 tag (10-byte header + syncsafe size)
+    let mut id3 = [0u8; 10];
+    if reader.read_exact(&mut id3).is_ok() && &id3[..3] == b"ID3" {
+        let size = id3[6..10].iter().fold(0u64, |acc, b| (acc << 7) | (*b as u64 & 0x7F));
+        pos = 10 + size + if id3[5] & 0x10 != 0 { 10 } else { 0 };
+    }
+    reader.seek(SeekFrom::Start(pos)).ok()?;
+
+    let mut seconds = 0.0;
+    let mut frames = 0;
+    let mut header = [0u8; 4];
+    while pos + 4 <= len && reader.read_exact(&mut header).is_ok() {
+        let Some((length, samples, sample_rate)) = parse_mp3_frame_header(header) else {
+            if frames > 0 {
+                break; // trailing ID3v1/APE tags or garbage
+            }
+            // Resync before the first frame, one byte on
+            pos += 1;
+            reader.seek_relative(-3).ok()?;
+            continue;
+        };
+        let length = length as u64;
+        if length < 4 || pos + length > len {
+            break;
+        }
+
+        // The Xing/Info frame carries no audio
+        let is_info_frame = frames == 0 && {
+            let mut frame = vec![0u8; (length - 4) as usize];
+            reader.read_exact(&mut frame).ok()?;
+            frame.windows(4).any(|w| w == b"Xing" || w == b"Info")
+        };
+        if frames > 0 {
+            reader.seek_relative(length as i64 - 4).ok()?;
+        }
+        if !is_info_frame {
+            seconds += samples as f64 / sample_rate as f64;
+        }
+
+        frames += 1;
+        pos += length;
+    }
+
+    (frames > 0).then_some(seconds)
+}
+
+/// Stat a media file and measure its duration for the enclosure length and itunes:duration
+#[tauri::command]
+pub async fn compute_enclosure_info(path: String) -> Result<EnclosureInfo, String> {
+    tokio::task::spawn_blocking(move || {
+        let file_path = Path::new(&path);
+        let length = std::fs::metadata(file_path)
+            .map_err(|e| format!("Failed to read file: {}", e))?
+            .len();
+
+        let duration_secs = match mp3_frame_duration(file_path) {
+            Some(secs) => secs,
+            None => read_audio_metadata(file_path)?.duration_secs,
+        };
+
+        Ok(EnclosureInfo {
+            mime_type: crate::guess_mime_type(&path).to_string(),
+            duration: format_itunes_duration(duration_secs),
+            duration_secs,
+            length,
+            file_path: path,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Read the embedded cover picture (front cover preferred) as (MIME type, bytes)
+pub fn read_embedded_picture(path: &Path) -> Result<Option<(String, Vec<u8>)>, String> {
+    let tagged = Probe::open(path)
+        .map_err(|e| format!("Failed to open audio file: {}", e))?
+        .read()
+        .map_err(|e| format!("Failed to read audio file: {}", e))?;
+
+    let pictures: Vec<_> = tagged.tags().iter().flat_map(|t| t.pictures()).collect();
+    let picture = pictures
+        .iter()
+        .find(|p| p.pic_type() == PictureType::CoverFront)
+        .or_else(|| pictures.first());
+
+    Ok(picture.map(|p| {
+        let mime_type = p
+            .mime_type()
+            .map(|m| m.as_str().to_string())
+            .unwrap_or_else(|| "image/jpeg".to_string());
+        (mime_type, p.data().to_vec())
+    }))
+}
+
+/// Read the embedded cover art (front cover preferred) from an audio file
+pub fn read_embedded_artwork(path: &Path) -> Result<Option<AudioArtwork>, String> {
+    Ok(read_embedded_picture(path)?.map(|(mime_type, data)| AudioArtwork {
+        mime_type,
+        data_base64: BASE64.encode(data),
+    }))
+}
+
+/// Extract tags, stream properties, and embedded artwork from a dropped audio file
+#[tauri::command]
+pub fn extract_audio_metadata(path: String) -> Result<AudioFileInfo, String> {
+    let path = Path::new(&path);
+    if !path.is_file() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+
+    Ok(AudioFileInfo {
+        metadata: read_audio_metadata(path)?,
+        artwork: read_embedded_artwork(path)?,
+    })
+}
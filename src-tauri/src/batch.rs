@@ -0,0 +1,586 @@
+// Batch operations across the local feed library: concurrent validation with results
+// streamed back per feed, catalog-wide URL rewrites, duplicate asset detection, and
+// GUID collision checks
+
+use crate::feed_xml::{parse_rss, parse_xml, render_document, XmlNode};
+use crate::validation::{feed_validate, ValidationReport};
+use futures_util::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use tauri::{AppHandle, Emitter};
+
+// Upper bound on feeds processed at once, whatever the core count
+const MAX_BATCH_PARALLELISM: usize = 8;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BatchFeedResult {
+    pub feed_id: String,
+    pub title: Option<String>,
+    pub ok: bool,
+    pub report: Option<ValidationReport>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BatchSummary {
+    pub operation: String,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BatchFeedResult>,
+}
+
+/// Validate one library feed with the rule set for its stored type
+fn validate_library_feed(feed_id: String) -> BatchFeedResult {
+    let validated = crate::load_feed_local(feed_id.clone())
+        .and_then(|feed| Ok((feed.title, feed_validate(feed.xml, Some(feed.feed_type), Some(feed.id))?)));
+    match validated {
+        Ok((title, report)) => BatchFeedResult {
+            feed_id,
+            title: Some(title),
+            ok: report.valid,
+            report: Some(report),
+            error: None,
+        },
+        Err(e) => BatchFeedResult {
+            feed_id,
+            title: None,
+            ok: false,
+            report: None,
+            error: Some(e),
+        },
+    }
+}
+
+/// Run an operation ("validate") over library feeds (all of them when no ids are
+/// given) on a bounded worker pool, emitting a `feeds://batch-result` event as each
+/// feed finishes
+#[tauri::command]
+pub async fn feeds_batch(
+    operation: String,
+    feed_ids: Option<Vec<String>>,
+    app: AppHandle,
+) -> Result<BatchSummary, String> {
+    if operation != "validate" {
+        return Err(format!("Unsupported batch operation: {}", operation));
+    }
+
+    let feed_ids = match feed_ids {
+        Some(ids) => ids,
+        None => crate::list_feeds_local()?.into_iter().map(|f| f.id).collect(),
+    };
+    let parallelism = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .min(MAX_BATCH_PARALLELISM);
+
+    let results: Vec<BatchFeedResult> = stream::iter(feed_ids)
+        .map(|feed_id| async move {
+            let fallback_id = feed_id.clone();
+            tokio::task::spawn_blocking(move || validate_library_feed(feed_id))
+                .await
+                .unwrap_or_else(|e| BatchFeedResult {
+                    feed_id: fallback_id,
+                    title: None,
+                    ok: false,
+                    report: None,
+                    error: Some(e.to_string()),
+                })
+        })
+        .buffer_unordered(parallelism)
+        .inspect(|result| {
+            let _ = app.emit("feeds://batch-result", result);
+        })
+        .collect()
+        .await;
+
+    let succeeded = results.iter().filter(|r| r.ok).count();
+    Ok(BatchSummary {
+        operation,
+        total: results.len(),
+        succeeded,
+        failed: results.len() - succeeded,
+        results,
+    })
+}
+
+// (element, attribute) pairs holding media URLs; an empty attribute means the text
+const URL_LOCATIONS: &[(&str, &str)] = &[
+    ("enclosure", "url"),
+    ("podcast:source", "uri"),
+    ("itunes:image", "href"),
+    ("podcast:image", "href"),
+    ("url", ""),
+    ("podcast:funding", "url"),
+    ("podcast:chapters", "url"),
+    ("podcast:transcript", "url"),
+    ("podcast:person", "img"),
+];
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UrlChange {
+    pub element: String,
+    pub old_url: String,
+    pub new_url: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FeedUrlChanges {
+    pub feed_id: String,
+    pub title: String,
+    pub changes: Vec<UrlChange>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CatalogReplaceReport {
+    pub dry_run: bool,
+    pub total_changes: usize,
+    pub feeds: Vec<FeedUrlChanges>,
+}
+
+/// Call `f(element, value)` for every value in a known URL location
+fn visit_urls(node: &mut XmlNode, parent: &str, f: &mut dyn FnMut(&str, &mut String)) {
+    for (element, attr) in URL_LOCATIONS {
+        if node.name != *element {
+            continue;
+        }
+        // <url> only carries artwork inside the channel <image>
+        if attr.is_empty() && parent != "image" {
+            continue;
+        }
+        let value = if attr.is_empty() {
+            Some(&mut node.text)
+        } else {
+            node.attrs.iter_mut().find(|(k, _)| k == attr).map(|(_, v)| v)
+        };
+        if let Some(value) = value {
+            f(element, value);
+        }
+    }
+
+    let name = node.name.clone();
+    for child in node.children.iter_mut() {
+        visit_urls(child, &name, f);
+    }
+}
+
+/// Rewrite URLs starting with `old_prefix` in known URL locations, recording each change
+fn replace_url_prefix(node: &mut XmlNode, old_prefix: &str, new_prefix: &str, changes: &mut Vec<UrlChange>) {
+    visit_urls(node, "", &mut |element, value| {
+        let trimmed = value.trim();
+        if let Some(rest) = trimmed.strip_prefix(old_prefix) {
+            let new_url = format!("{}{}", new_prefix, rest);
+            changes.push(UrlChange {
+                element: element.to_string(),
+                old_url: trimmed.to_string(),
+                new_url: new_url.clone(),
+            });
+            *value = new_url;
+        }
+    });
+}
+
+/// Apply (old prefix, new prefix) rewrites across every library feed, saving the
+/// feeds that change unless this is a dry run
+pub fn replace_url_prefixes(prefixes: &[(String, String)], dry_run: bool) -> Result<CatalogReplaceReport, String> {
+    let _operation = (!dry_run).then(|| crate::shutdown::begin("feed-write", "catalog"));
+    let mut conn = crate::open_library()?;
+    // Every changed feed is saved in one transaction, so a failure leaves none rewritten
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let library: Vec<crate::LocalFeed> = {
+        let mut stmt = tx
+            .prepare("SELECT id, title, feed_type, xml, created_at, updated_at FROM feeds")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], crate::row_to_local_feed).map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+    };
+
+    let mut feeds = Vec::new();
+    for feed in library {
+        let mut root = parse_xml(&feed.xml)?;
+        let mut changes = Vec::new();
+        for (old_prefix, new_prefix) in prefixes {
+            replace_url_prefix(&mut root, old_prefix, new_prefix, &mut changes);
+        }
+        if changes.is_empty() {
+            continue;
+        }
+
+        let feed_id = if dry_run {
+            feed.id
+        } else {
+            let xml = render_document(&root);
+            crate::write_library_feed(&tx, Some(feed.id), feed.title.clone(), feed.feed_type, xml, None)?.id
+        };
+        feeds.push(FeedUrlChanges {
+            feed_id,
+            title: feed.title,
+            changes,
+        });
+    }
+    if !dry_run {
+        tx.commit().map_err(|e| e.to_string())?;
+    }
+
+    Ok(CatalogReplaceReport {
+        dry_run,
+        total_changes: feeds.iter().map(|f| f.changes.len()).sum(),
+        feeds,
+    })
+}
+
+/// Replace a URL prefix (old domain or Blossom server) in enclosure, artwork, and
+/// funding URLs across every library feed. Changed feeds are saved together, which
+/// snapshots their previous revisions; a dry run only reports the changes.
+#[tauri::command]
+pub fn catalog_replace_url(
+    old_prefix: String,
+    new_prefix: String,
+    dry_run: bool,
+) -> Result<CatalogReplaceReport, String> {
+    if old_prefix.trim().is_empty() {
+        return Err("The URL prefix to replace cannot be empty".to_string());
+    }
+    if old_prefix == new_prefix {
+        return Err("The new prefix is the same as the old one".to_string());
+    }
+    replace_url_prefixes(&[(old_prefix, new_prefix)], dry_run)
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AssetReference {
+    pub feed_id: String,
+    pub feed_title: String,
+    pub element: String,
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DuplicateAsset {
+    pub sha256: String,
+    pub mime_type: String,
+    pub size: Option<u64>, // known when a copy is a local file
+    pub urls: Vec<String>,
+    pub references: Vec<AssetReference>,
+    pub suggested_url: Option<String>, // most-used Blossom URL, if any
+}
+
+/// Content hash of a media URL: hashed for local files, read from the path for
+/// Blossom-style URLs (https://server/<sha256>.ext). None when it cannot be known
+/// without downloading.
+fn asset_hash(url: &str) -> Option<(String, Option<u64>)> {
+    if let Some(path) = crate::file_url_path(url) {
+        let (sha256, size) = crate::hash_file_cached(&path).ok()?;
+        return Some((sha256, Some(size)));
+    }
+    blossom_hash(url).map(|sha256| (sha256, None))
+}
+
+/// The sha256 a Blossom URL is named after
+fn blossom_hash(url: &str) -> Option<String> {
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return None;
+    }
+    let name = url.split(['?', '#']).next()?.rsplit('/').next()?;
+    let stem = name.split('.').next()?;
+    (stem.len() == 64 && stem.chars().all(|c| c.is_ascii_hexdigit())).then(|| stem.to_lowercase())
+}
+
+/// Find audio, video, and artwork with identical content (by sha256) that feeds
+/// reference through different URLs. Local files are hashed, so this blocks.
+fn find_duplicate_assets() -> Result<Vec<DuplicateAsset>, String> {
+    let mut by_hash: BTreeMap<String, DuplicateAsset> = BTreeMap::new();
+    for summary in crate::list_feeds_local()? {
+        let feed = crate::load_feed_local(summary.id)?;
+        let Ok(mut root) = parse_xml(&feed.xml) else {
+            continue;
+        };
+        let mut found = Vec::new();
+        visit_urls(&mut root, "", &mut |element, value| {
+            if element != "podcast:funding" {
+                found.push((element.to_string(), value.trim().to_string()));
+            }
+        });
+
+        for (element, url) in found {
+            let mime_type = crate::guess_mime_type(&url);
+            let is_media = ["audio/", "video/", "image/"].iter().any(|p| mime_type.starts_with(p));
+            let Some((sha256, size)) = asset_hash(&url).filter(|_| is_media) else {
+                continue;
+            };
+            let entry = by_hash.entry(sha256.clone()).or_insert_with(|| DuplicateAsset {
+                sha256,
+                mime_type: mime_type.to_string(),
+                size: None,
+                urls: Vec::new(),
+                references: Vec::new(),
+                suggested_url: None,
+            });
+            entry.size = entry.size.or(size);
+            if !entry.urls.contains(&url) {
+                entry.urls.push(url.clone());
+            }
+            entry.references.push(AssetReference {
+                feed_id: feed.id.clone(),
+                feed_title: feed.title.clone(),
+                element,
+                url,
+            });
+        }
+    }
+
+    Ok(by_hash
+        .into_values()
+        .filter(|asset| {
+            let feeds: HashSet<&str> = asset.references.iter().map(|r| r.feed_id.as_str()).collect();
+            feeds.len() > 1 && asset.urls.len() > 1
+        })
+        .map(|mut asset| {
+            asset.suggested_url = asset
+                .urls
+                .iter()
+                .filter(|url| blossom_hash(url).is_some())
+                .max_by_key(|url| asset.references.iter().filter(|r| &r.url == *url).count())
+                .cloned();
+            asset
+        })
+        .collect())
+}
+
+/// Find audio, video, and artwork with identical content (by sha256) that feeds
+/// reference through different URLs, so they can share one hosted copy
+#[tauri::command]
+pub async fn catalog_find_duplicate_assets() -> Result<Vec<DuplicateAsset>, String> {
+    tokio::task::spawn_blocking(find_duplicate_assets)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Point every reference to a duplicated asset at one canonical Blossom URL (which
+/// must be named after the asset's sha256). Changed feeds are saved together, which
+/// snapshots their previous revisions; a dry run only reports the changes.
+#[tauri::command]
+pub async fn catalog_consolidate_asset(
+    sha256: String,
+    canonical_url: String,
+    dry_run: bool,
+) -> Result<CatalogReplaceReport, String> {
+    tokio::task::spawn_blocking(move || consolidate_asset(&sha256, &canonical_url, dry_run))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn consolidate_asset(sha256: &str, canonical_url: &str, dry_run: bool) -> Result<CatalogReplaceReport, String> {
+    let sha256 = sha256.trim().to_lowercase();
+    if blossom_hash(canonical_url.trim()).as_deref() != Some(sha256.as_str()) {
+        return Err("The canonical URL must be a Blossom URL for this asset's sha256".to_string());
+    }
+    let asset = find_duplicate_assets()?
+        .into_iter()
+        .find(|a| a.sha256 == sha256)
+        .ok_or("No duplicated asset with that sha256")?;
+
+    let _operation = (!dry_run).then(|| crate::shutdown::begin("feed-write", "catalog"));
+    let mut conn = crate::open_library()?;
+    // Every changed feed is saved in one transaction, so a failure leaves none rewritten
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut feeds = Vec::new();
+    let feed_ids: BTreeSet<&str> = asset.references.iter().map(|r| r.feed_id.as_str()).collect();
+    for feed_id in feed_ids {
+        let feed = crate::get_library_feed(&tx, feed_id)?.ok_or_else(|| format!("Feed not found: {}", feed_id))?;
+        let mut root = parse_xml(&feed.xml)?;
+        let mut changes = Vec::new();
+        visit_urls(&mut root, "", &mut |element, value| {
+            let trimmed = value.trim();
+            if trimmed != canonical_url.trim() && asset.urls.iter().any(|u| u == trimmed) {
+                changes.push(UrlChange {
+                    element: element.to_string(),
+                    old_url: trimmed.to_string(),
+                    new_url: canonical_url.trim().to_string(),
+                });
+                *value = canonical_url.trim().to_string();
+            }
+        });
+        if changes.is_empty() {
+            continue;
+        }
+
+        let feed_id = if dry_run {
+            feed.id
+        } else {
+            let xml = render_document(&root);
+            crate::write_library_feed(&tx, Some(feed.id), feed.title.clone(), feed.feed_type, xml, None)?.id
+        };
+        feeds.push(FeedUrlChanges {
+            feed_id,
+            title: feed.title,
+            changes,
+        });
+    }
+    if !dry_run {
+        tx.commit().map_err(|e| e.to_string())?;
+    }
+
+    Ok(CatalogReplaceReport {
+        dry_run,
+        total_changes: feeds.iter().map(|f| f.changes.len()).sum(),
+        feeds,
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GuidOwner {
+    pub feed_id: String,
+    pub feed_title: String,
+    pub item_title: Option<String>, // None for the channel's <podcast:guid>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GuidCollision {
+    pub guid: String,
+    pub owners: Vec<GuidOwner>,
+}
+
+/// GUIDs declared by a feed: the channel <podcast:guid> first, then each item <guid>,
+/// paired with the item title. Unparseable feeds declare nothing.
+fn feed_guids(xml: &str) -> (Option<String>, Vec<(String, Option<String>)>) {
+    let Ok(doc) = parse_rss(xml) else {
+        return (None, Vec::new());
+    };
+    let podcast_guid = doc
+        .channel()
+        .and_then(|c| c.child_text("podcast:guid"))
+        .map(str::to_string);
+    let items = doc
+        .items()
+        .into_iter()
+        .filter_map(|item| {
+            let guid = item.child_text("guid")?.to_string();
+            Some((guid, item.child_text("title").map(str::to_string)))
+        })
+        .collect();
+    (podcast_guid, items)
+}
+
+/// Find GUIDs shared by more than one feed or item across the whole library.
+/// GUIDs are compared case-insensitively, as directories treat them.
+#[tauri::command]
+pub fn catalog_check_guids() -> Result<Vec<GuidCollision>, String> {
+    let titles: BTreeMap<String, String> = crate::list_feeds_local()?
+        .into_iter()
+        .map(|f| (f.id, f.title))
+        .collect();
+
+    let mut owners: BTreeMap<String, (String, Vec<GuidOwner>)> = BTreeMap::new();
+    for (feed_id, xml) in crate::load_all_local_feed_xml()? {
+        let feed_title = titles.get(&feed_id).cloned().unwrap_or_else(|| feed_id.clone());
+        let (podcast_guid, items) = feed_guids(&xml);
+        let declared = podcast_guid.map(|g| (g, None)).into_iter().chain(items);
+        for (guid, item_title) in declared {
+            owners
+                .entry(guid.to_lowercase())
+                .or_insert_with(|| (guid, Vec::new()))
+                .1
+                .push(GuidOwner {
+                    feed_id: feed_id.clone(),
+                    feed_title: feed_title.clone(),
+                    item_title,
+                });
+        }
+    }
+
+    Ok(owners
+        .into_values()
+        .filter(|(_, owners)| owners.len() > 1)
+        .map(|(guid, owners)| GuidCollision { guid, owners })
+        .collect())
+}
+
+/// Record a feed's channel and item GUIDs in the `item_guids` index, replacing what
+/// was recorded for it before. Call in the transaction that writes the feed.
+pub fn index_guids(conn: &rusqlite::Connection, feed_id: &str, xml: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM item_guids WHERE feed_id = ?1", [feed_id])
+        .map_err(|e| e.to_string())?;
+    let (podcast_guid, items) = feed_guids(xml);
+    let declared = podcast_guid.map(|g| (g, true)).into_iter().chain(items.into_iter().map(|(g, _)| (g, false)));
+    for (guid, channel) in declared {
+        conn.execute(
+            "INSERT OR IGNORE INTO item_guids (feed_id, guid, channel) VALUES (?1, ?2, ?3)",
+            rusqlite::params![feed_id, guid, channel],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Index feeds written without updating `item_guids` (restores, recoveries, and
+/// libraries from before the index existed)
+fn index_pending_guids(conn: &rusqlite::Connection) -> Result<(), String> {
+    for table in ["feeds", "trashed_feeds"] {
+        let mut stmt = conn
+            .prepare(&format!("SELECT id, xml FROM {} WHERE guids_indexed = 0", table))
+            .map_err(|e| e.to_string())?;
+        let pending: Vec<(String, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        for (id, stored) in pending {
+            index_guids(conn, &id, &crate::library_crypto::open(stored)?)?;
+            conn.execute(&format!("UPDATE {} SET guids_indexed = 1 WHERE id = ?1", table), [&id])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Reject a save whose <podcast:guid> belongs to another library feed (trashed ones
+/// included, so they can still be restored), or whose items repeat a GUID. Item GUIDs
+/// shared with other feeds are left to `catalog_check_guids`, since splitting an album
+/// legitimately carries its items into the new feeds. Other feeds are looked up in the
+/// `item_guids` index rather than parsed.
+pub fn ensure_unique_guids(conn: &rusqlite::Connection, feed_id: Option<&str>, xml: &str) -> Result<(), String> {
+    use rusqlite::OptionalExtension;
+
+    let (podcast_guid, items) = feed_guids(xml);
+
+    let mut seen = HashSet::new();
+    for (guid, title) in &items {
+        if !seen.insert(guid.to_lowercase()) {
+            return Err(format!(
+                "Item GUID {} is used more than once in this feed (\"{}\")",
+                guid,
+                title.as_deref().unwrap_or("untitled")
+            ));
+        }
+    }
+
+    let Some(podcast_guid) = podcast_guid else {
+        return Ok(());
+    };
+    index_pending_guids(conn)?;
+    // Rows of feeds deleted outright (quarantined) are ignored. GUIDs are UUIDs, so
+    // case doesn't make two of them different.
+    let owner: Option<(String, bool)> = conn
+        .query_row(
+            "SELECT g.feed_id, EXISTS(SELECT 1 FROM trashed_feeds t WHERE t.id = g.feed_id)
+             FROM item_guids g
+             WHERE g.guid = ?1 COLLATE NOCASE AND g.channel = 1 AND g.feed_id IS NOT ?2
+               AND (EXISTS(SELECT 1 FROM feeds f WHERE f.id = g.feed_id)
+                    OR EXISTS(SELECT 1 FROM trashed_feeds t WHERE t.id = g.feed_id))
+             LIMIT 1",
+            rusqlite::params![podcast_guid, feed_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if let Some((other_id, trashed)) = owner {
+        return Err(format!(
+            "Podcast GUID {} is already used by feed \"{}\"{}; directories would merge the two releases",
+            podcast_guid,
+            other_id,
+            if trashed { " in the trash" } else { "" }
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,111 @@
+// Blossom server benchmark: reachability, upload requirements (BUD-06), and
+// round-trip latency for a list of servers, ranked so the best candidate for the
+// default server comes first.
+
+use crate::preflight::{upload_requirements, UploadRequirements};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tauri::State;
+
+// Latency is the median of this many requests
+const LATENCY_SAMPLES: usize = 5;
+const BENCHMARK_TIMEOUT_SECS: u64 = 10;
+
+// Upload requirements are checked for a typical album track
+const SAMPLE_SIZE: u64 = 50 * 1024 * 1024;
+const SAMPLE_TYPE: &str = "audio/mpeg";
+
+// A blob no server has; HEAD requests for it measure round trips without transfer
+const PROBE_SHA256: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Serialize, Deserialize)]
+pub struct ServerBenchmark {
+    pub server_url: String,
+    pub reachable: bool,
+    pub latency_ms: Option<u64>, // median round trip
+    pub min_latency_ms: Option<u64>,
+    pub max_latency_ms: Option<u64>,
+    pub uploads: String, // "accepted", "unchecked" (no BUD-06 support), "rejected", or "unknown"
+    pub detail: Option<String>,
+    pub rank: usize, // 1 is best
+}
+
+async fn benchmark_server(client: &reqwest::Client, server_url: &str, keys: Option<&nostr_sdk::Keys>) -> ServerBenchmark {
+    let server = crate::normalize_server_url(server_url);
+    let mut result = ServerBenchmark {
+        server_url: server.to_string(),
+        reachable: false,
+        latency_ms: None,
+        min_latency_ms: None,
+        max_latency_ms: None,
+        uploads: "unknown".to_string(),
+        detail: None,
+        rank: 0,
+    };
+
+    // Any HTTP answer counts as reachable; 404 is the expected one
+    let mut samples = Vec::new();
+    for _ in 0..LATENCY_SAMPLES {
+        let started = Instant::now();
+        match client.head(format!("{}/{}", server, PROBE_SHA256)).send().await {
+            Ok(_) => samples.push(started.elapsed().as_millis() as u64),
+            Err(e) => result.detail = Some(e.to_string()),
+        }
+    }
+    if samples.is_empty() {
+        return result;
+    }
+    samples.sort_unstable();
+    result.reachable = true;
+    result.detail = None;
+    result.latency_ms = Some(samples[samples.len() / 2]);
+    result.min_latency_ms = samples.first().copied();
+    result.max_latency_ms = samples.last().copied();
+
+    match upload_requirements(client, server, PROBE_SHA256, SAMPLE_SIZE, SAMPLE_TYPE, keys).await {
+        Ok(UploadRequirements::Accepted) => result.uploads = "accepted".to_string(),
+        Ok(UploadRequirements::Unsupported) => result.uploads = "unchecked".to_string(),
+        Ok(UploadRequirements::Rejected { status, reason }) => {
+            result.uploads = "rejected".to_string();
+            result.detail = Some(reason.unwrap_or_else(|| status.to_string()));
+        }
+        Ok(UploadRequirements::Unknown(e)) | Err(e) => result.detail = Some(e),
+    }
+    result
+}
+
+/// Check reachability, upload requirements, and latency of Blossom servers (the
+/// user's server list when none are given), ranked best first: servers that accept
+/// uploads, then those that can't be pre-checked, each by median latency
+#[tauri::command]
+pub async fn benchmark_blossom_servers(
+    urls: Option<Vec<String>>,
+    state: State<'_, crate::NostrState>,
+) -> Result<Vec<ServerBenchmark>, String> {
+    let urls = urls
+        .filter(|urls| !urls.is_empty())
+        .unwrap_or_else(|| state.blossom_servers.lock().unwrap().clone());
+    if urls.is_empty() {
+        return Err("No Blossom servers to benchmark".to_string());
+    }
+    let keys = state.signing_keys().ok();
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(BENCHMARK_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut results =
+        futures_util::future::join_all(urls.iter().map(|url| benchmark_server(&client, url, keys.as_ref()))).await;
+
+    let uploads_rank = |uploads: &str| match uploads {
+        "accepted" => 0,
+        "unchecked" => 1,
+        "unknown" => 2,
+        _ => 3,
+    };
+    results.sort_by_key(|r| (!r.reachable, uploads_rank(&r.uploads), r.latency_ms.unwrap_or(u64::MAX)));
+    for (i, result) in results.iter_mut().enumerate() {
+        result.rank = i + 1;
+    }
+    Ok(results)
+}
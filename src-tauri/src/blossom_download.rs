@@ -0,0 +1,121 @@
+// Download a blob from Blossom to disk, verifying it against its content address.
+// The file is streamed to a ".partial" file next to the destination and only moved
+// into place once its SHA-256 matches, so an interrupted or tampered download never
+// looks like a restored master.
+
+use crate::asset_audit::url_sha256;
+use crate::disk_space::{ensure_space, TaskError};
+use crate::upload_ledger;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, State};
+use tokio::io::AsyncWriteExt;
+
+#[derive(Serialize, Clone)]
+pub struct DownloadProgress {
+    pub url: String,
+    pub bytes_received: u64,
+    pub total_bytes: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DownloadedBlob {
+    pub url: String,
+    pub sha256: String,
+    pub size: u64,
+    pub path: String,
+}
+
+/// Stream `url` to `dest`, returning (sha256, size) of what was received
+async fn stream_to_file(url: &str, dest: &Path, app: &AppHandle) -> Result<(String, u64), TaskError> {
+    let response = reqwest::get(url).await.map_err(|e| format!("Download failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Download failed: {} returned {}", url, response.status()).into());
+    }
+    let total_bytes = response.content_length();
+    if let (Some(total), Some(parent)) = (total_bytes, dest.parent()) {
+        ensure_space(parent, total)?;
+    }
+
+    let mut file = tokio::fs::File::create(dest).await.map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    let mut bytes_received = 0u64;
+    let mut body = response.bytes_stream();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| format!("Download failed: {}", e))?;
+        hasher.update(&chunk);
+        file.write_all(&chunk).await.map_err(|e| e.to_string())?;
+        bytes_received += chunk.len() as u64;
+        let _ = app.emit(
+            "blossom://download-progress",
+            DownloadProgress {
+                url: url.to_string(),
+                bytes_received,
+                total_bytes,
+            },
+        );
+    }
+    file.flush().await.map_err(|e| e.to_string())?;
+    file.sync_all().await.map_err(|e| e.to_string())?;
+    Ok((hex::encode(hasher.finalize()), bytes_received))
+}
+
+/// Download a blob by URL, or by SHA-256 from the first of the user's Blossom servers
+/// that has it, to `dest_path`. Fails (leaving no file) if the content does not hash
+/// to the blob's address.
+#[tauri::command]
+pub async fn blossom_download(
+    url_or_sha256: String,
+    dest_path: String,
+    app: AppHandle,
+    state: State<'_, crate::NostrState>,
+) -> Result<DownloadedBlob, TaskError> {
+    let input = url_or_sha256.trim();
+    let (url, sha256) = if input.starts_with("http://") || input.starts_with("https://") {
+        let sha256 = url_sha256(input).ok_or("URL is not a Blossom blob address (no SHA-256 in the path)")?;
+        (input.to_string(), sha256)
+    } else {
+        let sha256 = url_sha256(input).ok_or("Expected a blob URL or a 64-character SHA-256")?;
+        let servers = state.blossom_servers.lock().unwrap().clone();
+        let mut found = None;
+        for server in &servers {
+            if crate::blossom_blob_exists(server, &sha256).await.unwrap_or(false) {
+                found = Some(format!("{}/{}", crate::normalize_server_url(server), sha256));
+                break;
+            }
+        }
+        (found.ok_or("None of your Blossom servers has this blob")?, sha256)
+    };
+
+    let dest = PathBuf::from(&dest_path);
+    let partial = PathBuf::from(format!("{}.partial", dest_path));
+    let _operation = crate::shutdown::begin("download", &dest_path);
+
+    let received = stream_to_file(&url, &partial, &app).await;
+    let (actual, size) = match received {
+        Ok(result) => result,
+        Err(e) => {
+            let _ = std::fs::remove_file(&partial);
+            return Err(e);
+        }
+    };
+    if actual != sha256 {
+        let _ = std::fs::remove_file(&partial);
+        return Err(format!("Downloaded content hashes to {}, not {}; the file was discarded", actual, sha256).into());
+    }
+    std::fs::rename(&partial, &dest).map_err(|e| format!("Failed to move download into place: {}", e))?;
+
+    // The restored file is now a local copy that repairs and migrations can upload from
+    if let Some((server, _)) = url.split_once(&format!("/{}", sha256)) {
+        let _ = upload_ledger::record(&sha256, &url, size, &upload_ledger::blossom_key(server), Some(&dest_path));
+    }
+
+    Ok(DownloadedBlob {
+        url,
+        sha256,
+        size,
+        path: dest_path,
+    })
+}
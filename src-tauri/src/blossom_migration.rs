@@ -0,0 +1,216 @@
+// Move a user's blobs from one Blossom server to another: each blob is mirrored
+// (BUD-04) or, where the new server can't mirror, uploaded again from the local copy
+// the upload ledger knows about or from a download of the old blob. Library feeds are
+// then rewritten to the new URLs, and only after that can the old copies be deleted.
+
+use crate::batch::{replace_url_prefixes, FeedUrlChanges};
+use crate::storage::{self, StorageTarget};
+use crate::upload_ledger;
+use futures_util::StreamExt;
+use nostr_sdk::prelude::Keys;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::{AppHandle, Emitter, State};
+use tokio::io::AsyncWriteExt;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BlobMigration {
+    pub sha256: String,
+    pub method: String, // "existing", "mirror", "local", or "download"
+    pub new_url: Option<String>,
+    pub deleted: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct MigrationProgress {
+    pub completed: usize,
+    pub total: usize,
+    pub blob: BlobMigration,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub old_url: String,
+    pub new_url: String,
+    pub migrated: usize,
+    pub failed: usize,
+    pub blobs: Vec<BlobMigration>,
+    pub feeds: Vec<FeedUrlChanges>,
+}
+
+/// A local file with this content, from the upload ledger. Candidates are hashed on
+/// a blocking thread.
+async fn local_copy(sha256: &str) -> Option<String> {
+    let sha256 = sha256.to_string();
+    tokio::task::spawn_blocking(move || {
+        upload_ledger::lookup(&sha256)
+            .ok()?
+            .into_iter()
+            .filter_map(|blob| blob.local_path)
+            .find(|path| crate::hash_file_cached(Path::new(path)).is_ok_and(|(hash, _)| hash == sha256))
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+/// Download a blob into a scratch workspace and upload it to the new server
+async fn reupload_download(keys: &Keys, source_url: &str, new_server: &str, mime_type: &str) -> Result<String, String> {
+    let workspace = crate::workspace::TaskWorkspace::create("migration")?;
+    let path = workspace.path().join("blob");
+
+    let response = reqwest::get(source_url)
+        .await
+        .map_err(|e| format!("Download failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Download failed: {}", response.status()));
+    }
+    let mut file = tokio::fs::File::create(&path).await.map_err(|e| e.to_string())?;
+    let mut body = response.bytes_stream();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| format!("Download failed: {}", e))?;
+        file.write_all(&chunk).await.map_err(|e| e.to_string())?;
+    }
+    file.flush().await.map_err(|e| e.to_string())?;
+
+    let target = StorageTarget::Blossom {
+        server_url: new_server.to_string(),
+    };
+    storage::upload_staged(&target, &path.to_string_lossy(), mime_type, Some(keys.clone()))
+        .await
+        .map(|file| file.url)
+        .map_err(String::from)
+}
+
+/// Copy one blob to the new server, trying the cheapest method first
+async fn migrate_blob(keys: &Keys, old_server: &str, new_server: &str, descriptor: &serde_json::Value) -> BlobMigration {
+    let sha256 = descriptor["sha256"].as_str().unwrap_or_default().to_lowercase();
+    let source_url = descriptor["url"]
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{}/{}", crate::normalize_server_url(old_server), sha256));
+    let mime_type = descriptor["type"]
+        .as_str()
+        .unwrap_or_else(|| crate::guess_mime_type(&source_url))
+        .to_string();
+    let size = descriptor["size"].as_u64().unwrap_or_default();
+
+    let mut migration = BlobMigration {
+        sha256: sha256.clone(),
+        method: "existing".to_string(),
+        new_url: None,
+        deleted: false,
+        error: None,
+    };
+
+    let result = if crate::blossom_blob_exists(new_server, &sha256).await.unwrap_or(false) {
+        Ok(crate::existing_blob_result(new_server, sha256.clone(), size).url)
+    } else {
+        migration.method = "mirror".to_string();
+        match crate::perform_blossom_mirror(keys, new_server, &source_url, &sha256).await {
+            Ok(url) => Ok(url),
+            Err(_) => match local_copy(&sha256).await {
+                Some(path) => {
+                    migration.method = "local".to_string();
+                    let target = StorageTarget::Blossom {
+                        server_url: new_server.to_string(),
+                    };
+                    storage::upload_file_as(&target, &path, &mime_type, Some(keys.clone()), None)
+                        .await
+                        .map(|file| file.url)
+                        .map_err(String::from)
+                }
+                None => {
+                    migration.method = "download".to_string();
+                    reupload_download(keys, &source_url, new_server, &mime_type).await
+                }
+            },
+        }
+    };
+
+    match result {
+        Ok(url) => {
+            let _ = upload_ledger::record(&sha256, &url, size, &upload_ledger::blossom_key(new_server), None);
+            migration.new_url = Some(url);
+        }
+        Err(e) => migration.error = Some(e),
+    }
+    migration
+}
+
+/// Move every blob of the logged-in user from one Blossom server to another, then
+/// rewrite library feeds that reference the old server. With `delete_old`, blobs are
+/// deleted from the old server once the feeds point at the new copy; a blob some feed
+/// still references (live or trashed) is kept.
+#[tauri::command]
+pub async fn migrate_blossom_server(
+    old_url: String,
+    new_url: String,
+    delete_old: Option<bool>,
+    app: AppHandle,
+    state: State<'_, crate::NostrState>,
+) -> Result<MigrationReport, String> {
+    let old_base = crate::normalize_server_url(&old_url).to_string();
+    let new_base = crate::normalize_server_url(&new_url).to_string();
+    if old_base == new_base {
+        return Err("The new server is the same as the old one".to_string());
+    }
+    let keys = state.signing_keys()?;
+    let _operation = crate::shutdown::begin("migration", &old_base);
+
+    let descriptors = crate::perform_blossom_list(&old_base, &keys.public_key().to_hex()).await?;
+    let total = descriptors.len();
+    let mut blobs = Vec::new();
+    for (i, descriptor) in descriptors.iter().enumerate() {
+        let blob = migrate_blob(&keys, &old_base, &new_base, descriptor).await;
+        let _ = app.emit(
+            "blossom://migration-progress",
+            MigrationProgress {
+                completed: i + 1,
+                total,
+                blob: blob.clone(),
+            },
+        );
+        blobs.push(blob);
+    }
+
+    // Only blobs the new server has are rewritten, so a partial migration leaves
+    // the rest of each feed pointing at the old server
+    let prefixes: Vec<(String, String)> = blobs
+        .iter()
+        .filter_map(|blob| {
+            let new_url = blob.new_url.as_ref()?;
+            Some((format!("{}/{}", old_base, blob.sha256), new_url.clone()))
+        })
+        .collect();
+    let feeds = replace_url_prefixes(&prefixes, false)?.feeds;
+
+    if delete_old.unwrap_or(false) {
+        let conn = crate::open_library()?;
+        let mut library = crate::stored_feed_xml(&conn, "feeds")?;
+        library.extend(crate::stored_feed_xml(&conn, "trashed_feeds")?);
+        drop(conn);
+        for blob in blobs.iter_mut().filter(|blob| blob.new_url.is_some()) {
+            let old_blob_url = format!("{}/{}", old_base, blob.sha256);
+            if library.iter().any(|(_, xml)| xml.contains(&old_blob_url)) {
+                blob.error = Some("Copied, but a feed still references the old blob, so it was kept".to_string());
+                continue;
+            }
+            match crate::perform_blossom_delete(&keys, &old_base, &blob.sha256).await {
+                Ok(()) => blob.deleted = true,
+                Err(e) => blob.error = Some(format!("Copied, but deleting the old blob failed: {}", e)),
+            }
+        }
+    }
+
+    let migrated = prefixes.len();
+    Ok(MigrationReport {
+        old_url: old_base,
+        new_url: new_base,
+        migrated,
+        failed: blobs.len() - migrated,
+        blobs,
+        feeds,
+    })
+}
@@ -0,0 +1,253 @@
+// Podcasting 2.0 JSON chapters: build a chapters document from a track list (a
+// continuous mix, each track starting where the last ended) or from user-entered
+// timestamps, upload it to Blossom, and point the track's <podcast:chapters> at it.
+
+use crate::feed_model::CHAPTERS_TYPE;
+use crate::feed_xml::{parse_rss, render_document, XmlNode};
+use crate::storage::{self, StorageTarget};
+use crate::timeline::{build_item_timeline, check_timeline, format_timestamp, parse_timestamp};
+use crate::validation::Issues;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+// Version of the chapters format written
+const CHAPTERS_VERSION: &str = "1.2.0";
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Chapter {
+    pub start_time: f64, // seconds
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub img: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ChaptersDocument {
+    pub version: String,
+    pub chapters: Vec<Chapter>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ChapterTrack {
+    pub title: String,
+    pub duration: String, // seconds, MM:SS, or HH:MM:SS
+    pub img: Option<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ChaptersAttachment {
+    pub feed_id: String,
+    pub item_guid: String,
+    pub url: String,
+    pub sha256: String,
+}
+
+fn ms_to_secs(ms: u64) -> f64 {
+    ms as f64 / 1000.0
+}
+
+/// Chapters for tracks played back to back, each starting where the last one ended
+fn chapters_for_tracks(tracks: &[ChapterTrack]) -> Result<ChaptersDocument, String> {
+    let mut start_ms = 0;
+    let mut chapters = Vec::new();
+    for (i, track) in tracks.iter().enumerate() {
+        let duration_ms = parse_timestamp(&track.duration)
+            .filter(|d| *d > 0)
+            .ok_or_else(|| format!("Track {} (\"{}\") has an invalid duration", i + 1, track.title))?;
+        chapters.push(Chapter {
+            start_time: ms_to_secs(start_ms),
+            title: track.title.trim().to_string(),
+            img: track.img.clone().filter(|v| !v.is_empty()),
+            url: track.url.clone().filter(|v| !v.is_empty()),
+            end_time: Some(ms_to_secs(start_ms + duration_ms)),
+        });
+        start_ms += duration_ms;
+    }
+    Ok(ChaptersDocument {
+        version: CHAPTERS_VERSION.to_string(),
+        chapters,
+    })
+}
+
+/// Chapters from lines of "<timestamp> <title>", e.g. "03:15 Second song"
+fn chapters_for_timestamps(text: &str) -> Result<ChaptersDocument, String> {
+    let mut chapters: Vec<(u64, String)> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (timestamp, title) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let start_ms = parse_timestamp(timestamp)
+            .ok_or_else(|| format!("Line {}: \"{}\" is not a timestamp", i + 1, timestamp))?;
+        let title = title.trim().trim_start_matches(['-', '–']).trim();
+        if chapters.iter().any(|(start, _)| *start == start_ms) {
+            return Err(format!("Line {}: two chapters start at {}", i + 1, format_timestamp(start_ms)));
+        }
+        chapters.push((start_ms, title.to_string()));
+    }
+    chapters.sort_by_key(|(start, _)| *start);
+
+    Ok(ChaptersDocument {
+        version: CHAPTERS_VERSION.to_string(),
+        chapters: chapters
+            .into_iter()
+            .map(|(start_ms, title)| Chapter {
+                start_time: ms_to_secs(start_ms),
+                title,
+                img: None,
+                url: None,
+                end_time: None,
+            })
+            .collect(),
+    })
+}
+
+/// Build chapters for a continuous mix from its track list
+#[tauri::command]
+pub fn chapters_from_tracks(tracks: Vec<ChapterTrack>) -> Result<ChaptersDocument, String> {
+    if tracks.is_empty() {
+        return Err("No tracks to build chapters from".to_string());
+    }
+    chapters_for_tracks(&tracks)
+}
+
+/// Build chapters from user-entered timestamps, one "<timestamp> <title>" per line
+#[tauri::command]
+pub fn chapters_from_timestamps(text: String) -> Result<ChaptersDocument, String> {
+    let chapters = chapters_for_timestamps(&text)?;
+    if chapters.chapters.is_empty() {
+        return Err("No chapters entered".to_string());
+    }
+    Ok(chapters)
+}
+
+/// Upload a chapters file and point a track's <podcast:chapters> at it. Chapters are
+/// checked against the track's duration first. Uploads go to the given Blossom server,
+/// else the feed's storage target.
+#[tauri::command]
+pub async fn chapters_attach(
+    feed_id: String,
+    item_guid: String,
+    chapters: ChaptersDocument,
+    server_url: Option<String>,
+    state: State<'_, crate::NostrState>,
+) -> Result<ChaptersAttachment, String> {
+    let feed = crate::load_feed_local(feed_id)?;
+    let mut doc = parse_rss(&feed.xml)?;
+    let json = serde_json::to_string_pretty(&chapters).map_err(|e| e.to_string())?;
+
+    {
+        let item = doc
+            .items()
+            .into_iter()
+            .find(|item| item.child_text("guid") == Some(item_guid.as_str()))
+            .ok_or_else(|| format!("Track not found: {}", item_guid))?;
+        let mut issues = Issues::default();
+        let timeline = build_item_timeline(item, Some(&json), None, &mut issues)?;
+        check_timeline(&timeline, &mut issues);
+        if let Some(issue) = issues.into_vec().into_iter().find(|i| i.severity == "error") {
+            return Err(issue.message);
+        }
+    }
+
+    let target = match server_url {
+        Some(server_url) => StorageTarget::Blossom { server_url },
+        None => storage::resolve_target(Some(&feed.id), &state)?,
+    };
+    let _operation = crate::shutdown::begin("upload", format!("{} chapters", feed.title));
+    let uploaded = storage::upload_bytes(
+        &target,
+        json.into_bytes(),
+        "chapters.json",
+        CHAPTERS_TYPE,
+        state.signing_keys().ok(),
+    )
+    .await?;
+
+    let item = doc
+        .root
+        .child_mut("channel")
+        .and_then(|c| {
+            c.children
+                .iter_mut()
+                .find(|c| c.name == "item" && c.child_text("guid") == Some(item_guid.as_str()))
+        })
+        .ok_or_else(|| format!("Track not found: {}", item_guid))?;
+    item.remove_children("podcast:chapters");
+    item.children.push(
+        XmlNode::new("podcast:chapters")
+            .with_attr("url", &uploaded.url)
+            .with_attr("type", CHAPTERS_TYPE),
+    );
+
+    let saved = crate::save_feed_local(Some(feed.id), feed.title, feed.feed_type, render_document(&doc.root), None)?;
+    Ok(ChaptersAttachment {
+        feed_id: saved.id,
+        item_guid,
+        url: uploaded.url,
+        sha256: uploaded.sha256,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(title: &str, duration: &str) -> ChapterTrack {
+        ChapterTrack {
+            title: title.to_string(),
+            duration: duration.to_string(),
+            img: None,
+            url: Some(String::new()),
+        }
+    }
+
+    #[test]
+    fn tracks_start_where_the_last_one_ended() {
+        let doc = chapters_for_tracks(&[track(" Intro ", "1:30"), track("Second", "125.5")]).unwrap();
+        assert_eq!(doc.version, CHAPTERS_VERSION);
+        assert_eq!(doc.chapters.len(), 2);
+        assert_eq!(doc.chapters[0].title, "Intro");
+        assert_eq!(doc.chapters[0].start_time, 0.0);
+        assert_eq!(doc.chapters[0].end_time, Some(90.0));
+        assert_eq!(doc.chapters[1].start_time, 90.0);
+        assert_eq!(doc.chapters[1].end_time, Some(215.5));
+        assert!(doc.chapters[1].url.is_none());
+    }
+
+    #[test]
+    fn tracks_without_a_duration_are_rejected() {
+        let err = chapters_for_tracks(&[track("Intro", "1:30"), track("Broken", "0")]).err().unwrap();
+        assert!(err.contains("Track 2"), "{}", err);
+    }
+
+    #[test]
+    fn timestamps_are_sorted_and_titles_trimmed() {
+        let doc = chapters_for_timestamps("03:15 - Second song\n\n0:00 First song\n1:02:03 Third").unwrap();
+        let chapters: Vec<(f64, &str)> = doc.chapters.iter().map(|c| (c.start_time, c.title.as_str())).collect();
+        assert_eq!(chapters, vec![(0.0, "First song"), (195.0, "Second song"), (3723.0, "Third")]);
+        assert!(doc.chapters.iter().all(|c| c.end_time.is_none()));
+    }
+
+    #[test]
+    fn bad_and_duplicate_timestamps_are_rejected() {
+        let err = chapters_for_timestamps("0:00 Intro\nsoon Outro").err().unwrap();
+        assert!(err.starts_with("Line 2"), "{}", err);
+        let err = chapters_for_timestamps("0:30 Intro\n00:00:30 Again").err().unwrap();
+        assert!(err.contains("two chapters start at 00:00:30.000"), "{}", err);
+    }
+
+    #[test]
+    fn chapters_serialize_in_the_podcasting_format() {
+        let doc = chapters_for_timestamps("0:05 Intro").unwrap();
+        let json = serde_json::to_string(&doc).unwrap();
+        assert_eq!(json, r#"{"version":"1.2.0","chapters":[{"startTime":5.0,"title":"Intro"}]}"#);
+    }
+}
@@ -0,0 +1,145 @@
+// Free disk space checks run before long writes (imports, backups, transcodes)
+
+use crate::formatting::format_size;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::path::Path;
+
+// Headroom kept free beyond the bytes a task expects to write
+const SPACE_MARGIN_BYTES: u64 = 16 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize)]
+pub struct DiskSpaceReport {
+    pub path: String,
+    pub required: u64,
+    pub available: u64,
+    pub sufficient: bool,
+}
+
+#[derive(Debug)]
+pub enum DiskSpaceError {
+    Insufficient { path: String, required: u64, available: u64 },
+    Unavailable { reason: String },
+}
+
+// Sent to the frontend as { kind, message, path?, required?, available? } so a
+// full disk can be shown with the numbers instead of only a message
+impl Serialize for DiskSpaceError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            DiskSpaceError::Insufficient { path, required, available } => {
+                let mut s = serializer.serialize_struct("DiskSpaceError", 5)?;
+                s.serialize_field("kind", "insufficientSpace")?;
+                s.serialize_field("message", &self.to_string())?;
+                s.serialize_field("path", path)?;
+                s.serialize_field("required", required)?;
+                s.serialize_field("available", available)?;
+                s.end()
+            }
+            DiskSpaceError::Unavailable { .. } => {
+                let mut s = serializer.serialize_struct("DiskSpaceError", 2)?;
+                s.serialize_field("kind", "spaceUnavailable")?;
+                s.serialize_field("message", &self.to_string())?;
+                s.end()
+            }
+        }
+    }
+}
+
+/// Error for commands that check disk space: the typed space error or a plain message
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum TaskError {
+    DiskSpace(DiskSpaceError),
+    Message(String),
+}
+
+impl fmt::Display for TaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskError::DiskSpace(e) => write!(f, "{}", e),
+            TaskError::Message(e) => f.write_str(e),
+        }
+    }
+}
+
+impl From<DiskSpaceError> for TaskError {
+    fn from(e: DiskSpaceError) -> Self {
+        TaskError::DiskSpace(e)
+    }
+}
+
+impl From<String> for TaskError {
+    fn from(e: String) -> Self {
+        TaskError::Message(e)
+    }
+}
+
+impl From<&str> for TaskError {
+    fn from(e: &str) -> Self {
+        TaskError::Message(e.to_string())
+    }
+}
+
+impl From<TaskError> for String {
+    fn from(e: TaskError) -> Self {
+        e.to_string()
+    }
+}
+
+impl fmt::Display for DiskSpaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiskSpaceError::Insufficient { path, required, available } => write!(
+                f,
+                "Not enough disk space in {}: {} required, {} available ({} required bytes, {} available bytes)",
+                path,
+                format_size(*required),
+                format_size(*available),
+                required,
+                available
+            ),
+            DiskSpaceError::Unavailable { reason } => write!(f, "Could not check free disk space: {}", reason),
+        }
+    }
+}
+
+/// Free space on the volume holding `path`; paths that do not exist yet are
+/// measured at their nearest existing ancestor
+pub fn available_space(path: &Path) -> Result<u64, DiskSpaceError> {
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .ok_or_else(|| DiskSpaceError::Unavailable {
+            reason: format!("No existing directory above {}", path.display()),
+        })?;
+    fs2::available_space(existing).map_err(|e| DiskSpaceError::Unavailable { reason: e.to_string() })
+}
+
+/// Fail early unless `bytes` (plus a safety margin) fit at `path`
+pub fn ensure_space(path: &Path, bytes: u64) -> Result<(), DiskSpaceError> {
+    let required = bytes.saturating_add(SPACE_MARGIN_BYTES);
+    let available = available_space(path)?;
+    if available < required {
+        return Err(DiskSpaceError::Insufficient {
+            path: path.to_string_lossy().to_string(),
+            required,
+            available,
+        });
+    }
+    Ok(())
+}
+
+/// Check whether `required_bytes` fit at a target location before starting a task
+#[tauri::command]
+pub fn disk_space_check(path: String, required_bytes: u64) -> Result<DiskSpaceReport, String> {
+    let required = required_bytes.saturating_add(SPACE_MARGIN_BYTES);
+    let available = available_space(Path::new(&path)).map_err(|e| e.to_string())?;
+    Ok(DiskSpaceReport {
+        path,
+        required,
+        available,
+        sufficient: available >= required,
+    })
+}
@@ -0,0 +1,196 @@
+// Drop folder: watch a configured folder (and its subfolders) for new audio files so a
+// studio can export masters straight into the app. Each file is reported once its size
+// stops changing, since exports appear on disk before they finish writing, by emitting
+// `dropfolder://file`; the frontend decides which feed to add it to. Folders created or
+// moved in are scanned for the files already inside them, and one worker thread checks
+// every pending file so a large drop doesn't start a thread per file.
+
+use crate::audio::{is_audio_file, read_audio_metadata, AudioMetadata};
+use fs_notify::event::ModifyKind;
+use fs_notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+// How often a new file's size is checked while it is still being written
+const SETTLE_INTERVAL: Duration = Duration::from_secs(2);
+
+// The running watcher, dropped to stop watching
+static WATCHER: Mutex<Option<RecommendedWatcher>> = Mutex::new(None);
+
+// Files seen but not yet reported, with their last checked size, so repeated events
+// for one export report it once
+static PENDING: Mutex<Option<HashMap<PathBuf, Option<u64>>>> = Mutex::new(None);
+
+// Whether the settle worker is running; only changed while PENDING is locked
+static SETTLING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct DropFolderSettings {
+    pub path: Option<String>,
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Clone)]
+pub struct DropFolderFile {
+    pub path: String,
+    pub metadata: Option<AudioMetadata>, // None when the tags could not be read
+    pub error: Option<String>,
+}
+
+fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(crate::get_appstate_dir()?.join("drop_folder.json"))
+}
+
+fn load_settings() -> DropFolderSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn report(app: &AppHandle, path: PathBuf) {
+    let (metadata, error) = match read_audio_metadata(&path) {
+        Ok(metadata) => (Some(metadata), None),
+        Err(e) => (None, Some(e)),
+    };
+    let _ = app.emit(
+        "dropfolder://file",
+        DropFolderFile {
+            path: path.to_string_lossy().to_string(),
+            metadata,
+            error,
+        },
+    );
+}
+
+/// Check every pending file each interval, reporting the ones whose size stopped
+/// changing. Files removed or renamed away before they settle are dropped silently.
+/// Exits once nothing is pending.
+fn settle_pending(app: AppHandle) {
+    loop {
+        std::thread::sleep(SETTLE_INTERVAL);
+        let settled: Vec<PathBuf> = {
+            let mut guard = PENDING.lock().unwrap();
+            let pending = guard.get_or_insert_with(HashMap::new);
+            if pending.is_empty() {
+                SETTLING.store(false, Ordering::SeqCst);
+                return;
+            }
+            let mut settled = Vec::new();
+            pending.retain(|path, last_size| match fs::metadata(path).map(|m| m.len()) {
+                Err(_) => false,
+                Ok(size) if size > 0 && *last_size == Some(size) => {
+                    settled.push(path.clone());
+                    false
+                }
+                Ok(size) => {
+                    *last_size = Some(size);
+                    true
+                }
+            });
+            settled
+        };
+        for path in settled {
+            report(&app, path);
+        }
+    }
+}
+
+/// Queue a file to be reported once it stops growing
+fn report_when_settled(app: &AppHandle, path: PathBuf) {
+    let mut guard = PENDING.lock().unwrap();
+    guard.get_or_insert_with(HashMap::new).entry(path).or_insert(None);
+    if !SETTLING.swap(true, Ordering::SeqCst) {
+        let app = app.clone();
+        std::thread::spawn(move || settle_pending(app));
+    }
+}
+
+/// Queue the audio files already inside a folder that appeared in the drop folder.
+/// Symlinked folders are not followed.
+fn report_folder(app: &AppHandle, dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if file_type.is_dir() {
+            report_folder(app, &path);
+        } else if file_type.is_file() && is_audio_file(&path) {
+            report_when_settled(app, path);
+        }
+    }
+}
+
+/// Start watching `folder`, replacing any running watcher
+fn start(app: AppHandle, folder: &Path) -> Result<(), String> {
+    let mut watcher = fs_notify::recommended_watcher(move |result: fs_notify::Result<fs_notify::Event>| {
+        let Ok(event) = result else {
+            return;
+        };
+        // Created in place, or moved in from elsewhere
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))) {
+            return;
+        }
+        for path in event.paths {
+            if path.is_dir() {
+                report_folder(&app, &path);
+            } else if is_audio_file(&path) && path.is_file() {
+                report_when_settled(&app, path);
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to create folder watcher: {}", e))?;
+
+    watcher
+        .watch(folder, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", folder.display(), e))?;
+    *WATCHER.lock().unwrap() = Some(watcher);
+    Ok(())
+}
+
+fn stop() {
+    *WATCHER.lock().unwrap() = None;
+}
+
+/// Start the drop folder watcher if it was enabled in a previous session
+pub fn init(app: AppHandle) {
+    let settings = load_settings();
+    if let (true, Some(folder)) = (settings.enabled, settings.path) {
+        if let Err(e) = start(app, Path::new(&folder)) {
+            eprintln!("Drop folder watcher failed to start: {}", e);
+        }
+    }
+}
+
+/// Get the drop folder settings
+#[tauri::command]
+pub fn drop_folder_get_settings() -> DropFolderSettings {
+    load_settings()
+}
+
+/// Set the drop folder and whether it is watched, starting or stopping the watcher
+#[tauri::command]
+pub fn drop_folder_set_settings(settings: DropFolderSettings, app: AppHandle) -> Result<DropFolderSettings, String> {
+    match (settings.enabled, settings.path.as_deref()) {
+        (true, Some(folder)) if Path::new(folder).is_dir() => start(app, Path::new(folder))?,
+        (true, Some(folder)) => return Err(format!("{} is not a folder", folder)),
+        (true, None) => return Err("Choose a drop folder to watch".to_string()),
+        (false, _) => stop(),
+    }
+
+    let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    crate::write_atomic(&get_settings_path()?, json.as_bytes())?;
+    Ok(settings)
+}
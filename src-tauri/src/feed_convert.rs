@@ -0,0 +1,190 @@
+// Feed type detection and album -> publisher restructuring
+
+use crate::feed_xml::{parse_rss, render_document, RssDocument, XmlNode};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize)]
+pub struct FeedTypeInfo {
+    pub feed_type: String,
+    pub medium: Option<String>,
+    pub item_count: usize,
+    pub remote_item_count: usize,
+    pub artists: Vec<String>,
+    pub can_split_by_artist: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ConvertedFeed {
+    pub title: String,
+    pub feed_type: String,
+    pub guid: String,
+    pub xml: String,
+    pub saved_id: Option<String>,
+}
+
+// Channel elements that are specific to one feed and must not be copied into split feeds
+const PER_FEED_ELEMENTS: &[&str] = &[
+    "title",
+    "item",
+    "podcast:guid",
+    "podcast:medium",
+    "podcast:remoteItem",
+    "podcast:publisher",
+    "itunes:author",
+    "atom:link",
+    "lastBuildDate",
+];
+
+/// Get the artist for an item: item author, then artist person, then channel author
+fn item_artist(item: &XmlNode, channel: &XmlNode) -> String {
+    item.child_text("itunes:author")
+        .or_else(|| {
+            item.children_named("podcast:person")
+                .find(|p| p.attr("role").map(|r| r.eq_ignore_ascii_case("artist")).unwrap_or(false))
+                .map(|p| p.text.trim())
+        })
+        .or_else(|| channel.child_text("itunes:author"))
+        .unwrap_or("Unknown Artist")
+        .to_string()
+}
+
+/// Distinct artists in item order
+fn distinct_artists(doc: &RssDocument) -> Vec<String> {
+    let Some(channel) = doc.channel() else {
+        return Vec::new();
+    };
+    let mut artists: Vec<String> = Vec::new();
+    for item in doc.items() {
+        let artist = item_artist(item, channel);
+        if !artists.contains(&artist) {
+            artists.push(artist);
+        }
+    }
+    artists
+}
+
+/// Detect a feed's type and describe its structure
+#[tauri::command]
+pub fn feed_detect_type(xml: String) -> Result<FeedTypeInfo, String> {
+    let doc = parse_rss(&xml)?;
+    let channel = doc.channel().ok_or("Missing <channel> element")?;
+    let artists = distinct_artists(&doc);
+    let feed_type = crate::detect_feed_type(&xml);
+
+    Ok(FeedTypeInfo {
+        can_split_by_artist: feed_type == "album" && artists.len() > 1,
+        medium: channel.child_text("podcast:medium").map(str::to_string),
+        item_count: doc.items().len(),
+        remote_item_count: channel.children_named("podcast:remoteItem").count(),
+        artists,
+        feed_type,
+    })
+}
+
+/// Split a multi-artist album into one album per artist plus a publisher feed
+/// listing them. When `save` is true the new feeds are written to the local library.
+#[tauri::command]
+pub fn feed_convert_to_publisher(xml: String, save: bool) -> Result<Vec<ConvertedFeed>, String> {
+    let doc = parse_rss(&xml)?;
+    let channel = doc.channel().ok_or("Missing <channel> element")?;
+    let artists = distinct_artists(&doc);
+    if artists.len() < 2 {
+        return Err("Feed has a single artist; nothing to split".to_string());
+    }
+
+    let source_title = channel.child_text("title").unwrap_or("Untitled");
+    let publisher_guid = Uuid::new_v4().to_string();
+    let shared: Vec<XmlNode> = channel
+        .children
+        .iter()
+        .filter(|c| !PER_FEED_ELEMENTS.contains(&c.name.as_str()))
+        .cloned()
+        .collect();
+
+    let mut converted = Vec::new();
+    let mut remote_items = Vec::new();
+
+    for artist in &artists {
+        let guid = Uuid::new_v4().to_string();
+        let title = format!("{} ({})", source_title, artist);
+
+        let mut album_channel = XmlNode::new("channel")
+            .with_child(XmlNode::new("title").with_text(&title))
+            .with_child(XmlNode::new("itunes:author").with_text(artist))
+            .with_child(XmlNode::new("podcast:guid").with_text(&guid))
+            .with_child(XmlNode::new("podcast:medium").with_text("music"))
+            .with_child(
+                XmlNode::new("podcast:publisher").with_child(
+                    XmlNode::new("podcast:remoteItem")
+                        .with_attr("feedGuid", &publisher_guid)
+                        .with_attr("medium", "publisher"),
+                ),
+            );
+        album_channel.children.extend(shared.iter().cloned());
+        album_channel.children.extend(
+            doc.items()
+                .into_iter()
+                .filter(|item| &item_artist(item, channel) == artist)
+                .cloned(),
+        );
+
+        let root = XmlNode {
+            name: "rss".to_string(),
+            attrs: doc.root.attrs.clone(),
+            children: vec![album_channel],
+            text: String::new(),
+        };
+
+        remote_items.push(
+            XmlNode::new("podcast:remoteItem")
+                .with_attr("feedGuid", &guid)
+                .with_attr("medium", "music"),
+        );
+        converted.push(ConvertedFeed {
+            title,
+            feed_type: "album".to_string(),
+            guid,
+            xml: render_document(&root),
+            saved_id: None,
+        });
+    }
+
+    let mut publisher_channel = XmlNode::new("channel")
+        .with_child(XmlNode::new("title").with_text(source_title))
+        .with_child(XmlNode::new("podcast:guid").with_text(&publisher_guid))
+        .with_child(XmlNode::new("podcast:medium").with_text("publisher"));
+    for element in ["description", "link", "language", "image", "itunes:image"] {
+        if let Some(node) = channel.child(element) {
+            publisher_channel.children.push(node.clone());
+        }
+    }
+    publisher_channel.children.extend(remote_items);
+
+    let publisher_root = XmlNode {
+        name: "rss".to_string(),
+        attrs: doc.root.attrs.clone(),
+        children: vec![publisher_channel],
+        text: String::new(),
+    };
+    converted.insert(
+        0,
+        ConvertedFeed {
+            title: source_title.to_string(),
+            feed_type: "publisher".to_string(),
+            guid: publisher_guid,
+            xml: render_document(&publisher_root),
+            saved_id: None,
+        },
+    );
+
+    if save {
+        for feed in &mut converted {
+            let saved =
+                crate::save_feed_local(None, feed.title.clone(), feed.feed_type.clone(), feed.xml.clone(), None)?;
+            feed.saved_id = Some(saved.id);
+        }
+    }
+
+    Ok(converted)
+}
@@ -0,0 +1,164 @@
+// Publishing the feed XML itself somewhere other than Nostr/Blossom, so the feed URL
+// can live on the artist's own bucket, CDN, or WebDAV host. Credentials are kept in
+// the encrypted settings store and never returned to the frontend once saved.
+
+use crate::feed_xml::{parse_xml, render_document};
+use crate::publish::{collect_local_refs, rewrite_local_refs};
+use crate::storage::{self, StorageTarget};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+// Name of the S3 publishing credentials in the encrypted settings store
+const S3_SETTINGS: &str = "s3_publish";
+
+const FEED_CONTENT_TYPE: &str = "application/rss+xml; charset=utf-8";
+
+// Feeds change on every publish, so CDNs and readers should revalidate them soon
+const FEED_CACHE_CONTROL: &str = "public, max-age=300";
+
+const DEFAULT_FEED_FILE: &str = "feed.xml";
+
+/// S3-compatible account (AWS S3, Cloudflare R2, Backblaze B2, MinIO, ...)
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct S3PublishConfig {
+    pub endpoint: String, // e.g. "https://<account>.r2.cloudflarestorage.com"
+    pub region: String, // "auto" for R2
+    pub access_key_id: String,
+    pub secret_access_key: String, // empty when read back; empty on save keeps the stored one
+    pub public_base_url: Option<String>, // CDN or public bucket URL the feed is served from
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HostedFeed {
+    pub feed_id: String,
+    pub url: String,
+    pub sha256: String,
+    pub size: u64,
+}
+
+/// Upload a feed's local files to `target` and save the feed with their hosted URLs,
+/// so the XML published afterwards has no file:// references left
+async fn host_local_assets(feed: crate::LocalFeed, target: &StorageTarget, app: &AppHandle) -> Result<crate::LocalFeed, String> {
+    let mut root = parse_xml(&feed.xml)?;
+    let mut refs = Vec::new();
+    collect_local_refs(&root, &mut refs);
+    if refs.is_empty() {
+        return Ok(feed);
+    }
+
+    let mut urls = HashMap::new();
+    for source in &refs {
+        let file_path = crate::file_url_path(source).ok_or_else(|| format!("Not a local file: {}", source))?;
+        let file_path = file_path.to_string_lossy();
+        let stored = storage::upload_file(target, &file_path, None, Some(app.clone()))
+            .await
+            .map_err(|e| format!("Upload of {} failed: {}", file_path, e))?;
+        urls.insert(source.as_str(), stored.url);
+    }
+    let url_refs: HashMap<&str, &str> = urls.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    rewrite_local_refs(&mut root, &url_refs);
+    crate::save_feed_local(Some(feed.id), feed.title, feed.feed_type, render_document(&root), None)
+}
+
+/// Get the saved S3 publishing account, without its secret key
+#[tauri::command]
+pub fn s3_publish_get_config() -> Result<Option<S3PublishConfig>, String> {
+    let config: Option<S3PublishConfig> = crate::secure_settings::get(S3_SETTINGS)?;
+    Ok(config.map(|config| S3PublishConfig {
+        secret_access_key: String::new(),
+        ..config
+    }))
+}
+
+/// Save the S3 publishing account (encrypted), or forget it with None. An empty
+/// secret key keeps the one already saved.
+#[tauri::command]
+pub fn s3_publish_set_config(config: Option<S3PublishConfig>) -> Result<(), String> {
+    let Some(mut config) = config else {
+        return crate::secure_settings::remove(S3_SETTINGS);
+    };
+    if config.endpoint.trim().is_empty() || config.access_key_id.trim().is_empty() {
+        return Err("An endpoint and access key ID are required".to_string());
+    }
+    if config.secret_access_key.is_empty() {
+        let saved: Option<S3PublishConfig> = crate::secure_settings::get(S3_SETTINGS)?;
+        config.secret_access_key = saved
+            .map(|saved| saved.secret_access_key)
+            .filter(|secret| !secret.is_empty())
+            .ok_or("A secret access key is required")?;
+    }
+    if config.region.trim().is_empty() {
+        config.region = "auto".to_string();
+    }
+    crate::secure_settings::put(S3_SETTINGS, &config)
+}
+
+/// Upload a library feed's XML to `bucket` at `key` (e.g. "podcast/feed.xml") using
+/// the saved S3 account, returning the public feed URL. Local files the feed still
+/// references are uploaded next to it first.
+#[tauri::command]
+pub async fn publish_feed_s3(feed_id: String, bucket: String, key: String, app: AppHandle) -> Result<HostedFeed, String> {
+    let config: S3PublishConfig =
+        crate::secure_settings::get(S3_SETTINGS)?.ok_or("No S3 account saved for publishing")?;
+    if bucket.trim().is_empty() || key.trim().is_empty() {
+        return Err("A bucket and object key are required".to_string());
+    }
+    let key = key.trim().trim_start_matches('/');
+    let _operation = crate::shutdown::begin("publish", &feed_id);
+
+    let target = StorageTarget::S3 {
+        endpoint: config.endpoint,
+        region: config.region,
+        bucket: bucket.trim().to_string(),
+        access_key_id: config.access_key_id,
+        secret_access_key: config.secret_access_key,
+        prefix: key.rsplit_once('/').map(|(dir, _)| format!("{}/", dir)),
+        public_base_url: config.public_base_url,
+    };
+    let feed = host_local_assets(crate::load_feed_local(feed_id)?, &target, &app).await?;
+
+    let bytes = feed.xml.into_bytes();
+    let sha256 = hex::encode(Sha256::digest(&bytes));
+    let size = bytes.len() as u64;
+    let url = storage::put_s3_object(&target, key, bytes, FEED_CONTENT_TYPE, Some(FEED_CACHE_CONTROL)).await?;
+
+    Ok(HostedFeed {
+        feed_id: feed.id,
+        url,
+        sha256,
+        size,
+    })
+}
+
+/// Upload a library feed's XML as `file_name` (default feed.xml) to the WebDAV target
+/// selected for the feed, next to its media, returning the public feed URL. Local
+/// files the feed still references are uploaded there first.
+#[tauri::command]
+pub async fn publish_feed_webdav(feed_id: String, file_name: Option<String>, app: AppHandle) -> Result<HostedFeed, String> {
+    let target = storage::feed_target(&feed_id)?
+        .filter(|target| matches!(target, StorageTarget::Webdav { .. }))
+        .ok_or("Select a WebDAV storage target for this feed first")?;
+    let file_name = file_name
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_FEED_FILE.to_string());
+    if file_name.contains('/') || file_name.contains('\\') {
+        return Err("The feed file name must not contain path separators".to_string());
+    }
+    let _operation = crate::shutdown::begin("publish", &feed_id);
+    let feed = host_local_assets(crate::load_feed_local(feed_id)?, &target, &app).await?;
+
+    let bytes = feed.xml.into_bytes();
+    let sha256 = hex::encode(Sha256::digest(&bytes));
+    let size = bytes.len() as u64;
+    let url = storage::put_webdav_object(&target, file_name.trim(), bytes, FEED_CONTENT_TYPE).await?;
+
+    Ok(HostedFeed {
+        feed_id: feed.id,
+        url,
+        sha256,
+        size,
+    })
+}
@@ -0,0 +1,1048 @@
+// Structured feed model, podcast-namespace RSS generation, and parsing back into the model.
+// Field names follow the frontend's Album/Track types so editor state can be passed as-is.
+
+use crate::feed_xml::{parse_rss, parse_xml, render_document, XmlNode};
+use crate::formatting::{days_from_civil, format_rfc822, normalize_itunes_duration};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const GENERATOR_NAME: &str = "MSP 2.0 - Music Side Project Studio";
+pub const PODCAST_NS: &str = "https://podcastindex.org/namespace/1.0";
+const ITUNES_NS: &str = "http://www.itunes.com/dtds/podcast-1.0.dtd";
+
+// MIME type of Podcasting 2.0 JSON chapters files
+pub const CHAPTERS_TYPE: &str = "application/json+chapters";
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PersonRole {
+    pub group: String,
+    pub role: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PersonModel {
+    pub name: String,
+    pub href: Option<String>,
+    pub img: Option<String>,
+    pub npub: Option<String>,
+    pub roles: Vec<PersonRole>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ValueRecipientModel {
+    pub name: String,
+    pub address: String,
+    pub split: u32,
+    #[serde(rename = "type")]
+    pub recipient_type: String, // "node" or "lnaddress"
+    pub custom_key: Option<String>,
+    pub custom_value: Option<String>,
+    pub fee: bool, // split is a percentage taken before the shares are divided
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ValueModel {
+    pub suggested: Option<String>,
+    pub recipients: Vec<ValueRecipientModel>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FundingModel {
+    pub url: String,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RemoteItemModel {
+    pub feed_guid: String,
+    pub feed_url: Option<String>,
+    pub item_guid: Option<String>,
+    pub medium: Option<String>,
+    pub title: Option<String>,
+    pub image: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PublisherRefModel {
+    pub feed_guid: String,
+    pub feed_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NamespaceModel {
+    pub prefix: String,
+    pub uri: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AlternateEnclosureModel {
+    pub url: String,
+    #[serde(rename = "type")]
+    pub mime_type: String,
+    pub length: u64,
+    pub bitrate: Option<u32>, // bits per second
+    pub title: Option<String>,
+    pub default: bool,
+    pub sha256: Option<String>, // hex; written as an SRI integrity value
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TrackModel {
+    pub track_number: u32,
+    pub episode: Option<u32>,
+    pub title: String,
+    pub author: Option<String>, // per-track artist, for compilations
+    pub description: String,
+    pub pub_date: String,
+    pub guid: String,
+    pub enclosure_url: String,
+    pub enclosure_length: String,
+    pub enclosure_type: String,
+    pub duration: String,
+    pub explicit: bool,
+    pub track_art_url: Option<String>,
+    pub transcript_url: Option<String>,
+    pub transcript_type: Option<String>,
+    pub transcript_language: Option<String>,
+    pub transcript_rel: Option<String>,
+    pub chapters_url: Option<String>, // Podcasting 2.0 JSON chapters
+    pub alternate_enclosures: Vec<AlternateEnclosureModel>,
+    pub override_persons: bool,
+    pub persons: Vec<PersonModel>,
+    pub override_value: bool,
+    pub value: Option<ValueModel>,
+    pub extensions: Vec<String>, // raw XML of custom namespaced elements, kept as-is
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FeedModel {
+    pub title: String,
+    pub author: String,
+    pub artist_npub: Option<String>,
+    pub description: String,
+    pub link: String,
+    pub language: String,
+    pub pub_date: String,
+    pub last_build_date: String,
+    pub podcast_guid: String,
+    pub medium: String, // "music", "video", or "publisher"
+    pub locked: bool,
+    pub locked_owner: String,
+    pub categories: Vec<String>,
+    pub keywords: String,
+    pub explicit: bool,
+    pub owner_name: String,
+    pub owner_email: String,
+    pub image_url: String,
+    pub image_title: String,
+    pub image_link: String,
+    pub image_description: String,
+    pub managing_editor: String,
+    pub web_master: String,
+    pub persons: Vec<PersonModel>,
+    pub value: ValueModel,
+    pub funding: Vec<FundingModel>,
+    pub publisher: Option<PublisherRefModel>,
+    pub remote_items: Vec<RemoteItemModel>,
+    pub tracks: Vec<TrackModel>,
+    pub op3: bool,
+    pub namespaces: Vec<NamespaceModel>, // extra xmlns declarations used by extensions
+    pub extensions: Vec<String>,
+}
+
+/// Generator tag text, fingerprinted with the app version
+pub fn generator() -> String {
+    format!("{} (desktop {})", GENERATOR_NAME, crate::APP_VERSION)
+}
+
+/// Normalize a model date to RFC-822: empty uses now, ISO dates are converted,
+/// anything else (already RFC-822) is kept
+fn feed_date(value: &str) -> String {
+    let value = value.trim();
+    if value.is_empty() {
+        return format_rfc822(crate::get_current_timestamp().unwrap_or_default());
+    }
+
+    let date = value.get(..10).unwrap_or_default();
+    let mut parts = date.split('-').map(|p| p.parse::<i64>().ok());
+    let (Some(Some(year)), Some(Some(month)), Some(Some(day))) = (parts.next(), parts.next(), parts.next()) else {
+        return value.to_string();
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return value.to_string();
+    }
+
+    let time: Vec<u64> = value
+        .get(11..19)
+        .map(|t| t.split(':').filter_map(|p| p.parse().ok()).collect())
+        .unwrap_or_default();
+    let secs = match time.as_slice() {
+        [h, m, s] => h * 3600 + m * 60 + s,
+        _ => 0,
+    };
+
+    let days = days_from_civil(year, month as u32, day as u32);
+    format_rfc822((days.max(0) as u64) * 86400 + secs)
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct FeedStampOptions {
+    pub auto: bool,
+    pub last_build_date: Option<String>,
+    pub pub_date: Option<String>,
+    pub generator: Option<String>,
+}
+
+impl Default for FeedStampOptions {
+    fn default() -> Self {
+        FeedStampOptions {
+            auto: true,
+            last_build_date: None,
+            pub_date: None,
+            generator: None,
+        }
+    }
+}
+
+/// Set a channel element's text, inserting it ahead of the items when missing.
+/// Returns whether anything changed.
+fn set_channel_text(channel: &mut XmlNode, name: &str, text: &str) -> bool {
+    if let Some(node) = channel.child_mut(name) {
+        if node.text == text {
+            return false;
+        }
+        node.text = text.to_string();
+        return true;
+    }
+    let index = channel
+        .children
+        .iter()
+        .position(|c| c.name == "item")
+        .unwrap_or(channel.children.len());
+    channel.children.insert(index, XmlNode::new(name).with_text(text));
+    true
+}
+
+/// Hash of a feed's content, ignoring the tags stamping maintains on its own
+fn content_hash(root: &XmlNode) -> String {
+    let mut root = root.clone();
+    if let Some(channel) = root.child_mut("channel") {
+        channel.remove_children("lastBuildDate");
+        channel.remove_children("generator");
+    }
+    hex::encode(Sha256::digest(root.to_xml(0).as_bytes()))
+}
+
+/// Whether two versions of a feed's XML differ only in the tags stamping maintains.
+/// XML that cannot be parsed is compared as text.
+pub fn same_content(a: &str, b: &str) -> bool {
+    match (parse_rss(a), parse_rss(b)) {
+        (Ok(a), Ok(b)) => content_hash(&a.root) == content_hash(&b.root),
+        _ => a == b,
+    }
+}
+
+/// Maintain lastBuildDate, pubDates, and the generator tag, touching only the nodes
+/// whose value changes. lastBuildDate moves only when it is missing or the content
+/// differs from `previous` (the feed as last saved; None for a new feed). Explicit
+/// overrides always win; with `auto` off, only the overrides are applied. Returns
+/// whether the document changed.
+pub fn stamp_feed_document(root: &mut XmlNode, previous: Option<&XmlNode>, options: &FeedStampOptions) -> bool {
+    let content_changed = match previous {
+        Some(previous) => content_hash(previous) != content_hash(root),
+        None => true,
+    };
+    let Some(channel) = root.child_mut("channel") else {
+        return false;
+    };
+    let mut changed = false;
+
+    if let Some(date) = &options.last_build_date {
+        changed |= set_channel_text(channel, "lastBuildDate", &feed_date(date));
+    } else if options.auto && (content_changed || channel.child("lastBuildDate").is_none()) {
+        changed |= set_channel_text(channel, "lastBuildDate", &feed_date(""));
+    }
+
+    if let Some(date) = &options.pub_date {
+        changed |= set_channel_text(channel, "pubDate", &feed_date(date));
+    } else if options.auto {
+        let current = channel.child_text("pubDate").unwrap_or_default().to_string();
+        changed |= set_channel_text(channel, "pubDate", &feed_date(&current));
+    }
+
+    if let Some(generator) = &options.generator {
+        changed |= set_channel_text(channel, "generator", generator);
+    } else if options.auto {
+        changed |= set_channel_text(channel, "generator", &generator());
+    }
+
+    if options.auto {
+        for item in channel.children.iter_mut().filter(|c| c.name == "item") {
+            let current = item.child_text("pubDate").unwrap_or_default().to_string();
+            let date = feed_date(&current);
+            if item.child("pubDate").is_none() || date != current {
+                item.set_child_text("pubDate", &date);
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// Stamp feed XML on save/publish against the previously saved XML (see
+/// `stamp_feed_document`). XML that cannot be parsed, or that needs no change, is
+/// returned as it was.
+pub fn stamp_feed_xml(xml: &str, previous: Option<&str>, options: &FeedStampOptions) -> String {
+    let nothing_to_do = !options.auto
+        && options.last_build_date.is_none()
+        && options.pub_date.is_none()
+        && options.generator.is_none();
+    if nothing_to_do {
+        return xml.to_string();
+    }
+
+    let Ok(mut doc) = parse_rss(xml) else {
+        return xml.to_string();
+    };
+    // A previous version that no longer parses counts as changed content
+    let previous = previous.and_then(|p| parse_rss(p).ok());
+    if stamp_feed_document(&mut doc.root, previous.as_ref().map(|p| &p.root), options) {
+        render_document(&doc.root)
+    } else {
+        xml.to_string()
+    }
+}
+
+/// Apply the OP3 analytics prefix to an enclosure URL
+fn op3_url(url: &str, podcast_guid: &str) -> String {
+    if url.is_empty() || url.starts_with("https://op3.dev/e") {
+        return url.to_string();
+    }
+    let pg = if podcast_guid.is_empty() { String::new() } else { format!(",pg={}", podcast_guid) };
+    format!("https://op3.dev/e{}/{}", pg, url.strip_prefix("https://").unwrap_or(url))
+}
+
+/// One <podcast:person> per role
+fn person_nodes(person: &PersonModel) -> Vec<XmlNode> {
+    person
+        .roles
+        .iter()
+        .map(|role| {
+            let mut node = XmlNode::new("podcast:person");
+            for (key, value) in [("href", &person.href), ("img", &person.img), ("npub", &person.npub)] {
+                if let Some(value) = value.as_deref().filter(|v| !v.is_empty()) {
+                    node = node.with_attr(key, value);
+                }
+            }
+            node.with_attr("group", &role.group)
+                .with_attr("role", &role.role)
+                .with_text(&person.name)
+        })
+        .collect()
+}
+
+/// <podcast:value> block, or None when there are no recipients
+pub fn value_node(value: &ValueModel) -> Option<XmlNode> {
+    if value.recipients.is_empty() {
+        return None;
+    }
+
+    let method = if value.recipients.iter().any(|r| r.recipient_type == "lnaddress") {
+        "lnaddress"
+    } else {
+        "keysend"
+    };
+    let mut node = XmlNode::new("podcast:value")
+        .with_attr("type", "lightning")
+        .with_attr("method", method);
+    if let Some(suggested) = value.suggested.as_deref().filter(|s| !s.is_empty()) {
+        node = node.with_attr("suggested", suggested);
+    }
+
+    for recipient in &value.recipients {
+        let recipient_type = if recipient.recipient_type.is_empty() { "node" } else { &recipient.recipient_type };
+        let mut child = XmlNode::new("podcast:valueRecipient")
+            .with_attr("name", &recipient.name)
+            .with_attr("address", &recipient.address)
+            .with_attr("split", &recipient.split.to_string())
+            .with_attr("type", recipient_type);
+        if let Some(key) = recipient.custom_key.as_deref().filter(|k| !k.is_empty()) {
+            child = child.with_attr("customKey", key);
+        }
+        if let Some(custom) = recipient.custom_value.as_deref().filter(|v| !v.is_empty()) {
+            child = child.with_attr("customValue", custom);
+        }
+        if recipient.fee {
+            child = child.with_attr("fee", "true");
+        }
+        node = node.with_child(child);
+    }
+    Some(node)
+}
+
+/// <podcast:alternateEnclosure> for one encoding of a track
+pub fn alternate_enclosure_node(alternate: &AlternateEnclosureModel) -> XmlNode {
+    let mut node = XmlNode::new("podcast:alternateEnclosure")
+        .with_attr("type", &alternate.mime_type)
+        .with_attr("length", &alternate.length.to_string());
+    if let Some(bitrate) = alternate.bitrate {
+        node = node.with_attr("bitrate", &bitrate.to_string());
+    }
+    if let Some(title) = alternate.title.as_deref().filter(|t| !t.is_empty()) {
+        node = node.with_attr("title", title);
+    }
+    if alternate.default {
+        node = node.with_attr("default", "true");
+    }
+    node = node.with_child(XmlNode::new("podcast:source").with_attr("uri", &alternate.url));
+    if let Some(digest) = alternate.sha256.as_deref().and_then(|h| hex::decode(h).ok()) {
+        node = node.with_child(
+            XmlNode::new("podcast:integrity")
+                .with_attr("type", "sri")
+                .with_attr("value", &format!("sha256-{}", BASE64.encode(digest))),
+        );
+    }
+    node
+}
+
+fn remote_item_node(item: &RemoteItemModel) -> XmlNode {
+    let mut node = XmlNode::new("podcast:remoteItem");
+    if !item.feed_guid.is_empty() {
+        node = node.with_attr("feedGuid", &item.feed_guid);
+    }
+    for (key, value) in [("feedUrl", &item.feed_url), ("itemGuid", &item.item_guid)] {
+        if let Some(value) = value.as_deref().filter(|v| !v.is_empty()) {
+            node = node.with_attr(key, value);
+        }
+    }
+    node = node.with_attr("medium", item.medium.as_deref().unwrap_or("music"));
+    if let Some(image) = item.image.as_deref().filter(|v| !v.is_empty()) {
+        node = node.with_attr("feedImg", image);
+    }
+    if let Some(title) = item.title.as_deref() {
+        node = node.with_text(title);
+    }
+    node
+}
+
+fn track_node(track: &TrackModel, feed: &FeedModel) -> XmlNode {
+    let mut item = XmlNode::new("item").with_child(XmlNode::new("title").with_text(&track.title));
+    if let Some(author) = track.author.as_deref().filter(|a| !a.is_empty()) {
+        item = item.with_child(XmlNode::new("itunes:author").with_text(author));
+    }
+    if !track.description.is_empty() {
+        item = item.with_child(XmlNode::new("description").with_text(&track.description));
+    }
+    item = item
+        .with_child(XmlNode::new("pubDate").with_text(&feed_date(&track.pub_date)))
+        .with_child(
+            XmlNode::new("guid")
+                .with_attr("isPermaLink", "false")
+                .with_text(&track.guid),
+        );
+
+    if let Some(url) = track.transcript_url.as_deref().filter(|u| !u.is_empty()) {
+        let mut transcript = XmlNode::new("podcast:transcript")
+            .with_attr("url", url)
+            .with_attr("type", track.transcript_type.as_deref().unwrap_or("application/srt"));
+        if let Some(language) = track.transcript_language.as_deref().filter(|l| !l.is_empty()) {
+            transcript = transcript.with_attr("language", language);
+        }
+        if let Some(rel) = track.transcript_rel.as_deref().filter(|r| !r.is_empty()) {
+            transcript = transcript.with_attr("rel", rel);
+        }
+        item = item.with_child(transcript);
+    }
+    if let Some(url) = track.chapters_url.as_deref().filter(|u| !u.is_empty()) {
+        item = item.with_child(
+            XmlNode::new("podcast:chapters")
+                .with_attr("url", url)
+                .with_attr("type", CHAPTERS_TYPE),
+        );
+    }
+
+    // Track artwork falls back to the album cover
+    let art = track
+        .track_art_url
+        .as_deref()
+        .filter(|u| !u.is_empty())
+        .unwrap_or(&feed.image_url);
+    if !art.is_empty() {
+        item = item.with_child(XmlNode::new("itunes:image").with_attr("href", art));
+    }
+
+    let enclosure_url = if feed.op3 {
+        op3_url(&track.enclosure_url, &feed.podcast_guid)
+    } else {
+        track.enclosure_url.clone()
+    };
+    let length = if track.enclosure_length.is_empty() { "0" } else { &track.enclosure_length };
+    item = item
+        .with_child(
+            XmlNode::new("enclosure")
+                .with_attr("url", &enclosure_url)
+                .with_attr("length", length)
+                .with_attr("type", &track.enclosure_type),
+        )
+        .with_child(XmlNode::new("itunes:duration").with_text(&normalize_itunes_duration(&track.duration)))
+        .with_child(XmlNode::new("podcast:season").with_text("1"))
+        .with_child(
+            XmlNode::new("podcast:episode").with_text(&track.episode.unwrap_or(track.track_number).to_string()),
+        )
+        .with_child(XmlNode::new("itunes:explicit").with_text(if track.explicit { "true" } else { "false" }));
+    item.children.extend(track.alternate_enclosures.iter().map(alternate_enclosure_node));
+
+    if track.override_persons {
+        item.children.extend(track.persons.iter().flat_map(person_nodes));
+    }
+
+    let value = match (&track.value, track.override_value) {
+        (Some(value), true) => value,
+        _ => &feed.value,
+    };
+    if let Some(node) = value_node(value) {
+        item = item.with_child(node);
+    }
+    item.children.extend(extension_nodes(&track.extensions));
+
+    item
+}
+
+/// Build the RSS tree for a feed model
+pub fn build_feed_document(feed: &FeedModel) -> XmlNode {
+    let medium = if feed.medium.is_empty() { "music" } else { &feed.medium };
+    let mut channel = XmlNode::new("channel")
+        .with_child(XmlNode::new("title").with_text(&feed.title))
+        .with_child(XmlNode::new("itunes:author").with_text(&feed.author))
+        .with_child(XmlNode::new("description").with_text(&feed.description));
+
+    if !feed.link.is_empty() {
+        channel = channel.with_child(XmlNode::new("link").with_text(&feed.link));
+    }
+    let language = if feed.language.is_empty() { "en" } else { &feed.language };
+    channel = channel
+        .with_child(XmlNode::new("language").with_text(language))
+        .with_child(XmlNode::new("generator").with_text(&generator()))
+        .with_child(XmlNode::new("pubDate").with_text(&feed_date(&feed.pub_date)))
+        .with_child(XmlNode::new("lastBuildDate").with_text(&feed_date(&feed.last_build_date)));
+
+    if feed.locked && !feed.locked_owner.is_empty() {
+        channel = channel.with_child(
+            XmlNode::new("podcast:locked")
+                .with_attr("owner", &feed.locked_owner)
+                .with_text("yes"),
+        );
+    }
+    if !feed.podcast_guid.is_empty() {
+        channel = channel.with_child(XmlNode::new("podcast:guid").with_text(&feed.podcast_guid));
+    }
+    if let Some(npub) = feed.artist_npub.as_deref().filter(|n| !n.is_empty()) {
+        channel = channel.with_child(
+            XmlNode::new("podcast:txt")
+                .with_attr("purpose", "npub")
+                .with_text(npub),
+        );
+    }
+
+    // Nested categories are carried as extensions; only default when there are none at all
+    let has_raw_category = feed.extensions.iter().any(|f| f.starts_with("<itunes:category"));
+    let default_categories = if has_raw_category { vec![] } else { vec!["Music".to_string()] };
+    let categories = if feed.categories.is_empty() { &default_categories } else { &feed.categories };
+    for category in categories {
+        channel = channel.with_child(XmlNode::new("itunes:category").with_attr("text", category));
+    }
+
+    for (name, value) in [
+        ("itunes:keywords", &feed.keywords),
+        ("managingEditor", &feed.managing_editor),
+        ("webMaster", &feed.web_master),
+    ] {
+        if !value.is_empty() {
+            channel = channel.with_child(XmlNode::new(name).with_text(value));
+        }
+    }
+
+    if !feed.image_url.is_empty() {
+        let image_title = if feed.image_title.is_empty() { &feed.title } else { &feed.image_title };
+        let mut image = XmlNode::new("image")
+            .with_child(XmlNode::new("url").with_text(&feed.image_url))
+            .with_child(XmlNode::new("title").with_text(image_title));
+        if !feed.image_link.is_empty() {
+            image = image.with_child(XmlNode::new("link").with_text(&feed.image_link));
+        }
+        if !feed.image_description.is_empty() {
+            image = image.with_child(XmlNode::new("description").with_text(&feed.image_description));
+        }
+        channel = channel
+            .with_child(image)
+            .with_child(XmlNode::new("itunes:image").with_attr("href", &feed.image_url));
+    }
+
+    channel = channel
+        .with_child(XmlNode::new("podcast:medium").with_text(medium))
+        .with_child(XmlNode::new("itunes:explicit").with_text(if feed.explicit { "true" } else { "false" }));
+
+    if !feed.owner_name.is_empty() || !feed.owner_email.is_empty() {
+        let mut owner = XmlNode::new("itunes:owner");
+        if !feed.owner_name.is_empty() {
+            owner = owner.with_child(XmlNode::new("itunes:name").with_text(&feed.owner_name));
+        }
+        if !feed.owner_email.is_empty() {
+            owner = owner.with_child(XmlNode::new("itunes:email").with_text(&feed.owner_email));
+        }
+        channel = channel.with_child(owner);
+    }
+
+    channel.children.extend(feed.persons.iter().flat_map(person_nodes));
+    if let Some(node) = value_node(&feed.value) {
+        channel = channel.with_child(node);
+    }
+    for funding in feed.funding.iter().filter(|f| !f.url.is_empty()) {
+        channel = channel.with_child(
+            XmlNode::new("podcast:funding")
+                .with_attr("url", &funding.url)
+                .with_text(&funding.text),
+        );
+    }
+
+    if let Some(publisher) = feed.publisher.as_ref().filter(|p| !p.feed_guid.is_empty() || p.feed_url.is_some()) {
+        let mut remote = XmlNode::new("podcast:remoteItem").with_attr("medium", "publisher");
+        if !publisher.feed_guid.is_empty() {
+            remote = remote.with_attr("feedGuid", &publisher.feed_guid);
+        }
+        if let Some(url) = publisher.feed_url.as_deref().filter(|u| !u.is_empty()) {
+            remote = remote.with_attr("feedUrl", url);
+        }
+        channel = channel.with_child(XmlNode::new("podcast:publisher").with_child(remote));
+    }
+
+    channel.children.extend(feed.remote_items.iter().map(remote_item_node));
+    channel.children.extend(extension_nodes(&feed.extensions));
+    channel.children.extend(feed.tracks.iter().map(|t| track_node(t, feed)));
+
+    let mut rss = XmlNode::new("rss")
+        .with_attr("xmlns:podcast", PODCAST_NS)
+        .with_attr("xmlns:itunes", ITUNES_NS);
+    for ns in &feed.namespaces {
+        if !ns.prefix.is_empty() && ns.prefix != "podcast" && ns.prefix != "itunes" {
+            rss = rss.with_attr(&format!("xmlns:{}", ns.prefix), &ns.uri);
+        }
+    }
+    rss.with_attr("version", "2.0").with_child(channel)
+}
+
+/// Render a feed model as a complete RSS document
+pub fn generate_feed(feed: &FeedModel) -> String {
+    render_document(&build_feed_document(feed))
+}
+
+/// Generate podcast-namespace RSS from a structured feed model
+#[tauri::command]
+pub fn generate_feed_xml(model: FeedModel) -> Result<String, String> {
+    if model.title.trim().is_empty() {
+        return Err("Feed title is required".to_string());
+    }
+    let fragments = model.extensions.iter().chain(model.tracks.iter().flat_map(|t| &t.extensions));
+    for fragment in fragments {
+        parse_xml(fragment).map_err(|e| format!("Invalid extension element: {}", e))?;
+    }
+    Ok(generate_feed(&model))
+}
+
+// Channel and item elements the model reads; other namespaced elements are
+// carried through as extensions
+const CHANNEL_ELEMENTS: &[&str] = &[
+    "itunes:author",
+    "podcast:locked",
+    "podcast:guid",
+    "itunes:keywords",
+    "itunes:image",
+    "podcast:medium",
+    "itunes:explicit",
+    "itunes:owner",
+    "podcast:person",
+    "podcast:value",
+    "podcast:funding",
+    "podcast:publisher",
+    "podcast:remoteItem",
+];
+const ITEM_ELEMENTS: &[&str] = &[
+    "itunes:author",
+    "podcast:chapters",
+    "podcast:alternateEnclosure",
+    "itunes:image",
+    "itunes:duration",
+    "podcast:season",
+    "podcast:episode",
+    "itunes:explicit",
+    "podcast:person",
+    "podcast:value",
+];
+
+// Transcript attributes the track model holds; transcripts with others stay raw
+const TRANSCRIPT_ATTRS: &[&str] = &["url", "type", "language", "rel"];
+
+/// Flat categories map to the model; nested subcategories are kept raw
+fn is_flat_category(node: &XmlNode) -> bool {
+    node.name == "itunes:category" && node.children.is_empty() && node.attr("text").is_some()
+}
+
+/// The single transcript the track model holds, if the item has one it can hold losslessly
+fn modeled_transcript(item: &XmlNode) -> Option<&XmlNode> {
+    item.children_named("podcast:transcript")
+        .next()
+        .filter(|t| t.children.is_empty() && t.attrs.iter().all(|(k, _)| TRANSCRIPT_ATTRS.contains(&k.as_str())))
+}
+
+/// Extra xmlns declarations on a node, other than the ones the generator always writes
+fn namespace_declarations(node: &XmlNode) -> impl Iterator<Item = NamespaceModel> + '_ {
+    node.attrs.iter().filter_map(|(key, uri)| {
+        let prefix = key.strip_prefix("xmlns:")?;
+        (prefix != "podcast" && prefix != "itunes").then(|| NamespaceModel {
+            prefix: prefix.to_string(),
+            uri: uri.clone(),
+        })
+    })
+}
+
+/// Raw XML of the namespaced children the model does not read
+fn extension_fragments(
+    node: &XmlNode,
+    known: &[&str],
+    is_modeled: impl Fn(&XmlNode) -> bool,
+) -> Vec<String> {
+    node.children
+        .iter()
+        .filter(|c| c.name.contains(':') && !known.contains(&c.name.as_str()) && !is_modeled(*c))
+        .map(|c| c.to_xml(0).trim_end().to_string())
+        .collect()
+}
+
+/// Parse extension fragments back into nodes, skipping any that are not valid XML
+fn extension_nodes(fragments: &[String]) -> impl Iterator<Item = XmlNode> + '_ {
+    fragments.iter().filter_map(|f| parse_xml(f).ok())
+}
+
+fn text_of(node: &XmlNode, name: &str) -> String {
+    node.child_text(name).unwrap_or_default().to_string()
+}
+
+fn is_explicit(node: &XmlNode) -> bool {
+    matches!(
+        node.child_text("itunes:explicit").map(str::to_lowercase).as_deref(),
+        Some("true" | "yes" | "explicit")
+    )
+}
+
+/// Collect <podcast:person> elements, merging the roles of the same person
+fn parse_persons(node: &XmlNode) -> Vec<PersonModel> {
+    let mut persons: Vec<PersonModel> = Vec::new();
+    for child in node.children_named("podcast:person") {
+        let attr = |key: &str| child.attr(key).filter(|v| !v.is_empty()).map(str::to_string);
+        let role = PersonRole {
+            group: child.attr("group").unwrap_or("music").to_string(),
+            role: child.attr("role").unwrap_or("band").to_string(),
+        };
+        let name = child.text.trim().to_string();
+        match persons
+            .iter_mut()
+            .find(|p| p.name == name && p.href == attr("href") && p.img == attr("img"))
+        {
+            Some(person) => person.roles.push(role),
+            None => persons.push(PersonModel {
+                name,
+                href: attr("href"),
+                img: attr("img"),
+                npub: attr("npub"),
+                roles: vec![role],
+            }),
+        }
+    }
+    persons
+}
+
+fn parse_value(node: &XmlNode) -> Option<ValueModel> {
+    let value = node.child("podcast:value")?;
+    Some(ValueModel {
+        suggested: value.attr("suggested").map(str::to_string),
+        recipients: value
+            .children_named("podcast:valueRecipient")
+            .map(|r| ValueRecipientModel {
+                name: r.attr("name").unwrap_or_default().to_string(),
+                address: r.attr("address").unwrap_or_default().to_string(),
+                split: r.attr("split").and_then(|s| s.trim().parse().ok()).unwrap_or(0),
+                recipient_type: r.attr("type").unwrap_or("node").to_string(),
+                custom_key: r.attr("customKey").map(str::to_string),
+                custom_value: r.attr("customValue").map(str::to_string),
+                fee: r.attr("fee").is_some_and(|f| f.trim().eq_ignore_ascii_case("true")),
+            })
+            .collect(),
+    })
+}
+
+pub fn parse_alternate_enclosure(node: &XmlNode) -> AlternateEnclosureModel {
+    let sha256 = node
+        .child("podcast:integrity")
+        .filter(|i| i.attr("type") == Some("sri"))
+        .and_then(|i| i.attr("value")?.strip_prefix("sha256-"))
+        .and_then(|digest| BASE64.decode(digest).ok())
+        .map(hex::encode);
+    AlternateEnclosureModel {
+        url: node
+            .child("podcast:source")
+            .and_then(|s| s.attr("uri"))
+            .unwrap_or_default()
+            .to_string(),
+        mime_type: node.attr("type").unwrap_or_default().to_string(),
+        length: node.attr("length").and_then(|l| l.trim().parse().ok()).unwrap_or(0),
+        bitrate: node.attr("bitrate").and_then(|b| b.trim().parse::<f64>().ok()).map(|b| b as u32),
+        title: node.attr("title").map(str::to_string),
+        default: node.attr("default") == Some("true"),
+        sha256,
+    }
+}
+
+fn parse_remote_item(node: &XmlNode) -> RemoteItemModel {
+    let attr = |key: &str| node.attr(key).map(str::to_string);
+    RemoteItemModel {
+        feed_guid: node.attr("feedGuid").unwrap_or_default().to_string(),
+        feed_url: attr("feedUrl"),
+        item_guid: attr("itemGuid"),
+        medium: attr("medium"),
+        title: Some(node.text.trim().to_string()).filter(|t| !t.is_empty()),
+        image: attr("feedImg"),
+    }
+}
+
+/// Undo the OP3 prefix added by `op3_url`
+fn strip_op3(url: &str) -> Option<String> {
+    let rest = url.strip_prefix("https://op3.dev/e")?;
+    let (_, target) = rest.split_once('/')?;
+    Some(if target.starts_with("http://") || target.starts_with("https://") {
+        target.to_string()
+    } else {
+        format!("https://{}", target)
+    })
+}
+
+fn parse_track(item: &XmlNode, index: usize, feed: &FeedModel) -> TrackModel {
+    let enclosure = item.child("enclosure");
+    let enclosure_attr = |key: &str| enclosure.and_then(|e| e.attr(key)).unwrap_or_default().to_string();
+    let enclosure_url = enclosure_attr("url");
+    let art = item.child("itunes:image").and_then(|i| i.attr("href")).unwrap_or_default();
+    let persons = parse_persons(item);
+    let value = parse_value(item);
+    let transcript = modeled_transcript(item);
+    let transcript_attr = |key: &str| transcript.and_then(|t| t.attr(key)).map(str::to_string);
+
+    TrackModel {
+        track_number: item
+            .child_text("podcast:episode")
+            .and_then(|e| e.parse().ok())
+            .unwrap_or(index as u32 + 1),
+        episode: None,
+        title: text_of(item, "title"),
+        author: item.child_text("itunes:author").map(str::to_string),
+        description: text_of(item, "description"),
+        pub_date: text_of(item, "pubDate"),
+        guid: text_of(item, "guid"),
+        enclosure_url: if feed.op3 { strip_op3(&enclosure_url).unwrap_or(enclosure_url) } else { enclosure_url },
+        enclosure_length: enclosure_attr("length"),
+        enclosure_type: enclosure_attr("type"),
+        duration: text_of(item, "itunes:duration"),
+        explicit: is_explicit(item),
+        track_art_url: (!art.is_empty() && art != feed.image_url).then(|| art.to_string()),
+        transcript_url: transcript_attr("url"),
+        transcript_type: transcript_attr("type"),
+        transcript_language: transcript_attr("language"),
+        transcript_rel: transcript_attr("rel"),
+        chapters_url: item.child("podcast:chapters").and_then(|c| c.attr("url")).map(str::to_string),
+        alternate_enclosures: item
+            .children_named("podcast:alternateEnclosure")
+            .map(parse_alternate_enclosure)
+            .collect(),
+        override_persons: !persons.is_empty(),
+        persons,
+        // Items repeat the channel value block unless they override it
+        override_value: value.as_ref().is_some_and(|v| *v != feed.value),
+        value,
+        extensions: extension_fragments(item, ITEM_ELEMENTS, |n| transcript.is_some_and(|t| std::ptr::eq(t, n))),
+    }
+}
+
+/// Read RSS into the structured model. Namespaced elements the model does not cover
+/// are kept as raw fragments on the channel or track they came from, so regenerating
+/// the feed does not drop them.
+pub fn parse_feed_model(xml: &str) -> Result<FeedModel, String> {
+    let doc = parse_rss(xml)?;
+    let channel = doc.channel().ok_or("Missing <channel> element")?;
+
+    let image = channel.child("image");
+    let image_text = |name: &str| image.map(|i| text_of(i, name)).unwrap_or_default();
+    let image_url = Some(image_text("url"))
+        .filter(|u| !u.is_empty())
+        .or_else(|| channel.child("itunes:image").and_then(|i| i.attr("href")).map(str::to_string))
+        .unwrap_or_default();
+    let owner = channel.child("itunes:owner");
+    let locked = channel.child("podcast:locked");
+    let publisher = channel
+        .child("podcast:publisher")
+        .and_then(|p| p.child("podcast:remoteItem"))
+        .map(|r| PublisherRefModel {
+            feed_guid: r.attr("feedGuid").unwrap_or_default().to_string(),
+            feed_url: r.attr("feedUrl").map(str::to_string),
+        });
+    let is_npub_txt = |n: &XmlNode| n.name == "podcast:txt" && n.attr("purpose") == Some("npub");
+    let mut namespaces: Vec<NamespaceModel> = Vec::new();
+    let declaring_nodes = [&doc.root, channel].into_iter().chain(doc.items());
+    for ns in declaring_nodes.flat_map(namespace_declarations) {
+        if !namespaces.iter().any(|n| n.prefix == ns.prefix) {
+            namespaces.push(ns);
+        }
+    }
+
+    let mut feed = FeedModel {
+        title: text_of(channel, "title"),
+        author: text_of(channel, "itunes:author"),
+        artist_npub: channel.children.iter().find(|n| is_npub_txt(*n)).map(|n| n.text.trim().to_string()),
+        description: text_of(channel, "description"),
+        link: text_of(channel, "link"),
+        language: text_of(channel, "language"),
+        pub_date: text_of(channel, "pubDate"),
+        last_build_date: text_of(channel, "lastBuildDate"),
+        podcast_guid: text_of(channel, "podcast:guid"),
+        medium: text_of(channel, "podcast:medium"),
+        locked: locked.is_some_and(|l| l.text.trim().eq_ignore_ascii_case("yes")),
+        locked_owner: locked.and_then(|l| l.attr("owner")).unwrap_or_default().to_string(),
+        categories: channel
+            .children_named("itunes:category")
+            .filter(|c| is_flat_category(c))
+            .filter_map(|c| c.attr("text").map(str::to_string))
+            .collect(),
+        keywords: text_of(channel, "itunes:keywords"),
+        explicit: is_explicit(channel),
+        owner_name: owner.map(|o| text_of(o, "itunes:name")).unwrap_or_default(),
+        owner_email: owner.map(|o| text_of(o, "itunes:email")).unwrap_or_default(),
+        image_url,
+        image_title: image_text("title"),
+        image_link: image_text("link"),
+        image_description: image_text("description"),
+        managing_editor: text_of(channel, "managingEditor"),
+        web_master: text_of(channel, "webMaster"),
+        persons: parse_persons(channel),
+        value: parse_value(channel).unwrap_or_default(),
+        funding: channel
+            .children_named("podcast:funding")
+            .map(|f| FundingModel {
+                url: f.attr("url").unwrap_or_default().to_string(),
+                text: f.text.trim().to_string(),
+            })
+            .collect(),
+        publisher,
+        remote_items: channel.children_named("podcast:remoteItem").map(parse_remote_item).collect(),
+        op3: doc.items().iter().any(|item| {
+            item.child("enclosure")
+                .and_then(|e| e.attr("url"))
+                .is_some_and(|u| u.starts_with("https://op3.dev/e"))
+        }),
+        namespaces,
+        extensions: extension_fragments(channel, CHANNEL_ELEMENTS, |n| is_npub_txt(n) || is_flat_category(n)),
+        ..Default::default()
+    };
+
+    feed.tracks = doc
+        .items()
+        .iter()
+        .enumerate()
+        .map(|(i, item)| parse_track(item, i, &feed))
+        .collect();
+    Ok(feed)
+}
+
+/// Parse RSS into the structured feed model, preserving unknown namespaced elements
+#[tauri::command]
+pub fn parse_feed_xml(xml: String) -> Result<FeedModel, String> {
+    parse_feed_model(&xml)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_FEED: &str = include_str!("../../example-feed.xml");
+
+    fn round_trip(xml: &str) -> (FeedModel, FeedModel) {
+        let parsed = parse_feed_model(xml).unwrap();
+        let reparsed = parse_feed_model(&generate_feed(&parsed)).unwrap();
+        (parsed, reparsed)
+    }
+
+    fn assert_same(a: &FeedModel, b: &FeedModel) {
+        assert_eq!(serde_json::to_value(a).unwrap(), serde_json::to_value(b).unwrap());
+    }
+
+    #[test]
+    fn example_feed_round_trips() {
+        let (parsed, reparsed) = round_trip(EXAMPLE_FEED);
+        assert_eq!(parsed.tracks.len(), 3);
+        assert_same(&parsed, &reparsed);
+    }
+
+    #[test]
+    fn nested_categories_are_kept() {
+        let xml = EXAMPLE_FEED.replace(
+            r#"<itunes:category text="Music" />"#,
+            r#"<itunes:category text="Music"><itunes:category text="Rock" /></itunes:category>"#,
+        );
+        let (parsed, reparsed) = round_trip(&xml);
+        assert!(parsed.categories.is_empty());
+        assert_same(&parsed, &reparsed);
+
+        let output = generate_feed(&parsed);
+        assert!(output.contains(r#"<itunes:category text="Rock"/>"#));
+        assert_eq!(output.matches(r#"<itunes:category text="Music""#).count(), 1);
+    }
+
+    #[test]
+    fn extra_transcripts_are_kept() {
+        let xml = EXAMPLE_FEED.replacen(
+            "<itunes:duration>",
+            concat!(
+                r#"<podcast:transcript url="https://example.com/a.srt" type="application/srt" language="en" rel="captions" />"#,
+                r#"<podcast:transcript url="https://example.com/a.vtt" type="text/vtt" />"#,
+                "<itunes:duration>",
+            ),
+            1,
+        );
+        let (parsed, reparsed) = round_trip(&xml);
+        let track = &parsed.tracks[0];
+        assert_eq!(track.transcript_language.as_deref(), Some("en"));
+        assert_eq!(track.transcript_rel.as_deref(), Some("captions"));
+        assert!(track.extensions.iter().any(|e| e.contains("a.vtt")));
+        assert_same(&parsed, &reparsed);
+    }
+
+    #[test]
+    fn nested_namespace_declarations_are_kept() {
+        let xml = EXAMPLE_FEED
+            .replacen("<channel>", r#"<channel xmlns:custom="https://example.com/ns">"#, 1)
+            .replacen("<itunes:duration>", "<custom:mood>calm</custom:mood><itunes:duration>", 1);
+        let (parsed, reparsed) = round_trip(&xml);
+        assert!(parsed.namespaces.iter().any(|n| n.prefix == "custom"));
+        assert!(generate_feed(&parsed).contains(r#"xmlns:custom="https://example.com/ns""#));
+        assert_same(&parsed, &reparsed);
+    }
+}
@@ -0,0 +1,248 @@
+// Lightweight XML tree for inspecting and rewriting RSS feeds
+
+use quick_xml::events::Event as XmlEvent;
+use quick_xml::reader::Reader;
+
+#[derive(Debug, Clone, Default)]
+pub struct XmlNode {
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<XmlNode>,
+    pub text: String,
+}
+
+impl XmlNode {
+    /// Create an empty element
+    pub fn new(name: &str) -> Self {
+        XmlNode {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Builder: add an attribute
+    pub fn with_attr(mut self, key: &str, value: &str) -> Self {
+        self.attrs.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Builder: set the text content
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.text = text.to_string();
+        self
+    }
+
+    /// Builder: append a child element
+    pub fn with_child(mut self, child: XmlNode) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Set the text of the first child with the given name, creating it if missing
+    pub fn set_child_text(&mut self, name: &str, text: &str) {
+        match self.children.iter_mut().find(|c| c.name == name) {
+            Some(child) => child.text = text.to_string(),
+            None => self.children.push(XmlNode::new(name).with_text(text)),
+        }
+    }
+
+    /// Remove all direct children with the given name
+    pub fn remove_children(&mut self, name: &str) {
+        self.children.retain(|c| c.name != name);
+    }
+
+    /// Serialize this element (and its children) as indented XML
+    pub fn to_xml(&self, depth: usize) -> String {
+        let indent = "  ".repeat(depth);
+        let mut out = format!("{}<{}", indent, self.name);
+        for (key, value) in &self.attrs {
+            out.push_str(&format!(" {}=\"{}\"", key, escape_xml(value)));
+        }
+
+        if self.children.is_empty() && self.text.is_empty() {
+            out.push_str("/>\n");
+            return out;
+        }
+
+        out.push('>');
+        if !self.text.is_empty() {
+            out.push_str(&escape_xml(&self.text));
+        }
+        if !self.children.is_empty() {
+            out.push('\n');
+            for child in &self.children {
+                out.push_str(&child.to_xml(depth + 1));
+            }
+            out.push_str(&indent);
+        }
+        out.push_str(&format!("</{}>\n", self.name));
+        out
+    }
+
+    /// Get an attribute value by name
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Get the first direct child with the given name
+    pub fn child(&self, name: &str) -> Option<&XmlNode> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    /// Get the first direct child with the given name, mutably
+    pub fn child_mut(&mut self, name: &str) -> Option<&mut XmlNode> {
+        self.children.iter_mut().find(|c| c.name == name)
+    }
+
+    /// Get all direct children with the given name
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlNode> + 'a {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    /// Get the trimmed text of the first direct child with the given name
+    pub fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name)
+            .map(|c| c.text.trim())
+            .filter(|t| !t.is_empty())
+    }
+
+    /// Collect all descendants (depth-first) with the given name
+    pub fn descendants_named<'a>(&'a self, name: &str) -> Vec<&'a XmlNode> {
+        let mut found = Vec::new();
+        for child in &self.children {
+            if child.name == name {
+                found.push(child);
+            }
+            found.extend(child.descendants_named(name));
+        }
+        found
+    }
+}
+
+/// A parsed RSS document
+pub struct RssDocument {
+    pub root: XmlNode,
+}
+
+impl RssDocument {
+    /// Get the <channel> element
+    pub fn channel(&self) -> Option<&XmlNode> {
+        self.root.child("channel")
+    }
+
+    /// Get all <item> elements in the channel
+    pub fn items(&self) -> Vec<&XmlNode> {
+        self.channel()
+            .map(|c| c.children_named("item").collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Escape text for use in XML content or attribute values
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Render a full XML document with declaration
+pub fn render_document(root: &XmlNode) -> String {
+    format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{}", root.to_xml(0))
+}
+
+/// Parse XML text into a node tree
+pub fn parse_xml(xml: &str) -> Result<XmlNode, String> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    // Synthetic document node holding the root element
+    let mut stack: Vec<XmlNode> = vec![XmlNode::default()];
+
+    loop {
+        match reader.read_event() {
+            Ok(XmlEvent::Start(e)) => {
+                stack.push(node_from_start(&e)?);
+            }
+            Ok(XmlEvent::Empty(e)) => {
+                let node = node_from_start(&e)?;
+                stack.last_mut().ok_or("Malformed XML")?.children.push(node);
+            }
+            Ok(XmlEvent::End(_)) => {
+                let node = stack.pop().ok_or("Malformed XML")?;
+                stack
+                    .last_mut()
+                    .ok_or("Malformed XML - unexpected closing tag")?
+                    .children
+                    .push(node);
+            }
+            Ok(XmlEvent::Text(t)) => {
+                let text = t.unescape().map_err(|e| format!("Invalid XML text: {}", e))?;
+                if let Some(node) = stack.last_mut() {
+                    node.text.push_str(&text);
+                }
+            }
+            Ok(XmlEvent::CData(c)) => {
+                if let Some(node) = stack.last_mut() {
+                    node.text.push_str(&String::from_utf8_lossy(&c.into_inner()));
+                }
+            }
+            Ok(XmlEvent::Eof) => break,
+            Ok(_) => {}
+            Err(e) => {
+                return Err(format!(
+                    "XML parse error at position {}: {}",
+                    reader.buffer_position(),
+                    e
+                ))
+            }
+        }
+    }
+
+    if stack.len() != 1 {
+        return Err("Malformed XML - unclosed elements".to_string());
+    }
+
+    let document = stack.pop().unwrap_or_default();
+    document
+        .children
+        .into_iter()
+        .next()
+        .ok_or_else(|| "XML document has no root element".to_string())
+}
+
+/// Parse an RSS feed, checking for the <rss><channel> structure
+pub fn parse_rss(xml: &str) -> Result<RssDocument, String> {
+    let root = parse_xml(xml)?;
+    if root.name != "rss" {
+        return Err(format!("Expected <rss> root element, found <{}>", root.name));
+    }
+    if root.child("channel").is_none() {
+        return Err("Missing <channel> element".to_string());
+    }
+    Ok(RssDocument { root })
+}
+
+fn node_from_start(e: &quick_xml::events::BytesStart) -> Result<XmlNode, String> {
+    let mut attrs = Vec::new();
+    for attr in e.attributes() {
+        let attr = attr.map_err(|e| format!("Invalid XML attribute: {}", e))?;
+        let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+        let value = attr
+            .unescape_value()
+            .map_err(|e| format!("Invalid XML attribute value: {}", e))?
+            .to_string();
+        attrs.push((key, value));
+    }
+
+    Ok(XmlNode {
+        name: String::from_utf8_lossy(e.name().as_ref()).to_string(),
+        attrs,
+        children: Vec::new(),
+        text: String::new(),
+    })
+}
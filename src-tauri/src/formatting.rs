@@ -0,0 +1,135 @@
+// Shared formatting for durations, sizes, and dates, so the feed generator and the
+// frontend preview always render the same values the same way
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct FormatPreview {
+    pub itunes_duration: Option<String>,
+    pub duration_secs: Option<f64>,
+    pub size: Option<String>,
+    pub byte_length: Option<String>, // enclosure length attribute value
+    pub rfc822: Option<String>,
+}
+
+/// Parse HH:MM:SS, MM:SS, or seconds into seconds
+pub fn parse_duration(value: &str) -> Option<f64> {
+    let parts: Vec<&str> = value.trim().split(':').collect();
+    let mut secs = 0.0;
+    for part in &parts {
+        secs = secs * 60.0 + part.trim().parse::<f64>().ok()?;
+    }
+    (parts.len() <= 3).then_some(secs)
+}
+
+/// Format seconds as an itunes:duration value (HH:MM:SS)
+pub fn format_itunes_duration(secs: f64) -> String {
+    let total = secs.round() as u64;
+    format!("{:02}:{:02}:{:02}", total / 3600, (total % 3600) / 60, total % 60)
+}
+
+/// Rewrite a duration in any accepted form as HH:MM:SS ("61:05" becomes
+/// "01:01:05"); values that do not parse are kept as written
+pub fn normalize_itunes_duration(value: &str) -> String {
+    match parse_duration(value) {
+        Some(secs) if !value.trim().is_empty() => format_itunes_duration(secs),
+        _ => value.trim().to_string(),
+    }
+}
+
+/// Format a byte count for messages ("64KB", "1.5MB")
+pub fn format_size(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1}MB", bytes as f64 / (1024.0 * 1024.0))
+    } else if bytes >= 1024 {
+        format!("{}KB", bytes / 1024)
+    } else {
+        format!("{}B", bytes)
+    }
+}
+
+/// Whether a locale ("de-DE", "fr") writes decimals with a comma
+fn uses_decimal_comma(locale: &str) -> bool {
+    const COMMA_LANGUAGES: &[&str] = &[
+        "cs", "da", "de", "es", "fi", "fr", "id", "it", "nb", "nl", "pl", "pt", "ru", "sv", "tr", "uk",
+    ];
+    let language = locale.split(['-', '_']).next().unwrap_or_default().to_lowercase();
+    COMMA_LANGUAGES.contains(&language.as_str())
+}
+
+/// Format a Unix timestamp as an RFC-822 date ("Tue, 15 Oct 2024 12:00:00 GMT")
+pub fn format_rfc822(timestamp: u64) -> String {
+    const DAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+    let days = (timestamp / 86400) as i64;
+    let secs = timestamp % 86400;
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        DAYS[(days % 7) as usize],
+        day,
+        MONTHS[(month - 1) as usize],
+        year,
+        secs / 3600,
+        (secs % 3600) / 60,
+        secs % 60
+    )
+}
+
+/// Convert days since the Unix epoch to (year, month, day)
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Convert (year, month, day) to days since the Unix epoch
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (i64::from(month) + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Render durations, sizes, and dates exactly as the feed generator will. The
+/// duration may be given as seconds or in any accepted text form; `locale` only
+/// affects the human-readable size.
+#[tauri::command]
+pub fn format_preview(
+    duration_secs: Option<f64>,
+    duration: Option<String>,
+    bytes: Option<u64>,
+    timestamp: Option<u64>,
+    locale: Option<String>,
+) -> FormatPreview {
+    let secs = duration_secs
+        .filter(|s| s.is_finite() && *s >= 0.0)
+        .or_else(|| duration.as_deref().and_then(parse_duration));
+    let size = bytes.map(|b| {
+        let size = format_size(b);
+        match &locale {
+            Some(locale) if uses_decimal_comma(locale) => size.replace('.', ","),
+            _ => size,
+        }
+    });
+
+    FormatPreview {
+        itunes_duration: secs.map(format_itunes_duration),
+        duration_secs: secs.map(f64::round),
+        size,
+        byte_length: bytes.map(|b| b.to_string()),
+        rfc822: timestamp.map(format_rfc822),
+    }
+}
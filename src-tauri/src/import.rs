@@ -0,0 +1,589 @@
+// Importers that turn external album sources and remote feeds into local feeds
+
+use crate::artwork::{artwork_info, extract_embedded, ArtworkInfo};
+use crate::audio::{is_audio_file, read_audio_metadata, AudioMetadata};
+use crate::disk_space::{ensure_space, TaskError};
+use crate::feed_model::{generate_feed, FeedModel, TrackModel};
+use crate::feed_xml::{parse_rss, RssDocument};
+use crate::formatting::format_itunes_duration;
+use crate::workspace::TaskWorkspace;
+use directories::ProjectDirs;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Read;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+// Filenames (without extension) recognized as album cover art
+const COVER_NAMES: &[&str] = &["cover", "folder", "front", "album", "artwork"];
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp"];
+// Largest artwork file accepted from an imported feed
+const MAX_ARTWORK_BYTES: u64 = 20 * 1024 * 1024;
+// Redirects followed per artwork download; each hop's address is checked again
+const MAX_ARTWORK_REDIRECTS: usize = 5;
+// Limits on what a zip import may unpack, so a crafted archive can't fill the disk
+const MAX_ZIP_ENTRIES: usize = 10_000;
+const MAX_ZIP_BYTES: u64 = 8 * 1024 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize)]
+pub struct ImportedAlbum {
+    pub feed_id: String,
+    pub title: String,
+    pub artist: String,
+    pub tracks: Vec<AudioMetadata>,
+    pub cover_path: Option<String>,
+    pub cover: Option<ArtworkInfo>, // dimensions and warnings for the cover used
+    pub extracted_to: String,
+}
+
+/// Get a fresh (not yet created) directory for extracted import files
+fn get_import_dir() -> Result<PathBuf, String> {
+    let proj_dirs = ProjectDirs::from("com", "podtards", "msp-studio")
+        .ok_or("Could not determine app data directory")?;
+
+    let imports_dir = proj_dirs.data_dir().join("imports");
+    fs::create_dir_all(&imports_dir).map_err(|e| e.to_string())?;
+
+    Ok(imports_dir.join(Uuid::new_v4().to_string()))
+}
+
+/// Extract a zip archive, rejecting entries that would escape the target directory
+pub fn extract_zip(zip_path: &Path, target: &Path) -> Result<Vec<PathBuf>, TaskError> {
+    let file = fs::File::open(zip_path).map_err(|e| format!("Failed to open zip: {}", e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Invalid zip archive: {}", e))?;
+
+    if archive.len() > MAX_ZIP_ENTRIES {
+        return Err(format!("Zip has {} entries; the limit is {}", archive.len(), MAX_ZIP_ENTRIES).into());
+    }
+
+    // Check the uncompressed total up front rather than failing mid-extraction
+    let total: u64 = (0..archive.len())
+        .filter_map(|i| archive.by_index(i).ok().map(|entry| entry.size()))
+        .sum();
+    if total > MAX_ZIP_BYTES {
+        return Err(format!("Zip unpacks to more than {} GB", MAX_ZIP_BYTES / (1024 * 1024 * 1024)).into());
+    }
+    ensure_space(target, total)?;
+
+    // Declared sizes can lie, so the limit is also enforced on the bytes written
+    let mut written = 0u64;
+
+    let mut extracted = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        let Some(relative) = entry.enclosed_name() else {
+            continue;
+        };
+        let out_path = target.join(relative);
+
+        if entry.is_dir() {
+            fs::create_dir_all(&out_path).map_err(|e| e.to_string())?;
+            continue;
+        }
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut out = fs::File::create(&out_path).map_err(|e| e.to_string())?;
+        let remaining = MAX_ZIP_BYTES - written;
+        let copied = std::io::copy(&mut (&mut entry).take(remaining + 1), &mut out).map_err(|e| e.to_string())?;
+        if copied > remaining {
+            return Err(format!("Zip unpacks to more than {} GB", MAX_ZIP_BYTES / (1024 * 1024 * 1024)).into());
+        }
+        written += copied;
+        extracted.push(out_path);
+    }
+
+    Ok(extracted)
+}
+
+/// Parse Bandcamp-style filenames ("Artist - Album - 01 Title") into (track number, title)
+fn parse_bandcamp_filename(path: &Path) -> (Option<u32>, Option<String>) {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let last = stem.rsplit(" - ").next().unwrap_or(&stem).trim();
+
+    let (number, title) = match last.split_once(' ') {
+        Some((num, rest)) if num.chars().all(|c| c.is_ascii_digit()) => (num.parse().ok(), rest.trim()),
+        _ => (None, last),
+    };
+    (number, (!title.is_empty()).then(|| title.to_string()))
+}
+
+/// Disc number from a subfolder name like "Disc 2", "CD2", or "disc-2"
+fn parse_disc_folder(path: &Path) -> Option<u32> {
+    let folder = path.parent()?.file_name()?.to_string_lossy().to_lowercase();
+    let rest = folder.strip_prefix("disc").or_else(|| folder.strip_prefix("cd"))?;
+    rest.trim_start_matches([' ', '-', '_']).parse().ok()
+}
+
+/// Find the cover image among extracted files
+fn find_cover(files: &[PathBuf]) -> Option<PathBuf> {
+    let images: Vec<&PathBuf> = files
+        .iter()
+        .filter(|p| {
+            p.extension()
+                .map(|e| IMAGE_EXTENSIONS.contains(&e.to_string_lossy().to_lowercase().as_str()))
+                .unwrap_or(false)
+        })
+        .collect();
+
+    images
+        .iter()
+        .find(|p| {
+            let stem = p.file_stem().unwrap_or_default().to_string_lossy().to_lowercase();
+            COVER_NAMES.iter().any(|name| stem.starts_with(name))
+        })
+        .or_else(|| images.first())
+        .map(|p| p.to_path_buf())
+}
+
+/// Read tags for all audio files, filling gaps from filenames, sorted by track number
+pub fn read_tracks(files: &[PathBuf]) -> Vec<AudioMetadata> {
+    let mut tracks: Vec<AudioMetadata> = files
+        .iter()
+        .filter(|p| is_audio_file(p))
+        .filter_map(|path| {
+            let mut meta = read_audio_metadata(path).ok()?;
+            let (number, title) = parse_bandcamp_filename(path);
+            if meta.track_number.is_none() {
+                meta.track_number = number;
+            }
+            if meta.title.is_none() {
+                meta.title = title;
+            }
+            if meta.disc_number.is_none() {
+                meta.disc_number = parse_disc_folder(path);
+            }
+            Some(meta)
+        })
+        .collect();
+
+    // Untagged discs count as disc 1, so single-disc albums sort by track alone
+    tracks.sort_by(|a, b| {
+        a.disc_number
+            .unwrap_or(1)
+            .cmp(&b.disc_number.unwrap_or(1))
+            .then_with(|| a.track_number.unwrap_or(u32::MAX).cmp(&b.track_number.unwrap_or(u32::MAX)))
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+    tracks
+}
+
+/// Build a draft album model from imported tracks. Enclosures point at the local
+/// files until they are uploaded.
+pub fn build_draft_album_model(title: &str, artist: &str, tracks: &[AudioMetadata], cover: Option<&Path>) -> FeedModel {
+    let tracks = tracks
+        .iter()
+        .enumerate()
+        .map(|(i, track)| TrackModel {
+            track_number: track.track_number.unwrap_or(i as u32 + 1),
+            title: track.title.clone().unwrap_or_else(|| format!("Track {}", i + 1)),
+            author: Some(track.artist.clone().unwrap_or_else(|| artist.to_string())),
+            guid: Uuid::new_v4().to_string(),
+            enclosure_url: crate::file_url(Path::new(&track.file_path)),
+            enclosure_length: track.file_size.to_string(),
+            enclosure_type: track.mime_type.clone(),
+            duration: format_itunes_duration(track.duration_secs),
+            ..Default::default()
+        })
+        .collect();
+
+    FeedModel {
+        title: title.to_string(),
+        author: artist.to_string(),
+        description: title.to_string(),
+        podcast_guid: Uuid::new_v4().to_string(),
+        medium: "music".to_string(),
+        image_url: cover
+            .map(crate::file_url)
+            .unwrap_or_default(),
+        tracks,
+        ..Default::default()
+    }
+}
+
+/// Build a draft album feed from imported tracks
+pub fn build_draft_album_xml(title: &str, artist: &str, tracks: &[AudioMetadata], cover: Option<&Path>) -> String {
+    generate_feed(&build_draft_album_model(title, artist, tracks, cover))
+}
+
+/// Split an "Artist - Album" archive or folder name into (artist, album)
+fn split_album_name(name: &str) -> (String, String) {
+    match name.split_once(" - ") {
+        Some((artist, album)) => (artist.trim().to_string(), album.trim().to_string()),
+        None => ("Unknown Artist".to_string(), name.to_string()),
+    }
+}
+
+/// Pick the album cover: an image file alongside the audio, else the first track's
+/// embedded art
+fn album_cover(files: &[PathBuf], tracks: &[AudioMetadata]) -> Option<ArtworkInfo> {
+    match find_cover(files) {
+        Some(path) => artwork_info(&path).ok(),
+        None => tracks
+            .iter()
+            .find_map(|t| extract_embedded(Path::new(&t.file_path)).ok().flatten()),
+    }
+}
+
+/// Import a Bandcamp-style album zip (audio + cover) as a draft album feed
+#[tauri::command]
+pub async fn import_album_zip(zip_path: String) -> Result<ImportedAlbum, TaskError> {
+    tokio::task::spawn_blocking(move || unpack_album_zip(zip_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn unpack_album_zip(zip_path: String) -> Result<ImportedAlbum, TaskError> {
+    // Extract into a task workspace so a failed import leaves nothing behind
+    let workspace = TaskWorkspace::create("import")?;
+    let staging = workspace.path().join("files");
+    let staged = extract_zip(Path::new(&zip_path), &staging)?;
+    if !staged.iter().any(|p| is_audio_file(p)) {
+        return Err("No audio files found in the archive".into());
+    }
+
+    let import_dir = get_import_dir()?;
+    workspace.persist(&staging, &import_dir)?;
+    let files: Vec<PathBuf> = staged
+        .iter()
+        .filter_map(|p| p.strip_prefix(&staging).ok())
+        .map(|relative| import_dir.join(relative))
+        .collect();
+
+    let tracks = read_tracks(&files);
+    if tracks.is_empty() {
+        return Err("No readable audio files found in the archive".into());
+    }
+
+    // Prefer tags; fall back to the zip name ("Artist - Album.zip")
+    let zip_stem = Path::new(&zip_path)
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let (stem_artist, stem_album) = split_album_name(&zip_stem);
+    let title = tracks.iter().find_map(|t| t.album.clone()).unwrap_or(stem_album);
+    let artist = tracks.iter().find_map(|t| t.artist.clone()).unwrap_or(stem_artist);
+
+    let cover = album_cover(&files, &tracks);
+    let cover_path = cover.as_ref().map(|c| PathBuf::from(&c.path));
+    let xml = build_draft_album_xml(&title, &artist, &tracks, cover_path.as_deref());
+    let saved = crate::save_feed_local(None, title.clone(), "album".to_string(), xml, None)?;
+
+    Ok(ImportedAlbum {
+        feed_id: saved.id,
+        title,
+        artist,
+        tracks,
+        cover_path: cover.as_ref().map(|c| c.path.clone()),
+        cover,
+        extracted_to: import_dir.to_string_lossy().to_string(),
+    })
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FolderAlbum {
+    pub album: FeedModel,
+    pub tracks: Vec<AudioMetadata>,
+    pub cover: Option<ArtworkInfo>,
+}
+
+/// List every file under a directory, descending into subfolders (e.g. "Disc 2").
+/// Each real folder is visited once, so symlinks that loop back are skipped.
+fn list_files(dir: &Path, files: &mut Vec<PathBuf>, visited: &mut HashSet<PathBuf>) -> Result<(), String> {
+    let real = fs::canonicalize(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    if !visited.insert(real) {
+        return Ok(());
+    }
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            list_files(&path, files, visited)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn scan_album_folder(path: String) -> Result<FolderAlbum, String> {
+    let dir = Path::new(&path);
+    if !dir.is_dir() {
+        return Err(format!("{} is not a folder", path));
+    }
+
+    let mut files = Vec::new();
+    list_files(dir, &mut files, &mut HashSet::new())?;
+    files.sort();
+
+    let tracks = read_tracks(&files);
+    if tracks.is_empty() {
+        return Err("No readable audio files found in the folder".to_string());
+    }
+
+    // Prefer tags; fall back to the folder name ("Artist - Album")
+    let folder_name = dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let (name_artist, name_album) = split_album_name(&folder_name);
+    let title = tracks.iter().find_map(|t| t.album.clone()).unwrap_or(name_album);
+    let artist = tracks.iter().find_map(|t| t.artist.clone()).unwrap_or(name_artist);
+
+    let cover = album_cover(&files, &tracks);
+    let cover_path = cover.as_ref().map(|c| PathBuf::from(&c.path));
+    let album = build_draft_album_model(&title, &artist, &tracks, cover_path.as_deref());
+
+    Ok(FolderAlbum { album, tracks, cover })
+}
+
+/// Scan a folder of audio files into a ready-to-edit album model: tags read from
+/// every file, tracks sorted by disc then track number, and cover art from an image
+/// in the folder or the tracks' embedded art. Files stay in place and nothing is saved.
+#[tauri::command]
+pub async fn import_album_folder(path: String) -> Result<FolderAlbum, String> {
+    tokio::task::spawn_blocking(move || scan_album_folder(path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ImportedArtwork {
+    pub url: String,
+    pub local_path: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ImportedFeed {
+    pub feed_id: String,
+    pub title: String,
+    pub feed_type: String,
+    pub track_count: usize,
+    pub source_url: String,
+    pub artwork: Vec<ImportedArtwork>,
+}
+
+/// Get the directory holding downloaded and extracted artwork copies
+pub fn get_artwork_dir() -> Result<PathBuf, String> {
+    let proj_dirs = ProjectDirs::from("com", "podtards", "msp-studio")
+        .ok_or("Could not determine app data directory")?;
+
+    let artwork_dir = proj_dirs.data_dir().join("artwork");
+    fs::create_dir_all(&artwork_dir).map_err(|e| e.to_string())?;
+
+    Ok(artwork_dir)
+}
+
+/// Collect distinct artwork URLs referenced by a feed (channel and item images)
+fn artwork_urls(doc: &RssDocument) -> Vec<String> {
+    let Some(channel) = doc.channel() else {
+        return Vec::new();
+    };
+
+    let mut urls: Vec<String> = Vec::new();
+    let mut push = |url: Option<&str>| {
+        if let Some(url) = url.map(str::trim).filter(|u| u.starts_with("http")) {
+            if !urls.iter().any(|u| u == url) {
+                urls.push(url.to_string());
+            }
+        }
+    };
+
+    push(channel.child("image").and_then(|i| i.child_text("url")));
+    for node in channel.descendants_named("itunes:image") {
+        push(node.attr("href"));
+    }
+    for node in channel.descendants_named("podcast:image") {
+        push(node.attr("href"));
+    }
+    urls
+}
+
+/// Whether an address is publicly routable (not loopback, private, link-local, or reserved)
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+                || v4.is_unspecified()
+                || v4.is_multicast()
+                || a == 0
+                || a >= 240
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+                || (first == 0x2001 && v6.segments()[1] == 0x0db8))
+        }
+    }
+}
+
+/// Resolve a URL's host, failing if any of its addresses is private or reserved
+async fn resolve_public(url: &reqwest::Url) -> Result<(String, SocketAddr), String> {
+    let host = url.host_str().ok_or("Artwork URL has no host")?.to_string();
+    let port = url.port_or_known_default().unwrap_or(443);
+    let lookup_host = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((lookup_host, port))
+        .await
+        .map_err(|e| format!("Could not resolve {}: {}", host, e))?
+        .collect();
+    if addrs.iter().any(|a| !is_public_ip(a.ip())) {
+        return Err(format!("{} resolves to a private or reserved address", host));
+    }
+    let addr = addrs.first().copied().ok_or_else(|| format!("Could not resolve {}", host))?;
+    Ok((host, addr))
+}
+
+/// Request artwork, following redirects by hand so every hop is checked and pinned
+/// to the public address it resolved to
+pub async fn fetch_public(url: &str) -> Result<(reqwest::Url, reqwest::Response), String> {
+    let mut url = reqwest::Url::parse(url).map_err(|e| format!("Invalid artwork URL: {}", e))?;
+    for _ in 0..=MAX_ARTWORK_REDIRECTS {
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err("Artwork URL must use http or https".to_string());
+        }
+        let (host, addr) = resolve_public(&url).await?;
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .resolve(&host, addr)
+            .build()
+            .map_err(|e| e.to_string())?;
+        let response = client
+            .get(url.clone())
+            .send()
+            .await
+            .map_err(|e| format!("Download failed: {}", e))?;
+        if !response.status().is_redirection() {
+            return Ok((url, response));
+        }
+        let location = response
+            .headers()
+            .get("Location")
+            .and_then(|v| v.to_str().ok())
+            .ok_or("Download failed: redirect without a Location")?;
+        url = url.join(location).map_err(|e| format!("Invalid redirect: {}", e))?;
+    }
+    Err("Download failed: too many redirects".to_string())
+}
+
+/// Download one artwork file into the artwork directory, named by URL hash. The
+/// download is staged in the workspace so partial files never reach `dir`.
+async fn download_artwork(url: &str, workspace: &TaskWorkspace, dir: &Path) -> Result<PathBuf, String> {
+    let (final_url, response) = fetch_public(url).await?;
+    if !response.status().is_success() {
+        return Err(format!("Download failed: HTTP {}", response.status()));
+    }
+
+    let content_type = response
+        .headers()
+        .get("Content-Type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let url_ext = final_url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, ext)| ext.to_lowercase())
+        .filter(|ext| IMAGE_EXTENSIONS.contains(&ext.as_str()));
+    let ext = url_ext.unwrap_or_else(|| match content_type.as_str() {
+        "image/png" => "png".to_string(),
+        "image/webp" => "webp".to_string(),
+        _ => "jpg".to_string(),
+    });
+
+    if let Some(length) = response.content_length() {
+        if length > MAX_ARTWORK_BYTES {
+            return Err(format!("Artwork is larger than {} MB", MAX_ARTWORK_BYTES / (1024 * 1024)));
+        }
+        ensure_space(dir, length).map_err(|e| e.to_string())?;
+    }
+
+    let file_name = format!("{}.{}", hex::encode(Sha256::digest(url.as_bytes())), ext);
+    let staged = workspace.path().join(&file_name);
+    let mut file = tokio::fs::File::create(&staged).await.map_err(|e| e.to_string())?;
+    let mut received = 0u64;
+    let mut body = response.bytes_stream();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| format!("Download failed: {}", e))?;
+        received += chunk.len() as u64;
+        // The declared length can be missing or wrong, so the cap is enforced on the stream
+        if received > MAX_ARTWORK_BYTES {
+            return Err(format!("Artwork is larger than {} MB", MAX_ARTWORK_BYTES / (1024 * 1024)));
+        }
+        file.write_all(&chunk).await.map_err(|e| e.to_string())?;
+    }
+    file.flush().await.map_err(|e| e.to_string())?;
+    drop(file);
+
+    let path = dir.join(file_name);
+    workspace.persist(&staged, &path)?;
+
+    Ok(path)
+}
+
+/// Import an existing RSS feed by URL (e.g. from Wavlake or RSS Blue) into the
+/// local library, keeping local copies of its artwork
+#[tauri::command]
+pub async fn import_feed_from_url(url: String) -> Result<ImportedFeed, String> {
+    let url = url.trim().to_string();
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err("Feed URL must start with http:// or https://".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch feed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch feed: HTTP {}", response.status()));
+    }
+    let xml = response.text().await.map_err(|e| format!("Failed to read feed: {}", e))?;
+
+    let doc = parse_rss(&xml)?;
+    let channel = doc.channel().ok_or("Missing <channel> element")?;
+    let title = channel.child_text("title").unwrap_or("Imported Feed").to_string();
+    let feed_type = crate::detect_feed_type(&xml);
+    let track_count = doc.items().len();
+
+    let artwork_dir = get_artwork_dir()?;
+    let workspace = TaskWorkspace::create("download")?;
+    let mut artwork = Vec::new();
+    for art_url in artwork_urls(&doc) {
+        let result = download_artwork(&art_url, &workspace, &artwork_dir).await;
+        artwork.push(ImportedArtwork {
+            url: art_url,
+            local_path: result.as_ref().ok().map(|p| p.to_string_lossy().to_string()),
+            error: result.err(),
+        });
+    }
+
+    let saved = crate::save_feed_local(None, title.clone(), feed_type.clone(), xml, None)?;
+
+    Ok(ImportedFeed {
+        feed_id: saved.id,
+        title,
+        feed_type,
+        track_count,
+        source_url: url,
+        artwork,
+    })
+}
@@ -0,0 +1,289 @@
+// Optional encryption of the feed library at rest. Feed XML (track titles, Lightning
+// addresses) and feed titles are sealed with a library key derived from the keystore
+// password before they are written to library.db, in live, trashed, and revision rows
+// alike. Sealed values are stored as "enc:v1:<nonce>:<ciphertext>" in the same
+// columns, so rows written before encryption was enabled still load. Feeds created
+// while encryption is on get opaque ids instead of title-based ones; ids stay
+// readable so the library can be listed (with placeholder titles) while locked.
+
+use argon2::password_hash::SaltString;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use zeroize::Zeroize;
+
+// Prefix marking a sealed column value
+const SEALED_PREFIX: &str = "enc:v1:";
+
+// Known plaintext sealed with the library key, to tell a wrong password apart
+const CHECK_PLAINTEXT: &str = "msp-studio-library";
+
+// Shown in place of a sealed title while the library is locked
+const LOCKED_TITLE: &str = "Locked feed";
+
+// Tables holding feeds and the sealed columns of each
+const SEALED_TABLES: [&str; 3] = ["feeds", "feed_versions", "trashed_feeds"];
+const SEALED_COLUMNS: [&str; 2] = ["xml", "title"];
+
+// The library key while the library is unlocked
+static LIBRARY_KEY: Mutex<Option<[u8; 32]>> = Mutex::new(None);
+
+// Present (in library_crypto.json) only while encryption is enabled
+#[derive(Serialize, Deserialize)]
+struct LibraryCryptoConfig {
+    argon2_salt: String,
+    kdf: crate::KdfParams,
+    check_nonce: String,
+    check_ciphertext: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LibraryEncryptionStatus {
+    pub enabled: bool,
+    pub unlocked: bool,
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    Ok(crate::get_appstate_dir()?.join("library_crypto.json"))
+}
+
+fn load_config() -> Result<Option<LibraryCryptoConfig>, String> {
+    let path = get_config_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|_| "Failed to parse library encryption settings".to_string())
+}
+
+fn locked_error() -> String {
+    "Library is locked - unlock it with your keystore password".to_string()
+}
+
+/// Derive the library key and check it against the stored check value
+fn derive_library_key(config: &LibraryCryptoConfig, password: &str) -> Result<[u8; 32], String> {
+    let mut key = crate::derive_key_from_password(password, config.argon2_salt.as_bytes(), &config.kdf)?;
+    if crate::decrypt_nsec(&config.check_nonce, &config.check_ciphertext, &key).ok().as_deref()
+        != Some(CHECK_PLAINTEXT)
+    {
+        key.zeroize();
+        return Err("Incorrect password".to_string());
+    }
+    Ok(key)
+}
+
+/// The password must open at least one password-protected stored key
+fn verify_keystore_password(password: &str) -> Result<(), String> {
+    let keystore = crate::load_keystore()?;
+    let matches = keystore.keys.iter().filter(|k| k.mode == "password").any(|entry| {
+        crate::derive_key_from_password(password, entry.argon2_salt.as_bytes(), &entry.kdf)
+            .and_then(|mut key| {
+                let nsec = crate::decrypt_nsec(&entry.nonce, &entry.ciphertext, &key);
+                key.zeroize();
+                nsec
+            })
+            .map(|mut nsec| nsec.zeroize())
+            .is_ok()
+    });
+    if matches {
+        Ok(())
+    } else {
+        Err("Password does not match any password-protected stored key".to_string())
+    }
+}
+
+fn seal_with(xml: &str, key: &[u8; 32]) -> Result<String, String> {
+    let (nonce, ciphertext) = crate::encrypt_nsec(xml, key)?;
+    Ok(format!("{}{}:{}", SEALED_PREFIX, nonce, ciphertext))
+}
+
+fn open_with(stored: &str, key: &[u8; 32]) -> Result<String, String> {
+    let sealed = stored.strip_prefix(SEALED_PREFIX).ok_or("Not a sealed value")?;
+    let (nonce, ciphertext) = sealed.split_once(':').ok_or("Corrupted sealed value")?;
+    crate::decrypt_nsec(nonce, ciphertext, key)
+}
+
+/// Whether library encryption is turned on
+pub fn enabled() -> Result<bool, String> {
+    Ok(load_config()?.is_some())
+}
+
+/// Whether the library is encrypted and its key has not been entered
+pub fn is_locked() -> Result<bool, String> {
+    Ok(enabled()? && LIBRARY_KEY.lock().unwrap().is_none())
+}
+
+/// Seal a feed's XML or title for storage; unchanged when library encryption is off
+pub fn seal(xml: String) -> Result<String, String> {
+    if load_config()?.is_none() {
+        return Ok(xml);
+    }
+    let key = LIBRARY_KEY.lock().unwrap().ok_or_else(locked_error)?;
+    seal_with(&xml, &key)
+}
+
+/// Open a stored title for listing, showing a placeholder while the library is locked
+pub fn open_title(stored: String) -> String {
+    open(stored).unwrap_or_else(|_| LOCKED_TITLE.to_string())
+}
+
+/// Open a stored feed XML or title; values written without encryption pass through
+pub fn open(stored: String) -> Result<String, String> {
+    if !stored.starts_with(SEALED_PREFIX) {
+        return Ok(stored);
+    }
+    let key = LIBRARY_KEY.lock().unwrap().ok_or_else(locked_error)?;
+    open_with(&stored, &key)
+}
+
+/// Unlock the library with the keystore password if it is encrypted. Called after a
+/// password-protected key is unlocked; a non-matching password is ignored.
+pub fn unlock_with_password(password: &str) {
+    if let Ok(Some(config)) = load_config() {
+        if let Ok(key) = derive_library_key(&config, password) {
+            *LIBRARY_KEY.lock().unwrap() = Some(key);
+        }
+    }
+}
+
+/// Forget the library key
+pub fn lock() {
+    if let Some(mut key) = LIBRARY_KEY.lock().unwrap().take() {
+        key.zeroize();
+    }
+}
+
+/// Rewrite every stored feed, trashed feed, and revision XML and title value, then
+/// vacuum so the old values do not linger in free pages. Returns the number of values
+/// rewritten.
+fn rewrite_library(transform: &dyn Fn(&str) -> Result<Option<String>, String>) -> Result<usize, String> {
+    let mut conn = crate::open_library()?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut rewritten = 0;
+    for table in SEALED_TABLES {
+        for column in SEALED_COLUMNS {
+            let mut stmt = tx
+                .prepare(&format!("SELECT rowid, {} FROM {}", column, table))
+                .map_err(|e| e.to_string())?;
+            let rows: Vec<(i64, String)> = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            drop(stmt);
+            for (rowid, value) in rows {
+                if let Some(value) = transform(&value)? {
+                    tx.execute(
+                        &format!("UPDATE {} SET {} = ?2 WHERE rowid = ?1", table, column),
+                        rusqlite::params![rowid, value],
+                    )
+                    .map_err(|e| e.to_string())?;
+                    rewritten += 1;
+                }
+            }
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    // The rows are already rewritten, so a failed VACUUM (disk full, a busy reader)
+    // only leaves old plaintext pages behind until the next one; it must not fail
+    // the rewrite, or callers would undo settings the committed rows depend on
+    if let Err(e) = conn.execute_batch("VACUUM") {
+        eprintln!("Library VACUUM after rewrite failed: {}", e);
+    }
+    Ok(rewritten)
+}
+
+/// Whether library encryption is on and whether the library is unlocked
+#[tauri::command]
+pub fn library_encryption_status() -> Result<LibraryEncryptionStatus, String> {
+    Ok(LibraryEncryptionStatus {
+        enabled: load_config()?.is_some(),
+        unlocked: LIBRARY_KEY.lock().unwrap().is_some(),
+    })
+}
+
+/// Turn on library encryption with the keystore password and encrypt every stored
+/// feed, trashed feed, and revision. Returns the number of values encrypted.
+#[tauri::command]
+pub fn library_enable_encryption(password: String) -> Result<usize, String> {
+    if load_config()?.is_some() {
+        return Err("Library encryption is already enabled".to_string());
+    }
+    verify_keystore_password(&password)?;
+
+    let salt = SaltString::generate(&mut rand::thread_rng());
+    let kdf = crate::load_kdf_params();
+    let mut key = crate::derive_key_from_password(&password, salt.as_str().as_bytes(), &kdf)?;
+    let (check_nonce, check_ciphertext) = crate::encrypt_nsec(CHECK_PLAINTEXT, &key)?;
+
+    // Settings go first so sealed rows are never left without the salt to open them
+    let config = LibraryCryptoConfig {
+        argon2_salt: salt.to_string(),
+        kdf,
+        check_nonce,
+        check_ciphertext,
+    };
+    let config_path = get_config_path()?;
+    let json = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+    crate::write_atomic(&config_path, json.as_bytes())?;
+
+    let rewritten = rewrite_library(&|xml| {
+        if xml.starts_with(SEALED_PREFIX) {
+            Ok(None)
+        } else {
+            seal_with(xml, &key).map(Some)
+        }
+    });
+    match rewritten {
+        Ok(rewritten) => {
+            *LIBRARY_KEY.lock().unwrap() = Some(key);
+            Ok(rewritten)
+        }
+        Err(e) => {
+            // The rewrite ran in one transaction, so nothing was sealed
+            key.zeroize();
+            let _ = fs::remove_file(&config_path);
+            Err(e)
+        }
+    }
+}
+
+/// Unlock the encrypted library for this session
+#[tauri::command]
+pub fn library_unlock(password: String) -> Result<LibraryEncryptionStatus, String> {
+    let config = load_config()?.ok_or("Library encryption is not enabled")?;
+    let key = derive_library_key(&config, &password)?;
+    *LIBRARY_KEY.lock().unwrap() = Some(key);
+    library_encryption_status()
+}
+
+/// Lock the encrypted library until the password is entered again
+#[tauri::command]
+pub fn library_lock() {
+    lock();
+}
+
+/// Turn off library encryption, decrypting every stored feed, trashed feed, and
+/// revision. Returns the number of values decrypted.
+#[tauri::command]
+pub fn library_disable_encryption(password: String) -> Result<usize, String> {
+    let config = load_config()?.ok_or("Library encryption is not enabled")?;
+    let mut key = derive_library_key(&config, &password)?;
+
+    let rewritten = rewrite_library(&|xml| {
+        if xml.starts_with(SEALED_PREFIX) {
+            open_with(xml, &key).map(Some)
+        } else {
+            Ok(None)
+        }
+    });
+    key.zeroize();
+    let rewritten = rewritten?;
+
+    fs::remove_file(get_config_path()?).map_err(|e| e.to_string())?;
+    lock();
+    Ok(rewritten)
+}
@@ -0,0 +1,130 @@
+// Loudness analysis with ffmpeg's loudnorm filter (EBU R128): integrated loudness,
+// true peak, and loudness range, compared with what streaming players normalize to.
+// The same measurement drives the optional normalization pass in transcode_audio.
+
+use crate::transcode::ffmpeg_path;
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+
+// Integrated loudness most streaming players normalize to, and how far off is too far
+const STREAMING_TARGET_LUFS: f64 = -14.0;
+const STREAMING_TOLERANCE_LU: f64 = 3.0;
+
+// Highest true peak that survives lossy encoding without clipping
+const MAX_TRUE_PEAK_DBTP: f64 = -1.0;
+
+// Loudness range passed to loudnorm; wide enough that it does not compress music
+const NORMALIZE_LRA: f64 = 20.0;
+
+/// Raw loudnorm measurement, as printed by print_format=json
+#[derive(Deserialize)]
+pub struct LoudnessMeasurement {
+    input_i: String,
+    input_tp: String,
+    input_lra: String,
+    input_thresh: String,
+    target_offset: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LoudnessReport {
+    pub path: String,
+    pub integrated_lufs: f64,
+    pub true_peak_dbtp: f64,
+    pub loudness_range_lu: f64,
+    pub gain_to_target_db: f64, // gain that would bring the track to the streaming target
+    pub warnings: Vec<String>,
+}
+
+fn parse_level(value: &str) -> Result<f64, String> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| "Track is silent or too short to measure".to_string())
+}
+
+/// Measure a file with a loudnorm analysis pass
+pub async fn measure(path: &str, target_lufs: f64) -> Result<LoudnessMeasurement, String> {
+    let filter = format!(
+        "loudnorm=I={}:TP={}:LRA={}:print_format=json",
+        target_lufs, MAX_TRUE_PEAK_DBTP, NORMALIZE_LRA
+    );
+    let output = tokio::process::Command::new(ffmpeg_path())
+        .args(["-hide_banner", "-nostdin", "-nostats", "-i", path, "-vn", "-af", &filter, "-f", "null", "-"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run ffmpeg (is it installed?): {}", e))?;
+
+    // The JSON block is the last thing loudnorm prints
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let json = stderr
+        .rfind('{')
+        .and_then(|start| Some(&stderr[start..=start + stderr[start..].rfind('}')?]));
+    match (output.status.success(), json) {
+        (true, Some(json)) => serde_json::from_str(json).map_err(|e| format!("Unexpected loudnorm output: {}", e)),
+        _ => {
+            let detail = stderr.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or("unknown error");
+            Err(format!("Loudness analysis failed: {}", detail.trim()))
+        }
+    }
+}
+
+/// loudnorm filter for the second, normalizing pass; `linear` keeps it to a plain
+/// gain change whenever the true-peak limit allows
+pub fn normalize_filter(measured: &LoudnessMeasurement, target_lufs: f64) -> String {
+    format!(
+        "loudnorm=I={}:TP={}:LRA={}:measured_I={}:measured_TP={}:measured_LRA={}:measured_thresh={}:offset={}:linear=true",
+        target_lufs,
+        MAX_TRUE_PEAK_DBTP,
+        NORMALIZE_LRA,
+        measured.input_i.trim(),
+        measured.input_tp.trim(),
+        measured.input_lra.trim(),
+        measured.input_thresh.trim(),
+        measured.target_offset.trim()
+    )
+}
+
+/// Measure integrated loudness (LUFS), true peak (dBTP), and loudness range of an
+/// audio file, warning when it is far from streaming norms
+#[tauri::command]
+pub async fn analyze_loudness(path: String) -> Result<LoudnessReport, String> {
+    let measured = measure(&path, STREAMING_TARGET_LUFS).await?;
+    let integrated_lufs = parse_level(&measured.input_i)?;
+    let true_peak_dbtp = parse_level(&measured.input_tp)?;
+    let loudness_range_lu = parse_level(&measured.input_lra).unwrap_or(0.0);
+    let gain_to_target_db = STREAMING_TARGET_LUFS - integrated_lufs;
+
+    let mut warnings = Vec::new();
+    if gain_to_target_db.abs() > STREAMING_TOLERANCE_LU {
+        warnings.push(format!(
+            "Integrated loudness is {:.1} LUFS, {:.1} LU {} the usual {} LUFS streaming target; players will {} it",
+            integrated_lufs,
+            gain_to_target_db.abs(),
+            if gain_to_target_db < 0.0 { "above" } else { "below" },
+            STREAMING_TARGET_LUFS,
+            if gain_to_target_db < 0.0 { "turn it down" } else { "play it quieter than other tracks" }
+        ));
+    }
+    if true_peak_dbtp > MAX_TRUE_PEAK_DBTP {
+        warnings.push(format!(
+            "True peak is {:.1} dBTP, above {} dBTP; lossy encodes may clip",
+            true_peak_dbtp, MAX_TRUE_PEAK_DBTP
+        ));
+    }
+
+    Ok(LoudnessReport {
+        path,
+        integrated_lufs,
+        true_peak_dbtp,
+        loudness_range_lu,
+        gain_to_target_db,
+        warnings,
+    })
+}
@@ -1,6 +1,56 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod alternate_enclosures;
+mod artwork;
+mod asset_audit;
+mod audio;
+mod batch;
+mod blossom_benchmark;
+mod blossom_download;
+mod blossom_migration;
+mod chapters;
+mod disk_space;
+mod drop_folder;
+mod feed_convert;
+mod feed_hosting;
+mod feed_model;
+mod feed_xml;
+mod formatting;
+mod import;
+mod library_crypto;
+mod loudness;
+mod messages;
+mod notify;
+mod os_auth;
+mod podcast_guid;
+mod podcast_index;
+mod preflight;
+mod preview;
+mod project;
+mod publish;
+mod relays;
+mod secure_settings;
+mod server_publish;
+mod session_lock;
+mod setup;
+mod shutdown;
+mod storage;
+mod submission;
+mod timeline;
+mod track_csv;
+mod transcode;
+mod transcripts;
+mod trash;
+mod upload_ledger;
+mod upload_queue;
+mod validation;
+mod value_block;
+mod wallet;
+mod waveform;
+mod workspace;
+mod zaps;
+
 use argon2::{Argon2, password_hash::SaltString};
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use chacha20poly1305::{
@@ -8,17 +58,23 @@ use chacha20poly1305::{
     XChaCha20Poly1305, XNonce,
 };
 use directories::ProjectDirs;
+use futures_util::StreamExt;
+use nostr_sdk::nips::nip26;
 use nostr_sdk::prelude::*;
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Mutex;
-use tauri::State;
+use tauri::{AppHandle, Emitter, Manager, State};
 use uuid::Uuid;
 use zeroize::Zeroize;
 
+/// App version stamped into generated feeds, published events, and feed history
+const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 /// Default Nostr relays for publishing and fetching events
 const DEFAULT_RELAYS: &[&str] = &[
     "wss://relay.damus.io",
@@ -27,19 +83,206 @@ const DEFAULT_RELAYS: &[&str] = &[
     "wss://relay.nostr.band",
 ];
 
-// Store for the Nostr client and keys
+/// Error for signing attempts in a watch-only (npub) session
+const READ_ONLY_SESSION_ERROR: &str = "Read-only session: log in with a private key to sign";
+
+// Store for the Nostr client and keys. The fields hold the active account; other
+// logged-in accounts stay connected in `accounts`. A watch-only account has
+// `watch_only` set and no keys.
 struct NostrState {
     keys: Mutex<Option<Keys>>,
+    watch_only: Mutex<Option<PublicKey>>,
     client: Mutex<Option<Client>>,
+    delegation: Mutex<Option<DelegationInfo>>,
+    relay_list: Mutex<Vec<RelayListEntry>>,
+    blossom_servers: Mutex<Vec<String>>,
+    subscriptions: Mutex<std::collections::HashMap<String, tauri::async_runtime::JoinHandle<()>>>,
+    accounts: Mutex<Accounts>,
+}
+
+// The active account's pubkey and the parked sessions of the other logged-in
+// accounts, keyed by hex pubkey. Account switches hold this one lock while they move
+// sessions in and out of the active slot, so the two never disagree.
+#[derive(Default)]
+struct Accounts {
+    active: Option<PublicKey>,
+    parked: std::collections::HashMap<String, NostrSession>,
+}
+
+// A logged-in account that is not currently active
+struct NostrSession {
+    public_key: PublicKey,
+    keys: Option<Keys>, // None for watch-only accounts
+    client: Client,
+    delegation: Option<DelegationInfo>,
+    relay_list: Vec<RelayListEntry>,
+    blossom_servers: Vec<String>,
+}
+
+impl NostrState {
+    /// Take the active account out of the active slot, stopping its live subscriptions
+    fn take_active(&self, accounts: &mut Accounts) -> Option<NostrSession> {
+        for (_, forwarder) in self.subscriptions.lock().unwrap().drain() {
+            forwarder.abort();
+        }
+        accounts.active = None;
+        let keys = self.keys.lock().unwrap().take();
+        let watch_only = self.watch_only.lock().unwrap().take();
+        let client = self.client.lock().unwrap().take();
+        let delegation = self.delegation.lock().unwrap().take();
+        let relay_list = std::mem::take(&mut *self.relay_list.lock().unwrap());
+        let blossom_servers = std::mem::take(&mut *self.blossom_servers.lock().unwrap());
+        Some(NostrSession {
+            public_key: keys.as_ref().map(|k| k.public_key()).or(watch_only)?,
+            keys,
+            client: client?,
+            delegation,
+            relay_list,
+            blossom_servers,
+        })
+    }
+
+    /// Make a session the active account, parking the previously active one
+    fn activate(&self, accounts: &mut Accounts, session: NostrSession) {
+        if let Some(previous) = self.take_active(accounts) {
+            accounts.parked.insert(previous.public_key.to_hex(), previous);
+        }
+        accounts.active = Some(session.public_key);
+        *self.delegation.lock().unwrap() = session.delegation;
+        *self.relay_list.lock().unwrap() = session.relay_list;
+        *self.blossom_servers.lock().unwrap() = session.blossom_servers;
+        *self.watch_only.lock().unwrap() = session.keys.is_none().then_some(session.public_key);
+        *self.keys.lock().unwrap() = session.keys;
+        *self.client.lock().unwrap() = Some(session.client);
+    }
+
+    /// Make a new session the active account. Returns the session it replaces when
+    /// the same account was already logged in, for the caller to disconnect.
+    fn start(&self, session: NostrSession) -> Option<NostrSession> {
+        let mut accounts = self.accounts.lock().unwrap();
+        let replaced = match accounts.parked.remove(&session.public_key.to_hex()) {
+            Some(parked) => Some(parked),
+            None if accounts.active == Some(session.public_key) => self.take_active(&mut accounts),
+            None => None,
+        };
+        self.activate(&mut accounts, session);
+        replaced
+    }
+
+    /// Switch the active account to another logged-in account
+    fn switch_to(&self, public_key: &PublicKey) -> Result<(), String> {
+        let mut accounts = self.accounts.lock().unwrap();
+        if accounts.active.as_ref() != Some(public_key) {
+            let pubkey = public_key.to_hex();
+            let session = accounts
+                .parked
+                .remove(&pubkey)
+                .ok_or_else(|| format!("Not logged in as {}", pubkey))?;
+            self.activate(&mut accounts, session);
+        }
+        Ok(())
+    }
+
+    /// Sign out the accounts `pick` selects, given each one's pubkey and whether it
+    /// holds keys. Returns their sessions for the caller to disconnect.
+    fn sign_out(&self, pick: impl Fn(&PublicKey, bool) -> bool) -> Vec<NostrSession> {
+        let mut accounts = self.accounts.lock().unwrap();
+        let mut removed = Vec::new();
+        if let Some(active) = accounts.active {
+            if pick(&active, self.keys.lock().unwrap().is_some()) {
+                removed.extend(self.take_active(&mut accounts));
+            }
+        }
+        let picked: Vec<String> = accounts
+            .parked
+            .iter()
+            .filter(|(_, session)| pick(&session.public_key, session.keys.is_some()))
+            .map(|(pubkey, _)| pubkey.clone())
+            .collect();
+        removed.extend(picked.iter().filter_map(|pubkey| accounts.parked.remove(pubkey)));
+        removed
+    }
+
+    /// Fill in an account's relay and Blossom server lists once they are fetched, wherever
+    /// its session is. Lists the user has changed since login are left alone.
+    fn set_fetched_lists(&self, public_key: &PublicKey, relay_list: Vec<RelayListEntry>, blossom_servers: Vec<String>) {
+        let mut accounts = self.accounts.lock().unwrap();
+        let fill = |current_relays: &mut Vec<RelayListEntry>, current_servers: &mut Vec<String>| {
+            if current_relays.is_empty() {
+                *current_relays = relay_list.clone();
+            }
+            if !blossom_servers.is_empty() && *current_servers == default_blossom_servers() {
+                *current_servers = blossom_servers.clone();
+            }
+        };
+        if accounts.active.as_ref() == Some(public_key) {
+            fill(&mut self.relay_list.lock().unwrap(), &mut self.blossom_servers.lock().unwrap());
+        } else if let Some(session) = accounts.parked.get_mut(&public_key.to_hex()) {
+            fill(&mut session.relay_list, &mut session.blossom_servers);
+        }
+    }
+
+    /// Public key of the active account, including watch-only accounts
+    fn active_public_key(&self) -> Option<PublicKey> {
+        self.accounts.lock().unwrap().active
+    }
+
+    /// Hex pubkey of the active account
+    fn active_pubkey(&self) -> Option<String> {
+        self.active_public_key().map(|pk| pk.to_hex())
+    }
+
+    /// Keys of the active account, for commands that sign
+    fn signing_keys(&self) -> Result<Keys, String> {
+        let _accounts = self.accounts.lock().unwrap();
+        if self.watch_only.lock().unwrap().is_some() {
+            return Err(READ_ONLY_SESSION_ERROR.to_string());
+        }
+        self.keys.lock().unwrap().clone().ok_or_else(|| "Not logged in".to_string())
+    }
+
+    /// The given Blossom server, else the first in the user's server list (kind 10063)
+    fn blossom_server(&self, server_url: Option<String>) -> Result<String, String> {
+        match server_url {
+            Some(server_url) => Ok(server_url),
+            None => self
+                .blossom_servers
+                .lock()
+                .unwrap()
+                .first()
+                .cloned()
+                .ok_or_else(|| "No Blossom server in your server list".to_string()),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
 struct NostrProfile {
     pubkey: String,
     npub: String,
+    #[serde(default)]
+    read_only: bool,
+}
+
+impl NostrProfile {
+    fn new(public_key: &PublicKey, read_only: bool) -> Self {
+        NostrProfile {
+            pubkey: public_key.to_hex(),
+            npub: public_key.to_bech32().unwrap_or_default(),
+            read_only,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
+struct NostrAccount {
+    pubkey: String,
+    npub: String,
+    active: bool,
+    read_only: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 struct SignedEvent {
     id: String,
     pubkey: String,
@@ -80,6 +323,28 @@ struct StoredKeyEntry {
     argon2_salt: String,
     created_at: u64,
     label: Option<String>, // Optional user-defined label
+    #[serde(default)]
+    require_os_auth: bool, // device mode only: the app asks for Touch ID / Windows Hello first (not cryptographic)
+    #[serde(default)]
+    kdf: KdfParams, // Argon2 settings used for this entry (password mode)
+}
+
+// Argon2id cost settings; entries saved before calibration existed use the defaults
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct KdfParams {
+    memory_kb: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams {
+            memory_kb: ARGON2_MEMORY_KB,
+            iterations: ARGON2_ITERATIONS,
+            parallelism: ARGON2_PARALLELISM,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -88,6 +353,24 @@ struct KeystoreFile {
     keys: Vec<StoredKeyEntry>,
 }
 
+// Passphrase-encrypted backup of the whole keystore
+#[derive(Serialize, Deserialize)]
+struct KeystoreBackupFile {
+    format: String,
+    version: u32,
+    created_at: u64,
+    argon2_salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+// Decrypted backup contents; device_keys lists entries re-wrapped from device mode
+#[derive(Serialize, Deserialize)]
+struct KeystoreBackupPayload {
+    keys: Vec<StoredKeyEntry>,
+    device_keys: Vec<String>,
+}
+
 // Legacy v1 format for migration
 #[derive(Serialize, Deserialize)]
 struct StoredKeyFileV1 {
@@ -106,6 +389,7 @@ struct StoredKeyInfo {
     mode: String,
     created_at: u64,
     label: Option<String>,
+    require_os_auth: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -113,14 +397,68 @@ struct StoredKeysResponse {
     keys: Vec<StoredKeyInfo>,
 }
 
+// A freshly generated identity; the nsec is only ever returned here
+#[derive(Serialize, Deserialize)]
+struct GeneratedKey {
+    pubkey: String,
+    npub: String,
+    nsec: String,
+    stored_mode: Option<String>, // "password" or "device" when saved to the keystore
+}
+
 // Constants for Argon2
 const ARGON2_MEMORY_KB: u32 = 65536; // 64MB
 const ARGON2_ITERATIONS: u32 = 3;
 const ARGON2_PARALLELISM: u32 = 1;
 
+// Calibration: time one unlock should take by default, and the upper bounds it may
+// tune up to. Calibrated settings are never weaker than the constants above. Memory
+// is capped well below what a fast machine could take, since a keystore synced or
+// restored to a smaller machine must still unlock there; iterations make up the rest.
+const KDF_CALIBRATION_TARGET_MS: u64 = 500;
+const KDF_MAX_MEMORY_KB: u32 = 256 * 1024; // 256MB
+const KDF_MAX_ITERATIONS: u32 = 64;
+
 // App-specific salt for device mode
 const DEVICE_MODE_APP_SALT: &[u8] = b"msp-studio-device-key-v1";
 
+// Format marker for keystore backup files
+const KEYSTORE_BACKUP_FORMAT: &str = "msp-studio-keystore-backup";
+
+/// Write a file through a synced temp file and a rename, so a crash leaves either
+/// the old or the new contents and never a torn file
+fn write_atomic(path: &std::path::Path, contents: &[u8]) -> Result<(), String> {
+    use std::io::Write;
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    let mut file = fs::File::create(&tmp).map_err(|e| e.to_string())?;
+    file.write_all(contents).map_err(|e| e.to_string())?;
+    file.sync_all().map_err(|e| e.to_string())?;
+    drop(file);
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+/// Local path named by a file:// URL, percent-decoded; None for other schemes or
+/// URLs that do not name a local file
+fn file_url_path(url: &str) -> Option<PathBuf> {
+    let url = reqwest::Url::parse(url.trim()).ok()?;
+    if url.scheme() != "file" {
+        return None;
+    }
+    url.to_file_path().ok()
+}
+
+/// file:// URL for a local path, percent-encoded so spaces and '#' survive a round trip
+/// through `file_url_path`
+fn file_url(path: &std::path::Path) -> String {
+    reqwest::Url::from_file_path(path)
+        .map(String::from)
+        .unwrap_or_else(|_| format!("file://{}", path.to_string_lossy()))
+}
+
 /// Get the current Unix timestamp in seconds
 fn get_current_timestamp() -> Result<u64, String> {
     std::time::SystemTime::now()
@@ -148,22 +486,67 @@ fn event_to_signed_event(event: &Event) -> SignedEvent {
 }
 
 /// Login helper that sets up the client with keys and connects to relays
-async fn login_with_keys(keys: Keys, state: &NostrState) -> Result<NostrProfile, String> {
-    let pubkey = keys.public_key().to_hex();
-    let npub = keys.public_key().to_bech32().map_err(|e| e.to_string())?;
+async fn login_with_keys(keys: Keys, state: &NostrState, app: &AppHandle) -> Result<NostrProfile, String> {
+    start_session(keys.public_key(), Some(keys), state, app).await
+}
+
+/// Blossom servers used until the account's own list (kind 10063) is known
+fn default_blossom_servers() -> Vec<String> {
+    setup::default_blossom_server().into_iter().collect()
+}
 
-    let client = Client::new(keys.clone());
+/// Connect a client for an account and make it the active one. Without keys the
+/// session is watch-only: reads work, signing fails with READ_ONLY_SESSION_ERROR.
+async fn start_session(
+    public_key: PublicKey,
+    keys: Option<Keys>,
+    state: &NostrState,
+    app: &AppHandle,
+) -> Result<NostrProfile, String> {
+    let pubkey = public_key.to_hex();
+    session_lock::touch();
+
+    // Answer NIP-42 AUTH challenges so paid/private relays accept our events
+    let mut builder = Client::builder().opts(Options::new().automatic_authentication(true));
+    if let Some(keys) = &keys {
+        builder = builder.signer(keys.clone());
+    }
+    let client = builder.build();
 
-    for relay in DEFAULT_RELAYS {
-        let _ = client.add_relay(*relay).await;
+    for relay in setup::bootstrap_relays() {
+        let _ = client.add_relay_with_opts(relay.as_str(), session_relay_options()).await;
     }
 
     client.connect().await;
 
-    *state.keys.lock().unwrap() = Some(keys);
-    *state.client.lock().unwrap() = Some(client);
+    // Logging in again as an account that is already signed in replaces its session
+    let read_only = keys.is_none();
+    let replaced = state.start(NostrSession {
+        delegation: load_delegation_for(&pubkey),
+        relay_list: Vec::new(),
+        blossom_servers: default_blossom_servers(),
+        public_key,
+        keys,
+        client: client.clone(),
+    });
+    if let Some(replaced) = replaced {
+        let _ = replaced.client.disconnect().await;
+    }
 
-    Ok(NostrProfile { pubkey, npub })
+    // The relay and Blossom server lists arrive in the background so login doesn't wait
+    // on slow relays; until then the bootstrap relays and default server are used
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let (relay_list, blossom_servers) = tokio::join!(
+            fetch_relay_list(&client, public_key),
+            fetch_blossom_servers(&client, public_key)
+        );
+        // Publish to the user's own NIP-65 write relays now that we know them
+        apply_relay_list(&client, &relay_list).await;
+        app.state::<NostrState>().set_fetched_lists(&public_key, relay_list, blossom_servers);
+    });
+
+    Ok(NostrProfile::new(&public_key, read_only))
 }
 
 /// Get the app data directory
@@ -231,21 +614,238 @@ fn sanitize_filename(title: &str) -> String {
     if result.is_empty() { "Untitled".to_string() } else { result }
 }
 
-/// Find a unique filename, appending _2, _3, etc. if needed
-fn unique_filename(feeds_dir: &std::path::Path, base: &str, current_slug: Option<&str>) -> String {
-    let candidate = base.to_string();
-    let xml_path = feeds_dir.join(format!("{}.xml", candidate));
-    if !xml_path.exists() || current_slug == Some(base) {
-        return candidate;
+/// Library schema migrations, applied in order via PRAGMA user_version
+const LIBRARY_MIGRATIONS: &[&str] = &[
+    "CREATE TABLE feeds (
+        id TEXT PRIMARY KEY,
+        title TEXT NOT NULL,
+        feed_type TEXT NOT NULL,
+        xml TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+    CREATE INDEX feeds_updated_at ON feeds(updated_at DESC);",
+    "CREATE TABLE feed_versions (
+        feed_id TEXT NOT NULL,
+        version INTEGER NOT NULL,
+        title TEXT NOT NULL,
+        feed_type TEXT NOT NULL,
+        xml TEXT NOT NULL,
+        saved_at INTEGER NOT NULL,
+        PRIMARY KEY (feed_id, version)
+    );",
+    "ALTER TABLE feeds ADD COLUMN app_version TEXT;
+    ALTER TABLE feed_versions ADD COLUMN app_version TEXT;",
+    "CREATE TABLE file_hashes (
+        path TEXT PRIMARY KEY,
+        size INTEGER NOT NULL,
+        mtime_ns INTEGER NOT NULL,
+        sha256 TEXT NOT NULL
+    );",
+    "CREATE TABLE directory_submissions (
+        feed_id TEXT NOT NULL,
+        directory TEXT NOT NULL,
+        status TEXT NOT NULL,
+        listing_url TEXT,
+        notes TEXT,
+        updated_at INTEGER NOT NULL,
+        PRIMARY KEY (feed_id, directory)
+    );",
+    "CREATE TABLE trashed_feeds (
+        id TEXT PRIMARY KEY,
+        title TEXT NOT NULL,
+        feed_type TEXT NOT NULL,
+        xml TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL,
+        app_version TEXT,
+        deleted_at INTEGER NOT NULL
+    );",
+    "ALTER TABLE feeds ADD COLUMN podcast_guid TEXT;
+    ALTER TABLE trashed_feeds ADD COLUMN podcast_guid TEXT;",
+    "CREATE TABLE track_waveforms (
+        feed_id TEXT NOT NULL,
+        item_guid TEXT NOT NULL,
+        source_url TEXT NOT NULL,
+        duration_secs REAL NOT NULL,
+        peaks TEXT NOT NULL,
+        generated_at INTEGER NOT NULL,
+        PRIMARY KEY (feed_id, item_guid)
+    );",
+    "CREATE TABLE upload_ledger (
+        sha256 TEXT NOT NULL,
+        target TEXT NOT NULL,
+        url TEXT NOT NULL,
+        size INTEGER NOT NULL,
+        local_path TEXT,
+        uploaded_at INTEGER NOT NULL,
+        PRIMARY KEY (sha256, target)
+    );",
+    "CREATE TABLE item_guids (
+        feed_id TEXT NOT NULL,
+        guid TEXT NOT NULL COLLATE NOCASE,
+        channel INTEGER NOT NULL,
+        PRIMARY KEY (feed_id, channel, guid)
+    );
+    CREATE INDEX item_guids_guid ON item_guids(guid);
+    ALTER TABLE feeds ADD COLUMN guids_indexed INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE trashed_feeds ADD COLUMN guids_indexed INTEGER NOT NULL DEFAULT 0;",
+];
+
+// Schema version that added feeds.podcast_guid; older libraries get it backfilled
+const PODCAST_GUID_SCHEMA_VERSION: usize = 7;
+
+// Number of previous revisions kept per feed
+const MAX_FEED_VERSIONS: i64 = 25;
+
+/// Get the library database path
+fn get_library_db_path() -> Result<PathBuf, String> {
+    let proj_dirs = ProjectDirs::from("com", "podtards", "msp-studio")
+        .ok_or("Could not determine app data directory")?;
+
+    let data_dir = proj_dirs.data_dir();
+    fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+
+    Ok(data_dir.join("library.db"))
+}
+
+/// Open the feed library, creating/migrating the schema and importing loose files
+fn open_library() -> Result<rusqlite::Connection, String> {
+    let conn = rusqlite::Connection::open(get_library_db_path()?).map_err(|e| e.to_string())?;
+    conn.busy_timeout(std::time::Duration::from_secs(5))
+        .map_err(|e| e.to_string())?;
+
+    let version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    let version = version as usize;
+
+    if version < LIBRARY_MIGRATIONS.len() {
+        for (i, sql) in LIBRARY_MIGRATIONS.iter().enumerate().skip(version) {
+            conn.execute_batch(sql).map_err(|e| format!("Library migration {} failed: {}", i + 1, e))?;
+            conn.execute_batch(&format!("PRAGMA user_version = {}", i + 1))
+                .map_err(|e| e.to_string())?;
+        }
+        if version == 0 {
+            import_loose_feed_files(&conn)?;
+        }
+        if version < PODCAST_GUID_SCHEMA_VERSION {
+            podcast_guid::backfill_pinned_guids(&conn)?;
+        }
+    }
+
+    Ok(conn)
+}
+
+/// One-time import of the per-feed .xml/.json files into the library.
+/// Imported files are moved to feeds/migrated/ as a backup.
+fn import_loose_feed_files(conn: &rusqlite::Connection) -> Result<(), String> {
+    let feeds_dir = get_data_dir()?;
+    let migrated_dir = feeds_dir.join("migrated");
+
+    let entries = fs::read_dir(&feeds_dir).map_err(|e| e.to_string())?;
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+        let feed = if let Some(slug) = filename.strip_suffix(".xml") {
+            if check_feed_file(&path).is_some() {
+                continue; // Left for the integrity check to quarantine
+            }
+            let xml = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            let mtime = file_mtime(&path);
+            LocalFeed {
+                id: slug.to_string(),
+                title: extract_xml_title(&xml).unwrap_or_else(|| slug.to_string()),
+                feed_type: detect_feed_type(&xml),
+                xml,
+                created_at: mtime,
+                updated_at: mtime,
+            }
+        } else if filename.ends_with(".json") && !filename.ends_with(".meta.json") {
+            if check_feed_file(&path).is_some() {
+                continue;
+            }
+            let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            parse_feed_record(&content)?
+        } else {
+            continue;
+        };
+
+        // .xml files win over legacy .json copies of the same feed
+        conn.execute(
+            "INSERT OR IGNORE INTO feeds (id, title, feed_type, xml, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                feed.id,
+                library_crypto::seal(feed.title)?,
+                feed.feed_type,
+                library_crypto::seal(feed.xml)?,
+                feed.created_at,
+                feed.updated_at
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+        fs::create_dir_all(&migrated_dir).map_err(|e| e.to_string())?;
+        let _ = fs::rename(&path, migrated_dir.join(&filename));
+    }
+
+    Ok(())
+}
+
+/// Map a feeds row to a LocalFeed, decrypting the title and XML if the library is encrypted
+fn row_to_local_feed(row: &rusqlite::Row) -> rusqlite::Result<LocalFeed> {
+    let open = |index: usize| -> rusqlite::Result<String> {
+        library_crypto::open(row.get(index)?)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, e.into()))
+    };
+    let xml = open(3)?;
+    Ok(LocalFeed {
+        id: row.get(0)?,
+        title: open(1)?,
+        feed_type: row.get(2)?,
+        xml,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+/// Fetch a single feed from the library
+fn get_library_feed(conn: &rusqlite::Connection, id: &str) -> Result<Option<LocalFeed>, String> {
+    use rusqlite::OptionalExtension;
+
+    conn.query_row(
+        "SELECT id, title, feed_type, xml, created_at, updated_at FROM feeds WHERE id = ?1",
+        [id],
+        row_to_local_feed,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Find a unique feed id, appending _2, _3, etc. if needed
+fn unique_feed_id(conn: &rusqlite::Connection, base: &str, current_id: Option<&str>) -> Result<String, String> {
+    // Ids in the trash stay reserved so a trashed feed can always be restored
+    let taken = |id: &str| -> Result<bool, String> {
+        conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM feeds WHERE id = ?1) OR EXISTS(SELECT 1 FROM trashed_feeds WHERE id = ?1)",
+            [id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())
+    };
+
+    if current_id == Some(base) || !taken(base)? {
+        return Ok(base.to_string());
     }
     for n in 2..100 {
         let suffixed = format!("{}_{}", base, n);
-        let path = feeds_dir.join(format!("{}.xml", suffixed));
-        if !path.exists() || current_slug == Some(suffixed.as_str()) {
-            return suffixed;
+        if current_id == Some(suffixed.as_str()) || !taken(&suffixed)? {
+            return Ok(suffixed);
         }
     }
-    Uuid::new_v4().to_string()
+    Ok(Uuid::new_v4().to_string())
 }
 
 /// Get file modification time as unix timestamp
@@ -258,162 +858,303 @@ fn file_mtime(path: &std::path::Path) -> u64 {
         .unwrap_or(0)
 }
 
-/// Save a feed locally as a plain XML file with title-based filename
+/// Snapshot a feed's current state into its revision history, pruning old revisions
+fn snapshot_feed_version(conn: &rusqlite::Connection, feed: &LocalFeed) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO feed_versions (feed_id, version, title, feed_type, xml, saved_at, app_version)
+         VALUES (?1, (SELECT COALESCE(MAX(version), 0) + 1 FROM feed_versions WHERE feed_id = ?1), ?2, ?3, ?4, ?5,
+                 (SELECT app_version FROM feeds WHERE id = ?1))",
+        rusqlite::params![
+            feed.id,
+            library_crypto::seal(feed.title.clone())?,
+            feed.feed_type,
+            library_crypto::seal(feed.xml.clone())?,
+            feed.updated_at
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "DELETE FROM feed_versions WHERE feed_id = ?1 AND version <=
+         (SELECT MAX(version) FROM feed_versions WHERE feed_id = ?1) - ?2",
+        rusqlite::params![feed.id, MAX_FEED_VERSIONS],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Save a feed to the local library with a title-based id. Dates and the generator
+/// tag are maintained automatically unless `stamp` turns that off or overrides them.
 #[tauri::command]
 fn save_feed_local(
     id: Option<String>,
     title: String,
     feed_type: String,
     xml: String,
+    stamp: Option<feed_model::FeedStampOptions>,
 ) -> Result<LocalFeed, String> {
-    let feeds_dir = get_data_dir()?;
+    let _operation = shutdown::begin("feed-write", &title);
+    let mut conn = open_library()?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let feed = write_library_feed(&tx, id, title, feed_type, xml, stamp)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(feed)
+}
+
+/// Save a feed inside the caller's transaction, so several saves can commit together
+fn write_library_feed(
+    tx: &rusqlite::Connection,
+    id: Option<String>,
+    title: String,
+    feed_type: String,
+    xml: String,
+    stamp: Option<feed_model::FeedStampOptions>,
+) -> Result<LocalFeed, String> {
+    let now = get_current_timestamp()?;
+    let old_id = id.as_deref();
 
-    // If an old slug (filename) was provided, delete the old file if title changed
-    let old_slug = id.as_deref();
-    if let Some(slug) = old_slug {
-        // Clean up old .xml file
-        let old_xml = feeds_dir.join(format!("{}.xml", slug));
-        let _ = fs::remove_file(&old_xml);
-        // Clean up leftover .meta.json if present
-        let old_meta = feeds_dir.join(format!("{}.meta.json", slug));
-        let _ = fs::remove_file(&old_meta);
-        // Clean up legacy .json if present
-        let old_json = feeds_dir.join(format!("{}.json", slug));
-        let _ = fs::remove_file(&old_json);
+    let previous = match old_id {
+        Some(old) => get_library_feed(tx, old)?,
+        None => None,
+    };
+    let xml = feed_model::stamp_feed_xml(&xml, previous.as_ref().map(|f| f.xml.as_str()), &stamp.unwrap_or_default());
+    // The startup integrity check quarantines feeds that fail this, so never store one
+    if let Some(reason) = check_feed_xml(&xml) {
+        return Err(format!("Not a valid RSS feed: {}", reason));
     }
+    batch::ensure_unique_guids(tx, old_id, &xml)?;
 
-    let base_slug = sanitize_filename(&title);
-    let slug = unique_filename(&feeds_dir, &base_slug, old_slug);
-    let xml_path = feeds_dir.join(format!("{}.xml", slug));
+    // Keep the original creation time when the feed is renamed/updated
+    let created_at = previous.as_ref().map(|f| f.created_at).unwrap_or(now);
 
-    fs::write(&xml_path, &xml).map_err(|e| e.to_string())?;
+    // The GUID stays pinned to the first one saved; validation warns when it drifts
+    let pinned_guid = match &previous {
+        Some(previous) => podcast_guid::pinned_guid(tx, &previous.id)?,
+        None => None,
+    }
+    .or_else(|| podcast_guid::xml_guid(&xml));
 
-    let mtime = file_mtime(&xml_path);
+    // An encrypted library gives new feeds opaque ids, since ids are stored readable
+    let base_id = match (library_crypto::enabled()?, old_id) {
+        (false, _) => sanitize_filename(&title),
+        (true, Some(old)) => old.to_string(),
+        (true, None) => Uuid::new_v4().to_string(),
+    };
+    let new_id = unique_feed_id(tx, &base_id, old_id)?;
+    if let Some(previous) = &previous {
+        // Saves that change nothing but the stamped dates don't add a revision
+        let unchanged = previous.title == title
+            && previous.feed_type == feed_type
+            && feed_model::same_content(&previous.xml, &xml);
+        if !unchanged {
+            snapshot_feed_version(tx, previous)?;
+        }
+        tx.execute("DELETE FROM feeds WHERE id = ?1", [&previous.id])
+            .map_err(|e| e.to_string())?;
+        // Records left under the new id belong to no live or trashed feed (the id
+        // was free), so they are cleared rather than colliding with the moved ones
+        if new_id != previous.id {
+            for table in ["feed_versions", "directory_submissions", "track_waveforms"] {
+                tx.execute(&format!("DELETE FROM {} WHERE feed_id = ?1", table), [&new_id])
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        // History follows the feed when a title change renames it
+        tx.execute(
+            "UPDATE feed_versions SET feed_id = ?1 WHERE feed_id = ?2",
+            rusqlite::params![new_id, previous.id],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute(
+            "UPDATE directory_submissions SET feed_id = ?1 WHERE feed_id = ?2",
+            rusqlite::params![new_id, previous.id],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute(
+            "UPDATE track_waveforms SET feed_id = ?1 WHERE feed_id = ?2",
+            rusqlite::params![new_id, previous.id],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM item_guids WHERE feed_id = ?1", [&previous.id])
+            .map_err(|e| e.to_string())?;
+    }
 
-    Ok(LocalFeed {
-        id: slug,
+    let feed = LocalFeed {
+        id: new_id,
         title,
         feed_type,
         xml,
-        created_at: mtime,
-        updated_at: mtime,
-    })
+        created_at,
+        updated_at: now,
+    };
+    tx.execute(
+        "INSERT OR REPLACE INTO feeds (id, title, feed_type, xml, created_at, updated_at, app_version, podcast_guid, guids_indexed)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 1)",
+        rusqlite::params![
+            feed.id,
+            library_crypto::seal(feed.title.clone())?,
+            feed.feed_type,
+            library_crypto::seal(feed.xml.clone())?,
+            feed.created_at,
+            feed.updated_at,
+            APP_VERSION,
+            pinned_guid
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    batch::index_guids(tx, &feed.id, &feed.xml)?;
+
+    Ok(feed)
 }
 
-/// Load a feed by slug (filename stem)
+/// Load a feed by id
 #[tauri::command]
 fn load_feed_local(id: String) -> Result<LocalFeed, String> {
-    let feeds_dir = get_data_dir()?;
-
-    // Try .xml file
-    let xml_path = feeds_dir.join(format!("{}.xml", id));
-    if xml_path.exists() {
-        let xml = fs::read_to_string(&xml_path).map_err(|e| e.to_string())?;
-        let title = extract_xml_title(&xml).unwrap_or_else(|| id.clone());
-        let feed_type = detect_feed_type(&xml);
-        let mtime = file_mtime(&xml_path);
-        return Ok(LocalFeed {
-            id,
-            title,
-            feed_type,
-            xml,
-            created_at: mtime,
-            updated_at: mtime,
-        });
-    }
-
-    // Legacy .json fallback
-    let legacy_path = feeds_dir.join(format!("{}.json", id));
-    if legacy_path.exists() {
-        let content = fs::read_to_string(&legacy_path).map_err(|e| e.to_string())?;
-        let feed: LocalFeed = serde_json::from_str(&content).map_err(|e| e.to_string())?;
-        return Ok(feed);
-    }
-
-    Err(format!("Feed not found: {}", id))
+    let conn = open_library()?;
+    get_library_feed(&conn, &id)?.ok_or_else(|| format!("Feed not found: {}", id))
 }
 
-/// List all local feeds (scans .xml files in feeds directory)
+/// List all local feeds (summaries come from the index, without loading XML)
 #[tauri::command]
 fn list_feeds_local() -> Result<Vec<FeedSummary>, String> {
-    let feeds_dir = get_data_dir()?;
+    let conn = open_library()?;
 
-    let mut feeds = Vec::new();
-    let mut seen_slugs = std::collections::HashSet::new();
-
-    // Scan .xml files
-    let entries = fs::read_dir(&feeds_dir).map_err(|e| e.to_string())?;
-    for entry in entries {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
-        let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let mut stmt = conn
+        .prepare("SELECT id, title, feed_type, created_at, updated_at FROM feeds ORDER BY updated_at DESC")
+        .map_err(|e| e.to_string())?;
+    let feeds = stmt
+        .query_map([], |row| {
+            Ok(FeedSummary {
+                id: row.get(0)?,
+                title: library_crypto::open_title(row.get(1)?),
+                feed_type: row.get(2)?,
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
 
-        if filename.ends_with(".xml") {
-            let slug = filename.trim_end_matches(".xml").to_string();
-            if let Ok(xml) = fs::read_to_string(&path) {
-                let title = extract_xml_title(&xml).unwrap_or_else(|| slug.clone());
-                let feed_type = detect_feed_type(&xml);
-                let mtime = file_mtime(&path);
-                seen_slugs.insert(slug.clone());
-                feeds.push(FeedSummary {
-                    id: slug,
-                    title,
-                    feed_type,
-                    created_at: mtime,
-                    updated_at: mtime,
-                });
-            }
-        }
-    }
+    Ok(feeds)
+}
 
-    // Legacy fallback: .json files
-    let entries = fs::read_dir(&feeds_dir).map_err(|e| e.to_string())?;
-    for entry in entries {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
-        let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+/// Read the XML of every local feed as (id, xml) pairs
+fn load_all_local_feed_xml() -> Result<Vec<(String, String)>, String> {
+    stored_feed_xml(&open_library()?, "feeds")
+}
 
-        if filename.ends_with(".json") && !filename.ends_with(".meta.json") {
-            if let Ok(content) = fs::read_to_string(&path) {
-                if let Ok(feed) = serde_json::from_str::<LocalFeed>(&content) {
-                    if !seen_slugs.contains(&feed.id) {
-                        feeds.push(FeedSummary {
-                            id: feed.id,
-                            title: feed.title,
-                            feed_type: feed.feed_type,
-                            created_at: feed.created_at,
-                            updated_at: feed.updated_at,
-                        });
-                    }
-                }
-            }
-        }
-    }
+/// (id, xml) of every feed in a library table ("feeds" or "trashed_feeds")
+fn stored_feed_xml(conn: &rusqlite::Connection, table: &str) -> Result<Vec<(String, String)>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT id, xml FROM {}", table))
+        .map_err(|e| e.to_string())?;
+    let feeds: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
 
-    feeds.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
-    Ok(feeds)
+    feeds
+        .into_iter()
+        .map(|(id, xml)| Ok((id, library_crypto::open(xml)?)))
+        .collect()
 }
 
-/// Delete a feed by slug
+/// Delete a feed by id, moving it to the trash until it is purged
 #[tauri::command]
 fn delete_feed_local(id: String) -> Result<(), String> {
-    let feeds_dir = get_data_dir()?;
-    let xml_path = feeds_dir.join(format!("{}.xml", id));
-    let legacy_path = feeds_dir.join(format!("{}.json", id));
-    let meta_path = feeds_dir.join(format!("{}.meta.json", id));
+    let mut conn = open_library()?;
+    trash::move_to_trash(&mut conn, &id)
+}
 
-    let mut found = false;
-    for path in [&xml_path, &legacy_path, &meta_path] {
-        if path.exists() {
-            fs::remove_file(path).map_err(|e| e.to_string())?;
-            found = true;
-        }
-    }
+// Feed version history types
+#[derive(Serialize, Deserialize)]
+struct FeedVersionSummary {
+    version: u32,
+    title: String,
+    feed_type: String,
+    saved_at: u64,
+    size: Option<usize>, // bytes of feed XML; None while a sealed revision can't be opened
+    app_version: Option<String>, // app version that produced this revision
+}
 
-    if !found {
-        return Err(format!("Feed not found: {}", id));
-    }
-    Ok(())
+/// List the saved revisions of a feed, newest first
+#[tauri::command]
+fn list_feed_versions(id: String) -> Result<Vec<FeedVersionSummary>, String> {
+    let conn = open_library()?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT version, title, feed_type, saved_at, xml, app_version FROM feed_versions
+             WHERE feed_id = ?1 ORDER BY version DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let versions = stmt
+        .query_map([&id], |row| {
+            Ok(FeedVersionSummary {
+                version: row.get(0)?,
+                title: library_crypto::open_title(row.get(1)?),
+                feed_type: row.get(2)?,
+                saved_at: row.get(3)?,
+                size: library_crypto::open(row.get(4)?).ok().map(|xml| xml.len()),
+                app_version: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(versions)
+}
+
+/// Restore a feed to a previous revision (the current state is kept as a new revision)
+#[tauri::command]
+fn restore_feed_version(id: String, version: u32) -> Result<LocalFeed, String> {
+    use rusqlite::OptionalExtension;
+
+    let mut conn = open_library()?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let current = get_library_feed(&tx, &id)?.ok_or_else(|| format!("Feed not found: {}", id))?;
+    let (title, feed_type, xml, app_version): (String, String, String, Option<String>) = tx
+        .query_row(
+            "SELECT title, feed_type, xml, app_version FROM feed_versions WHERE feed_id = ?1 AND version = ?2",
+            rusqlite::params![id, version],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Version {} not found for feed {}", version, id))?;
+
+    snapshot_feed_version(&tx, &current)?;
+
+    let restored = LocalFeed {
+        id: current.id,
+        title: library_crypto::open(title)?,
+        feed_type,
+        xml: library_crypto::open(xml)?,
+        created_at: current.created_at,
+        updated_at: get_current_timestamp()?,
+    };
+    tx.execute(
+        "UPDATE feeds SET title = ?2, feed_type = ?3, xml = ?4, updated_at = ?5, app_version = ?6, guids_indexed = 0
+         WHERE id = ?1",
+        rusqlite::params![
+            restored.id,
+            library_crypto::seal(restored.title.clone())?,
+            restored.feed_type,
+            library_crypto::seal(restored.xml.clone())?,
+            restored.updated_at,
+            app_version
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(restored)
 }
 
 /// Export feed XML to a file (using native save dialog)
@@ -423,366 +1164,3438 @@ fn get_feeds_directory() -> Result<String, String> {
     Ok(feeds_dir.to_string_lossy().to_string())
 }
 
-// Blossom server types
-#[derive(Serialize, Deserialize)]
-struct BlossomUploadResult {
-    url: String,
-    sha256: String,
-    size: usize,
+// ============================================================================
+// Startup Integrity Check
+// ============================================================================
+
+// Integrity check types
+#[derive(Serialize, Deserialize, Clone)]
+struct QuarantinedFile {
+    file: String,
+    reason: String,
+    quarantined_as: String,
+    recovered: bool,
+    backup_saved_at: Option<u64>, // older pre-library backup, restored only if the user asks
 }
 
-/// Create a Blossom auth event (kind 24242)
-fn create_blossom_auth(
-    keys: &Keys,
-    sha256: &str,
-    action: &str,
-    expiration_secs: u64,
-) -> Result<Event, String> {
-    let expiration = get_current_timestamp()? + expiration_secs;
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct IntegrityReport {
+    checked_at: u64,
+    created_dirs: Vec<String>,
+    library_status: String,
+    quarantined: Vec<QuarantinedFile>,
+}
 
-    let event = EventBuilder::new(Kind::from(24242), "")
-        .tag(Tag::parse(["t", action]).map_err(|e| e.to_string())?)
-        .tag(Tag::parse(["x", sha256]).map_err(|e| e.to_string())?)
-        .tag(Tag::parse(["expiration", &expiration.to_string()]).map_err(|e| e.to_string())?)
+struct IntegrityState {
+    last_report: Mutex<Option<IntegrityReport>>,
+}
+
+/// Get the quarantine directory for corrupt feed files
+fn get_quarantine_dir() -> Result<PathBuf, String> {
+    let proj_dirs = ProjectDirs::from("com", "podtards", "msp-studio")
+        .ok_or("Could not determine app data directory")?;
+
+    let quarantine_dir = proj_dirs.data_dir().join("quarantine");
+    fs::create_dir_all(&quarantine_dir).map_err(|e| e.to_string())?;
+
+    Ok(quarantine_dir)
+}
+
+/// Check feed XML for truncation or corruption, returning the reason if it is bad
+fn check_feed_xml(xml: &str) -> Option<String> {
+    if xml.trim().is_empty() {
+        return Some("Feed is empty".to_string());
+    }
+    if !xml.contains("<rss") {
+        return Some("Missing <rss> root element".to_string());
+    }
+    if !xml.trim_end().ends_with("</rss>") {
+        return Some("Truncated - missing closing </rss>".to_string());
+    }
+    None
+}
+
+/// Check a feed file for truncation or corruption, returning the reason if it is bad
+fn check_feed_file(path: &std::path::Path) -> Option<String> {
+    let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => return Some(format!("Unreadable: {}", e)),
+    };
+
+    if content.trim().is_empty() {
+        return Some("File is empty".to_string());
+    }
+
+    if filename.ends_with(".xml") {
+        return check_feed_xml(&content);
+    } else if filename.ends_with(".json") {
+        if let Err(e) = parse_feed_record(&content) {
+            return Some(format!("Invalid feed JSON: {}", e));
+        }
+    }
+
+    None
+}
+
+/// Try to restore a quarantined feed from its version history
+fn recover_from_history(conn: &rusqlite::Connection, id: &str) -> bool {
+    // Newest intact revision from the feed's version history
+    let mut history = Vec::new();
+    if let Ok(mut stmt) = conn.prepare(
+        "SELECT title, feed_type, xml, saved_at FROM feed_versions WHERE feed_id = ?1 ORDER BY version DESC",
+    ) {
+        if let Ok(rows) = stmt.query_map([id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, u64>(3)?))
+        }) {
+            history.extend(rows.filter_map(|r| r.ok()));
+        }
+    }
+    // Sealed revisions can only be checked while the encrypted library is unlocked
+    if let Some((title, feed_type, xml, saved_at)) = history
+        .into_iter()
+        .filter_map(|(title, feed_type, xml, saved_at)| Some((title, feed_type, library_crypto::open(xml).ok()?, saved_at)))
+        .find(|(_, _, xml, _)| check_feed_xml(xml).is_none())
+    {
+        let Ok(xml) = library_crypto::seal(xml) else {
+            return false;
+        };
+        return conn
+            .execute(
+                "INSERT OR REPLACE INTO feeds (id, title, feed_type, xml, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+                rusqlite::params![id, title, feed_type, xml, saved_at],
+            )
+            .is_ok();
+    }
+
+    false
+}
+
+/// An intact pre-library backup of a feed from feeds/migrated/, if there is one
+fn read_migrated_backup(id: &str) -> Option<LocalFeed> {
+    let migrated_dir = get_data_dir().ok()?.join("migrated");
+    let xml_backup = migrated_dir.join(format!("{}.xml", id));
+    let json_backup = migrated_dir.join(format!("{}.json", id));
+    if check_feed_file(&xml_backup).is_none() {
+        fs::read_to_string(&xml_backup).ok().map(|xml| LocalFeed {
+            id: id.to_string(),
+            title: extract_xml_title(&xml).unwrap_or_else(|| id.to_string()),
+            feed_type: detect_feed_type(&xml),
+            created_at: file_mtime(&xml_backup),
+            updated_at: file_mtime(&xml_backup),
+            xml,
+        })
+    } else {
+        fs::read_to_string(&json_backup)
+            .ok()
+            .and_then(|content| parse_feed_record(&content).ok())
+            .filter(|feed| check_feed_xml(&feed.xml).is_none())
+    }
+}
+
+/// Insert a recovered feed into the library
+fn insert_recovered_feed(conn: &rusqlite::Connection, feed: LocalFeed) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO feeds (id, title, feed_type, xml, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![
+            feed.id,
+            library_crypto::seal(feed.title)?,
+            feed.feed_type,
+            library_crypto::seal(feed.xml)?,
+            feed.created_at,
+            feed.updated_at
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Move a corrupt loose feed file into quarantine
+fn quarantine_feed_file(path: &std::path::Path, reason: String) -> Result<QuarantinedFile, String> {
+    let quarantine_dir = get_quarantine_dir()?;
+    let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+    let quarantined_as = format!("{}-{}", get_current_timestamp()?, filename);
+    fs::rename(path, quarantine_dir.join(&quarantined_as)).map_err(|e| e.to_string())?;
+
+    Ok(QuarantinedFile {
+        file: filename,
+        reason,
+        quarantined_as,
+        recovered: false,
+        backup_saved_at: None,
+    })
+}
+
+/// Move a corrupt library feed into quarantine and attempt recovery. The feed and its
+/// GUID index entries go in one transaction; its waveforms are kept if it is recovered.
+fn quarantine_library_feed(
+    conn: &mut rusqlite::Connection,
+    feed: &LocalFeed,
+    reason: String,
+) -> Result<QuarantinedFile, String> {
+    let quarantined_as = format!("{}-{}.json", get_current_timestamp()?, feed.id);
+    fs::write(get_quarantine_dir()?.join(&quarantined_as), serialize_feed_record(feed)?)
+        .map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM feeds WHERE id = ?1", [&feed.id])
+        .map_err(|e| e.to_string())?;
+    // A recovered feed is reindexed from its restored XML on the next library open
+    tx.execute("DELETE FROM item_guids WHERE feed_id = ?1", [&feed.id])
+        .map_err(|e| e.to_string())?;
+
+    let mut recovered = recover_from_history(&tx, &feed.id);
+    let mut backup_saved_at = None;
+    if !recovered {
+        // A pre-library backup predating the feed's last save would roll it back silently,
+        // so only one at least as new is restored; older ones wait for the user
+        match read_migrated_backup(&feed.id) {
+            Some(backup) if backup.updated_at >= feed.updated_at => {
+                recovered = insert_recovered_feed(&tx, backup).is_ok();
+            }
+            Some(backup) => backup_saved_at = Some(backup.updated_at),
+            None => {}
+        }
+    }
+    if !recovered {
+        tx.execute("DELETE FROM track_waveforms WHERE feed_id = ?1", [&feed.id])
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(QuarantinedFile {
+        file: feed.id.clone(),
+        reason,
+        quarantined_as,
+        recovered,
+        backup_saved_at,
+    })
+}
+
+/// Verify the data directory structure and quarantine corrupt feeds
+fn run_integrity_check() -> Result<IntegrityReport, String> {
+    let proj_dirs = ProjectDirs::from("com", "podtards", "msp-studio")
+        .ok_or("Could not determine app data directory")?;
+    let data_dir = proj_dirs.data_dir();
+
+    let mut report = IntegrityReport {
+        checked_at: get_current_timestamp()?,
+        ..Default::default()
+    };
+
+    for sub in ["feeds", "appstate", "quarantine"] {
+        let dir = data_dir.join(sub);
+        if !dir.exists() {
+            fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            report.created_dirs.push(sub.to_string());
+        }
+    }
+
+    // Opening the library imports any loose feed files that are still intact
+    let mut conn = open_library()?;
+    report.library_status = conn
+        .query_row("PRAGMA quick_check", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+
+    // Loose files left behind are ones that failed to import
+    let feeds_dir = get_data_dir()?;
+    let entries = fs::read_dir(&feeds_dir).map_err(|e| e.to_string())?;
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let is_feed_file = filename.ends_with(".xml")
+            || (filename.ends_with(".json") && !filename.ends_with(".meta.json"));
+
+        if !is_feed_file {
+            continue;
+        }
+
+        if let Some(reason) = check_feed_file(&path) {
+            report.quarantined.push(quarantine_feed_file(&path, reason)?);
+        }
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT id, title, feed_type, xml, created_at, updated_at FROM feeds")
+        .map_err(|e| e.to_string())?;
+    let corrupt: Vec<(LocalFeed, String)> = stmt
+        .query_map([], row_to_local_feed)
+        .map_err(|e| e.to_string())?
+        .filter_map(|row| row.ok())
+        .filter_map(|feed| check_feed_xml(&feed.xml).map(|reason| (feed, reason)))
+        .collect();
+    drop(stmt);
+    for (feed, reason) in corrupt {
+        report.quarantined.push(quarantine_library_feed(&mut conn, &feed, reason)?);
+    }
+
+    if !report.quarantined.is_empty() {
+        let report_path = get_quarantine_dir()?.join(format!("report-{}.json", report.checked_at));
+        let json = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+        fs::write(report_path, json).map_err(|e| e.to_string())?;
+    }
+
+    Ok(report)
+}
+
+/// Restore a quarantined feed from its older pre-library backup, once the user has
+/// chosen to accept losing the changes made since
+#[tauri::command]
+fn restore_feed_backup(id: String) -> Result<LocalFeed, String> {
+    let conn = open_library()?;
+    if get_library_feed(&conn, &id)?.is_some() {
+        return Err(format!("Feed {} is already in the library", id));
+    }
+    let feed = read_migrated_backup(&id).ok_or_else(|| format!("No backup found for {}", id))?;
+    insert_recovered_feed(&conn, feed.clone())?;
+    Ok(feed)
+}
+
+/// Get the integrity report from startup (or the most recent check)
+#[tauri::command]
+fn get_integrity_report(state: State<'_, IntegrityState>) -> Option<IntegrityReport> {
+    state.last_report.lock().unwrap().clone()
+}
+
+/// Re-run the data integrity check on demand
+#[tauri::command]
+fn check_data_integrity(state: State<'_, IntegrityState>) -> Result<IntegrityReport, String> {
+    let report = run_integrity_check()?;
+    *state.last_report.lock().unwrap() = Some(report.clone());
+    Ok(report)
+}
+
+// ============================================================================
+// Format Versioning & Migrations
+// ============================================================================
+
+// Current on-disk format version for feed records
+const FEED_FORMAT_VERSION: u32 = 1;
+
+// Current keystore file format version
+const KEYSTORE_FORMAT_VERSION: u32 = 2;
+
+/// A single schema migration step from `from` to `from + 1`
+struct Migration {
+    from: u32,
+    migrate: fn(serde_json::Value) -> Result<serde_json::Value, String>,
+}
+
+/// Feed record migrations, in order (v0 is the unversioned legacy LocalFeed JSON)
+const FEED_MIGRATIONS: &[Migration] = &[Migration { from: 0, migrate: migrate_feed_v0_to_v1 }];
+
+/// Keystore migrations, in order
+const KEYSTORE_MIGRATIONS: &[Migration] = &[Migration { from: 1, migrate: migrate_keystore_v1_to_v2 }];
+
+/// Versioned envelope for feed records written to disk
+#[derive(Serialize, Deserialize)]
+struct FeedEnvelope {
+    version: u32,
+    feed: LocalFeed,
+}
+
+/// Run migrations until the value reaches the target version.
+/// Returns the migrated value and whether any migration ran.
+fn apply_migrations(
+    mut value: serde_json::Value,
+    migrations: &[Migration],
+    target: u32,
+) -> Result<(serde_json::Value, bool), String> {
+    let mut version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+
+    if version > target {
+        return Err(format!(
+            "Unsupported format version {} (this app supports up to {})",
+            version, target
+        ));
+    }
+
+    let migrated = version < target;
+    while version < target {
+        let step = migrations
+            .iter()
+            .find(|m| m.from == version)
+            .ok_or_else(|| format!("No migration from format version {}", version))?;
+        value = (step.migrate)(value)?;
+        version += 1;
+        value["version"] = serde_json::json!(version);
+    }
+
+    Ok((value, migrated))
+}
+
+/// v0 -> v1: wrap the bare LocalFeed in an envelope and fill fields older files may lack
+fn migrate_feed_v0_to_v1(mut value: serde_json::Value) -> Result<serde_json::Value, String> {
+    let feed = value.as_object_mut().ok_or("Feed record is not a JSON object")?;
+    feed.remove("version");
+
+    if !feed.contains_key("feed_type") {
+        let xml = feed.get("xml").and_then(|x| x.as_str()).unwrap_or_default();
+        let feed_type = detect_feed_type(xml);
+        feed.insert("feed_type".to_string(), serde_json::json!(feed_type));
+    }
+    for field in ["created_at", "updated_at"] {
+        feed.entry(field.to_string()).or_insert(serde_json::json!(0));
+    }
+
+    Ok(serde_json::json!({ "version": 1, "feed": value }))
+}
+
+/// v1 -> v2: move the single flat key entry into a keys list
+fn migrate_keystore_v1_to_v2(value: serde_json::Value) -> Result<serde_json::Value, String> {
+    let v1: StoredKeyFileV1 = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    let entry = StoredKeyEntry {
+        pubkey: v1.pubkey,
+        mode: v1.mode,
+        nonce: v1.nonce,
+        ciphertext: v1.ciphertext,
+        argon2_salt: v1.argon2_salt,
+        created_at: v1.created_at,
+        label: None,
+        require_os_auth: false,
+        kdf: KdfParams::default(),
+    };
+    let keystore = KeystoreFile {
+        version: 2,
+        keys: vec![entry],
+    };
+    serde_json::to_value(keystore).map_err(|e| e.to_string())
+}
+
+/// Parse a feed record of any known version into the current LocalFeed
+fn parse_feed_record(content: &str) -> Result<LocalFeed, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("Invalid feed JSON: {}", e))?;
+    let (value, _) = apply_migrations(value, FEED_MIGRATIONS, FEED_FORMAT_VERSION)?;
+    let envelope: FeedEnvelope = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    Ok(envelope.feed)
+}
+
+/// Serialize a feed record in the current versioned envelope
+fn serialize_feed_record(feed: &LocalFeed) -> Result<String, String> {
+    let envelope = FeedEnvelope {
+        version: FEED_FORMAT_VERSION,
+        feed: feed.clone(),
+    };
+    serde_json::to_string_pretty(&envelope).map_err(|e| e.to_string())
+}
+
+// Blossom server types
+#[derive(Serialize, Deserialize)]
+struct BlossomUploadResult {
+    url: String,
+    sha256: String,
+    size: usize,
+}
+
+impl From<storage::StoredFile> for BlossomUploadResult {
+    fn from(file: storage::StoredFile) -> Self {
+        BlossomUploadResult {
+            url: file.url,
+            sha256: file.sha256,
+            size: file.size as usize,
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+struct BlossomUploadProgress {
+    file_path: String,
+    server_url: String,
+    bytes_sent: u64,
+    total_bytes: u64,
+}
+
+// Read size for streamed uploads (also the progress event granularity)
+const UPLOAD_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Create a Blossom auth event (kind 24242)
+fn create_blossom_auth(
+    keys: &Keys,
+    sha256: &str,
+    action: &str,
+    expiration_secs: u64,
+) -> Result<Event, String> {
+    let expiration = get_current_timestamp()? + expiration_secs;
+
+    let event = EventBuilder::new(Kind::from(24242), "")
+        .tag(Tag::parse(["t", action]).map_err(|e| e.to_string())?)
+        .tag(Tag::parse(["x", sha256]).map_err(|e| e.to_string())?)
+        .tag(Tag::parse(["expiration", &expiration.to_string()]).map_err(|e| e.to_string())?)
         .sign_with_keys(keys)
         .map_err(|e| e.to_string())?;
 
-    Ok(event)
+    Ok(event)
+}
+
+/// Create a NIP-98 HTTP auth event (kind 27235) for one request. `payload_hash` is
+/// the sha256 of the request body, for servers that check it.
+fn create_http_auth(keys: &Keys, url: &str, method: &str, payload_hash: Option<&str>) -> Result<Event, String> {
+    let mut builder = EventBuilder::new(Kind::from(27235), "")
+        .tag(Tag::parse(["u", url]).map_err(|e| e.to_string())?)
+        .tag(Tag::parse(["method", &method.to_uppercase()]).map_err(|e| e.to_string())?);
+    if let Some(hash) = payload_hash {
+        builder = builder.tag(Tag::parse(["payload", hash]).map_err(|e| e.to_string())?);
+    }
+    builder.sign_with_keys(keys).map_err(|e| e.to_string())
+}
+
+/// Authorization header value carrying a signed auth event ("Nostr <base64 event>")
+fn nostr_auth_header(event: &Event) -> Result<String, String> {
+    let json = serde_json::to_string(event).map_err(|e| e.to_string())?;
+    Ok(format!("Nostr {}", BASE64.encode(json)))
+}
+
+/// Create a NIP-98 Authorization header for a request to a Nostr-authenticated API
+#[tauri::command]
+fn create_http_auth_header(
+    url: String,
+    method: String,
+    payload_hash: Option<String>,
+    state: State<'_, NostrState>,
+) -> Result<String, String> {
+    let keys = state.signing_keys()?;
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(format!("Not an HTTP URL: {}", url));
+    }
+    if let Some(hash) = &payload_hash {
+        if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err("Payload hash must be a hex sha256".to_string());
+        }
+    }
+    let event = create_http_auth(&keys, &url, &method, payload_hash.as_deref())?;
+    nostr_auth_header(&event)
+}
+
+/// Hash a file with a streaming reader, returning (sha256 hex, size in bytes)
+fn hash_file_streaming(path: &std::path::Path) -> Result<(String, u64), String> {
+    use std::io::Read;
+
+    let file = fs::File::open(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let mut reader = std::io::BufReader::new(file);
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut size = 0u64;
+
+    loop {
+        let read = reader
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+
+    Ok((hex::encode(hasher.finalize()), size))
+}
+
+/// Size and modification time (ns) used to tell whether a cached hash is still valid
+fn file_fingerprint(path: &std::path::Path) -> Result<(u64, i64), String> {
+    let meta = fs::metadata(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let mtime_ns = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as i64)
+        .unwrap_or(0);
+    Ok((meta.len(), mtime_ns))
+}
+
+/// Hash a file, reusing the cached hash when its size and mtime are unchanged
+fn hash_file_cached(path: &std::path::Path) -> Result<(String, u64), String> {
+    let (size, mtime_ns) = file_fingerprint(path)?;
+    let key = fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .to_string();
+
+    // The cache is an optimization; hashing still works if the library is unavailable
+    let Ok(conn) = open_library() else {
+        return hash_file_streaming(path);
+    };
+    let cached: Option<String> = conn
+        .query_row(
+            "SELECT sha256 FROM file_hashes WHERE path = ?1 AND size = ?2 AND mtime_ns = ?3",
+            rusqlite::params![key, size as i64, mtime_ns],
+            |row| row.get(0),
+        )
+        .ok();
+    if let Some(sha256) = cached {
+        return Ok((sha256, size));
+    }
+
+    let (sha256, hashed_size) = hash_file_streaming(path)?;
+    // Only cache if the file did not change while it was being read
+    if file_fingerprint(path)? == (hashed_size, mtime_ns) {
+        conn.execute(
+            "INSERT OR REPLACE INTO file_hashes (path, size, mtime_ns, sha256) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![key, hashed_size as i64, mtime_ns, sha256],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok((sha256, hashed_size))
+}
+
+/// Forget all cached file hashes, returning how many were removed
+#[tauri::command]
+fn hash_cache_clear() -> Result<usize, String> {
+    let conn = open_library()?;
+    conn.execute("DELETE FROM file_hashes", []).map_err(|e| e.to_string())
+}
+
+/// Guess a MIME type from a file extension
+fn guess_mime_type(file_path: &str) -> &'static str {
+    match file_path.rsplit('.').next().map(|ext| ext.to_lowercase()).as_deref() {
+        Some("xml") => "application/xml",
+        Some("json") => "application/json",
+        Some("mp3") => "audio/mpeg",
+        Some("flac") => "audio/flac",
+        Some("wav") => "audio/wav",
+        Some("ogg") => "audio/ogg",
+        Some("opus") => "audio/opus",
+        Some("m4a") => "audio/mp4",
+        Some("aac") => "audio/aac",
+        Some("aiff") | Some("aif") => "audio/aiff",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("webp") => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Check whether a Blossom server already has a blob (HEAD /<sha256>)
+async fn blossom_blob_exists(server_url: &str, sha256: &str) -> Result<bool, String> {
+    let client = reqwest::Client::new();
+    let blob_url = format!("{}/{}", normalize_server_url(server_url), sha256);
+
+    let response = client
+        .head(&blob_url)
+        .send()
+        .await
+        .map_err(|e| format!("Blob check failed: {}", e))?;
+
+    Ok(response.status().is_success())
+}
+
+/// Build the result for a blob the server already has, skipping the upload
+fn existing_blob_result(server_url: &str, sha256: String, size: u64) -> BlossomUploadResult {
+    BlossomUploadResult {
+        url: format!("{}/{}", normalize_server_url(server_url), sha256),
+        sha256,
+        size: size as usize,
+    }
+}
+
+/// Send an upload request with a pre-computed hash and size
+async fn send_blossom_upload(
+    body: reqwest::Body,
+    sha256: String,
+    size: u64,
+    keys: &Keys,
+    server_url: &str,
+    mime_type: &str,
+) -> Result<BlossomUploadResult, storage::UploadError> {
+    // Fail fast on a size or type the server refuses, before sending the body
+    preflight::ensure_upload_accepted(server_url, &sha256, size, mime_type, keys).await?;
+
+    // Create auth event (valid for 5 minutes)
+    let auth_event = create_blossom_auth(keys, &sha256, "upload", 300)?;
+    let auth_header = nostr_auth_header(&auth_event)?;
+
+    // Upload to Blossom server
+    let client = reqwest::Client::new();
+    let base_url = normalize_server_url(server_url);
+    let upload_url = format!("{}/upload", base_url);
+
+    let response = client
+        .put(&upload_url)
+        .header("Authorization", &auth_header)
+        .header("Content-Type", mime_type)
+        .header("Content-Length", size)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| storage::UploadError::Transport(format!("Upload failed: {}", e)))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(storage::UploadError::Status(
+            status,
+            format!("Blossom server error {}: {}", status, error_text),
+        ));
+    }
+
+    let blob_url = format!("{}/{}", base_url, sha256);
+
+    Ok(BlossomUploadResult {
+        url: blob_url,
+        sha256,
+        size: size as usize,
+    })
+}
+
+/// Upload a file from disk without loading it into memory, emitting
+/// `blossom://progress` events when an app handle is provided. Backs the Blossom
+/// storage provider, which records the upload in the ledger.
+async fn perform_blossom_upload_file(
+    file_path: &str,
+    keys: &Keys,
+    server_url: &str,
+    mime_type: &str,
+    app: Option<AppHandle>,
+) -> Result<BlossomUploadResult, storage::UploadError> {
+    // Hash on a blocking thread so large masters don't stall the async runtime
+    let path = PathBuf::from(file_path);
+    let (sha256, size) = tokio::task::spawn_blocking(move || hash_file_cached(&path))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    // Skip the transfer if the server already has this content. The ledger alone is
+    // not trusted, since the blob may have been deleted since it was recorded.
+    if blossom_blob_exists(server_url, &sha256).await.unwrap_or(false) {
+        if let Some(app) = &app {
+            let _ = app.emit(
+                "blossom://progress",
+                BlossomUploadProgress {
+                    file_path: file_path.to_string(),
+                    server_url: server_url.to_string(),
+                    bytes_sent: size,
+                    total_bytes: size,
+                },
+            );
+        }
+        return Ok(existing_blob_result(server_url, sha256, size));
+    }
+
+    let file = tokio::fs::File::open(file_path)
+        .await
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let progress_file = file_path.to_string();
+    let progress_server = server_url.to_string();
+    let mut bytes_sent = 0u64;
+    let stream = tokio_util::io::ReaderStream::with_capacity(file, UPLOAD_CHUNK_SIZE).map(move |chunk| {
+        if let (Ok(bytes), Some(app)) = (&chunk, &app) {
+            bytes_sent += bytes.len() as u64;
+            let _ = app.emit(
+                "blossom://progress",
+                BlossomUploadProgress {
+                    file_path: progress_file.clone(),
+                    server_url: progress_server.clone(),
+                    bytes_sent,
+                    total_bytes: size,
+                },
+            );
+        }
+        chunk
+    });
+    let body = reqwest::Body::wrap_stream(stream);
+
+    send_blossom_upload(body, sha256, size, keys, server_url, mime_type).await
+}
+
+// Default chunk size and retry budget for resumable uploads
+const RESUMABLE_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+const UPLOAD_MAX_RETRIES: u32 = 5;
+
+/// Exponential backoff with jitter: ~1s, 2s, 4s... capped at 30s
+fn backoff_delay(attempt: u32) -> std::time::Duration {
+    let base_ms = 1000u64.saturating_mul(1u64 << attempt.min(5)).min(30_000);
+    let jitter_ms = rand::random::<u64>() % (base_ms / 4 + 1);
+    std::time::Duration::from_millis(base_ms + jitter_ms)
+}
+
+/// Whether a server response is worth retrying: 429 and 5xx
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Read a chunk of a file at the given offset
+async fn read_file_chunk(file_path: &str, offset: u64, len: u64) -> Result<Vec<u8>, String> {
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+    let mut file = tokio::fs::File::open(file_path)
+        .await
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    file.seek(std::io::SeekFrom::Start(offset))
+        .await
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let mut buffer = Vec::with_capacity(len as usize);
+    file.take(len)
+        .read_to_end(&mut buffer)
+        .await
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    Ok(buffer)
+}
+
+/// Check whether a server accepts resumable (tus) uploads on /upload
+async fn supports_resumable_upload(client: &reqwest::Client, base_url: &str) -> bool {
+    match client.request(reqwest::Method::OPTIONS, format!("{}/upload", base_url)).send().await {
+        Ok(response) => response.headers().contains_key("Tus-Resumable"),
+        Err(_) => false,
+    }
+}
+
+/// Upload a file in chunks using the tus protocol, resuming from the server's
+/// confirmed offset after each failure
+#[allow(clippy::too_many_arguments)]
+async fn perform_tus_upload(
+    client: &reqwest::Client,
+    file_path: &str,
+    sha256: &str,
+    size: u64,
+    keys: &Keys,
+    server_url: &str,
+    mime_type: &str,
+    chunk_size: u64,
+    app: &AppHandle,
+) -> Result<BlossomUploadResult, String> {
+    let base_url = normalize_server_url(server_url);
+    let auth_event = create_blossom_auth(keys, sha256, "upload", 3600)?;
+    let auth_header = nostr_auth_header(&auth_event)?;
+
+    // Create the upload session
+    let response = client
+        .post(format!("{}/upload", base_url))
+        .header("Authorization", &auth_header)
+        .header("Tus-Resumable", "1.0.0")
+        .header("Upload-Length", size)
+        .header("Upload-Metadata", format!("filetype {}", BASE64.encode(mime_type)))
+        .send()
+        .await
+        .map_err(|e| format!("Upload failed: {}", e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Blossom server error {}: {}", status, error_text));
+    }
+    let location = response
+        .headers()
+        .get("Location")
+        .and_then(|v| v.to_str().ok())
+        .ok_or("Server did not return an upload location")?;
+    let session_url = if location.starts_with("http") {
+        location.to_string()
+    } else {
+        format!("{}{}", base_url, location)
+    };
+
+    let mut offset = 0u64;
+    let mut failures = 0u32;
+    while offset < size {
+        let chunk = read_file_chunk(file_path, offset, chunk_size.min(size - offset)).await?;
+        let chunk_len = chunk.len() as u64;
+
+        let result = client
+            .patch(&session_url)
+            .header("Authorization", &auth_header)
+            .header("Tus-Resumable", "1.0.0")
+            .header("Upload-Offset", offset)
+            .header("Content-Type", "application/offset+octet-stream")
+            .body(chunk)
+            .send()
+            .await;
+
+        match result {
+            Ok(response) if response.status().is_success() => {
+                offset = response
+                    .headers()
+                    .get("Upload-Offset")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(offset + chunk_len);
+                failures = 0;
+                let _ = app.emit(
+                    "blossom://progress",
+                    BlossomUploadProgress {
+                        file_path: file_path.to_string(),
+                        server_url: server_url.to_string(),
+                        bytes_sent: offset,
+                        total_bytes: size,
+                    },
+                );
+            }
+            // 409 means our offset is out of step with the server's; resync and carry on
+            Ok(response)
+                if !is_retryable_status(response.status())
+                    && response.status() != reqwest::StatusCode::CONFLICT =>
+            {
+                let status = response.status();
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(format!("Blossom server error {}: {}", status, error_text));
+            }
+            failed => {
+                failures += 1;
+                if failures > UPLOAD_MAX_RETRIES {
+                    return Err(match failed {
+                        Ok(response) => format!("Blossom server error {} after {} retries", response.status(), UPLOAD_MAX_RETRIES),
+                        Err(e) => format!("Upload failed after {} retries: {}", UPLOAD_MAX_RETRIES, e),
+                    });
+                }
+                tokio::time::sleep(backoff_delay(failures)).await;
+
+                // Ask the server how much it actually received before resuming
+                if let Ok(head) = client
+                    .head(&session_url)
+                    .header("Tus-Resumable", "1.0.0")
+                    .send()
+                    .await
+                {
+                    if let Some(confirmed) = head
+                        .headers()
+                        .get("Upload-Offset")
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| v.parse().ok())
+                    {
+                        offset = confirmed;
+                    }
+                }
+            }
+        }
+    }
+
+    // The last PATCH succeeding is not proof the server assembled the file; confirm
+    // the session is complete and the blob is served under its hash
+    let confirmed = client
+        .head(&session_url)
+        .header("Tus-Resumable", "1.0.0")
+        .send()
+        .await
+        .ok()
+        .and_then(|head| head.headers().get("Upload-Offset")?.to_str().ok()?.parse::<u64>().ok());
+    if let Some(confirmed) = confirmed.filter(|c| *c != size) {
+        return Err(format!("Upload incomplete: server has {} of {} bytes", confirmed, size));
+    }
+    if !blossom_blob_exists(server_url, sha256).await? {
+        return Err("Upload finished but the server does not have the blob".to_string());
+    }
+
+    Ok(existing_blob_result(server_url, sha256.to_string(), size))
+}
+
+/// Upload a large file with automatic retry. Uses chunked, resumable uploads when
+/// the server supports them, otherwise retries the whole streamed upload with backoff.
+/// Goes to the user's preferred Blossom server unless one is given.
+#[tauri::command]
+async fn blossom_upload_file_resumable(
+    server_url: Option<String>,
+    file_path: String,
+    chunk_size: Option<u64>,
+    app: AppHandle,
+    state: State<'_, NostrState>,
+) -> Result<BlossomUploadResult, String> {
+    let keys = state.signing_keys()?;
+    let server_url = state.blossom_server(server_url)?;
+
+    let mime_type = guess_mime_type(&file_path);
+    let _operation = shutdown::begin("upload", &file_path);
+    let client = reqwest::Client::new();
+    let base_url = normalize_server_url(&server_url);
+
+    if supports_resumable_upload(&client, base_url).await {
+        let path = PathBuf::from(&file_path);
+        let (sha256, size) = tokio::task::spawn_blocking(move || hash_file_cached(&path))
+            .await
+            .map_err(|e| e.to_string())??;
+
+        if blossom_blob_exists(&server_url, &sha256).await.unwrap_or(false) {
+            return Ok(existing_blob_result(&server_url, sha256, size));
+        }
+
+        preflight::ensure_upload_accepted(&server_url, &sha256, size, mime_type, &keys).await?;
+        let chunk_size = chunk_size.unwrap_or(RESUMABLE_CHUNK_SIZE).max(64 * 1024);
+        let result = perform_tus_upload(
+            &client, &file_path, &sha256, size, &keys, &server_url, mime_type, chunk_size, &app,
+        )
+        .await?;
+        let _ = upload_ledger::record(
+            &result.sha256,
+            &result.url,
+            size,
+            &upload_ledger::blossom_key(&server_url),
+            Some(&file_path),
+        );
+        return Ok(result);
+    }
+
+    let target = storage::StorageTarget::Blossom { server_url };
+    let mut attempt = 0;
+    loop {
+        match storage::upload_file(&target, &file_path, Some(keys.clone()), Some(app.clone())).await {
+            Ok(file) => return Ok(file.into()),
+            Err(e) if !e.is_retryable() => return Err(e.to_string()),
+            Err(e) if attempt >= UPLOAD_MAX_RETRIES => {
+                return Err(format!("{} (after {} retries)", e, UPLOAD_MAX_RETRIES))
+            }
+            Err(_) => {
+                attempt += 1;
+                tokio::time::sleep(backoff_delay(attempt)).await;
+            }
+        }
+    }
+}
+
+/// Upload content to a Blossom server (the user's preferred one unless given)
+#[tauri::command]
+async fn blossom_upload(
+    server_url: Option<String>,
+    content: String,
+    content_type: Option<String>,
+    state: State<'_, NostrState>,
+) -> Result<BlossomUploadResult, String> {
+    let keys = state.signing_keys()?;
+    let server_url = state.blossom_server(server_url)?;
+
+    let mime_type = content_type.unwrap_or_else(|| "application/xml".to_string());
+    let target = storage::StorageTarget::Blossom { server_url };
+
+    let file = storage::upload_bytes(&target, content.into_bytes(), "content", &mime_type, Some(keys)).await?;
+    Ok(file.into())
+}
+
+/// Upload a file from disk to Blossom (streamed, so memory stays flat for large masters),
+/// to the user's preferred server unless one is given
+#[tauri::command]
+async fn blossom_upload_file(
+    server_url: Option<String>,
+    file_path: String,
+    app: AppHandle,
+    state: State<'_, NostrState>,
+) -> Result<BlossomUploadResult, String> {
+    let keys = state.signing_keys()?;
+    let target = storage::StorageTarget::Blossom {
+        server_url: state.blossom_server(server_url)?,
+    };
+
+    let file = storage::upload_file(&target, &file_path, Some(keys), Some(app)).await?;
+    Ok(file.into())
+}
+
+#[derive(Serialize, Deserialize)]
+struct MirrorStatus {
+    server_url: String,
+    method: String, // "upload", "mirror", or "reupload"
+    url: Option<String>,
+    error: Option<String>,
+}
+
+/// Ask a Blossom server to mirror a blob from another URL (BUD-04)
+async fn perform_blossom_mirror(
+    keys: &Keys,
+    server_url: &str,
+    source_url: &str,
+    sha256: &str,
+) -> Result<String, String> {
+    let auth_event = create_blossom_auth(keys, sha256, "upload", 300)?;
+    let auth_header = nostr_auth_header(&auth_event)?;
+
+    let client = reqwest::Client::new();
+    let base_url = normalize_server_url(server_url);
+    let mirror_url = format!("{}/mirror", base_url);
+
+    let response = client
+        .put(&mirror_url)
+        .header("Authorization", &auth_header)
+        .json(&serde_json::json!({ "url": source_url }))
+        .send()
+        .await
+        .map_err(|e| format!("Mirror failed: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Blossom server error {}: {}", status, error_text));
+    }
+
+    Ok(format!("{}/{}", base_url, sha256))
+}
+
+/// Upload a file to the first server, then mirror it to the remaining servers
+/// (falling back to a direct re-upload where `/mirror` isn't supported)
+#[tauri::command]
+async fn blossom_upload_mirrored(
+    servers: Vec<String>,
+    file_path: String,
+    app: AppHandle,
+    state: State<'_, NostrState>,
+) -> Result<Vec<MirrorStatus>, String> {
+    let keys = state.signing_keys()?;
+
+    let (primary, backups) = servers.split_first().ok_or("At least one server is required")?;
+    let _operation = shutdown::begin("upload", &file_path);
+
+    let primary_target = storage::StorageTarget::Blossom {
+        server_url: primary.clone(),
+    };
+    let uploaded = storage::upload_file(&primary_target, &file_path, Some(keys.clone()), Some(app)).await?;
+    let mut statuses = vec![MirrorStatus {
+        server_url: primary.clone(),
+        method: "upload".to_string(),
+        url: Some(uploaded.url.clone()),
+        error: None,
+    }];
+
+    for server in backups {
+        let status = match perform_blossom_mirror(&keys, server, &uploaded.url, &uploaded.sha256).await {
+            Ok(url) => MirrorStatus {
+                server_url: server.clone(),
+                method: "mirror".to_string(),
+                url: Some(url),
+                error: None,
+            },
+            Err(_) => match storage::upload_file(
+                &storage::StorageTarget::Blossom {
+                    server_url: server.clone(),
+                },
+                &file_path,
+                Some(keys.clone()),
+                None,
+            )
+            .await
+            {
+                Ok(result) => MirrorStatus {
+                    server_url: server.clone(),
+                    method: "reupload".to_string(),
+                    url: Some(result.url),
+                    error: None,
+                },
+                Err(e) => MirrorStatus {
+                    server_url: server.clone(),
+                    method: "reupload".to_string(),
+                    url: None,
+                    error: Some(e.to_string()),
+                },
+            },
+        };
+        statuses.push(status);
+    }
+
+    Ok(statuses)
+}
+
+/// Check whether a Blossom server already has a blob
+#[tauri::command]
+async fn blossom_has_blob(server_url: String, sha256: String) -> Result<bool, String> {
+    blossom_blob_exists(&server_url, &sha256).await
+}
+
+/// Delete a blob from a Blossom server
+async fn perform_blossom_delete(keys: &Keys, server_url: &str, sha256: &str) -> Result<(), String> {
+    let auth_event = create_blossom_auth(keys, sha256, "delete", 300)?;
+    let auth_header = nostr_auth_header(&auth_event)?;
+
+    let client = reqwest::Client::new();
+    let delete_url = format!("{}/{}", normalize_server_url(server_url), sha256);
+
+    let response = client
+        .delete(&delete_url)
+        .header("Authorization", &auth_header)
+        .send()
+        .await
+        .map_err(|e| format!("Delete failed: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Blossom server error {}: {}", status, error_text));
+    }
+
+    let _ = upload_ledger::forget(sha256, &upload_ledger::blossom_key(server_url));
+    Ok(())
+}
+
+/// Delete a blob from a Blossom server
+#[tauri::command]
+async fn blossom_delete(
+    server_url: String,
+    sha256: String,
+    state: State<'_, NostrState>,
+) -> Result<(), String> {
+    let keys = state.signing_keys()?;
+    perform_blossom_delete(&keys, &server_url, &sha256).await
+}
+
+/// List a user's blobs on a Blossom server (BUD-02 blob descriptors)
+async fn perform_blossom_list(server_url: &str, pubkey: &str) -> Result<Vec<serde_json::Value>, String> {
+    let client = reqwest::Client::new();
+    let list_url = format!("{}/list/{}", normalize_server_url(server_url), pubkey);
+
+    let response = client
+        .get(&list_url)
+        .send()
+        .await
+        .map_err(|e| format!("List failed: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Blossom server error {}: {}", status, error_text));
+    }
+
+    let blobs: Vec<serde_json::Value> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    Ok(blobs)
+}
+
+/// List blobs on a Blossom server for the logged-in user
+#[tauri::command]
+async fn blossom_list(
+    server_url: String,
+    state: State<'_, NostrState>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let pubkey = state.active_pubkey().ok_or("Not logged in")?;
+    perform_blossom_list(&server_url, &pubkey).await
+}
+
+// Largest blobs listed per server in the usage summary
+const USAGE_LARGEST_BLOBS: usize = 10;
+
+#[derive(Serialize, Deserialize)]
+struct BlobSummary {
+    sha256: String,
+    url: String,
+    size: u64,
+    mime_type: Option<String>,
+    uploaded: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ServerUsage {
+    server_url: String,
+    total_bytes: u64,
+    blob_count: usize,
+    largest: Vec<BlobSummary>,
+    error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BlossomUsage {
+    total_bytes: u64, // across servers, counting mirrored copies on each
+    unique_bytes: u64, // counting each blob once
+    blob_count: usize,
+    unique_blobs: usize,
+    servers: Vec<ServerUsage>,
+}
+
+/// Summarize one server's blob list
+async fn server_usage(server_url: &str, pubkey: &str) -> (ServerUsage, Vec<BlobSummary>) {
+    let mut usage = ServerUsage {
+        server_url: normalize_server_url(server_url).to_string(),
+        total_bytes: 0,
+        blob_count: 0,
+        largest: Vec::new(),
+        error: None,
+    };
+    let mut blobs: Vec<BlobSummary> = match perform_blossom_list(server_url, pubkey).await {
+        Ok(descriptors) => descriptors
+            .iter()
+            .map(|d| BlobSummary {
+                sha256: d["sha256"].as_str().unwrap_or_default().to_string(),
+                url: d["url"].as_str().unwrap_or_default().to_string(),
+                size: d["size"].as_u64().unwrap_or_default(),
+                mime_type: d["type"].as_str().map(str::to_string),
+                uploaded: d["uploaded"].as_u64(),
+            })
+            .collect(),
+        Err(e) => {
+            usage.error = Some(e);
+            return (usage, Vec::new());
+        }
+    };
+    blobs.sort_by_key(|b| std::cmp::Reverse(b.size));
+    usage.total_bytes = blobs.iter().map(|b| b.size).sum();
+    usage.blob_count = blobs.len();
+    let rest = blobs.split_off(blobs.len().min(USAGE_LARGEST_BLOBS));
+    usage.largest = blobs;
+    (usage, rest)
+}
+
+/// Total bytes, blob counts, and largest blobs for the logged-in user on each server
+/// (the user's server list when none are given), for managing quota across hosts
+#[tauri::command]
+async fn blossom_usage(
+    servers: Option<Vec<String>>,
+    state: State<'_, NostrState>,
+) -> Result<BlossomUsage, String> {
+    let pubkey = state.active_pubkey().ok_or("Not logged in")?;
+    let servers = servers
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| state.blossom_servers.lock().unwrap().clone());
+
+    let results = futures_util::future::join_all(servers.iter().map(|s| server_usage(s, &pubkey))).await;
+
+    let mut unique = std::collections::HashMap::new();
+    for (usage, rest) in &results {
+        for blob in usage.largest.iter().chain(rest) {
+            unique.insert(blob.sha256.as_str(), blob.size);
+        }
+    }
+    let unique_bytes = unique.values().sum();
+    let unique_blobs = unique.len();
+
+    let servers: Vec<ServerUsage> = results.into_iter().map(|(usage, _)| usage).collect();
+    Ok(BlossomUsage {
+        total_bytes: servers.iter().map(|s| s.total_bytes).sum(),
+        unique_bytes,
+        blob_count: servers.iter().map(|s| s.blob_count).sum(),
+        unique_blobs,
+        servers,
+    })
+}
+
+/// Login with nsec (private key)
+#[tauri::command]
+async fn nostr_login_nsec(
+    nsec: String,
+    app: AppHandle,
+    state: State<'_, NostrState>,
+) -> Result<NostrProfile, String> {
+    let secret_key = SecretKey::from_bech32(&nsec).map_err(|e| e.to_string())?;
+    let keys = Keys::new(secret_key);
+    login_with_keys(keys, &state, &app).await
+}
+
+/// Login with hex private key
+#[tauri::command]
+async fn nostr_login_hex(
+    hex_key: String,
+    app: AppHandle,
+    state: State<'_, NostrState>,
+) -> Result<NostrProfile, String> {
+    let secret_key = SecretKey::from_hex(&hex_key).map_err(|e| e.to_string())?;
+    let keys = Keys::new(secret_key);
+    login_with_keys(keys, &state, &app).await
+}
+
+/// Watch-only login with an npub: events, profiles, and zap receipts can be fetched,
+/// but anything that signs returns a read-only session error
+#[tauri::command]
+async fn nostr_login_npub(
+    npub: String,
+    app: AppHandle,
+    state: State<'_, NostrState>,
+) -> Result<NostrProfile, String> {
+    let public_key = PublicKey::parse(npub.trim()).map_err(|e| e.to_string())?;
+    start_session(public_key, None, &state, &app).await
+}
+
+/// Logout - clear keys and disconnect. With a pubkey only that account is signed
+/// out (leaving no active account if it was the active one); otherwise all are.
+#[tauri::command]
+async fn nostr_logout(pubkey: Option<String>, state: State<'_, NostrState>) -> Result<(), String> {
+    let sessions = match pubkey {
+        Some(pubkey) => {
+            let pubkey = PublicKey::parse(pubkey.trim()).map_err(|e| e.to_string())?;
+            state.sign_out(|public_key, _| *public_key == pubkey)
+        }
+        None => {
+            library_crypto::lock();
+            state.sign_out(|_, _| true)
+        }
+    };
+    for session in sessions {
+        let _ = session.client.disconnect().await;
+    }
+    Ok(())
+}
+
+/// List logged-in accounts, the active one first
+#[tauri::command]
+fn nostr_list_accounts(state: State<'_, NostrState>) -> Vec<NostrAccount> {
+    let account = |public_key: &PublicKey, active: bool, read_only: bool| NostrAccount {
+        pubkey: public_key.to_hex(),
+        npub: public_key.to_bech32().unwrap_or_default(),
+        active,
+        read_only,
+    };
+    let read_only = state.watch_only.lock().unwrap().is_some();
+    let mut accounts: Vec<NostrAccount> = state
+        .active_public_key()
+        .iter()
+        .map(|pk| account(pk, true, read_only))
+        .collect();
+    let mut others: Vec<NostrAccount> = state
+        .accounts
+        .lock()
+        .unwrap()
+        .parked
+        .values()
+        .map(|session| account(&session.public_key, false, session.keys.is_none()))
+        .collect();
+    others.sort_by(|a, b| a.npub.cmp(&b.npub));
+    accounts.extend(others);
+    accounts
+}
+
+/// Switch the active account to another logged-in account without re-entering its
+/// password. Live subscriptions belong to the previous account and are stopped.
+#[tauri::command]
+fn active_account(pubkey: String, state: State<'_, NostrState>) -> Result<NostrProfile, String> {
+    let public_key = PublicKey::parse(pubkey.trim()).map_err(|e| e.to_string())?;
+    state.switch_to(&public_key)?;
+    let read_only = state.watch_only.lock().unwrap().is_some();
+    Ok(NostrProfile::new(&public_key, read_only))
+}
+
+/// Get current login status
+#[tauri::command]
+fn nostr_get_pubkey(state: State<'_, NostrState>) -> Option<NostrProfile> {
+    let read_only = state.watch_only.lock().unwrap().is_some();
+    state
+        .active_public_key()
+        .map(|public_key| NostrProfile::new(&public_key, read_only))
+}
+
+/// Tag identifying the app version that produced an event
+fn app_version_tag() -> Result<Tag, String> {
+    Tag::parse(["generator", "MSP 2.0 desktop", APP_VERSION]).map_err(|e| e.to_string())
+}
+
+/// Sign an app event: the given tags plus any delegation tag and the generator tag,
+/// mined to the requested proof-of-work difficulty. Feeds that declare another owner
+/// are refused until the user confirms ownership.
+async fn sign_app_event(
+    kind: Kind,
+    content: &str,
+    tags: &[Vec<String>],
+    keys: &Keys,
+    state: &NostrState,
+    pow_difficulty: Option<u8>,
+) -> Result<Event, String> {
+    if kind.as_u16() == SYNCED_FEED_KIND {
+        let identifier = tags
+            .iter()
+            .find(|t| t.first().map(String::as_str) == Some("d"))
+            .and_then(|t| t.get(1))
+            .map(String::as_str);
+        let report = check_feed_ownership(content, identifier, state).await?;
+        if report.mismatch && !report.confirmed {
+            return Err(format!(
+                "Ownership not confirmed: {}. Confirm ownership before publishing this feed.",
+                report.warnings.join("; ")
+            ));
+        }
+    }
+
+    let mut event_tags = Vec::new();
+    for tag in tags {
+        if !tag.is_empty() {
+            event_tags.push(Tag::parse(tag).map_err(|e| e.to_string())?);
+        }
+    }
+    if let Some(tag) = delegation_tag_for(state, keys, kind.as_u16())? {
+        event_tags.push(tag);
+    }
+    if !tags.iter().any(|t| t.first().map(String::as_str) == Some("generator")) {
+        event_tags.push(app_version_tag()?);
+    }
+
+    match pow_difficulty.filter(|d| *d > 0) {
+        Some(difficulty) => mine_event(kind, content.to_string(), event_tags, keys.clone(), difficulty).await,
+        None => EventBuilder::new(kind, content)
+            .tags(event_tags)
+            .sign_with_keys(keys)
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Sign an event
+#[tauri::command]
+async fn nostr_sign_event(
+    kind: u16,
+    content: String,
+    tags: Vec<Vec<String>>,
+    pow_difficulty: Option<u8>,
+    state: State<'_, NostrState>,
+) -> Result<SignedEvent, String> {
+    let keys = state.signing_keys()?;
+    
+    let event = sign_app_event(Kind::from(kind), &content, &tags, &keys, &state, pow_difficulty).await?;
+
+    Ok(event_to_signed_event(&event))
+}
+
+/// Publish an event to relays
+#[tauri::command]
+async fn nostr_publish_event(
+    kind: u16,
+    content: String,
+    tags: Vec<Vec<String>>,
+    pow_difficulty: Option<u8>,
+    state: State<'_, NostrState>,
+) -> Result<String, String> {
+    let published = publish_event_with_results(kind, content, tags, pow_difficulty, state).await?;
+    published.require_accepted()?;
+    Ok(published.event_id)
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct RelayPublishResult {
+    url: String,
+    ok: bool,
+    error: Option<String>,
+    #[serde(default)]
+    reason: Option<String>, // machine-readable prefix of the relay's rejection, e.g. "rate-limited"
+    #[serde(default)]
+    auth_failed: bool, // relay wants NIP-42 AUTH, or refused our authenticated key
+}
+
+/// Build a failed relay result, classifying the relay's "prefix: message" rejection
+fn failed_relay_result(url: String, error: String) -> RelayPublishResult {
+    let reason = error
+        .split_once(':')
+        .map(|(prefix, _)| prefix.trim())
+        .filter(|prefix| !prefix.is_empty() && prefix.chars().all(|c| c.is_ascii_lowercase() || c == '-'))
+        .map(str::to_string);
+    let auth_failed = matches!(reason.as_deref(), Some("auth-required") | Some("restricted"));
+    RelayPublishResult {
+        url,
+        ok: false,
+        error: Some(error),
+        reason,
+        auth_failed,
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct EventPublishResult {
+    event_id: String,
+    relays: Vec<RelayPublishResult>,
+}
+
+impl EventPublishResult {
+    /// Err when relays were tried and none accepted the event, naming NIP-42 auth
+    /// failures first since those need the user to fix relay access, not retry
+    fn require_accepted(&self) -> Result<(), String> {
+        if self.relays.is_empty() || self.relays.iter().any(|r| r.ok) {
+            return Ok(());
+        }
+        let auth_failures: Vec<&str> = self.relays.iter().filter(|r| r.auth_failed).map(|r| r.url.as_str()).collect();
+        if !auth_failures.is_empty() {
+            return Err(format!(
+                "Relay authentication (NIP-42) failed; your key is not allowed to publish to: {}",
+                auth_failures.join(", ")
+            ));
+        }
+        Err(format!(
+            "No relay accepted the event: {}",
+            self.relays
+                .iter()
+                .map(|r| format!("{} ({})", r.url, r.error.as_deref().unwrap_or("unknown error")))
+                .collect::<Vec<_>>()
+                .join("; ")
+        ))
+    }
+}
+
+/// Sign and publish an event, reporting which relays accepted it. Relay rejections
+/// are results, not errors, so callers can record them; call `require_accepted`
+/// to fail when no relay took the event.
+async fn publish_event_with_results(
+    kind: u16,
+    mut content: String,
+    tags: Vec<Vec<String>>,
+    pow_difficulty: Option<u8>,
+    state: State<'_, NostrState>,
+) -> Result<EventPublishResult, String> {
+    let keys = state.signing_keys()?;
+    
+    let client = state
+        .client
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("Client not initialized")?;
+    
+    // The library copy was stamped when it was saved, so publishing only fills in
+    // missing dates
+    if kind == SYNCED_FEED_KIND {
+        content = feed_model::stamp_feed_xml(&content, Some(&content), &feed_model::FeedStampOptions::default());
+    }
+
+    let event = sign_app_event(Kind::from(kind), &content, &tags, &keys, &state, pow_difficulty).await?;
+    send_event_with_results(&client, event).await
+}
+
+/// Send an already signed event, reporting each relay's answer
+async fn send_event_with_results(client: &Client, event: Event) -> Result<EventPublishResult, String> {
+    let event_id = event.id.to_hex();
+
+    let output = client
+        .send_event(event)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut relays: Vec<RelayPublishResult> = output
+        .success
+        .iter()
+        .map(|url| RelayPublishResult {
+            url: url.to_string(),
+            ok: true,
+            error: None,
+            reason: None,
+            auth_failed: false,
+        })
+        .collect();
+    relays.extend(
+        output
+            .failed
+            .iter()
+            .map(|(url, error)| failed_relay_result(url.to_string(), error.clone())),
+    );
+    relays.sort_by(|a, b| a.url.cmp(&b.url));
+
+    Ok(EventPublishResult { event_id, relays })
+}
+
+/// Request deletion (NIP-09) of events published by the logged-in key. Events found
+/// on the relays also get `k` tags. Whole replaceable coordinates ("<kind>:<pubkey>:<d>")
+/// are deleted only when listed in `coordinates`; deleting one old revision by id
+/// leaves the live event at its coordinate alone.
+#[tauri::command]
+async fn nostr_delete_event(
+    event_ids: Vec<String>,
+    coordinates: Option<Vec<String>>,
+    reason: Option<String>,
+    state: State<'_, NostrState>,
+) -> Result<String, String> {
+    let keys = state.signing_keys()?;
+    let client = state.client.lock().unwrap().clone().ok_or("Client not initialized")?;
+    let coordinates = coordinates.unwrap_or_default();
+
+    if event_ids.is_empty() && coordinates.is_empty() {
+        return Err("No events to delete".to_string());
+    }
+
+    let ids = event_ids
+        .iter()
+        .map(|id| EventId::parse(id.trim()).map_err(|e| format!("Invalid event id {}: {}", id, e)))
+        .collect::<Result<Vec<EventId>, String>>()?;
+
+    let mut tags: Vec<Vec<String>> = ids.iter().map(|id| vec!["e".to_string(), id.to_hex()]).collect();
+    let mut kinds: Vec<u16> = Vec::new();
+    for coordinate in &coordinates {
+        let (kind, author) = parse_coordinate(coordinate)?;
+        if author != keys.public_key() {
+            return Err(format!("{} was not published by this account", coordinate));
+        }
+        if !is_replaceable_kind(kind) {
+            return Err(format!("Kind {} has no coordinate to delete", kind));
+        }
+        kinds.push(kind);
+        tags.push(vec!["a".to_string(), coordinate.trim().to_string()]);
+    }
+
+    // Look the events up so the request can name their kinds
+    if !ids.is_empty() {
+        let found = client
+            .fetch_events(vec![Filter::new().ids(ids)], Some(std::time::Duration::from_secs(5)))
+            .await
+            .map(|events| events.into_iter().collect::<Vec<Event>>())
+            .unwrap_or_default();
+        if let Some(foreign) = found.iter().find(|e| e.pubkey != keys.public_key()) {
+            return Err(format!("Event {} was not published by this account", foreign.id.to_hex()));
+        }
+        kinds.extend(found.iter().map(|e| e.kind.as_u16()));
+    }
+    kinds.sort_unstable();
+    kinds.dedup();
+    tags.extend(kinds.iter().map(|kind| vec!["k".to_string(), kind.to_string()]));
+
+    let event = sign_app_event(Kind::EventDeletion, &reason.unwrap_or_default(), &tags, &keys, &state, None).await?;
+    let event_id = event.id.to_hex();
+
+    client.send_event(event).await.map_err(|e| e.to_string())?;
+
+    Ok(event_id)
+}
+
+/// Split a "<kind>:<pubkey>:<d-tag>" coordinate into its kind and author
+fn parse_coordinate(coordinate: &str) -> Result<(u16, PublicKey), String> {
+    let mut parts = coordinate.trim().splitn(3, ':');
+    let invalid = || format!("Invalid coordinate: {}", coordinate);
+    let kind = parts.next().and_then(|k| k.parse().ok()).ok_or_else(invalid)?;
+    let author = parts.next().and_then(|p| PublicKey::parse(p).ok()).ok_or_else(invalid)?;
+    parts.next().ok_or_else(invalid)?;
+    Ok((kind, author))
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReplaceableEventResult {
+    event_id: String,
+    coordinate: String, // "<kind>:<pubkey>:<d-tag>"
+}
+
+/// Check that a kind is addressable (parameterized replaceable, 30000-39999)
+fn ensure_addressable_kind(kind: u16) -> Result<(), String> {
+    if (30000..40000).contains(&kind) {
+        Ok(())
+    } else {
+        Err(format!("Kind {} is not addressable (expected 30000-39999)", kind))
+    }
+}
+
+/// Publish an addressable event under a single d-tag, replacing any earlier
+/// version at the same coordinate
+#[tauri::command]
+async fn nostr_publish_replaceable(
+    kind: u16,
+    d_tag: String,
+    content: String,
+    tags: Vec<Vec<String>>,
+    state: State<'_, NostrState>,
+) -> Result<ReplaceableEventResult, String> {
+    ensure_addressable_kind(kind)?;
+    let pubkey = state.signing_keys()?.public_key().to_hex();
+
+    // A second d-tag would make the coordinate ambiguous, so ours replaces any given
+    let mut tags: Vec<Vec<String>> = tags
+        .into_iter()
+        .filter(|t| t.first().map(String::as_str) != Some("d"))
+        .collect();
+    tags.insert(0, vec!["d".to_string(), d_tag.clone()]);
+
+    let event_id = nostr_publish_event(kind, content, tags, None, state).await?;
+
+    Ok(ReplaceableEventResult {
+        event_id,
+        coordinate: format!("{}:{}:{}", kind, pubkey, d_tag),
+    })
+}
+
+/// Fetch the latest version of an addressable event by coordinate
+#[tauri::command]
+async fn nostr_fetch_replaceable(
+    kind: u16,
+    pubkey: String,
+    d_tag: String,
+    state: State<'_, NostrState>,
+) -> Result<Option<SignedEvent>, String> {
+    ensure_addressable_kind(kind)?;
+    let client = state.client.lock().unwrap().clone().ok_or("Client not initialized")?;
+    let author = PublicKey::parse(pubkey.trim()).map_err(|e| e.to_string())?;
+
+    let filter = Filter::new().kind(Kind::from(kind)).author(author).identifier(d_tag);
+    let events = client
+        .fetch_events(vec![filter], None)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Relays may still hold older versions; NIP-01 breaks created_at ties by lowest id
+    let latest = events
+        .into_iter()
+        .min_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| a.id.cmp(&b.id)));
+    Ok(latest.as_ref().map(event_to_signed_event))
+}
+
+/// Fetch events from relays. Besides kinds/authors/limit, accepts event ids, a
+/// since/until window, single-letter tag filters ({"e": [...], "p": [...]}), and a
+/// NIP-50 search string (only honored by relays that support search).
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn nostr_fetch_events(
+    kinds: Vec<u16>,
+    authors: Option<Vec<String>>,
+    limit: Option<usize>,
+    ids: Option<Vec<String>>,
+    since: Option<u64>,
+    until: Option<u64>,
+    tags: Option<std::collections::HashMap<String, Vec<String>>>,
+    search: Option<String>,
+    state: State<'_, NostrState>,
+) -> Result<Vec<SignedEvent>, String> {
+    let client = state
+        .client
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("Client not initialized")?;
+
+    let filter = EventFilterSpec {
+        kinds: Some(kinds),
+        authors,
+        ids,
+        tags: tags.unwrap_or_default(),
+        since,
+        until,
+        search,
+        limit,
+    }
+    .to_filter()?;
+
+    let events = client
+        .fetch_events(vec![filter], None)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(events.iter().map(event_to_signed_event).collect())
+}
+
+// How often the background monitor samples relay status
+const RELAY_STATUS_INTERVAL_SECS: u64 = 5;
+
+// How often the relay supervisor checks for dropped relays, and how many SDK
+// reconnection attempts a relay gets before it is reported as lost
+const RELAY_SUPERVISOR_INTERVAL_SECS: u64 = 2;
+const RELAY_RECONNECT_MAX_ATTEMPTS: u32 = 8;
+
+// First interval between the SDK's reconnection attempts; it grows while a relay
+// keeps failing
+const RELAY_RETRY_INTERVAL_SECS: u64 = 5;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct RelayReconnectEvent {
+    url: String,
+    outcome: String, // "reconnected" or "failed"
+    attempts: u32,
+}
+
+// A dropped relay: its connection attempt count when it dropped, and whether it was
+// already reported as lost
+struct RelayRetry {
+    attempts_at_drop: usize,
+    gave_up: bool,
+}
+
+/// Options for the session's relays: the SDK reconnects dropped ones with a growing
+/// interval
+fn session_relay_options() -> RelayOptions {
+    RelayOptions::new()
+        .reconnect(true)
+        .retry_interval(std::time::Duration::from_secs(RELAY_RETRY_INTERVAL_SECS))
+        .adjust_retry_interval(true)
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct RelayStatusInfo {
+    url: String,
+    status: String, // "connected", "connecting", "disconnected", ...
+    connected: bool,
+    latency_ms: Option<u64>,
+    attempts: usize,
+    successes: usize,
+}
+
+/// Snapshot the status of every relay in the client's pool, sorted by URL
+async fn collect_relay_status(client: &Client) -> Vec<RelayStatusInfo> {
+    let mut statuses: Vec<RelayStatusInfo> = client
+        .relays()
+        .await
+        .into_iter()
+        .map(|(url, relay)| {
+            let status = relay.status();
+            let stats = relay.stats();
+            RelayStatusInfo {
+                url: url.to_string(),
+                status: status.to_string().to_lowercase(),
+                connected: status == RelayStatus::Connected,
+                latency_ms: stats.latency().map(|d| d.as_millis() as u64),
+                attempts: stats.attempts(),
+                successes: stats.success(),
+            }
+        })
+        .collect();
+    statuses.sort_by(|a, b| a.url.cmp(&b.url));
+    statuses
+}
+
+/// Get per-relay connection status and latency
+#[tauri::command]
+async fn nostr_relay_status(state: State<'_, NostrState>) -> Result<Vec<RelayStatusInfo>, String> {
+    let client = state.client.lock().unwrap().clone();
+    match client {
+        Some(client) => Ok(collect_relay_status(&client).await),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Emit "nostr://relay-status" whenever relay connectivity changes (including login/logout)
+fn spawn_relay_status_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last: Option<Vec<(String, bool)>> = None;
+        loop {
+            let client = app.state::<NostrState>().client.lock().unwrap().clone();
+            let statuses = match client {
+                Some(client) => collect_relay_status(&client).await,
+                None => Vec::new(),
+            };
+
+            // Latency jitters constantly; only connectivity changes are worth an event
+            let fingerprint: Vec<(String, bool)> =
+                statuses.iter().map(|s| (s.url.clone(), s.connected)).collect();
+            if last.as_ref() != Some(&fingerprint) {
+                let _ = app.emit("nostr://relay-status", &statuses);
+                last = Some(fingerprint);
+            }
+
+            tokio::time::sleep(std::time::Duration::from_secs(RELAY_STATUS_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+/// Watch the active account's relays, which the SDK reconnects on its own (see
+/// `session_relay_options`), and emit "nostr://relay-reconnect" when a dropped relay
+/// comes back, or once it has failed RELAY_RECONNECT_MAX_ATTEMPTS reconnection
+/// attempts (it is still reported if it recovers later). Terminated relays were shut
+/// down on purpose and are not reported.
+fn spawn_relay_supervisor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut dropped: std::collections::HashMap<String, RelayRetry> = std::collections::HashMap::new();
+        let mut account: Option<String> = None;
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(RELAY_SUPERVISOR_INTERVAL_SECS)).await;
+
+            let state = app.state::<NostrState>();
+            let active = state.active_pubkey();
+            if active != account {
+                dropped.clear();
+                account = active;
+            }
+            let Some(client) = state.client.lock().unwrap().clone() else {
+                continue;
+            };
+
+            let relays = client.relays().await;
+            dropped.retain(|url, _| relays.keys().any(|u| u.to_string() == *url));
+            for (url, relay) in relays {
+                let url = url.to_string();
+                let attempts = relay.stats().attempts();
+                match relay.status() {
+                    RelayStatus::Connected => {
+                        if let Some(retry) = dropped.remove(&url) {
+                            let _ = app.emit(
+                                "nostr://relay-reconnect",
+                                RelayReconnectEvent {
+                                    url,
+                                    outcome: "reconnected".to_string(),
+                                    attempts: attempts.saturating_sub(retry.attempts_at_drop) as u32,
+                                },
+                            );
+                        }
+                    }
+                    RelayStatus::Disconnected => {
+                        let retry = dropped.entry(url.clone()).or_insert(RelayRetry {
+                            attempts_at_drop: attempts,
+                            gave_up: false,
+                        });
+                        let tried = attempts.saturating_sub(retry.attempts_at_drop);
+                        if !retry.gave_up && tried >= RELAY_RECONNECT_MAX_ATTEMPTS as usize {
+                            retry.gave_up = true;
+                            let _ = app.emit(
+                                "nostr://relay-reconnect",
+                                RelayReconnectEvent {
+                                    url,
+                                    outcome: "failed".to_string(),
+                                    attempts: tried as u32,
+                                },
+                            );
+                        }
+                    }
+                    RelayStatus::Terminated => {
+                        dropped.remove(&url);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Proof of Work (NIP-13)
+// ============================================================================
+
+// Highest difficulty accepted; each extra bit doubles the expected mining time
+const MAX_POW_DIFFICULTY: u8 = 32;
+
+/// Mine a nonce tag giving the event id at least `difficulty` leading zero bits,
+/// searching on every core in a blocking thread pool, then sign the event
+async fn mine_event(
+    kind: Kind,
+    content: String,
+    tags: Vec<Tag>,
+    keys: Keys,
+    difficulty: u8,
+) -> Result<Event, String> {
+    use nostr_sdk::nips::nip13;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    if difficulty > MAX_POW_DIFFICULTY {
+        return Err(format!("Proof-of-work difficulty cannot exceed {}", MAX_POW_DIFFICULTY));
+    }
+    let tags: Vec<Tag> = tags.into_iter().filter(|t| t.kind() != TagKind::Nonce).collect();
+
+    tokio::task::spawn_blocking(move || {
+        let pubkey = keys.public_key();
+        let created_at = Timestamp::now();
+        let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1) as u64;
+        let found = AtomicBool::new(false);
+
+        let nonce = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|start| {
+                    let (tags, content, found) = (&tags, &content, &found);
+                    scope.spawn(move || {
+                        let mut attempt = tags.clone();
+                        attempt.push(Tag::pow(0, difficulty));
+                        let last = attempt.len() - 1;
+                        let mut nonce = start;
+                        while !found.load(Ordering::Relaxed) {
+                            attempt[last] = Tag::pow(nonce as u128, difficulty);
+                            let id = EventId::new(&pubkey, &created_at, &kind, &attempt, content);
+                            if nip13::get_leading_zero_bits(id.as_bytes()) >= difficulty {
+                                found.store(true, Ordering::Relaxed);
+                                return Some(nonce);
+                            }
+                            nonce += threads;
+                        }
+                        None
+                    })
+                })
+                .collect();
+            workers.into_iter().filter_map(|w| w.join().ok().flatten()).min()
+        });
+
+        let nonce = nonce.ok_or("Proof-of-work mining stopped without a result")?;
+        EventBuilder::new(kind, content)
+            .tags(tags)
+            .tag(Tag::pow(nonce as u128, difficulty))
+            .custom_created_at(created_at)
+            .sign_with_keys(&keys)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// ============================================================================
+// Live Subscriptions
+// ============================================================================
+
+// Filter as sent by the frontend; tag keys are single letters ("e", "p", "a", ...)
+#[derive(Serialize, Deserialize, Default)]
+struct EventFilterSpec {
+    kinds: Option<Vec<u16>>, // None matches any kind; an empty list matches none
+    authors: Option<Vec<String>>,
+    ids: Option<Vec<String>>,
+    #[serde(default)]
+    tags: std::collections::HashMap<String, Vec<String>>,
+    since: Option<u64>,
+    until: Option<u64>,
+    search: Option<String>,
+    limit: Option<usize>,
+}
+
+impl EventFilterSpec {
+    fn to_filter(&self) -> Result<Filter, String> {
+        let mut filter = Filter::new();
+        if let Some(kinds) = &self.kinds {
+            filter = filter.kinds(kinds.iter().copied().map(Kind::from));
+        }
+        if let Some(authors) = &self.authors {
+            let pubkeys = authors
+                .iter()
+                .map(|a| PublicKey::parse(a.trim()))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            filter = filter.authors(pubkeys);
+        }
+        if let Some(ids) = &self.ids {
+            let ids = ids
+                .iter()
+                .map(|id| EventId::parse(id.trim()))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            filter = filter.ids(ids);
+        }
+        for (name, values) in &self.tags {
+            let letter = name.trim_start_matches('#');
+            let mut chars = letter.chars();
+            let tag = match (chars.next(), chars.next()) {
+                (Some(c), None) => SingleLetterTag::from_char(c).map_err(|e| e.to_string())?,
+                _ => return Err(format!("Tag filters need a single letter, got \"{}\"", name)),
+            };
+            filter = filter.custom_tag(tag, values.iter().cloned());
+        }
+        if let Some(since) = self.since {
+            filter = filter.since(Timestamp::from(since));
+        }
+        if let Some(until) = self.until {
+            filter = filter.until(Timestamp::from(until));
+        }
+        if let Some(search) = self.search.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+            filter = filter.search(search);
+        }
+        if let Some(limit) = self.limit {
+            filter = filter.limit(limit);
+        }
+        Ok(filter)
+    }
+}
+
+// Event ids remembered per subscription to drop copies delivered by other relays;
+// relays deliver copies close together, so older ids can be forgotten
+const SUBSCRIPTION_DEDUP_CAPACITY: usize = 4096;
+
+// The most recent event ids, oldest forgotten first once full
+struct RecentEventIds {
+    order: std::collections::VecDeque<EventId>,
+    ids: std::collections::HashSet<EventId>,
+}
+
+impl RecentEventIds {
+    fn new() -> Self {
+        Self {
+            order: std::collections::VecDeque::with_capacity(SUBSCRIPTION_DEDUP_CAPACITY),
+            ids: std::collections::HashSet::with_capacity(SUBSCRIPTION_DEDUP_CAPACITY),
+        }
+    }
+
+    /// Remember an id; false when it was already seen
+    fn insert(&mut self, id: EventId) -> bool {
+        if !self.ids.insert(id) {
+            return false;
+        }
+        self.order.push_back(id);
+        if self.order.len() > SUBSCRIPTION_DEDUP_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+#[derive(Serialize, Clone)]
+struct SubscriptionEvent {
+    subscription_id: String,
+    relay_url: String,
+    event: SignedEvent,
+}
+
+/// Open a relay subscription that stays live, forwarding each matching event once
+/// (however many relays deliver it) as a "nostr://event" event. Returns the
+/// subscription id for `nostr_unsubscribe`.
+#[tauri::command]
+async fn nostr_subscribe(
+    filters: Vec<EventFilterSpec>,
+    app: AppHandle,
+    state: State<'_, NostrState>,
+) -> Result<String, String> {
+    let client = state.client.lock().unwrap().clone().ok_or("Client not initialized")?;
+    if filters.is_empty() {
+        return Err("At least one filter is required".to_string());
+    }
+    let filters = filters
+        .iter()
+        .map(EventFilterSpec::to_filter)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Listen before subscribing so stored events sent right away are not missed
+    let mut notifications = client.notifications();
+    let subscription_id = client.subscribe(filters, None).await.map_err(|e| e.to_string())?.val;
+    let id = subscription_id.to_string();
+
+    let forward_id = id.clone();
+    let forwarder = tauri::async_runtime::spawn(async move {
+        let mut seen = RecentEventIds::new();
+        loop {
+            match notifications.recv().await {
+                Ok(RelayPoolNotification::Event {
+                    relay_url,
+                    subscription_id: sub_id,
+                    event,
+                }) if sub_id == subscription_id && seen.insert(event.id) => {
+                    let payload = SubscriptionEvent {
+                        subscription_id: forward_id.clone(),
+                        relay_url: relay_url.to_string(),
+                        event: event_to_signed_event(&event),
+                    };
+                    let _ = app.emit("nostr://event", payload);
+                }
+                Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    state.subscriptions.lock().unwrap().insert(id.clone(), forwarder);
+    Ok(id)
+}
+
+/// Close a subscription opened with `nostr_subscribe`
+#[tauri::command]
+async fn nostr_unsubscribe(subscription_id: String, state: State<'_, NostrState>) -> Result<(), String> {
+    let forwarder = state
+        .subscriptions
+        .lock()
+        .unwrap()
+        .remove(&subscription_id)
+        .ok_or_else(|| format!("Unknown subscription: {}", subscription_id))?;
+    forwarder.abort();
+
+    let client = state.client.lock().unwrap().clone();
+    if let Some(client) = client {
+        client.unsubscribe(SubscriptionId::new(subscription_id)).await;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// NIP-19 Share Links
+// ============================================================================
+
+#[derive(Serialize, Deserialize)]
+struct Nip19Entity {
+    prefix: String, // "npub", "nprofile", "note", "nevent", or "naddr"
+    pubkey: Option<String>,
+    event_id: Option<String>,
+    kind: Option<u16>,
+    identifier: Option<String>,
+    relays: Vec<String>,
+}
+
+/// Encode an addressable event coordinate as an naddr with relay hints
+#[tauri::command]
+fn nip19_encode_naddr(
+    kind: u16,
+    pubkey: String,
+    identifier: String,
+    relays: Vec<String>,
+) -> Result<String, String> {
+    let public_key = PublicKey::parse(pubkey.trim()).map_err(|e| e.to_string())?;
+    let mut coordinate = Coordinate::new(Kind::from(kind), public_key).identifier(identifier);
+    coordinate.relays = relays;
+    coordinate.to_bech32().map_err(|e| e.to_string())
+}
+
+/// Encode an event id as an nevent with optional author, kind, and relay hints
+#[tauri::command]
+fn nip19_encode_nevent(
+    event_id: String,
+    author: Option<String>,
+    kind: Option<u16>,
+    relays: Vec<String>,
+) -> Result<String, String> {
+    let event_id = EventId::parse(event_id.trim()).map_err(|e| e.to_string())?;
+    let mut nevent = Nip19Event::new(event_id, relays);
+    if let Some(author) = author {
+        nevent = nevent.author(PublicKey::parse(author.trim()).map_err(|e| e.to_string())?);
+    }
+    if let Some(kind) = kind {
+        nevent = nevent.kind(Kind::from(kind));
+    }
+    nevent.to_bech32().map_err(|e| e.to_string())
+}
+
+/// Encode a pubkey as an nprofile with relay hints
+#[tauri::command]
+fn nip19_encode_nprofile(pubkey: String, relays: Vec<String>) -> Result<String, String> {
+    let public_key = PublicKey::parse(pubkey.trim()).map_err(|e| e.to_string())?;
+    Nip19Profile::new(public_key, relays)
+        .map_err(|e| e.to_string())?
+        .to_bech32()
+        .map_err(|e| e.to_string())
+}
+
+/// Decode a pasted npub/nprofile/note/nevent/naddr (with or without "nostr:")
+#[tauri::command]
+fn nip19_decode(value: String) -> Result<Nip19Entity, String> {
+    let value = value.trim();
+    let value = value.strip_prefix("nostr:").unwrap_or(value);
+    let entity = |prefix: &str| Nip19Entity {
+        prefix: prefix.to_string(),
+        pubkey: None,
+        event_id: None,
+        kind: None,
+        identifier: None,
+        relays: Vec::new(),
+    };
+
+    match Nip19::from_bech32(value).map_err(|e| e.to_string())? {
+        Nip19::Pubkey(pubkey) => Ok(Nip19Entity {
+            pubkey: Some(pubkey.to_hex()),
+            ..entity("npub")
+        }),
+        Nip19::Profile(profile) => Ok(Nip19Entity {
+            pubkey: Some(profile.public_key.to_hex()),
+            relays: profile.relays.iter().map(|r| r.to_string()).collect(),
+            ..entity("nprofile")
+        }),
+        Nip19::EventId(event_id) => Ok(Nip19Entity {
+            event_id: Some(event_id.to_hex()),
+            ..entity("note")
+        }),
+        Nip19::Event(event) => Ok(Nip19Entity {
+            event_id: Some(event.event_id.to_hex()),
+            pubkey: event.author.map(|a| a.to_hex()),
+            kind: event.kind.map(|k| k.as_u16()),
+            relays: event.relays,
+            ..entity("nevent")
+        }),
+        Nip19::Coordinate(coordinate) => Ok(Nip19Entity {
+            pubkey: Some(coordinate.public_key.to_hex()),
+            kind: Some(coordinate.kind.as_u16()),
+            identifier: Some(coordinate.identifier),
+            relays: coordinate.relays,
+            ..entity("naddr")
+        }),
+        // Never echo secrets back through share-link handling
+        Nip19::Secret(_) | Nip19::EncryptedSecret(_) => {
+            Err("This is a private key, not a shareable link".to_string())
+        }
+    }
+}
+
+// ============================================================================
+// Key Rotation
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Clone)]
+struct RotationItem {
+    id: String,
+    kind: u16,
+    d_tag: Option<String>,
+    created_at: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeyRotationPlan {
+    old_pubkey: String,
+    items: Vec<RotationItem>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RotationStep {
+    item: RotationItem,
+    new_event_id: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeyRotationResult {
+    old_pubkey: String,
+    new_pubkey: String,
+    new_npub: String,
+    new_nsec: Option<String>, // Only returned when the key was generated here
+    republished: Vec<RotationStep>,
+    old_key_notice_id: Option<String>,
+    new_key_notice_id: Option<String>,
+}
+
+/// Whether a kind is replaceable (0, 3, 10000-19999) or addressable (30000-39999)
+fn is_replaceable_kind(kind: u16) -> bool {
+    kind == 0 || kind == 3 || (10000..20000).contains(&kind) || (30000..40000).contains(&kind)
+}
+
+/// Get the d-tag value of an event, if any
+fn event_d_tag(event: &Event) -> Option<String> {
+    event.tags.iter().find_map(|t| {
+        let parts = t.as_slice();
+        if parts.len() >= 2 && parts[0] == "d" {
+            Some(parts[1].clone())
+        } else {
+            None
+        }
+    })
+}
+
+// Replaceable and addressable kinds carried over to a new key: profile, follows, mute
+// and pin lists, relay lists, bookmarks, follow sets, long-form posts, synced feeds,
+// and app data
+const ROTATION_KINDS: &[u16] = &[
+    0, 3, 10000, 10001, 10002, 10003, 10050, 10063, 30000, 30003, 30023, 30054, 30078,
+];
+
+// Most announcements of one kind fetched for re-publishing
+const ROTATION_ANNOUNCEMENT_LIMIT: usize = 500;
+
+/// Fetch the latest replaceable events plus any announcement kinds authored by a pubkey.
+/// Each kind gets its own filter, so a busy kind can't crowd the others out of a limit.
+async fn fetch_rotation_events(
+    client: &Client,
+    pubkey: PublicKey,
+    announcement_kinds: &[u16],
+) -> Result<Vec<Event>, String> {
+    let mut filters: Vec<Filter> = ROTATION_KINDS
+        .iter()
+        .map(|kind| Filter::new().author(pubkey).kind(Kind::from(*kind)))
+        .collect();
+    filters.extend(
+        announcement_kinds
+            .iter()
+            .filter(|kind| !ROTATION_KINDS.contains(kind))
+            .map(|kind| {
+                Filter::new()
+                    .author(pubkey)
+                    .kind(Kind::from(*kind))
+                    .limit(ROTATION_ANNOUNCEMENT_LIMIT)
+            }),
+    );
+    let events = client
+        .fetch_events(filters, None)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Keep only the newest version of each replaceable coordinate
+    let mut latest: std::collections::HashMap<(u16, Option<String>), Event> = std::collections::HashMap::new();
+    let mut announcements = Vec::new();
+    for event in events.into_iter() {
+        let kind = event.kind.as_u16();
+        if is_replaceable_kind(kind) {
+            let key = (kind, event_d_tag(&event));
+            let newer = latest
+                .get(&key)
+                .map(|existing| event.created_at > existing.created_at)
+                .unwrap_or(true);
+            if newer {
+                latest.insert(key, event);
+            }
+        } else if announcement_kinds.contains(&kind) {
+            announcements.push(event);
+        }
+    }
+
+    let mut result: Vec<Event> = latest.into_values().collect();
+    result.extend(announcements);
+    result.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    Ok(result)
+}
+
+/// Re-sign an event under a new key, rewriting references to the old pubkey. NIP-26
+/// delegation tags are dropped, since the delegation was granted to the old key.
+fn resign_event(event: &Event, old_pubkey: &str, new_keys: &Keys) -> Result<Event, String> {
+    let new_pubkey = new_keys.public_key().to_hex();
+    let mut builder = EventBuilder::new(event.kind, event.content.to_string());
+
+    for tag in event.tags.iter() {
+        if tag.as_slice().first().map(String::as_str) == Some("delegation") {
+            continue;
+        }
+        let parts: Vec<String> = tag
+            .as_slice()
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                // Rewrite `a` coordinates ("kind:pubkey:d") that point at the old key
+                if i == 1 && tag.as_slice()[0] == "a" {
+                    value.replace(&format!(":{}:", old_pubkey), &format!(":{}:", new_pubkey))
+                } else {
+                    value.clone()
+                }
+            })
+            .collect();
+        builder = builder.tag(Tag::parse(&parts).map_err(|e| e.to_string())?);
+    }
+
+    builder.sign_with_keys(new_keys).map_err(|e| e.to_string())
+}
+
+/// Preview which events a key rotation would re-publish
+#[tauri::command]
+async fn key_rotation_plan(
+    announcement_kinds: Option<Vec<u16>>,
+    state: State<'_, NostrState>,
+) -> Result<KeyRotationPlan, String> {
+    let keys = state.signing_keys()?;
+    let client = state.client.lock().unwrap().clone().ok_or("Client not initialized")?;
+
+    let events = fetch_rotation_events(&client, keys.public_key(), &announcement_kinds.unwrap_or_default()).await?;
+
+    Ok(KeyRotationPlan {
+        old_pubkey: keys.public_key().to_hex(),
+        items: events
+            .iter()
+            .map(|e| RotationItem {
+                id: e.id.to_hex(),
+                kind: e.kind.as_u16(),
+                d_tag: event_d_tag(e),
+                created_at: e.created_at.as_u64(),
+            })
+            .collect(),
+    })
+}
+
+/// Rotate from the logged-in key to a new key: re-publish replaceable events and
+/// announcements under the new key, publish cross-referencing migration notices,
+/// then switch the session to the new key. A key generated here is saved to the
+/// keystore before anything is published, password-protected when a password is given.
+#[tauri::command]
+async fn key_rotation_execute(
+    new_nsec: Option<String>,
+    password: Option<String>,
+    announcement_kinds: Option<Vec<u16>>,
+    app: AppHandle,
+    state: State<'_, NostrState>,
+) -> Result<KeyRotationResult, String> {
+    let old_keys = state.signing_keys()?;
+    let client = state.client.lock().unwrap().clone().ok_or("Client not initialized")?;
+
+    let (new_keys, generated) = match new_nsec {
+        Some(nsec) => {
+            let secret_key = SecretKey::from_bech32(&nsec).map_err(|e| e.to_string())?;
+            (Keys::new(secret_key), false)
+        }
+        None => (Keys::generate(), true),
+    };
+
+    let old_pubkey = old_keys.public_key().to_hex();
+    if new_keys.public_key().to_hex() == old_pubkey {
+        return Err("New key must differ from the current key".to_string());
+    }
+
+    // Losing a generated key after its events are out would strand the new identity
+    if generated {
+        let nsec = new_keys.secret_key().to_bech32().map_err(|e| e.to_string())?;
+        let label = Some("Rotated key".to_string());
+        match password {
+            Some(password) => store_key_with_password(nsec, password, label)?,
+            None => store_key_without_password(nsec, label)?,
+        }
+    }
+
+    let events = fetch_rotation_events(&client, old_keys.public_key(), &announcement_kinds.unwrap_or_default()).await?;
+
+    // Re-publish everything (including the kind 10063 Blossom server list) under the new key
+    let mut republished = Vec::new();
+    for event in &events {
+        let item = RotationItem {
+            id: event.id.to_hex(),
+            kind: event.kind.as_u16(),
+            d_tag: event_d_tag(event),
+            created_at: event.created_at.as_u64(),
+        };
+        let outcome = match resign_event(event, &old_pubkey, &new_keys) {
+            Ok(new_event) => client
+                .send_event(new_event)
+                .await
+                .map(|output| output.id().to_hex())
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e),
+        };
+        republished.push(match outcome {
+            Ok(id) => RotationStep { item, new_event_id: Some(id), error: None },
+            Err(e) => RotationStep { item, new_event_id: None, error: Some(e) },
+        });
+    }
+
+    let new_npub = new_keys.public_key().to_bech32().map_err(|e| e.to_string())?;
+    let old_npub = old_keys.public_key().to_bech32().map_err(|e| e.to_string())?;
+
+    // Migration notice from the old key pointing at the new one
+    let old_notice = sign_app_event(
+        Kind::TextNote,
+        &format!("This account has moved to nostr:{}", new_npub),
+        &[
+            vec!["p".to_string(), new_keys.public_key().to_hex()],
+            vec!["t".to_string(), "key-rotation".to_string()],
+        ],
+        &old_keys,
+        &state,
+        None,
+    )
+    .await?;
+    let old_key_notice_id = client.send_event(old_notice).await.ok().map(|o| o.id().to_hex());
+
+    // Matching notice from the new key pointing back at the old one
+    let new_notice = sign_app_event(
+        Kind::TextNote,
+        &format!("Previously nostr:{}", old_npub),
+        &[
+            vec!["p".to_string(), old_pubkey.clone()],
+            vec!["t".to_string(), "key-rotation".to_string()],
+        ],
+        &new_keys,
+        &state,
+        None,
+    )
+    .await?;
+    let new_key_notice_id = client.send_event(new_notice).await.ok().map(|o| o.id().to_hex());
+
+    let new_nsec = if generated {
+        Some(new_keys.secret_key().to_bech32().map_err(|e| e.to_string())?)
+    } else {
+        None
+    };
+
+    // Switch the session over to the new key; the retired key is not kept signed in
+    state.sign_out(|public_key, _| public_key.to_hex() == old_pubkey);
+    let _ = client.disconnect().await;
+    let profile = login_with_keys(new_keys, &state, &app).await?;
+
+    Ok(KeyRotationResult {
+        old_pubkey,
+        new_pubkey: profile.pubkey,
+        new_npub: profile.npub,
+        new_nsec,
+        republished,
+        old_key_notice_id,
+        new_key_notice_id,
+    })
+}
+
+// ============================================================================
+// Delegated Signing (NIP-26)
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Clone)]
+struct DelegationInfo {
+    delegator_pubkey: String,
+    delegatee_pubkey: String,
+    kinds: Vec<u16>,
+    valid_from: u64,
+    valid_until: u64,
+    conditions: String,
+    sig: String,
+}
+
+/// Build the NIP-26 conditions query string
+fn delegation_conditions(kinds: &[u16], valid_from: u64, valid_until: u64) -> String {
+    let mut parts: Vec<String> = kinds.iter().map(|k| format!("kind={}", k)).collect();
+    parts.push(format!("created_at>{}", valid_from));
+    parts.push(format!("created_at<{}", valid_until));
+    parts.join("&")
+}
+
+/// Path of the persisted delegation for this machine's key
+fn get_delegation_path() -> Result<PathBuf, String> {
+    Ok(get_appstate_dir()?.join("delegation.json"))
+}
+
+/// Load a persisted delegation token if it was issued to the given pubkey
+fn load_delegation_for(pubkey: &str) -> Option<DelegationInfo> {
+    let content = fs::read_to_string(get_delegation_path().ok()?).ok()?;
+    let delegation: DelegationInfo = serde_json::from_str(&content).ok()?;
+    (delegation.delegatee_pubkey == pubkey).then_some(delegation)
+}
+
+/// Get the delegation tag to attach to an event, if a token covers this kind and time
+fn delegation_tag_for(state: &NostrState, keys: &Keys, kind: u16) -> Result<Option<Tag>, String> {
+    let delegation = state.delegation.lock().unwrap().clone();
+    let Some(delegation) = delegation else {
+        return Ok(None);
+    };
+
+    let now = get_current_timestamp()?;
+    let covered = delegation.delegatee_pubkey == keys.public_key().to_hex()
+        && delegation.kinds.contains(&kind)
+        && now > delegation.valid_from
+        && now < delegation.valid_until;
+    if !covered {
+        return Ok(None);
+    }
+
+    let tag = Tag::parse([
+        "delegation",
+        &delegation.delegator_pubkey,
+        &delegation.conditions,
+        &delegation.sig,
+    ])
+    .map_err(|e| e.to_string())?;
+    Ok(Some(tag))
+}
+
+/// Create a time-boxed delegation token with a master key for a per-machine key.
+/// The master nsec is only used for signing and is never stored.
+#[tauri::command]
+fn nostr_create_delegation(
+    delegator_nsec: String,
+    delegatee_pubkey: String,
+    kinds: Vec<u16>,
+    valid_days: u64,
+) -> Result<DelegationInfo, String> {
+    if kinds.is_empty() {
+        return Err("At least one event kind must be delegated".to_string());
+    }
+
+    let mut nsec = delegator_nsec;
+    let secret_key = SecretKey::from_bech32(&nsec).map_err(|e| e.to_string())?;
+    nsec.zeroize();
+    let delegator_keys = Keys::new(secret_key);
+
+    let delegatee = PublicKey::parse(&delegatee_pubkey).map_err(|e| e.to_string())?;
+    let valid_from = get_current_timestamp()?;
+    let valid_until = valid_from + valid_days * 86400;
+    let conditions_str = delegation_conditions(&kinds, valid_from, valid_until);
+    let conditions = nip26::Conditions::from_str(&conditions_str).map_err(|e| e.to_string())?;
+
+    let sig = nip26::sign_delegation(&delegator_keys, &delegatee, &conditions).map_err(|e| e.to_string())?;
+
+    Ok(DelegationInfo {
+        delegator_pubkey: delegator_keys.public_key().to_hex(),
+        delegatee_pubkey: delegatee.to_hex(),
+        kinds,
+        valid_from,
+        valid_until,
+        conditions: conditions_str,
+        sig: sig.to_string(),
+    })
+}
+
+/// Install (or clear) the delegation token used when signing with the current key
+#[tauri::command]
+fn nostr_set_delegation(
+    delegation: Option<DelegationInfo>,
+    state: State<'_, NostrState>,
+) -> Result<(), String> {
+    let path = get_delegation_path()?;
+
+    match delegation {
+        Some(delegation) => {
+            let delegator = PublicKey::from_hex(&delegation.delegator_pubkey).map_err(|e| e.to_string())?;
+            let delegatee = PublicKey::from_hex(&delegation.delegatee_pubkey).map_err(|e| e.to_string())?;
+            let conditions = nip26::Conditions::from_str(&delegation.conditions).map_err(|e| e.to_string())?;
+            let sig = Signature::from_str(&delegation.sig).map_err(|e| e.to_string())?;
+            nip26::verify_delegation_signature(&delegator, &sig, &delegatee, &conditions)
+                .map_err(|_| "Invalid delegation signature".to_string())?;
+
+            let json = serde_json::to_string_pretty(&delegation).map_err(|e| e.to_string())?;
+            fs::write(&path, json).map_err(|e| e.to_string())?;
+            *state.delegation.lock().unwrap() = Some(delegation);
+        }
+        None => {
+            if path.exists() {
+                fs::remove_file(&path).map_err(|e| e.to_string())?;
+            }
+            *state.delegation.lock().unwrap() = None;
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the active delegation token, if any
+#[tauri::command]
+fn nostr_get_delegation(state: State<'_, NostrState>) -> Option<DelegationInfo> {
+    state.delegation.lock().unwrap().clone()
+}
+
+// ============================================================================
+// Identity Bootstrap
+// ============================================================================
+
+// Kind used by the frontend to sync saved feeds to relays (parameterized replaceable)
+const SYNCED_FEED_KIND: u16 = 30054;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct RelayListEntry {
+    url: String,
+    read: bool,
+    write: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SyncedFeedInfo {
+    d_tag: String,
+    title: Option<String>,
+    created_at: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IdentityBootstrap {
+    pubkey: String,
+    npub: String,
+    nip05: Option<String>,
+    profile: Option<serde_json::Value>,
+    relays: Vec<RelayListEntry>,
+    blossom_servers: Vec<String>,
+    synced_feeds: Vec<SyncedFeedInfo>,
+    has_stored_key: bool,
 }
 
-/// Shared implementation for Blossom uploads
-async fn perform_blossom_upload(
-    content_bytes: Vec<u8>,
-    keys: &Keys,
-    server_url: &str,
-    mime_type: &str,
-) -> Result<BlossomUploadResult, String> {
-    let size = content_bytes.len();
+/// Resolve a NIP-05 identifier ("name@domain" or "domain") to a pubkey and its advertised relays
+async fn resolve_nip05(identifier: &str) -> Result<(PublicKey, Vec<String>), String> {
+    let (name, domain) = identifier.split_once('@').unwrap_or(("_", identifier));
+    let url = format!("https://{}/.well-known/nostr.json?name={}", domain, name);
 
-    // Calculate SHA256
-    let mut hasher = Sha256::new();
-    hasher.update(&content_bytes);
-    let sha256 = hex::encode(hasher.finalize());
+    let response = reqwest::get(&url)
+        .await
+        .map_err(|e| format!("NIP-05 lookup failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("NIP-05 lookup failed: HTTP {}", response.status()));
+    }
+    let doc: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid NIP-05 document: {}", e))?;
+
+    let hex = doc
+        .get("names")
+        .and_then(|names| names.get(name))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| format!("{} is not listed at {}", name, domain))?;
+    let pubkey = PublicKey::from_hex(hex).map_err(|e| e.to_string())?;
+
+    let relays = doc
+        .get("relays")
+        .and_then(|r| r.get(hex))
+        .and_then(|r| r.as_array())
+        .map(|list| list.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+
+    Ok((pubkey, relays))
+}
 
-    // Create auth event (valid for 5 minutes)
-    let auth_event = create_blossom_auth(keys, &sha256, "upload", 300)?;
-    let auth_json = serde_json::to_string(&auth_event).map_err(|e| e.to_string())?;
-    let auth_base64 = BASE64.encode(&auth_json);
+/// Newest event of a kind from a fetched set
+fn newest_of_kind(events: &[Event], kind: u16) -> Option<&Event> {
+    events
+        .iter()
+        .filter(|e| e.kind.as_u16() == kind)
+        .max_by_key(|e| e.created_at)
+}
 
-    // Upload to Blossom server
-    let client = reqwest::Client::new();
-    let base_url = normalize_server_url(server_url);
-    let upload_url = format!("{}/upload", base_url);
+/// Discover an identity's profile, relays, Blossom servers, and synced feeds before
+/// login, so a fresh install can offer to restore them once the key is unlocked
+#[tauri::command]
+async fn identity_bootstrap(npub_or_nip05: String) -> Result<IdentityBootstrap, String> {
+    let input = npub_or_nip05.trim();
+    let is_nip05 = input.contains('@') || (input.contains('.') && !input.starts_with("npub"));
+    let (pubkey, nip05, mut relay_urls) = if is_nip05 {
+        let (pubkey, relays) = resolve_nip05(input).await?;
+        (pubkey, Some(input.to_string()), relays)
+    } else {
+        let pubkey = PublicKey::parse(input)
+            .map_err(|_| "Enter an npub, hex pubkey, or NIP-05 address".to_string())?;
+        (pubkey, None, Vec::new())
+    };
+    relay_urls.extend(DEFAULT_RELAYS.iter().map(|r| r.to_string()));
 
-    let response = client
-        .put(&upload_url)
-        .header("Authorization", format!("Nostr {}", auth_base64))
-        .header("Content-Type", mime_type)
-        .body(content_bytes)
-        .send()
+    let client = Client::default();
+    for relay in &relay_urls {
+        let _ = client.add_relay(relay.as_str()).await;
+    }
+    client.connect().await;
+
+    let timeout = Some(std::time::Duration::from_secs(10));
+    let metadata_filter = Filter::new()
+        .author(pubkey)
+        .kinds([Kind::Metadata, Kind::from(10002), Kind::from(10063)]);
+    let metadata: Vec<Event> = client
+        .fetch_events(vec![metadata_filter], timeout)
         .await
-        .map_err(|e| format!("Upload failed: {}", e))?;
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .collect();
 
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Blossom server error {}: {}", status, error_text));
+    let profile = newest_of_kind(&metadata, 0).and_then(|e| serde_json::from_str(&e.content).ok());
+
+    let relays = newest_of_kind(&metadata, 10002).map(relay_list_from_event).unwrap_or_default();
+
+    let blossom_servers = newest_of_kind(&metadata, 10063)
+        .map(blossom_servers_from_event)
+        .unwrap_or_default();
+
+    // Synced feeds live on the identity's write relays as well as the defaults
+    for relay in relays.iter().filter(|r| r.write) {
+        let _ = client.add_relay(relay.url.as_str()).await;
     }
+    client.connect().await;
 
-    let blob_url = format!("{}/{}", base_url, sha256);
+    let feeds_filter = Filter::new().author(pubkey).kind(Kind::from(SYNCED_FEED_KIND));
+    let feed_events = client
+        .fetch_events(vec![feeds_filter], timeout)
+        .await
+        .map_err(|e| e.to_string())?;
+    let _ = client.disconnect().await;
 
-    Ok(BlossomUploadResult {
-        url: blob_url,
-        sha256,
-        size,
+    let mut latest: std::collections::HashMap<String, Event> = std::collections::HashMap::new();
+    for event in feed_events.into_iter() {
+        let Some(d_tag) = event_d_tag(&event) else {
+            continue;
+        };
+        if latest.get(&d_tag).map(|e| event.created_at > e.created_at).unwrap_or(true) {
+            latest.insert(d_tag, event);
+        }
+    }
+    let mut synced_feeds: Vec<SyncedFeedInfo> = latest
+        .into_iter()
+        .map(|(d_tag, event)| SyncedFeedInfo {
+            title: event
+                .tags
+                .iter()
+                .find(|tag| tag.as_slice().first().map(String::as_str) == Some("title"))
+                .and_then(|tag| tag.as_slice().get(1).cloned()),
+            created_at: event.created_at.as_u64(),
+            d_tag,
+        })
+        .collect();
+    synced_feeds.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+    let pubkey_hex = pubkey.to_hex();
+    let has_stored_key = load_keystore()
+        .map(|ks| ks.keys.iter().any(|k| k.pubkey == pubkey_hex))
+        .unwrap_or(false);
+
+    Ok(IdentityBootstrap {
+        npub: pubkey.to_bech32().map_err(|e| e.to_string())?,
+        pubkey: pubkey_hex,
+        nip05,
+        profile,
+        relays,
+        blossom_servers,
+        synced_feeds,
+        has_stored_key,
     })
 }
 
-/// Upload content to a Blossom server
-#[tauri::command]
-async fn blossom_upload(
-    server_url: String,
-    content: String,
-    content_type: Option<String>,
-    state: State<'_, NostrState>,
-) -> Result<BlossomUploadResult, String> {
-    let keys = state
-        .keys
-        .lock()
-        .unwrap()
-        .clone()
-        .ok_or("Not logged in - Nostr key required for Blossom upload")?;
+// ============================================================================
+// Relay List (NIP-65)
+// ============================================================================
 
-    let mime_type = content_type.unwrap_or_else(|| "application/xml".to_string());
+/// Parse the "r" tags of a NIP-65 relay list event (kind 10002)
+fn relay_list_from_event(event: &Event) -> Vec<RelayListEntry> {
+    event
+        .tags
+        .iter()
+        .filter_map(|tag| {
+            let parts = tag.as_slice();
+            if parts.first().map(String::as_str) != Some("r") {
+                return None;
+            }
+            let marker = parts.get(2).map(String::as_str);
+            Some(RelayListEntry {
+                url: parts.get(1)?.clone(),
+                read: marker != Some("write"),
+                write: marker != Some("read"),
+            })
+        })
+        .collect()
+}
 
-    perform_blossom_upload(content.into_bytes(), &keys, &server_url, &mime_type).await
+/// Fetch a pubkey's newest relay list, or an empty list if none is found
+async fn fetch_relay_list(client: &Client, pubkey: PublicKey) -> Vec<RelayListEntry> {
+    let filter = Filter::new().author(pubkey).kind(Kind::from(10002));
+    match client
+        .fetch_events(vec![filter], Some(std::time::Duration::from_secs(5)))
+        .await
+    {
+        Ok(events) => events
+            .into_iter()
+            .max_by_key(|e| e.created_at)
+            .map(|e| relay_list_from_event(&e))
+            .unwrap_or_default(),
+        Err(_) => Vec::new(),
+    }
 }
 
-/// Upload a file from disk to Blossom
-#[tauri::command]
-async fn blossom_upload_file(
-    server_url: String,
-    file_path: String,
-    state: State<'_, NostrState>,
-) -> Result<BlossomUploadResult, String> {
-    let keys = state
-        .keys
-        .lock()
-        .unwrap()
-        .clone()
-        .ok_or("Not logged in - Nostr key required for Blossom upload")?;
+/// Replace the client's relay pool with a relay list, keeping read/write markers.
+/// Entries marked neither read nor write are skipped; a list with no usable entries
+/// leaves the pool (the defaults) untouched.
+async fn apply_relay_list(client: &Client, relays: &[RelayListEntry]) {
+    if !relays.iter().any(|r| r.read || r.write) {
+        return;
+    }
 
-    // Read file
-    let content_bytes = fs::read(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    for url in client.relays().await.into_keys() {
+        let _ = client.remove_relay(url).await;
+    }
+    for relay in relays {
+        let url = relay.url.as_str();
+        let options = match (relay.read, relay.write) {
+            (true, true) => session_relay_options(),
+            (true, false) => session_relay_options().read(true).write(false),
+            (false, true) => session_relay_options().read(false).write(true),
+            (false, false) => continue,
+        };
+        let _ = client.add_relay_with_opts(url, options).await;
+    }
+    client.connect().await;
+}
 
-    // Guess content type from extension
-    let mime_type = match file_path.rsplit('.').next() {
-        Some("xml") => "application/xml",
-        Some("json") => "application/json",
-        Some("mp3") => "audio/mpeg",
-        Some("flac") => "audio/flac",
-        Some("wav") => "audio/wav",
-        Some("ogg") => "audio/ogg",
-        Some("png") => "image/png",
-        Some("jpg") | Some("jpeg") => "image/jpeg",
-        Some("webp") => "image/webp",
-        _ => "application/octet-stream",
-    };
+/// Relays events are published to: the user's write relays, or the defaults
+fn write_relay_urls(state: &NostrState) -> Vec<String> {
+    let relay_list = state.relay_list.lock().unwrap();
+    let write: Vec<String> = relay_list.iter().filter(|r| r.write).map(|r| r.url.clone()).collect();
+    if write.is_empty() {
+        setup::bootstrap_relays()
+    } else {
+        write
+    }
+}
 
-    perform_blossom_upload(content_bytes, &keys, &server_url, mime_type).await
+/// Get the logged-in user's relay list (empty when they have not published one)
+#[tauri::command]
+fn nostr_get_relay_list(state: State<'_, NostrState>) -> Vec<RelayListEntry> {
+    state.relay_list.lock().unwrap().clone()
 }
 
-/// Delete a blob from a Blossom server
+/// Publish a new relay list (kind 10002) and switch the client over to it
 #[tauri::command]
-async fn blossom_delete(
-    server_url: String,
-    sha256: String,
+async fn nostr_publish_relay_list(
+    relays: Vec<RelayListEntry>,
     state: State<'_, NostrState>,
-) -> Result<(), String> {
-    let keys = state
-        .keys
-        .lock()
-        .unwrap()
-        .clone()
-        .ok_or("Not logged in")?;
+) -> Result<String, String> {
+    let keys = state.signing_keys()?;
+    let client = state.client.lock().unwrap().clone().ok_or("Client not initialized")?;
 
-    let auth_event = create_blossom_auth(&keys, &sha256, "delete", 300)?;
-    let auth_json = serde_json::to_string(&auth_event).map_err(|e| e.to_string())?;
-    let auth_base64 = BASE64.encode(&auth_json);
+    if !relays.iter().any(|r| r.write) {
+        return Err("The relay list needs at least one write relay".to_string());
+    }
+    if let Some(bad) = relays
+        .iter()
+        .find(|r| !r.url.starts_with("wss://") && !r.url.starts_with("ws://"))
+    {
+        return Err(format!("Invalid relay URL: {}", bad.url));
+    }
 
-    let client = reqwest::Client::new();
-    let delete_url = format!("{}/{}", normalize_server_url(&server_url), sha256);
+    let tags: Vec<Vec<String>> = relays
+        .iter()
+        .map(|relay| {
+            let mut tag = vec!["r".to_string(), relay.url.clone()];
+            match (relay.read, relay.write) {
+                (true, false) => tag.push("read".to_string()),
+                (false, true) => tag.push("write".to_string()),
+                _ => {}
+            }
+            tag
+        })
+        .collect();
+    let event = sign_app_event(Kind::from(10002), "", &tags, &keys, &state, None).await?;
+    let event_id = event.id.to_hex();
 
-    let response = client
-        .delete(&delete_url)
-        .header("Authorization", format!("Nostr {}", auth_base64))
-        .send()
-        .await
-        .map_err(|e| format!("Delete failed: {}", e))?;
+    // Announce the new list on the old write relays as well as the new ones
+    for relay in &relays {
+        let _ = client.add_relay_with_opts(relay.url.as_str(), session_relay_options()).await;
+    }
+    client.connect().await;
+    client.send_event(event).await.map_err(|e| e.to_string())?;
 
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Blossom server error {}: {}", status, error_text));
+    apply_relay_list(&client, &relays).await;
+    *state.relay_list.lock().unwrap() = relays;
+
+    Ok(event_id)
+}
+
+// ============================================================================
+// Blossom Server List (kind 10063)
+// ============================================================================
+
+/// Parse the "server" tags of a Blossom server list event, in preference order
+fn blossom_servers_from_event(event: &Event) -> Vec<String> {
+    event
+        .tags
+        .iter()
+        .filter(|tag| tag.as_slice().first().map(String::as_str) == Some("server"))
+        .filter_map(|tag| tag.as_slice().get(1).cloned())
+        .collect()
+}
+
+/// Fetch a pubkey's newest Blossom server list, or an empty list if none is found
+async fn fetch_blossom_servers(client: &Client, pubkey: PublicKey) -> Vec<String> {
+    let filter = Filter::new().author(pubkey).kind(Kind::from(10063));
+    match client
+        .fetch_events(vec![filter], Some(std::time::Duration::from_secs(5)))
+        .await
+    {
+        Ok(events) => events
+            .into_iter()
+            .max_by_key(|e| e.created_at)
+            .map(|e| blossom_servers_from_event(&e))
+            .unwrap_or_default(),
+        Err(_) => Vec::new(),
     }
+}
 
-    Ok(())
+/// Get the logged-in user's preferred Blossom servers (empty when they have none)
+#[tauri::command]
+fn blossom_get_server_list(state: State<'_, NostrState>) -> Vec<String> {
+    state.blossom_servers.lock().unwrap().clone()
 }
 
-/// List blobs on a Blossom server for the logged-in user
+/// Publish the preferred Blossom server list (kind 10063), first server preferred
 #[tauri::command]
-async fn blossom_list(
-    server_url: String,
+async fn blossom_publish_server_list(
+    servers: Vec<String>,
     state: State<'_, NostrState>,
-) -> Result<Vec<serde_json::Value>, String> {
-    let keys = state
-        .keys
-        .lock()
-        .unwrap()
-        .clone()
-        .ok_or("Not logged in")?;
+) -> Result<String, String> {
+    let keys = state.signing_keys()?;
+    let client = state.client.lock().unwrap().clone().ok_or("Client not initialized")?;
 
-    let pubkey = keys.public_key().to_hex();
+    let servers: Vec<String> = servers
+        .iter()
+        .map(|s| normalize_server_url(s.trim()).to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if servers.is_empty() {
+        return Err("The server list needs at least one server".to_string());
+    }
+    if let Some(bad) = servers
+        .iter()
+        .find(|s| !s.starts_with("https://") && !s.starts_with("http://"))
+    {
+        return Err(format!("Invalid Blossom server URL: {}", bad));
+    }
 
-    let client = reqwest::Client::new();
-    let list_url = format!("{}/list/{}", normalize_server_url(&server_url), pubkey);
+    let tags: Vec<Vec<String>> = servers
+        .iter()
+        .map(|server| vec!["server".to_string(), server.clone()])
+        .collect();
+    let event = sign_app_event(Kind::from(10063), "", &tags, &keys, &state, None).await?;
+    let event_id = event.id.to_hex();
 
-    let response = client
-        .get(&list_url)
-        .send()
-        .await
-        .map_err(|e| format!("List failed: {}", e))?;
+    client.send_event(event).await.map_err(|e| e.to_string())?;
+    *state.blossom_servers.lock().unwrap() = servers;
 
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Blossom server error {}: {}", status, error_text));
-    }
+    Ok(event_id)
+}
 
-    let blobs: Vec<serde_json::Value> = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+// ============================================================================
+// Profile Metadata (kind 0)
+// ============================================================================
 
-    Ok(blobs)
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ProfileMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    about: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    picture: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    banner: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    website: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nip05: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lud16: Option<String>,
+    // Fields set by other clients, kept so publishing does not drop them
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
 }
 
-/// Login with nsec (private key)
+/// Fetch a profile (npub or hex pubkey) from the connected relays, or the defaults
+/// when not logged in
 #[tauri::command]
-async fn nostr_login_nsec(
-    nsec: String,
+async fn nostr_fetch_profile(
+    pubkey: String,
     state: State<'_, NostrState>,
-) -> Result<NostrProfile, String> {
-    let secret_key = SecretKey::from_bech32(&nsec).map_err(|e| e.to_string())?;
-    let keys = Keys::new(secret_key);
-    login_with_keys(keys, &state).await
+) -> Result<Option<ProfileMetadata>, String> {
+    let pubkey = PublicKey::parse(pubkey.trim()).map_err(|e| e.to_string())?;
+
+    let connected = state.client.lock().unwrap().clone();
+    let client = match connected {
+        Some(client) => client,
+        None => {
+            let client = Client::default();
+            for relay in DEFAULT_RELAYS {
+                let _ = client.add_relay(*relay).await;
+            }
+            client.connect().await;
+            client
+        }
+    };
+
+    let filter = Filter::new().author(pubkey).kind(Kind::Metadata);
+    let events = client
+        .fetch_events(vec![filter], Some(std::time::Duration::from_secs(10)))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match events.into_iter().max_by_key(|e| e.created_at) {
+        Some(event) => serde_json::from_str(&event.content)
+            .map(Some)
+            .map_err(|e| format!("Invalid profile metadata: {}", e)),
+        None => Ok(None),
+    }
 }
 
-/// Login with hex private key
-#[tauri::command]
-async fn nostr_login_hex(
-    hex_key: String,
-    state: State<'_, NostrState>,
-) -> Result<NostrProfile, String> {
-    let secret_key = SecretKey::from_hex(&hex_key).map_err(|e| e.to_string())?;
-    let keys = Keys::new(secret_key);
-    login_with_keys(keys, &state).await
+#[derive(Serialize, Deserialize)]
+struct Nip05Verification {
+    identifier: String,
+    verified: bool,
+    resolved_pubkey: Option<String>,
+    relays: Vec<String>,
+    error: Option<String>,
 }
 
-/// Logout - clear keys and disconnect
+/// Check that a NIP-05 identifier ("name@domain") resolves to the given pubkey
 #[tauri::command]
-async fn nostr_logout(state: State<'_, NostrState>) -> Result<(), String> {
-    let client = state.client.lock().unwrap().take();
-    if let Some(c) = client {
-        let _ = c.disconnect().await;
-    }
-    *state.keys.lock().unwrap() = None;
-    Ok(())
+async fn nostr_verify_nip05(identifier: String, pubkey: String) -> Result<Nip05Verification, String> {
+    let identifier = identifier.trim().to_lowercase();
+    let expected = PublicKey::parse(pubkey.trim()).map_err(|e| e.to_string())?;
+
+    Ok(match resolve_nip05(&identifier).await {
+        Ok((resolved, relays)) => {
+            let verified = resolved == expected;
+            Nip05Verification {
+                error: (!verified).then(|| format!("{} points to a different pubkey", identifier)),
+                identifier,
+                verified,
+                resolved_pubkey: Some(resolved.to_hex()),
+                relays,
+            }
+        }
+        Err(e) => Nip05Verification {
+            identifier,
+            verified: false,
+            resolved_pubkey: None,
+            relays: Vec::new(),
+            error: Some(e),
+        },
+    })
 }
 
-/// Get current login status
+/// Publish the logged-in user's profile (kind 0), replacing the previous one
 #[tauri::command]
-fn nostr_get_pubkey(state: State<'_, NostrState>) -> Option<NostrProfile> {
-    state.keys.lock().unwrap().as_ref().map(|keys| {
-        NostrProfile {
-            pubkey: keys.public_key().to_hex(),
-            npub: keys.public_key().to_bech32().unwrap_or_default(),
+async fn nostr_publish_profile(
+    metadata: ProfileMetadata,
+    state: State<'_, NostrState>,
+) -> Result<String, String> {
+    let keys = state.signing_keys()?;
+    let client = state.client.lock().unwrap().clone().ok_or("Client not initialized")?;
+
+    if let Some(lud16) = metadata.lud16.as_deref().filter(|l| !l.is_empty()) {
+        if !lud16.contains('@') {
+            return Err(format!("Invalid lightning address: {}", lud16));
         }
-    })
+    }
+    if let Some(nip05) = metadata.nip05.as_deref().filter(|n| !n.is_empty()) {
+        if !nip05.contains('@') && !nip05.contains('.') {
+            return Err(format!("Invalid NIP-05 address: {}", nip05));
+        }
+    }
+
+    let content = serde_json::to_string(&metadata).map_err(|e| e.to_string())?;
+    let event = sign_app_event(Kind::Metadata, &content, &[], &keys, &state, None).await?;
+    let event_id = event.id.to_hex();
+
+    client.send_event(event).await.map_err(|e| e.to_string())?;
+
+    Ok(event_id)
+}
+
+// ============================================================================
+// Feed Ownership Verification
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Clone)]
+struct DeclaredOwner {
+    source: String, // "podcast:txt", "value-recipient", or "nostr-event"
+    pubkey: String,
+    matches_current: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct OwnershipReport {
+    feed_guid: Option<String>,
+    identifier: Option<String>, // d tag the feed is synced under, which confirmations are keyed by
+    current_pubkey: String,
+    declared_owners: Vec<DeclaredOwner>,
+    warnings: Vec<String>,
+    mismatch: bool,
+    confirmed: bool,
+}
+
+// A confirmation names the feed's kind-30054 coordinate: the d tag it is synced under
+// plus the confirming pubkey. Older entries stored the podcast:guid, which is the d tag
+// feeds are synced under.
+#[derive(Serialize, Deserialize)]
+struct OwnershipConfirmation {
+    #[serde(alias = "feed_guid")]
+    identifier: String,
+    pubkey: String,
+    confirmed_at: u64,
+}
+
+// How long the owners found on relays for a feed are reused before asking again
+const OWNERSHIP_CACHE_SECS: u64 = 600;
+
+// Pubkeys that published a synced feed under each d tag, with when they were fetched
+static RELAY_OWNERS: Mutex<Option<std::collections::HashMap<String, (u64, Vec<PublicKey>)>>> =
+    Mutex::new(None);
+
+/// Get the path of the confirmed-ownership list
+fn get_ownership_path() -> Result<PathBuf, String> {
+    Ok(get_appstate_dir()?.join("ownership.json"))
+}
+
+fn load_ownership_confirmations() -> Vec<OwnershipConfirmation> {
+    get_ownership_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Find an npub embedded in a string (e.g. "npub1...@npub.cash") and decode it
+fn find_npub(text: &str) -> Option<PublicKey> {
+    let start = text.find("npub1")?;
+    let candidate: String = text[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect();
+    PublicKey::from_bech32(&candidate).ok()
 }
 
-/// Sign an event
-#[tauri::command]
-async fn nostr_sign_event(
-    kind: u16,
-    content: String,
-    tags: Vec<Vec<String>>,
-    state: State<'_, NostrState>,
-) -> Result<SignedEvent, String> {
-    let keys = state
-        .keys
-        .lock()
-        .unwrap()
-        .clone()
-        .ok_or("Not logged in")?;
-    
-    let kind = Kind::from(kind);
-    
-    let mut builder = EventBuilder::new(kind, &content);
-    
-    for tag in &tags {
-        if !tag.is_empty() {
-            let tag = Tag::parse(tag).map_err(|e| e.to_string())?;
-            builder = builder.tag(tag);
-        }
+/// Pubkeys the current session may publish as: the signing key and any delegator
+fn current_identities(state: &NostrState, keys: &Keys) -> Vec<String> {
+    let mut identities = vec![keys.public_key().to_hex()];
+    if let Some(delegation) = state.delegation.lock().unwrap().as_ref() {
+        identities.push(delegation.delegator_pubkey.clone());
     }
-    
-    let event = builder.sign_with_keys(&keys).map_err(|e| e.to_string())?;
-
-    Ok(event_to_signed_event(&event))
+    identities
 }
 
-/// Publish an event to relays
-#[tauri::command]
-async fn nostr_publish_event(
-    kind: u16,
-    content: String,
-    tags: Vec<Vec<String>>,
-    state: State<'_, NostrState>,
-) -> Result<String, String> {
-    let keys = state
-        .keys
-        .lock()
-        .unwrap()
-        .clone()
-        .ok_or("Not logged in")?;
-    
-    let client = state
-        .client
+/// Pubkeys that published a synced feed under `identifier`, cached for a while so
+/// repeated signs of the same feed don't each wait on the relays
+async fn relay_feed_owners(client: &Client, identifier: &str) -> Result<Vec<PublicKey>, String> {
+    let now = get_current_timestamp()?;
+    let cached = RELAY_OWNERS
         .lock()
         .unwrap()
-        .clone()
-        .ok_or("Client not initialized")?;
-    
-    let kind = Kind::from(kind);
-    
-    let mut builder = EventBuilder::new(kind, &content);
-    
-    for tag in &tags {
-        if !tag.is_empty() {
-            let tag = Tag::parse(tag).map_err(|e| e.to_string())?;
-            builder = builder.tag(tag);
+        .as_ref()
+        .and_then(|owners| owners.get(identifier).cloned());
+    if let Some((fetched_at, owners)) = cached {
+        if now.saturating_sub(fetched_at) < OWNERSHIP_CACHE_SECS {
+            return Ok(owners);
         }
     }
-    
-    let event = builder.sign_with_keys(&keys).map_err(|e| e.to_string())?;
-    let event_id = event.id.to_hex();
-    
-    client
-        .send_event(event)
+
+    let filter = Filter::new()
+        .kind(Kind::from(SYNCED_FEED_KIND))
+        .identifier(identifier)
+        .limit(20);
+    let events = client
+        .fetch_events(vec![filter], Some(std::time::Duration::from_secs(8)))
         .await
         .map_err(|e| e.to_string())?;
-    
-    Ok(event_id)
-}
-
-/// Fetch events from relays
-#[tauri::command]
-async fn nostr_fetch_events(
-    kinds: Vec<u16>,
-    authors: Option<Vec<String>>,
-    limit: Option<usize>,
-    state: State<'_, NostrState>,
-) -> Result<Vec<SignedEvent>, String> {
-    let client = state
-        .client
+    let owners: Vec<PublicKey> = events.into_iter().map(|event| event.pubkey).collect();
+    RELAY_OWNERS
         .lock()
         .unwrap()
-        .clone()
-        .ok_or("Client not initialized")?;
-    
-    let kinds: Vec<Kind> = kinds.into_iter().map(Kind::from).collect();
-    
-    let mut filter = Filter::new().kinds(kinds);
-    
-    if let Some(authors) = authors {
-        let pubkeys: Result<Vec<PublicKey>, _> = authors
-            .iter()
-            .map(|a| PublicKey::from_hex(a))
-            .collect();
-        filter = filter.authors(pubkeys.map_err(|e| e.to_string())?);
+        .get_or_insert_with(std::collections::HashMap::new)
+        .insert(identifier.to_string(), (now, owners.clone()));
+    Ok(owners)
+}
+
+/// Compare the owners a feed declares against the logged-in identity. `identifier` is
+/// the d tag the feed is synced under, defaulting to its podcast:guid.
+async fn check_feed_ownership(
+    xml: &str,
+    identifier: Option<&str>,
+    state: &NostrState,
+) -> Result<OwnershipReport, String> {
+    let keys = state.signing_keys()?;
+    let client = state.client.lock().unwrap().clone();
+    let identities = current_identities(state, &keys);
+
+    let doc = feed_xml::parse_rss(xml)?;
+    let channel = doc.channel().ok_or("Missing <channel> element")?;
+    let feed_guid = channel.child_text("podcast:guid").map(str::to_string);
+    let identifier = identifier.map(str::to_string).or_else(|| feed_guid.clone());
+
+    let mut declared: Vec<DeclaredOwner> = Vec::new();
+    let mut declare = |source: &str, pubkey: PublicKey| {
+        let pubkey = pubkey.to_hex();
+        if !declared.iter().any(|d| d.source == source && d.pubkey == pubkey) {
+            declared.push(DeclaredOwner {
+                source: source.to_string(),
+                matches_current: identities.contains(&pubkey),
+                pubkey,
+            });
+        }
+    };
+
+    for txt in channel.children_named("podcast:txt") {
+        if txt.attr("purpose") == Some("npub") {
+            if let Some(pubkey) = find_npub(&txt.text) {
+                declare("podcast:txt", pubkey);
+            }
+        }
     }
-    
-    if let Some(limit) = limit {
-        filter = filter.limit(limit);
+    for recipient in channel.descendants_named("podcast:valueRecipient") {
+        if let Some(pubkey) = recipient.attr("address").and_then(find_npub) {
+            declare("value-recipient", pubkey);
+        }
     }
-    
-    let events = client
-        .fetch_events(vec![filter], None)
-        .await
-        .map_err(|e| e.to_string())?;
 
-    Ok(events.iter().map(event_to_signed_event).collect())
+    // A synced copy of this feed signed by someone else is an attestation of ownership
+    if let (Some(identifier), Some(client)) = (&identifier, client) {
+        for pubkey in relay_feed_owners(&client, identifier).await? {
+            declare("nostr-event", pubkey);
+        }
+    }
+
+    let mut warnings = Vec::new();
+    for owner in declared.iter().filter(|d| !d.matches_current && d.source != "value-recipient") {
+        let npub = PublicKey::from_hex(&owner.pubkey)
+            .ok()
+            .and_then(|pk| pk.to_bech32().ok())
+            .unwrap_or_else(|| owner.pubkey.clone());
+        warnings.push(match owner.source.as_str() {
+            "podcast:txt" => format!("Feed declares artist npub {}, which is not your key", npub),
+            _ => format!("This feed has already been published to Nostr by {}", npub),
+        });
+    }
+
+    // Value splits are often shared, so only warn when none of the npub-based recipients is you
+    let recipients: Vec<&DeclaredOwner> = declared.iter().filter(|d| d.source == "value-recipient").collect();
+    let pays_someone_else = !recipients.is_empty() && !recipients.iter().any(|r| r.matches_current);
+    if pays_someone_else {
+        warnings.push("Value splits pay other Nostr identities but not you".to_string());
+    }
+
+    let mismatch = !warnings.is_empty();
+    let current_pubkey = keys.public_key().to_hex();
+    let confirmed = match &identifier {
+        Some(identifier) => load_ownership_confirmations()
+            .iter()
+            .any(|c| &c.identifier == identifier && c.pubkey == current_pubkey),
+        None => false,
+    };
+
+    Ok(OwnershipReport {
+        feed_guid,
+        identifier,
+        current_pubkey,
+        declared_owners: declared,
+        warnings,
+        mismatch,
+        confirmed,
+    })
+}
+
+/// Check whether an imported or synced feed declares a different owner than the logged-in
+/// key. `identifier` is the d tag it is synced under (its podcast:guid unless given).
+#[tauri::command]
+async fn feed_check_ownership(
+    xml: String,
+    identifier: Option<String>,
+    state: State<'_, NostrState>,
+) -> Result<OwnershipReport, String> {
+    check_feed_ownership(&xml, identifier.as_deref(), &state).await
+}
+
+/// Confirm that the current identity may publish a feed despite declared ownership by
+/// others. `identifier` is the report's identifier, the d tag the feed is synced under.
+#[tauri::command]
+fn feed_confirm_ownership(identifier: String, state: State<'_, NostrState>) -> Result<(), String> {
+    let keys = state.signing_keys()?;
+    let pubkey = keys.public_key().to_hex();
+
+    let mut confirmations = load_ownership_confirmations();
+    confirmations.retain(|c| !(c.identifier == identifier && c.pubkey == pubkey));
+    confirmations.push(OwnershipConfirmation {
+        identifier,
+        pubkey,
+        confirmed_at: get_current_timestamp()?,
+    });
+
+    let json = serde_json::to_string_pretty(&confirmations).map_err(|e| e.to_string())?;
+    fs::write(get_ownership_path()?, json).map_err(|e| e.to_string())
 }
 
 // ============================================================================
@@ -801,11 +4614,11 @@ fn get_keystore_path() -> Result<PathBuf, String> {
 }
 
 /// Derive encryption key from password using Argon2id
-fn derive_key_from_password(password: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+fn derive_key_from_password(password: &str, salt: &[u8], kdf: &KdfParams) -> Result<[u8; 32], String> {
     let argon2 = Argon2::new(
         argon2::Algorithm::Argon2id,
         argon2::Version::V0x13,
-        argon2::Params::new(ARGON2_MEMORY_KB, ARGON2_ITERATIONS, ARGON2_PARALLELISM, Some(32))
+        argon2::Params::new(kdf.memory_kb, kdf.iterations, kdf.parallelism, Some(32))
             .map_err(|e| e.to_string())?,
     );
 
@@ -831,7 +4644,59 @@ fn derive_key_from_device() -> Result<[u8; 32], String> {
     hasher.update(&combined);
     let salt = hasher.finalize();
 
-    derive_key_from_password(&machine_id, &salt[..16])
+    derive_key_from_password(&machine_id, &salt[..16], &KdfParams::default())
+}
+
+/// Path of the calibrated Argon2 settings used for newly stored keys
+fn get_kdf_params_path() -> Result<PathBuf, String> {
+    Ok(get_appstate_dir()?.join("kdf_params.json"))
+}
+
+/// Argon2 settings for new password-protected entries: calibrated if available
+fn load_kdf_params() -> KdfParams {
+    get_kdf_params_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Time one key derivation with the given settings
+fn time_kdf(kdf: &KdfParams) -> Result<u64, String> {
+    let started = std::time::Instant::now();
+    let mut key = derive_key_from_password("calibration", b"msp-studio-kdf-calibration", kdf)?;
+    key.zeroize();
+    Ok(started.elapsed().as_millis() as u64)
+}
+
+/// Benchmark Argon2 on this machine and save settings that take about `target_ms`
+/// (default 500ms) per unlock. Memory is raised first, up to 256MB, then iterations
+/// fill the rest.
+/// Keys stored afterwards use these settings and record them in their entry.
+#[tauri::command]
+async fn calibrate_kdf(target_ms: Option<u64>) -> Result<KdfParams, String> {
+    let target_ms = target_ms.unwrap_or(KDF_CALIBRATION_TARGET_MS).max(1);
+
+    let kdf = tokio::task::spawn_blocking(move || -> Result<KdfParams, String> {
+        let mut kdf = KdfParams {
+            iterations: 1,
+            ..KdfParams::default()
+        };
+        loop {
+            let pass_ms = time_kdf(&kdf)?.max(1);
+            if pass_ms * ARGON2_ITERATIONS as u64 >= target_ms || kdf.memory_kb >= KDF_MAX_MEMORY_KB {
+                kdf.iterations = ((target_ms / pass_ms) as u32).clamp(ARGON2_ITERATIONS, KDF_MAX_ITERATIONS);
+                return Ok(kdf);
+            }
+            kdf.memory_kb = (kdf.memory_kb * 2).min(KDF_MAX_MEMORY_KB);
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let json = serde_json::to_string_pretty(&kdf).map_err(|e| e.to_string())?;
+    fs::write(get_kdf_params_path()?, json).map_err(|e| e.to_string())?;
+    Ok(kdf)
 }
 
 /// Encrypt nsec with the given key
@@ -900,55 +4765,74 @@ fn load_keystore() -> Result<KeystoreFile, String> {
 
     if !keystore_path.exists() {
         return Ok(KeystoreFile {
-            version: 2,
+            version: KEYSTORE_FORMAT_VERSION,
             keys: Vec::new(),
         });
     }
 
-    let content = fs::read_to_string(&keystore_path).map_err(|e| e.to_string())?;
-
-    // Try to parse as v2 first
-    if let Ok(keystore) = serde_json::from_str::<KeystoreFile>(&content) {
-        if keystore.version == 2 {
-            return Ok(keystore);
+    let value = match read_keystore_json(&keystore_path) {
+        Ok(value) => value,
+        Err(e) => {
+            // A keystore torn by an older non-atomic write: fall back to the last good copy
+            let backup_path = get_keystore_backup_path()?;
+            if !backup_path.exists() {
+                return Err(e);
+            }
+            eprintln!("Keystore unreadable ({}), restoring from {}", e, backup_path.display());
+            read_keystore_json(&backup_path).map_err(|_| e)?
         }
-    }
-
-    // Try to parse as v1 and migrate
-    if let Ok(v1) = serde_json::from_str::<StoredKeyFileV1>(&content) {
-        let entry = StoredKeyEntry {
-            pubkey: v1.pubkey,
-            mode: v1.mode,
-            nonce: v1.nonce,
-            ciphertext: v1.ciphertext,
-            argon2_salt: v1.argon2_salt,
-            created_at: v1.created_at,
-            label: None,
-        };
+    };
 
-        let keystore = KeystoreFile {
-            version: 2,
-            keys: vec![entry],
-        };
+    let (value, migrated) = apply_migrations(value, KEYSTORE_MIGRATIONS, KEYSTORE_FORMAT_VERSION)?;
+    let keystore: KeystoreFile =
+        serde_json::from_value(value).map_err(|_| "Failed to parse keystore file".to_string())?;
 
-        // Save migrated keystore
+    // Save migrated keystore
+    if migrated {
         save_keystore(&keystore)?;
-
-        return Ok(keystore);
     }
 
-    Err("Failed to parse keystore file".to_string())
+    Ok(keystore)
+}
+
+fn read_keystore_json(path: &std::path::Path) -> Result<serde_json::Value, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|_| "Failed to parse keystore file".to_string())
+}
+
+/// Previous keystore, kept in case the current one becomes unreadable
+fn get_keystore_backup_path() -> Result<PathBuf, String> {
+    Ok(get_keystore_path()?.with_extension("json.bak"))
 }
 
-/// Save keystore to disk
+/// Save keystore to disk, keeping the previous version as keystore.json.bak
 fn save_keystore(keystore: &KeystoreFile) -> Result<(), String> {
+    let _operation = shutdown::begin("keystore-write", "keystore");
     let keystore_path = get_keystore_path()?;
     let json = serde_json::to_string_pretty(keystore).map_err(|e| e.to_string())?;
-    fs::write(&keystore_path, json).map_err(|e| e.to_string())?;
+
+    // Only a readable keystore replaces the backup, so a torn file never overwrites it
+    if read_keystore_json(&keystore_path).is_ok() {
+        let backup_path = get_keystore_backup_path()?;
+        fs::copy(&keystore_path, &backup_path).map_err(|e| e.to_string())?;
+        set_file_permissions(&backup_path)?;
+    }
+
+    write_atomic(&keystore_path, json.as_bytes())?;
     set_file_permissions(&keystore_path)?;
     Ok(())
 }
 
+/// Save a keystore that had keys removed. The backup gets the same contents rather
+/// than the previous version, so removed keys don't live on in it.
+fn save_keystore_after_removal(keystore: &KeystoreFile) -> Result<(), String> {
+    save_keystore(keystore)?;
+    let backup_path = get_keystore_backup_path()?;
+    let json = serde_json::to_string_pretty(keystore).map_err(|e| e.to_string())?;
+    write_atomic(&backup_path, json.as_bytes())?;
+    set_file_permissions(&backup_path)
+}
+
 /// List all stored keys
 #[tauri::command]
 fn list_stored_keys() -> Result<StoredKeysResponse, String> {
@@ -962,6 +4846,7 @@ fn list_stored_keys() -> Result<StoredKeysResponse, String> {
             mode: k.mode.clone(),
             created_at: k.created_at,
             label: k.label.clone(),
+            require_os_auth: k.require_os_auth,
         })
         .collect();
 
@@ -997,7 +4882,8 @@ fn store_key_with_password(nsec: String, password: String, label: Option<String>
     let salt_str = salt.as_str();
 
     // Derive key and encrypt
-    let mut encryption_key = derive_key_from_password(&password, salt_str.as_bytes())?;
+    let kdf = load_kdf_params();
+    let mut encryption_key = derive_key_from_password(&password, salt_str.as_bytes(), &kdf)?;
     let (nonce, ciphertext) = encrypt_nsec(&nsec, &encryption_key)?;
 
     // Zeroize sensitive data
@@ -1011,6 +4897,8 @@ fn store_key_with_password(nsec: String, password: String, label: Option<String>
         argon2_salt: salt.to_string(),
         created_at: get_current_timestamp()?,
         label,
+        require_os_auth: false,
+        kdf,
     };
 
     keystore.keys.push(entry);
@@ -1030,7 +4918,8 @@ fn store_key_without_password(nsec: String, label: Option<String>) -> Result<(),
     // Load existing keystore
     let mut keystore = load_keystore()?;
 
-    // Remove existing entry for this pubkey if present
+    // Remove existing entry for this pubkey if present, keeping its OS auth gate
+    let require_os_auth = keystore.keys.iter().any(|k| k.pubkey == pubkey && k.require_os_auth);
     keystore.keys.retain(|k| k.pubkey != pubkey);
 
     // Derive key from device ID
@@ -1048,6 +4937,8 @@ fn store_key_without_password(nsec: String, label: Option<String>) -> Result<(),
         argon2_salt: String::new(),
         created_at: get_current_timestamp()?,
         label,
+        require_os_auth,
+        kdf: KdfParams::default(),
     };
 
     keystore.keys.push(entry);
@@ -1056,11 +4947,47 @@ fn store_key_without_password(nsec: String, label: Option<String>) -> Result<(),
     Ok(())
 }
 
+/// Generate a new Nostr identity. The nsec is returned once for the user to back
+/// up; with `store` it is also saved to the keystore, password-protected when a
+/// password is given and device-protected otherwise.
+#[tauri::command]
+fn nostr_generate_key(
+    store: Option<bool>,
+    password: Option<String>,
+    label: Option<String>,
+) -> Result<GeneratedKey, String> {
+    let keys = Keys::generate();
+    let nsec = keys.secret_key().to_bech32().map_err(|e| e.to_string())?;
+
+    let stored_mode = if store.unwrap_or(false) {
+        match password {
+            Some(password) => {
+                store_key_with_password(nsec.clone(), password, label)?;
+                Some("password".to_string())
+            }
+            None => {
+                store_key_without_password(nsec.clone(), label)?;
+                Some("device".to_string())
+            }
+        }
+    } else {
+        None
+    };
+
+    Ok(GeneratedKey {
+        pubkey: keys.public_key().to_hex(),
+        npub: keys.public_key().to_bech32().map_err(|e| e.to_string())?,
+        nsec,
+        stored_mode,
+    })
+}
+
 /// Unlock a stored key by pubkey and login
 #[tauri::command]
 async fn unlock_stored_key(
     pubkey: Option<String>,
     password: Option<String>,
+    app: AppHandle,
     state: State<'_, NostrState>,
 ) -> Result<NostrProfile, String> {
     let keystore = load_keystore()?;
@@ -1083,9 +5010,16 @@ async fn unlock_stored_key(
     let mut decryption_key = match entry.mode.as_str() {
         "password" => {
             let password = password.ok_or("Password required for this key")?;
-            derive_key_from_password(&password, entry.argon2_salt.as_bytes())?
+            // The keystore password also unlocks an encrypted feed library
+            library_crypto::unlock_with_password(&password);
+            derive_key_from_password(&password, entry.argon2_salt.as_bytes(), &entry.kdf)?
+        }
+        "device" => {
+            if entry.require_os_auth {
+                os_auth::verify_user("unlock your Nostr key").await?;
+            }
+            derive_key_from_device()?
         }
-        "device" => derive_key_from_device()?,
         _ => return Err(format!("Unknown storage mode: {}", entry.mode)),
     };
 
@@ -1105,7 +5039,7 @@ async fn unlock_stored_key(
         return Err("Key verification failed - pubkey mismatch".to_string());
     }
 
-    login_with_keys(keys, &state).await
+    login_with_keys(keys, &state, &app).await
 }
 
 /// Remove a stored key by pubkey
@@ -1120,17 +5054,16 @@ fn remove_stored_key(pubkey: String) -> Result<(), String> {
         return Err(format!("Key not found: {}", pubkey));
     }
 
-    save_keystore(&keystore)?;
-    Ok(())
+    save_keystore_after_removal(&keystore)
 }
 
-/// Clear all stored keys
+/// Clear all stored keys, including the backup copy
 #[tauri::command]
 fn clear_stored_key() -> Result<(), String> {
-    let keystore_path = get_keystore_path()?;
-
-    if keystore_path.exists() {
-        fs::remove_file(&keystore_path).map_err(|e| e.to_string())?;
+    for path in [get_keystore_path()?, get_keystore_backup_path()?] {
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| e.to_string())?;
+        }
     }
 
     Ok(())
@@ -1153,9 +5086,39 @@ fn update_key_label(pubkey: String, label: Option<String>) -> Result<(), String>
     Ok(())
 }
 
+/// Require (or stop requiring) an OS authentication prompt before the app unlocks a
+/// device-mode key. This does not change how the key is encrypted; use password mode
+/// for a key that cannot be decrypted without the user. Either change needs a
+/// successful prompt.
+#[tauri::command]
+async fn set_key_os_auth(pubkey: String, enabled: bool) -> Result<(), String> {
+    let mut keystore = load_keystore()?;
+    let entry = keystore
+        .keys
+        .iter_mut()
+        .find(|k| k.pubkey == pubkey)
+        .ok_or_else(|| format!("Key not found: {}", pubkey))?;
+
+    if entry.mode != "device" {
+        return Err("OS authentication only applies to device-protected keys".to_string());
+    }
+    if entry.require_os_auth == enabled {
+        return Ok(());
+    }
+    if !os_auth::available() {
+        return Err("OS authentication is not available on this device".to_string());
+    }
+    os_auth::verify_user("change how your Nostr key is unlocked").await?;
+
+    entry.require_os_auth = enabled;
+    save_keystore(&keystore)?;
+
+    Ok(())
+}
+
 /// Change key password or protection mode
 #[tauri::command]
-fn change_key_password(
+async fn change_key_password(
     pubkey: String,
     current_password: Option<String>,
     new_password: Option<String>,
@@ -1172,9 +5135,14 @@ fn change_key_password(
     let mut decryption_key = match entry.mode.as_str() {
         "password" => {
             let password = current_password.ok_or("Current password required")?;
-            derive_key_from_password(&password, entry.argon2_salt.as_bytes())?
+            derive_key_from_password(&password, entry.argon2_salt.as_bytes(), &entry.kdf)?
+        }
+        "device" => {
+            if entry.require_os_auth {
+                os_auth::verify_user("change how your Nostr key is protected").await?;
+            }
+            derive_key_from_device()?
         }
-        "device" => derive_key_from_device()?,
         _ => return Err(format!("Unknown storage mode: {}", entry.mode)),
     };
 
@@ -1197,6 +5165,151 @@ fn change_key_password(
     Ok(())
 }
 
+/// Re-wrap an nsec under a passphrase, returning (salt, nonce, ciphertext)
+fn wrap_with_passphrase(nsec: &str, passphrase: &str) -> Result<(String, String, String), String> {
+    let salt = SaltString::generate(&mut rand::thread_rng());
+    let mut key = derive_key_from_password(passphrase, salt.as_str().as_bytes(), &KdfParams::default())?;
+    let (nonce, ciphertext) = encrypt_nsec(nsec, &key)?;
+    key.zeroize();
+    Ok((salt.to_string(), nonce, ciphertext))
+}
+
+/// Export all stored keys to a single passphrase-encrypted backup file.
+/// Device-bound keys are re-wrapped under the passphrase so they can be restored elsewhere.
+#[tauri::command]
+async fn export_keystore_backup(path: String, passphrase: String) -> Result<usize, disk_space::TaskError> {
+    if passphrase.is_empty() {
+        return Err("Passphrase cannot be empty".into());
+    }
+
+    let keystore = load_keystore()?;
+    if keystore.keys.is_empty() {
+        return Err("No stored keys to back up".into());
+    }
+    // Device keys are decrypted for re-wrapping, so gated ones need the OS prompt
+    if keystore.keys.iter().any(|k| k.mode == "device" && k.require_os_auth) {
+        os_auth::verify_user("back up your Nostr keys").await?;
+    }
+
+    let mut payload = KeystoreBackupPayload {
+        keys: Vec::new(),
+        device_keys: Vec::new(),
+    };
+
+    for entry in &keystore.keys {
+        if entry.mode != "device" {
+            payload.keys.push(entry.clone());
+            continue;
+        }
+
+        let mut device_key = derive_key_from_device()?;
+        let mut nsec = decrypt_nsec(&entry.nonce, &entry.ciphertext, &device_key)?;
+        device_key.zeroize();
+
+        let (argon2_salt, nonce, ciphertext) = wrap_with_passphrase(&nsec, &passphrase)?;
+        nsec.zeroize();
+
+        payload.device_keys.push(entry.pubkey.clone());
+        payload.keys.push(StoredKeyEntry {
+            mode: "password".to_string(),
+            nonce,
+            ciphertext,
+            argon2_salt,
+            require_os_auth: false,
+            kdf: KdfParams::default(),
+            ..entry.clone()
+        });
+    }
+
+    let mut payload_json = serde_json::to_string(&payload).map_err(|e| e.to_string())?;
+    let (argon2_salt, nonce, ciphertext) = wrap_with_passphrase(&payload_json, &passphrase)?;
+    payload_json.zeroize();
+
+    let backup = KeystoreBackupFile {
+        format: KEYSTORE_BACKUP_FORMAT.to_string(),
+        version: KEYSTORE_FORMAT_VERSION,
+        created_at: get_current_timestamp()?,
+        argon2_salt,
+        nonce,
+        ciphertext,
+    };
+
+    let backup_path = PathBuf::from(&path);
+    let json = serde_json::to_string_pretty(&backup).map_err(|e| e.to_string())?;
+    disk_space::ensure_space(&backup_path, json.len() as u64)?;
+    fs::write(&backup_path, json).map_err(|e| format!("Failed to write backup: {}", e))?;
+    set_file_permissions(&backup_path)?;
+
+    Ok(payload.keys.len())
+}
+
+/// Restore keys from a backup file, replacing stored keys with the same pubkey.
+/// Keys that were device-bound when exported are re-bound to this device.
+#[tauri::command]
+fn import_keystore_backup(path: String, passphrase: String) -> Result<StoredKeysResponse, String> {
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read backup: {}", e))?;
+    let backup: KeystoreBackupFile =
+        serde_json::from_str(&content).map_err(|_| "Not a valid keystore backup".to_string())?;
+
+    if backup.format != KEYSTORE_BACKUP_FORMAT {
+        return Err("Not a valid keystore backup".to_string());
+    }
+    if backup.version > KEYSTORE_FORMAT_VERSION {
+        return Err(format!(
+            "Backup version {} is newer than this app supports ({})",
+            backup.version, KEYSTORE_FORMAT_VERSION
+        ));
+    }
+
+    let mut backup_key = derive_key_from_password(&passphrase, backup.argon2_salt.as_bytes(), &KdfParams::default())?;
+    let mut payload_json = decrypt_nsec(&backup.nonce, &backup.ciphertext, &backup_key)?;
+    backup_key.zeroize();
+
+    let payload: KeystoreBackupPayload =
+        serde_json::from_str(&payload_json).map_err(|_| "Corrupted keystore backup".to_string())?;
+    payload_json.zeroize();
+
+    let mut keystore = load_keystore()?;
+
+    // Re-binding to this device's key would silently drop the password protection (and
+    // the library unlock it provides) from a key stored here with a password
+    if let Some(existing) = keystore
+        .keys
+        .iter()
+        .find(|k| k.mode == "password" && payload.device_keys.contains(&k.pubkey))
+    {
+        return Err(format!(
+            "Key {} is password-protected here but device-bound in the backup; remove it before restoring",
+            existing.pubkey
+        ));
+    }
+
+    for mut entry in payload.keys {
+        if payload.device_keys.contains(&entry.pubkey) {
+            let mut passphrase_key = derive_key_from_password(&passphrase, entry.argon2_salt.as_bytes(), &entry.kdf)?;
+            let mut nsec = decrypt_nsec(&entry.nonce, &entry.ciphertext, &passphrase_key)?;
+            passphrase_key.zeroize();
+
+            let mut device_key = derive_key_from_device()?;
+            let (nonce, ciphertext) = encrypt_nsec(&nsec, &device_key)?;
+            device_key.zeroize();
+            nsec.zeroize();
+
+            entry.mode = "device".to_string();
+            entry.nonce = nonce;
+            entry.ciphertext = ciphertext;
+            entry.argon2_salt = String::new();
+            entry.kdf = KdfParams::default();
+        }
+
+        keystore.keys.retain(|k| k.pubkey != entry.pubkey);
+        keystore.keys.push(entry);
+    }
+
+    save_keystore(&keystore)?;
+    list_stored_keys()
+}
+
 /// Get the app state directory for persistent key-value storage
 fn get_appstate_dir() -> Result<PathBuf, String> {
     let proj_dirs = ProjectDirs::from("com", "podtards", "msp-studio")
@@ -1216,9 +5329,10 @@ fn save_app_data(key: String, value: String) -> Result<(), String> {
         return Err("Invalid key: must not contain path separators".to_string());
     }
 
+    let _operation = shutdown::begin("app-data-write", &key);
     let appstate_dir = get_appstate_dir()?;
     let file_path = appstate_dir.join(format!("{}.json", key));
-    fs::write(&file_path, value).map_err(|e| e.to_string())?;
+    write_atomic(&file_path, value.as_bytes())?;
 
     Ok(())
 }
@@ -1268,38 +5382,335 @@ fn main() {
         .plugin(tauri_plugin_process::init())
         .manage(NostrState {
             keys: Mutex::new(None),
+            watch_only: Mutex::new(None),
             client: Mutex::new(None),
+            delegation: Mutex::new(None),
+            relay_list: Mutex::new(Vec::new()),
+            blossom_servers: Mutex::new(Vec::new()),
+            subscriptions: Mutex::new(std::collections::HashMap::new()),
+            accounts: Mutex::new(Accounts::default()),
+        })
+        .manage(IntegrityState {
+            last_report: Mutex::new(None),
+        })
+        .setup(|app| {
+            match run_integrity_check() {
+                Ok(report) => {
+                    *app.state::<IntegrityState>().last_report.lock().unwrap() = Some(report);
+                }
+                Err(e) => eprintln!("Startup integrity check failed: {}", e),
+            }
+            if let Err(e) = trash::purge_expired() {
+                eprintln!("Trash cleanup failed: {}", e);
+            }
+            // Scratch files left by a crash or forced quit are never resumed
+            if let Err(e) = workspace::clean_stale_workspaces(&mut Default::default()) {
+                eprintln!("Workspace cleanup failed: {}", e);
+            }
+            spawn_relay_status_monitor(app.handle().clone());
+            spawn_relay_supervisor(app.handle().clone());
+            session_lock::spawn_idle_monitor(app.handle().clone());
+            shutdown::init(app.handle().clone());
+            upload_queue::restore();
+            drop_folder::init(app.handle().clone());
+            Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(session_lock::touch_on_invoke(tauri::generate_handler![
             nostr_login_nsec,
             nostr_login_hex,
+            nostr_login_npub,
             nostr_logout,
+            nostr_list_accounts,
+            active_account,
+            session_lock::session_lock_get_settings,
+            session_lock::session_lock_set_settings,
+            session_lock::session_touch,
+            session_lock::session_lock_now,
             nostr_get_pubkey,
             nostr_sign_event,
             nostr_publish_event,
+            nostr_delete_event,
             nostr_fetch_events,
+            nostr_subscribe,
+            nostr_unsubscribe,
+            nostr_publish_replaceable,
+            nostr_fetch_replaceable,
+            nip19_encode_naddr,
+            nip19_encode_nevent,
+            nip19_encode_nprofile,
+            nip19_decode,
+            nostr_relay_status,
+            key_rotation_plan,
+            key_rotation_execute,
+            identity_bootstrap,
+            nostr_get_relay_list,
+            nostr_publish_relay_list,
+            blossom_get_server_list,
+            blossom_publish_server_list,
+            nostr_fetch_profile,
+            nostr_publish_profile,
+            nostr_verify_nip05,
+            feed_check_ownership,
+            feed_confirm_ownership,
+            nostr_create_delegation,
+            nostr_set_delegation,
+            nostr_get_delegation,
             save_feed_local,
             load_feed_local,
             list_feeds_local,
             delete_feed_local,
+            trash::list_trashed_feeds,
+            trash::restore_feed,
+            trash::purge_trash,
+            trash::get_trash_settings,
+            trash::set_trash_settings,
+            list_feed_versions,
+            restore_feed_version,
             get_feeds_directory,
+            disk_space::disk_space_check,
+            workspace::workspace_clean,
+            get_integrity_report,
+            restore_feed_backup,
+            check_data_integrity,
+            validation::feed_validate,
+            validation::validate_feed_xml,
+            value_block::value_block_build,
+            value_block::value_block_inject,
+            value_block::value_block_validate,
+            batch::feeds_batch,
+            batch::catalog_replace_url,
+            batch::catalog_check_guids,
+            batch::catalog_find_duplicate_assets,
+            batch::catalog_consolidate_asset,
+            podcast_guid::podcast_guid_generate,
+            podcast_guid::podcast_guid_assign,
+            feed_convert::feed_detect_type,
+            feed_convert::feed_convert_to_publisher,
+            feed_model::generate_feed_xml,
+            feed_model::parse_feed_xml,
+            preview::feed_preview_render,
+            audio::extract_audio_metadata,
+            audio::compute_enclosure_info,
+            artwork::artwork_extract_embedded,
+            artwork::artwork_inspect,
+            artwork::process_artwork,
+            alternate_enclosures::alternate_enclosures_attach,
+            transcode::transcode_audio,
+            loudness::analyze_loudness,
+            waveform::waveform_generate,
+            waveform::waveform_get,
+            upload_ledger::lookup_uploaded,
+            upload_queue::upload_queue_enqueue,
+            upload_queue::upload_queue_state,
+            upload_queue::upload_queue_pause,
+            upload_queue::upload_queue_resume,
+            upload_queue::upload_queue_set_concurrency,
+            upload_queue::upload_queue_cancel,
+            upload_queue::upload_queue_clear,
+            asset_audit::audit_feed_assets,
+            asset_audit::repair_feed_assets,
+            blossom_migration::migrate_blossom_server,
+            blossom_benchmark::benchmark_blossom_servers,
+            blossom_download::blossom_download,
+            feed_hosting::s3_publish_get_config,
+            feed_hosting::s3_publish_set_config,
+            feed_hosting::publish_feed_s3,
+            feed_hosting::publish_feed_webdav,
+            server_publish::server_targets_list,
+            server_publish::server_target_save,
+            server_publish::server_target_accept_host_key,
+            server_publish::server_target_delete,
+            server_publish::publish_feed_server,
+            import::import_album_zip,
+            import::import_album_folder,
+            drop_folder::drop_folder_get_settings,
+            drop_folder::drop_folder_set_settings,
+            import::import_feed_from_url,
+            track_csv::feed_import_tracks_csv,
+            track_csv::feed_export_tracks_csv,
+            timeline::track_timeline_check,
+            chapters::chapters_from_tracks,
+            chapters::chapters_from_timestamps,
+            chapters::chapters_attach,
+            transcripts::transcript_validate,
+            transcripts::transcript_attach,
+            preflight::publish_preflight,
+            publish::publish_album,
+            publish::publish_album_status,
+            publish::pipeline_runs_list,
+            publish::pipeline_run_export,
+            notify::notifiers_get,
+            notify::notifiers_set,
+            notify::notify_feed_published,
+            notify::podping_get_settings,
+            notify::podping_set_settings,
+            notify::send_podping,
+            notify::websub_get_settings,
+            notify::websub_ping,
+            notify::websub_set_settings,
+            project::project_create,
+            project::project_list,
+            project::project_open,
+            project::project_save,
+            project::project_export,
+            project::project_import,
+            setup::setup_status,
+            setup::setup_apply,
+            zaps::zap_resolve_lnurl,
+            zaps::zap_create_request,
+            zaps::zap_fetch_receipts,
+            wallet::wallet_connect,
+            wallet::wallet_status,
+            wallet::wallet_disconnect,
+            wallet::wallet_pay_invoice,
+            wallet::wallet_get_balance,
+            wallet::wallet_list_transactions,
+            podcast_index::podcast_index_set_credentials,
+            podcast_index::podcast_index_has_credentials,
+            podcast_index::podcast_index_check_feed,
+            submission::submission_list_directories,
+            submission::submission_prepare,
+            submission::submission_set_status,
+            submission::submission_list,
+            messages::nostr_send_dm,
+            messages::nostr_fetch_dms,
+            relays::nostr_raw_request,
+            relays::relay_info,
+            formatting::format_preview,
+            storage::storage_list_providers,
+            storage::storage_get_feed_target,
+            storage::storage_set_feed_target,
+            storage::storage_upload_file,
+            create_http_auth_header,
             blossom_upload,
             blossom_upload_file,
+            blossom_upload_mirrored,
+            blossom_upload_file_resumable,
+            blossom_has_blob,
+            hash_cache_clear,
             blossom_delete,
             blossom_list,
+            blossom_usage,
             list_stored_keys,
             check_stored_key,
             store_key_with_password,
             store_key_without_password,
+            nostr_generate_key,
+            calibrate_kdf,
+            library_crypto::library_encryption_status,
+            library_crypto::library_enable_encryption,
+            library_crypto::library_unlock,
+            library_crypto::library_lock,
+            library_crypto::library_disable_encryption,
             unlock_stored_key,
             remove_stored_key,
             clear_stored_key,
             update_key_label,
             change_key_password,
+            set_key_os_auth,
+            os_auth::os_auth_available,
+            export_keystore_backup,
+            import_keystore_backup,
             save_app_data,
             load_app_data,
             delete_app_data,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+            shutdown::app_shutdown_status,
+            shutdown::app_shutdown_flushed,
+            shutdown::app_cancel_shutdown,
+            shutdown::app_force_quit,
+        ]))
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| match event {
+            // Hold the close/quit while uploads, publishes, or saves are still running
+            tauri::RunEvent::ExitRequested { api, .. } => {
+                if shutdown::defer_exit() {
+                    api.prevent_exit();
+                }
+            }
+            tauri::RunEvent::WindowEvent {
+                event: tauri::WindowEvent::CloseRequested { api, .. },
+                ..
+            } => {
+                if shutdown::defer_exit() {
+                    api.prevent_close();
+                }
+            }
+            _ => {}
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feed_v0_migrates_to_v1_envelope() {
+        let legacy = r#"{"id":"my-album","title":"My Album","feed_type":"album","xml":"<rss></rss>","created_at":10,"updated_at":20}"#;
+        let feed = parse_feed_record(legacy).unwrap();
+        assert_eq!(feed.id, "my-album");
+        assert_eq!(feed.title, "My Album");
+        assert_eq!(feed.created_at, 10);
+        assert_eq!(feed.updated_at, 20);
+    }
+
+    #[test]
+    fn feed_v0_migration_fills_missing_fields() {
+        let legacy = r#"{"id":"pub","title":"Label","xml":"<podcast:medium>publisher</podcast:medium>"}"#;
+        let feed = parse_feed_record(legacy).unwrap();
+        assert_eq!(feed.feed_type, "publisher");
+        assert_eq!(feed.created_at, 0);
+        assert_eq!(feed.updated_at, 0);
+    }
+
+    #[test]
+    fn feed_current_version_round_trips() {
+        let feed = LocalFeed {
+            id: "x".to_string(),
+            title: "X".to_string(),
+            feed_type: "video".to_string(),
+            xml: "<rss></rss>".to_string(),
+            created_at: 1,
+            updated_at: 2,
+        };
+        let json = serialize_feed_record(&feed).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["version"], FEED_FORMAT_VERSION);
+        let parsed = parse_feed_record(&json).unwrap();
+        assert_eq!(parsed.feed_type, "video");
+    }
+
+    #[test]
+    fn newer_format_version_is_rejected() {
+        let future = r#"{"version":99,"feed":{}}"#;
+        let err = parse_feed_record(future).err().unwrap();
+        assert!(err.contains("Unsupported format version 99"));
+    }
+
+    #[test]
+    fn keystore_v1_migrates_to_v2() {
+        let v1 = serde_json::json!({
+            "version": 1,
+            "mode": "device",
+            "nonce": "n",
+            "ciphertext": "c",
+            "argon2_salt": "",
+            "pubkey": "abc",
+            "created_at": 5
+        });
+        let (value, migrated) = apply_migrations(v1, KEYSTORE_MIGRATIONS, KEYSTORE_FORMAT_VERSION).unwrap();
+        assert!(migrated);
+        let keystore: KeystoreFile = serde_json::from_value(value).unwrap();
+        assert_eq!(keystore.version, 2);
+        assert_eq!(keystore.keys.len(), 1);
+        assert_eq!(keystore.keys[0].pubkey, "abc");
+        assert!(keystore.keys[0].label.is_none());
+    }
+
+    #[test]
+    fn keystore_v2_is_not_migrated() {
+        let v2 = serde_json::json!({ "version": 2, "keys": [] });
+        let (_, migrated) = apply_migrations(v2, KEYSTORE_MIGRATIONS, KEYSTORE_FORMAT_VERSION).unwrap();
+        assert!(!migrated);
+    }
 }
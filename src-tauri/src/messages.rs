@@ -0,0 +1,240 @@
+// Encrypted direct messages so labels can coordinate releases with collaborators.
+// Messages are sent as NIP-17 gift wraps (NIP-44 encrypted); legacy NIP-04 kind 4
+// messages are still read.
+
+use nostr_sdk::nips::nip04;
+use nostr_sdk::nips::nip59::UnwrappedGift;
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+// Legacy NIP-04 direct message
+const LEGACY_DM_KIND: u16 = 4;
+
+// NIP-59 gift wrap carrying a NIP-17 private message
+const GIFT_WRAP_KIND: u16 = 1059;
+
+// NIP-17 chat message inside the seal
+const PRIVATE_MESSAGE_KIND: u16 = 14;
+
+// NIP-17 list of the relays a user wants to receive DMs on
+const DM_RELAY_LIST_KIND: u16 = 10050;
+
+#[derive(Serialize, Deserialize)]
+pub struct DirectMessage {
+    pub id: String, // gift wrap id, or the kind 4 event id
+    pub sender: String,
+    pub recipient: String,
+    pub content: String,
+    pub created_at: u64,
+    pub encryption: String, // "nip44" or "nip04"
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SentMessage {
+    pub id: String, // the recipient's gift wrap id
+    pub own_copy_error: Option<String>, // set when only the recipient's copy went out
+}
+
+fn session(state: &State<'_, crate::NostrState>) -> Result<(Keys, Client), String> {
+    let keys = state.signing_keys()?;
+    let client = state
+        .client
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("Client not initialized")?;
+    Ok((keys, client))
+}
+
+/// Open a gift wrap addressed to us; None if it is not a NIP-17 message
+async fn open_gift_wrap(keys: &Keys, event: &Event) -> Option<DirectMessage> {
+    let unwrapped = UnwrappedGift::from_gift_wrap(keys, event).await.ok()?;
+    let rumor = unwrapped.rumor;
+    if rumor.kind != Kind::from(PRIVATE_MESSAGE_KIND) {
+        return None;
+    }
+    let recipient = rumor.tags.public_keys().next().copied()?;
+    Some(DirectMessage {
+        id: event.id.to_hex(),
+        sender: unwrapped.sender.to_hex(),
+        recipient: recipient.to_hex(),
+        content: rumor.content,
+        created_at: rumor.created_at.as_u64(),
+        encryption: "nip44".to_string(),
+    })
+}
+
+/// Decrypt a legacy kind 4 message we sent or received
+fn open_legacy_dm(keys: &Keys, event: &Event) -> Option<DirectMessage> {
+    let recipient = event.tags.public_keys().next().copied()?;
+    let counterparty = if event.pubkey == keys.public_key() {
+        recipient
+    } else {
+        event.pubkey
+    };
+    let content = nip04::decrypt(keys.secret_key(), &counterparty, &event.content).ok()?;
+    Some(DirectMessage {
+        id: event.id.to_hex(),
+        sender: event.pubkey.to_hex(),
+        recipient: recipient.to_hex(),
+        content,
+        created_at: event.created_at.as_u64(),
+        encryption: "nip04".to_string(),
+    })
+}
+
+/// Relays a pubkey asks to receive DMs on (kind 10050), empty if it has no list
+async fn dm_relays(client: &Client, pubkey: PublicKey) -> Vec<String> {
+    let filter = Filter::new().author(pubkey).kind(Kind::from(DM_RELAY_LIST_KIND));
+    match client
+        .fetch_events(vec![filter], Some(std::time::Duration::from_secs(5)))
+        .await
+    {
+        Ok(events) => events
+            .into_iter()
+            .max_by_key(|e| e.created_at)
+            .map(|e| {
+                e.tags
+                    .iter()
+                    .filter(|tag| tag.as_slice().first().map(String::as_str) == Some("relay"))
+                    .filter_map(|tag| tag.as_slice().get(1).cloned())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// A short-lived client connected to DM relays
+async fn connect_dm_relays(relays: &[String]) -> Client {
+    let client = Client::default();
+    for relay in relays {
+        let _ = client.add_relay(relay.as_str()).await;
+    }
+    client.connect().await;
+    client
+}
+
+/// Deliver a gift wrap to the pubkey's DM relays, or through the session's relays
+/// when it has none. DM relays get their own short-lived connection so they are not
+/// added to the session.
+async fn send_to_dm_relays(client: &Client, pubkey: PublicKey, event: Event) -> Result<EventId, String> {
+    let relays = dm_relays(client, pubkey).await;
+    if relays.is_empty() {
+        return client.send_event(event).await.map(|output| output.val).map_err(|e| e.to_string());
+    }
+
+    let delivery = connect_dm_relays(&relays).await;
+    let sent = delivery.send_event(event).await;
+    let _ = delivery.disconnect().await;
+    sent.map(|output| output.val).map_err(|e| e.to_string())
+}
+
+/// Send an encrypted direct message (NIP-17) to the recipient's DM relays. A copy is
+/// wrapped for ourselves so the conversation shows sent messages. Once the recipient's
+/// copy is out, failing to store our own copy is reported rather than returned as an
+/// error, so the message isn't sent twice on retry.
+#[tauri::command]
+pub async fn nostr_send_dm(
+    recipient: String,
+    message: String,
+    state: State<'_, crate::NostrState>,
+) -> Result<SentMessage, String> {
+    let (keys, client) = session(&state)?;
+    let recipient = PublicKey::parse(recipient.trim()).map_err(|_| "Invalid recipient pubkey")?;
+    if message.trim().is_empty() {
+        return Err("Message cannot be empty".to_string());
+    }
+
+    let rumor = EventBuilder::new(Kind::from(PRIVATE_MESSAGE_KIND), &message)
+        .tag(Tag::public_key(recipient))
+        .build(keys.public_key());
+    let wrapped = EventBuilder::gift_wrap(&keys, &recipient, rumor.clone(), [])
+        .await
+        .map_err(|e| e.to_string())?;
+    let wrap_id = send_to_dm_relays(&client, recipient, wrapped).await?;
+
+    let mut own_copy_error = None;
+    if recipient != keys.public_key() {
+        let sent = match EventBuilder::gift_wrap(&keys, &keys.public_key(), rumor, []).await {
+            Ok(own_copy) => send_to_dm_relays(&client, keys.public_key(), own_copy).await.map(|_| ()),
+            Err(e) => Err(e.to_string()),
+        };
+        own_copy_error = sent.err();
+    }
+
+    Ok(SentMessage {
+        id: wrap_id.to_hex(),
+        own_copy_error,
+    })
+}
+
+/// Fetch direct messages sent to or by us, oldest first, optionally limited to one
+/// counterparty and to messages after `since`
+#[tauri::command]
+pub async fn nostr_fetch_dms(
+    with: Option<String>,
+    since: Option<u64>,
+    limit: Option<usize>,
+    state: State<'_, crate::NostrState>,
+) -> Result<Vec<DirectMessage>, String> {
+    let (keys, client) = session(&state)?;
+    let me = keys.public_key();
+    let with = with
+        .map(|w| PublicKey::parse(w.trim()).map_err(|_| "Invalid pubkey".to_string()))
+        .transpose()?;
+
+    let to_me = crate::EventFilterSpec {
+        kinds: Some(vec![GIFT_WRAP_KIND, LEGACY_DM_KIND]),
+        tags: std::collections::HashMap::from([("p".to_string(), vec![me.to_hex()])]),
+        limit,
+        ..Default::default()
+    };
+    let from_me = crate::EventFilterSpec {
+        kinds: Some(vec![LEGACY_DM_KIND]),
+        authors: Some(vec![me.to_hex()]),
+        since,
+        limit,
+        ..Default::default()
+    };
+    // Gift wraps carry randomized timestamps, so `since` is applied after unwrapping
+    let mut events: Vec<Event> = client
+        .fetch_events(vec![to_me.to_filter()?, from_me.to_filter()?], None)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .collect();
+
+    // Gift wraps are delivered to our own DM relays when we have a list
+    let inbox_relays = dm_relays(&client, me).await;
+    if !inbox_relays.is_empty() {
+        let inbox = connect_dm_relays(&inbox_relays).await;
+        if let Ok(found) = inbox
+            .fetch_events(vec![to_me.to_filter()?], Some(std::time::Duration::from_secs(10)))
+            .await
+        {
+            events.extend(found);
+        }
+        let _ = inbox.disconnect().await;
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    let mut messages = Vec::new();
+    for event in events.iter().filter(|e| seen.insert(e.id)) {
+        let message = if event.kind == Kind::from(GIFT_WRAP_KIND) {
+            open_gift_wrap(&keys, event).await
+        } else {
+            open_legacy_dm(&keys, event)
+        };
+        messages.extend(message);
+    }
+
+    messages.retain(|m| {
+        let counterparty = if m.sender == me.to_hex() { &m.recipient } else { &m.sender };
+        since.map(|s| m.created_at >= s).unwrap_or(true)
+            && with.map(|w| *counterparty == w.to_hex()).unwrap_or(true)
+    });
+    messages.sort_by_key(|m| m.created_at);
+    Ok(messages)
+}
@@ -0,0 +1,681 @@
+// Post-publish notifications behind a common Notifier trait: Podping, WebSub hubs,
+// webhooks, and Nostr announcements. Each feed keeps its own list of enabled
+// notifiers (in the encrypted settings store, since they hold tokens and webhook
+// secrets), and a Podping account and WebSub hubs can be set to ping after every
+// publish; running them produces one combined report.
+
+use crate::feed_xml::parse_rss;
+use crate::storage::hmac_sha256;
+use futures_util::future::BoxFuture;
+use nostr_sdk::prelude::{Event, Kind};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::State;
+
+// Podping relay used when a notifier does not name its own
+const DEFAULT_PODPING_ENDPOINT: &str = "https://podping.cloud/";
+
+// Medium pinged for feeds without a <podcast:medium>; MSP feeds are music releases
+const DEFAULT_PODPING_MEDIUM: &str = "music";
+
+// Secure settings entry holding the Podping account
+const PODPING_SETTINGS: &str = "podping_settings";
+
+// Reasons and mediums accepted by Podping (the "L" mediums are live streams)
+const PODPING_REASONS: &[&str] = &["update", "live", "liveEnd"];
+const PODPING_MEDIUMS: &[&str] = &[
+    "podcast", "music", "video", "film", "audiobook", "newsletter", "blog", "publisher", "course",
+    "mixed", "podcastL", "musicL", "videoL", "filmL", "audiobookL", "newsletterL", "blogL",
+    "publisherL", "courseL", "mixedL",
+];
+
+// Kind 1 text note used for release announcements
+const ANNOUNCEMENT_KIND: u16 = 1;
+
+// Announcement text when a Nostr notifier has no template
+const DEFAULT_ANNOUNCEMENT: &str = "New release: {title} {url}";
+
+// Secure settings entry holding every feed's notifiers
+const FEED_NOTIFIERS: &str = "feed_notifiers";
+
+// Attempts per notifier, and the delay before the first retry (doubled after each)
+const NOTIFY_ATTEMPTS: u32 = 3;
+const NOTIFY_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "notifier", rename_all = "lowercase")]
+pub enum NotifierConfig {
+    Podping {
+        token: String,
+        endpoint: Option<String>,
+    },
+    Websub {
+        hub_url: String,
+    },
+    Webhook {
+        url: String,
+        secret: Option<String>,
+    },
+    Nostr {
+        template: Option<String>, // "{title}" and "{url}" are filled in
+    },
+}
+
+impl NotifierConfig {
+    /// Identifies the notifier within a feed's list, so a resumed publish can tell
+    /// which notifiers already ran
+    pub fn key(&self) -> String {
+        match self {
+            NotifierConfig::Podping { endpoint, .. } => {
+                format!("podping:{}", endpoint.as_deref().unwrap_or(DEFAULT_PODPING_ENDPOINT))
+            }
+            NotifierConfig::Websub { hub_url } => format!("websub:{}", hub_url),
+            NotifierConfig::Webhook { url, .. } => format!("webhook:{}", url),
+            NotifierConfig::Nostr { .. } => "nostr".to_string(),
+        }
+    }
+
+    /// A copy safe to hand to the frontend: the Podping token and webhook secret are blanked
+    pub fn without_secrets(&self) -> NotifierConfig {
+        let mut config = self.clone();
+        match &mut config {
+            NotifierConfig::Podping { token, .. } => token.clear(),
+            NotifierConfig::Webhook { secret, .. } => *secret = None,
+            NotifierConfig::Websub { .. } | NotifierConfig::Nostr { .. } => {}
+        }
+        config
+    }
+
+    /// Fill blank secrets from the saved notifier with the same key, so a redacted
+    /// list sent back by the frontend keeps them
+    fn keep_secrets_from(&mut self, saved: &[NotifierConfig]) {
+        let key = self.key();
+        let Some(saved) = saved.iter().find(|s| s.key() == key) else {
+            return;
+        };
+        match (self, saved) {
+            (NotifierConfig::Podping { token, .. }, NotifierConfig::Podping { token: saved_token, .. })
+                if token.is_empty() =>
+            {
+                token.clone_from(saved_token)
+            }
+            (NotifierConfig::Webhook { secret, .. }, NotifierConfig::Webhook { secret: saved_secret, .. })
+                if secret.is_none() =>
+            {
+                secret.clone_from(saved_secret)
+            }
+            _ => {}
+        }
+    }
+}
+
+// Podping account used by `send_podping` and the automatic ping after publishing
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct PodpingSettings {
+    pub token: Option<String>, // never sent to the frontend; None when saving keeps the saved one
+    pub endpoint: Option<String>,
+    pub auto_ping: bool, // ping after every publish for feeds without their own Podping notifier
+    pub has_token: bool, // set when reading the settings
+}
+
+// WebSub hubs pinged by the automatic ping after publishing
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct WebsubSettings {
+    pub hubs: Vec<String>,
+    pub auto_ping: bool, // ping these hubs after every publish, besides the feed's own
+}
+
+/// What was published, as handed to every notifier
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PublishNotice {
+    pub feed_id: String,
+    pub title: String,
+    pub feed_url: Option<String>,
+    pub medium: Option<String>,
+    pub podcast_guid: Option<String>,
+    pub feed_event_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct NotificationResult {
+    pub notifier: String,
+    #[serde(default)]
+    pub key: String, // NotifierConfig::key of the notifier that ran
+    pub ok: bool,
+    pub detail: String, // event id, hub response, or the error
+    #[serde(default)]
+    pub attempts: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct NotificationReport {
+    pub feed_id: String,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<NotificationResult>,
+}
+
+/// Why a notifier failed, which decides whether it is worth trying again
+#[derive(Debug)]
+pub enum NotifyError {
+    Transport(String),                   // no response, or no relay took the announcement
+    Status(reqwest::StatusCode, String), // the endpoint answered with an error status
+    Other(String),                       // bad settings, signing, and relay auth problems
+}
+
+impl NotifyError {
+    /// Network failures and 5xx responses are retried; a 4xx will only be refused again
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            NotifyError::Transport(_) => true,
+            NotifyError::Status(status, _) => status.is_server_error(),
+            NotifyError::Other(_) => false,
+        }
+    }
+}
+
+impl std::fmt::Display for NotifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotifyError::Transport(message) | NotifyError::Status(_, message) | NotifyError::Other(message) => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl From<String> for NotifyError {
+    fn from(message: String) -> Self {
+        NotifyError::Other(message)
+    }
+}
+
+impl From<NotifyError> for String {
+    fn from(error: NotifyError) -> Self {
+        error.to_string()
+    }
+}
+
+/// A publish notification channel. `notify` returns a short detail on success.
+pub trait Notifier: Send + Sync {
+    fn id(&self) -> &'static str;
+    fn notify<'a>(
+        &'a self,
+        notice: &'a PublishNotice,
+        state: State<'a, crate::NostrState>,
+    ) -> BoxFuture<'a, Result<String, NotifyError>>;
+}
+
+/// Build the notifier for a config
+pub fn notifier_for(config: &NotifierConfig) -> Box<dyn Notifier> {
+    match config.clone() {
+        NotifierConfig::Podping { token, endpoint } => Box::new(PodpingNotifier {
+            token,
+            endpoint: endpoint.unwrap_or_else(|| DEFAULT_PODPING_ENDPOINT.to_string()),
+        }),
+        NotifierConfig::Websub { hub_url } => Box::new(WebsubNotifier { hub_url }),
+        NotifierConfig::Webhook { url, secret } => Box::new(WebhookNotifier { url, secret }),
+        NotifierConfig::Nostr { template } => Box::new(NostrNotifier {
+            template: template.unwrap_or_else(|| DEFAULT_ANNOUNCEMENT.to_string()),
+            signed: Mutex::new(None),
+        }),
+    }
+}
+
+fn require_feed_url(notice: &PublishNotice) -> Result<&str, String> {
+    notice
+        .feed_url
+        .as_deref()
+        .ok_or_else(|| "Feed has no public URL (<atom:link rel=\"self\">)".to_string())
+}
+
+/// Turn a response into a detail string, or an error with the server's message
+async fn response_detail(response: reqwest::Response, host: &str) -> Result<String, NotifyError> {
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    if status.is_success() {
+        Ok(format!("{} {}", host, status))
+    } else {
+        Err(NotifyError::Status(status, format!("{} error {}: {}", host, status, text)))
+    }
+}
+
+/// Send one Podping through a podping.cloud-compatible endpoint
+async fn podping(
+    endpoint: &str,
+    token: &str,
+    feed_url: &str,
+    reason: &str,
+    medium: &str,
+) -> Result<String, NotifyError> {
+    if !PODPING_REASONS.contains(&reason) {
+        return Err(format!("Unknown Podping reason: {}", reason).into());
+    }
+    if !PODPING_MEDIUMS.contains(&medium) {
+        return Err(format!("Unknown Podping medium: {}", medium).into());
+    }
+    let response = reqwest::Client::new()
+        .get(endpoint)
+        .header("Authorization", token)
+        .header("User-Agent", format!("MSP 2.0 desktop {}", crate::APP_VERSION))
+        .query(&[("url", feed_url), ("reason", reason), ("medium", medium)])
+        .send()
+        .await
+        .map_err(|e| NotifyError::Transport(format!("Podping failed: {}", e)))?;
+    response_detail(response, "Podping").await
+}
+
+struct PodpingNotifier {
+    token: String,
+    endpoint: String,
+}
+
+impl Notifier for PodpingNotifier {
+    fn id(&self) -> &'static str {
+        "podping"
+    }
+
+    fn notify<'a>(
+        &'a self,
+        notice: &'a PublishNotice,
+        _state: State<'a, crate::NostrState>,
+    ) -> BoxFuture<'a, Result<String, NotifyError>> {
+        Box::pin(async move {
+            let feed_url = require_feed_url(notice)?;
+            let medium = notice.medium.as_deref().unwrap_or(DEFAULT_PODPING_MEDIUM);
+            podping(&self.endpoint, &self.token, feed_url, "update", medium).await
+        })
+    }
+}
+
+/// Tell a WebSub (PubSubHubbub) hub that a feed changed
+async fn websub_publish(hub_url: &str, feed_url: &str) -> Result<String, NotifyError> {
+    let response = reqwest::Client::new()
+        .post(hub_url)
+        .form(&[("hub.mode", "publish"), ("hub.url", feed_url)])
+        .send()
+        .await
+        .map_err(|e| NotifyError::Transport(format!("WebSub ping failed: {}", e)))?;
+    response_detail(response, "WebSub hub").await
+}
+
+struct WebsubNotifier {
+    hub_url: String,
+}
+
+impl Notifier for WebsubNotifier {
+    fn id(&self) -> &'static str {
+        "websub"
+    }
+
+    fn notify<'a>(
+        &'a self,
+        notice: &'a PublishNotice,
+        _state: State<'a, crate::NostrState>,
+    ) -> BoxFuture<'a, Result<String, NotifyError>> {
+        Box::pin(async move {
+            let feed_url = require_feed_url(notice)?;
+            websub_publish(&self.hub_url, feed_url).await
+        })
+    }
+}
+
+struct WebhookNotifier {
+    url: String,
+    secret: Option<String>,
+}
+
+impl Notifier for WebhookNotifier {
+    fn id(&self) -> &'static str {
+        "webhook"
+    }
+
+    /// POSTs the notice as JSON, signed with HMAC-SHA256 when a secret is set
+    fn notify<'a>(
+        &'a self,
+        notice: &'a PublishNotice,
+        _state: State<'a, crate::NostrState>,
+    ) -> BoxFuture<'a, Result<String, NotifyError>> {
+        Box::pin(async move {
+            let body = serde_json::to_vec(notice).map_err(|e| e.to_string())?;
+            let mut request = reqwest::Client::new()
+                .post(&self.url)
+                .header("Content-Type", "application/json");
+            if let Some(secret) = &self.secret {
+                let signature = hex::encode(hmac_sha256(secret.as_bytes(), &body));
+                request = request.header("X-MSP-Signature", format!("sha256={}", signature));
+            }
+            let response = request
+                .body(body)
+                .send()
+                .await
+                .map_err(|e| NotifyError::Transport(format!("Webhook failed: {}", e)))?;
+            response_detail(response, "Webhook").await
+        })
+    }
+}
+
+struct NostrNotifier {
+    template: String,
+    signed: Mutex<Option<Event>>, // the announcement, signed once so retries resend the same event
+}
+
+impl Notifier for NostrNotifier {
+    fn id(&self) -> &'static str {
+        "nostr"
+    }
+
+    /// Posts a kind 1 announcement; the detail is the event id
+    fn notify<'a>(
+        &'a self,
+        notice: &'a PublishNotice,
+        state: State<'a, crate::NostrState>,
+    ) -> BoxFuture<'a, Result<String, NotifyError>> {
+        Box::pin(async move {
+            let signed = self.signed.lock().unwrap().clone();
+            let event = match signed {
+                Some(event) => event,
+                None => {
+                    let text = self
+                        .template
+                        .replace("{title}", &notice.title)
+                        .replace("{url}", notice.feed_url.as_deref().unwrap_or(""));
+                    let keys = state.signing_keys()?;
+                    let kind = Kind::from(ANNOUNCEMENT_KIND);
+                    let event = crate::sign_app_event(kind, text.trim(), &[], &keys, &state, None).await?;
+                    *self.signed.lock().unwrap() = Some(event.clone());
+                    event
+                }
+            };
+
+            let client = state.client.lock().unwrap().clone().ok_or("Client not initialized".to_string())?;
+            let published = crate::send_event_with_results(&client, event)
+                .await
+                .map_err(NotifyError::Transport)?;
+            published.require_accepted().map_err(|e| {
+                // A relay that refused our key will refuse the same event again
+                if published.relays.iter().any(|r| r.auth_failed) {
+                    NotifyError::Other(e)
+                } else {
+                    NotifyError::Transport(e)
+                }
+            })?;
+            Ok(published.event_id)
+        })
+    }
+}
+
+/// Describe a library feed for notifiers
+pub fn publish_notice(feed_id: &str, feed_event_id: Option<String>) -> Result<PublishNotice, String> {
+    let feed = crate::load_feed_local(feed_id.to_string())?;
+    let doc = parse_rss(&feed.xml)?;
+    let channel = doc.channel();
+    let feed_url = channel
+        .and_then(|c| {
+            c.children_named("atom:link")
+                .find(|l| l.attr("rel") == Some("self"))
+                .and_then(|l| l.attr("href"))
+        })
+        .map(str::to_string);
+    Ok(PublishNotice {
+        feed_id: feed.id,
+        title: feed.title,
+        feed_url,
+        medium: channel.and_then(|c| c.child_text("podcast:medium")).map(str::to_string),
+        podcast_guid: channel.and_then(|c| c.child_text("podcast:guid")).map(str::to_string),
+        feed_event_id,
+    })
+}
+
+/// Run one notifier, retrying with a growing delay when it fails in a way that may
+/// pass next time (see `NotifyError::is_retryable`)
+pub async fn run_notifier(
+    config: &NotifierConfig,
+    notice: &PublishNotice,
+    state: State<'_, crate::NostrState>,
+) -> NotificationResult {
+    let notifier = notifier_for(config);
+    let mut delay = NOTIFY_RETRY_DELAY;
+    let mut attempts = 0;
+    let outcome = loop {
+        attempts += 1;
+        match notifier.notify(notice, state.clone()).await {
+            Err(e) if e.is_retryable() && attempts < NOTIFY_ATTEMPTS => {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            outcome => break outcome,
+        }
+    };
+    NotificationResult {
+        notifier: notifier.id().to_string(),
+        key: config.key(),
+        ok: outcome.is_ok(),
+        detail: outcome.unwrap_or_else(|e| e.to_string()),
+        attempts,
+    }
+}
+
+/// Combine notifier outcomes into a report
+pub fn notification_report(feed_id: &str, results: Vec<NotificationResult>) -> NotificationReport {
+    let succeeded = results.iter().filter(|r| r.ok).count();
+    NotificationReport {
+        feed_id: feed_id.to_string(),
+        succeeded,
+        failed: results.len() - succeeded,
+        results,
+    }
+}
+
+/// Run notifiers one after another, collecting every outcome (a failure does not
+/// stop the rest)
+pub async fn run_notifiers(
+    notifiers: &[NotifierConfig],
+    notice: &PublishNotice,
+    state: State<'_, crate::NostrState>,
+) -> NotificationReport {
+    let mut results = Vec::new();
+    for config in notifiers {
+        results.push(run_notifier(config, notice, state.clone()).await);
+    }
+    notification_report(&notice.feed_id, results)
+}
+
+/// Path of the per-feed notifier settings from before they were encrypted
+fn get_legacy_notifiers_path() -> Result<PathBuf, String> {
+    Ok(crate::get_appstate_dir()?.join("notifiers.json"))
+}
+
+fn load_all_notifiers() -> Result<HashMap<String, Vec<NotifierConfig>>, String> {
+    if let Some(notifiers) = crate::secure_settings::get(FEED_NOTIFIERS)? {
+        return Ok(notifiers);
+    }
+
+    // Move notifiers saved in plaintext into the encrypted store
+    let path = get_legacy_notifiers_path()?;
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let notifiers: HashMap<String, Vec<NotifierConfig>> = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    crate::secure_settings::put(FEED_NOTIFIERS, &notifiers)?;
+    fs::remove_file(&path).map_err(|e| e.to_string())?;
+    Ok(notifiers)
+}
+
+/// Notifiers enabled for a feed, with their secrets
+pub fn feed_notifiers(feed_id: &str) -> Result<Vec<NotifierConfig>, String> {
+    Ok(load_all_notifiers()?.remove(feed_id).unwrap_or_default())
+}
+
+/// Notifiers to run after a feed is published: its own, plus the automatic Podping
+/// when that is on and the feed has no Podping notifier of its own, plus any
+/// automatically pinged WebSub hubs the feed does not already ping
+pub fn publish_notifiers(feed_id: &str) -> Result<Vec<NotifierConfig>, String> {
+    let mut notifiers = feed_notifiers(feed_id)?;
+    let podping = load_podping_settings();
+    if let (true, Some(token)) = (podping.auto_ping, podping.token) {
+        if !notifiers.iter().any(|n| matches!(n, NotifierConfig::Podping { .. })) {
+            notifiers.push(NotifierConfig::Podping {
+                token,
+                endpoint: podping.endpoint,
+            });
+        }
+    }
+    let websub = load_websub_settings();
+    if websub.auto_ping {
+        for hub_url in websub.hubs {
+            let listed = notifiers
+                .iter()
+                .any(|n| matches!(n, NotifierConfig::Websub { hub_url: url } if *url == hub_url));
+            if !listed {
+                notifiers.push(NotifierConfig::Websub { hub_url });
+            }
+        }
+    }
+    Ok(notifiers)
+}
+
+/// Path of the Podping settings from before they were encrypted
+fn get_legacy_podping_settings_path() -> Result<PathBuf, String> {
+    Ok(crate::get_appstate_dir()?.join("podping.json"))
+}
+
+fn load_podping_settings() -> PodpingSettings {
+    if let Ok(Some(settings)) = crate::secure_settings::get(PODPING_SETTINGS) {
+        return settings;
+    }
+
+    // Move a token saved in plaintext into the encrypted store
+    let Some(path) = get_legacy_podping_settings_path().ok().filter(|p| p.exists()) else {
+        return PodpingSettings::default();
+    };
+    let settings: PodpingSettings = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    if crate::secure_settings::put(PODPING_SETTINGS, &settings).is_ok() {
+        let _ = fs::remove_file(&path);
+    }
+    settings
+}
+
+/// Get the Podping account settings; the token itself is not returned
+#[tauri::command]
+pub fn podping_get_settings() -> PodpingSettings {
+    let settings = load_podping_settings();
+    PodpingSettings {
+        has_token: settings.token.as_deref().is_some_and(|t| !t.trim().is_empty()),
+        token: None,
+        ..settings
+    }
+}
+
+/// Set the Podping token and endpoint, and whether to ping after every publish. No
+/// token keeps the saved one; an empty token removes it.
+#[tauri::command]
+pub fn podping_set_settings(mut settings: PodpingSettings) -> Result<(), String> {
+    match settings.token.as_deref().map(str::trim) {
+        None => settings.token = load_podping_settings().token,
+        Some("") => settings.token = None,
+        Some(token) => settings.token = Some(token.to_string()),
+    }
+    if settings.auto_ping && settings.token.is_none() {
+        return Err("A Podping token is required to ping after publishing".to_string());
+    }
+    settings.has_token = false;
+    crate::secure_settings::put(PODPING_SETTINGS, &settings)
+}
+
+/// Notify aggregators through Podping that a feed changed. `reason` is "update",
+/// "live" or "liveEnd"; `medium` defaults to "music".
+#[tauri::command]
+pub async fn send_podping(feed_url: String, reason: Option<String>, medium: Option<String>) -> Result<String, String> {
+    let settings = load_podping_settings();
+    let token = settings.token.ok_or("Podping token is not set")?;
+    let endpoint = settings.endpoint.unwrap_or_else(|| DEFAULT_PODPING_ENDPOINT.to_string());
+    podping(
+        &endpoint,
+        &token,
+        &feed_url,
+        reason.as_deref().unwrap_or("update"),
+        medium.as_deref().unwrap_or(DEFAULT_PODPING_MEDIUM),
+    )
+    .await
+    .map_err(String::from)
+}
+
+fn get_websub_settings_path() -> Result<PathBuf, String> {
+    Ok(crate::get_appstate_dir()?.join("websub.json"))
+}
+
+fn load_websub_settings() -> WebsubSettings {
+    get_websub_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Get the configured WebSub hubs
+#[tauri::command]
+pub fn websub_get_settings() -> WebsubSettings {
+    load_websub_settings()
+}
+
+/// Set the WebSub hubs, and whether to ping them after every publish
+#[tauri::command]
+pub fn websub_set_settings(settings: WebsubSettings) -> Result<(), String> {
+    if let Some(hub) = settings.hubs.iter().find(|h| !h.starts_with("https://") && !h.starts_with("http://")) {
+        return Err(format!("Invalid hub URL: {}", hub));
+    }
+    let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    fs::write(get_websub_settings_path()?, json).map_err(|e| e.to_string())
+}
+
+/// Tell a WebSub hub that a feed changed
+#[tauri::command]
+pub async fn websub_ping(hub_url: String, feed_url: String) -> Result<String, String> {
+    websub_publish(&hub_url, &feed_url).await.map_err(String::from)
+}
+
+/// Get the notifiers enabled for a feed, with tokens and secrets blanked
+#[tauri::command]
+pub fn notifiers_get(feed_id: String) -> Result<Vec<NotifierConfig>, String> {
+    Ok(feed_notifiers(&feed_id)?.iter().map(NotifierConfig::without_secrets).collect())
+}
+
+/// Set the notifiers run after a feed is published (empty disables them all). A
+/// blank token or secret keeps the saved one.
+#[tauri::command]
+pub fn notifiers_set(feed_id: String, mut notifiers: Vec<NotifierConfig>) -> Result<(), String> {
+    let mut all = load_all_notifiers()?;
+    if notifiers.is_empty() {
+        all.remove(&feed_id);
+    } else {
+        let saved = all.remove(&feed_id).unwrap_or_default();
+        for notifier in &mut notifiers {
+            notifier.keep_secrets_from(&saved);
+        }
+        all.insert(feed_id, notifiers);
+    }
+    crate::secure_settings::put(FEED_NOTIFIERS, &all)
+}
+
+/// Run a feed's enabled notifiers now, e.g. after publishing outside the album pipeline
+#[tauri::command]
+pub async fn notify_feed_published(
+    feed_id: String,
+    feed_event_id: Option<String>,
+    state: State<'_, crate::NostrState>,
+) -> Result<NotificationReport, String> {
+    let notice = publish_notice(&feed_id, feed_event_id)?;
+    let notifiers = publish_notifiers(&feed_id)?;
+    Ok(run_notifiers(&notifiers, &notice, state).await)
+}
@@ -0,0 +1,76 @@
+// OS user verification (Touch ID on macOS, Windows Hello on Windows). Device-mode
+// keys decrypt without a password, so keys that opt in ask for this prompt before the
+// app unlocks, exports, or re-protects them. It is a confirmation step in the app,
+// not encryption: the device key does not depend on the prompt, so anything that can
+// read the keystore and run as the user can still decrypt the key. Users who need
+// that protection should use password mode.
+
+/// Whether this platform can show an OS authentication prompt
+pub fn available() -> bool {
+    platform::available()
+}
+
+/// Show the OS authentication prompt, failing if the user cancels or fails it
+pub async fn verify_user(reason: &str) -> Result<(), String> {
+    let reason = reason.to_string();
+    let verified = tokio::task::spawn_blocking(move || platform::authenticate(&reason))
+        .await
+        .map_err(|e| e.to_string())??;
+    if verified {
+        Ok(())
+    } else {
+        Err("OS authentication failed or was cancelled".to_string())
+    }
+}
+
+/// Whether OS authentication (Touch ID / Windows Hello) can gate stored keys here
+#[tauri::command]
+pub fn os_auth_available() -> bool {
+    available()
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use localauthentication_rs::{LAPolicy, LocalAuthentication};
+
+    // Biometrics with the account password as fallback, so Macs without Touch ID work
+    pub fn available() -> bool {
+        true
+    }
+
+    pub fn authenticate(reason: &str) -> Result<bool, String> {
+        Ok(LocalAuthentication::new().evaluate_policy(LAPolicy::DeviceOwnerAuthentication, reason))
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use windows::core::HSTRING;
+    use windows::Security::Credentials::UI::{
+        UserConsentVerificationResult, UserConsentVerifier, UserConsentVerifierAvailability,
+    };
+
+    pub fn available() -> bool {
+        UserConsentVerifier::CheckAvailabilityAsync()
+            .and_then(|op| op.get())
+            .is_ok_and(|availability| availability == UserConsentVerifierAvailability::Available)
+    }
+
+    pub fn authenticate(reason: &str) -> Result<bool, String> {
+        let result = UserConsentVerifier::RequestVerificationAsync(&HSTRING::from(reason))
+            .and_then(|op| op.get())
+            .map_err(|e| format!("Windows Hello failed: {}", e))?;
+        Ok(result == UserConsentVerificationResult::Verified)
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+mod platform {
+    pub fn available() -> bool {
+        false
+    }
+
+    pub fn authenticate(_reason: &str) -> Result<bool, String> {
+        Err("OS authentication is not supported on this platform".to_string())
+    }
+}
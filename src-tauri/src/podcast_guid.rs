@@ -0,0 +1,116 @@
+// podcast:guid handling: derive the GUID from the feed URL the way the podcast
+// namespace specifies (UUIDv5), remember the GUID each library feed was first saved
+// with, and warn when an edit would change it. Apps key subscriptions on the GUID,
+// so a changed GUID looks like a different show.
+
+use crate::feed_xml::{parse_rss, render_document};
+use crate::validation::Issues;
+use uuid::Uuid;
+
+// Namespace UUID defined by the podcast namespace for podcast:guid
+const PODCAST_GUID_NAMESPACE: Uuid = uuid::uuid!("ead4c236-bf58-58c6-a2c6-a6b28d128cb6");
+
+/// The podcast:guid for a feed URL: UUIDv5 of the URL without its scheme and
+/// trailing slashes
+pub fn guid_for_url(feed_url: &str) -> String {
+    let url = feed_url.trim();
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    Uuid::new_v5(&PODCAST_GUID_NAMESPACE, url.trim_end_matches('/').as_bytes()).to_string()
+}
+
+/// The channel's <podcast:guid>, if the XML has one
+pub fn xml_guid(xml: &str) -> Option<String> {
+    parse_rss(xml)
+        .ok()?
+        .channel()?
+        .child_text("podcast:guid")
+        .map(str::to_string)
+}
+
+/// The GUID a library feed is pinned to
+pub fn pinned_guid(conn: &rusqlite::Connection, feed_id: &str) -> Result<Option<String>, String> {
+    match conn.query_row("SELECT podcast_guid FROM feeds WHERE id = ?1", [feed_id], |row| row.get(0)) {
+        Ok(guid) => Ok(guid),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Pin feeds saved before GUIDs were tracked to the GUID in their stored XML, so the
+/// first edit after upgrading is checked against what was published. Feeds whose XML
+/// can't be read right now are pinned on their next save instead.
+pub fn backfill_pinned_guids(conn: &rusqlite::Connection) -> Result<(), String> {
+    for table in ["feeds", "trashed_feeds"] {
+        let mut stmt = conn
+            .prepare(&format!("SELECT id, xml FROM {} WHERE podcast_guid IS NULL", table))
+            .map_err(|e| e.to_string())?;
+        let feeds: Vec<(String, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        for (id, stored) in feeds {
+            let Some(guid) = crate::library_crypto::open(stored).ok().and_then(|xml| xml_guid(&xml)) else {
+                continue;
+            };
+            conn.execute(
+                &format!("UPDATE {} SET podcast_guid = ?2 WHERE id = ?1", table),
+                rusqlite::params![id, guid],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Warn when feed XML no longer carries the GUID its library feed is pinned to
+pub fn check_guid_stability(feed_id: &str, xml: &str, issues: &mut Issues) -> Result<(), String> {
+    let conn = crate::open_library()?;
+    let Some(pinned) = pinned_guid(&conn, feed_id)? else {
+        return Ok(());
+    };
+    match xml_guid(xml) {
+        Some(guid) if guid.eq_ignore_ascii_case(&pinned) => {}
+        Some(guid) => issues.warning(
+            "podcast-guid-changed",
+            format!(
+                "<podcast:guid> changed from {} to {}; apps will treat this as a new feed and listeners lose their subscription",
+                pinned, guid
+            ),
+        ),
+        None => issues.warning(
+            "podcast-guid-changed",
+            format!("<podcast:guid> {} was removed; apps will no longer recognize this feed", pinned),
+        ),
+    }
+    Ok(())
+}
+
+/// Generate the podcast:guid for a feed URL
+#[tauri::command]
+pub fn podcast_guid_generate(feed_url: String) -> Result<String, String> {
+    if feed_url.trim().is_empty() {
+        return Err("Feed URL is empty".to_string());
+    }
+    Ok(guid_for_url(&feed_url))
+}
+
+/// Set a library feed's <podcast:guid> from its feed URL and pin the feed to it.
+/// This is the one place a pinned GUID is replaced on purpose.
+#[tauri::command]
+pub fn podcast_guid_assign(feed_id: String, feed_url: String) -> Result<String, String> {
+    let guid = podcast_guid_generate(feed_url)?;
+    let feed = crate::load_feed_local(feed_id)?;
+    let mut doc = parse_rss(&feed.xml)?;
+    doc.root
+        .child_mut("channel")
+        .ok_or("Missing <channel> element")?
+        .set_child_text("podcast:guid", &guid);
+
+    let saved = crate::save_feed_local(Some(feed.id), feed.title, feed.feed_type, render_document(&doc.root), None)?;
+    let conn = crate::open_library()?;
+    conn.execute("UPDATE feeds SET podcast_guid = ?2 WHERE id = ?1", rusqlite::params![saved.id, guid])
+        .map_err(|e| e.to_string())?;
+    Ok(guid)
+}
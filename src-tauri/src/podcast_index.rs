@@ -0,0 +1,190 @@
+// Aggregator pickup check: ask Podcast Index when it last crawled and parsed a feed
+// and compare that with the local publish time, so the user can see whether apps
+// have picked up the new version yet. Needs a (free) Podcast Index API key.
+
+use crate::feed_xml::parse_rss;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::fs;
+use std::path::PathBuf;
+
+const PODCAST_INDEX_API: &str = "https://api.podcastindex.org/api/1.0";
+
+// API credentials as stored on disk: the secret is encrypted with the device key
+#[derive(Serialize, Deserialize)]
+struct StoredCredentials {
+    api_key: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct IndexFeed {
+    id: Option<u64>,
+    url: Option<String>,
+    last_update_time: Option<u64>,
+    last_crawl_time: Option<u64>,
+    last_parse_time: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AggregatorStatus {
+    pub feed_id: String,
+    pub published_at: u64,
+    pub indexed: bool,
+    pub podcast_index_id: Option<u64>,
+    pub indexed_url: Option<String>,
+    pub last_update_time: Option<u64>,
+    pub last_crawl_time: Option<u64>,
+    pub last_parse_time: Option<u64>,
+    pub crawled_since_publish: bool,
+    pub picked_up: bool, // parsed at or after the publish time
+}
+
+fn get_credentials_path() -> Result<PathBuf, String> {
+    Ok(crate::get_appstate_dir()?.join("podcast_index.json"))
+}
+
+/// Load the API key and decrypted secret
+fn load_credentials() -> Result<(String, String), String> {
+    let path = get_credentials_path()?;
+    if !path.exists() {
+        return Err("Podcast Index API credentials are not set".to_string());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let stored: StoredCredentials = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    let key = crate::derive_key_from_device()?;
+    let secret = crate::decrypt_nsec(&stored.nonce, &stored.ciphertext, &key)?;
+    Ok((stored.api_key, secret))
+}
+
+/// GET a Podcast Index endpoint with the API's SHA-1 header auth
+async fn index_get(path: &str, query: &[(&str, &str)]) -> Result<serde_json::Value, String> {
+    let (api_key, api_secret) = load_credentials()?;
+    let date = crate::get_current_timestamp()?.to_string();
+    let authorization = hex::encode(Sha1::digest(format!("{}{}{}", api_key, api_secret, date)));
+
+    let response = reqwest::Client::new()
+        .get(format!("{}{}", PODCAST_INDEX_API, path))
+        .query(query)
+        .header(
+            "User-Agent",
+            format!("MSP 2.0 desktop {}", crate::APP_VERSION),
+        )
+        .header("X-Auth-Key", &api_key)
+        .header("X-Auth-Date", &date)
+        .header("Authorization", authorization)
+        .send()
+        .await
+        .map_err(|e| format!("Podcast Index request failed: {}", e))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED {
+        return Err("Podcast Index rejected the API credentials".to_string());
+    }
+    if !status.is_success() && status != reqwest::StatusCode::NOT_FOUND {
+        return Err(format!("Podcast Index error {}", status));
+    }
+    response.json().await.map_err(|e| e.to_string())
+}
+
+/// Look a feed up by podcast:guid, falling back to its public URL. None when the
+/// index does not know it.
+async fn lookup_feed(
+    podcast_guid: Option<&str>,
+    feed_url: Option<&str>,
+) -> Result<Option<IndexFeed>, String> {
+    let mut lookups = Vec::new();
+    if let Some(guid) = podcast_guid {
+        lookups.push(("/podcasts/byguid", ("guid", guid)));
+    }
+    if let Some(url) = feed_url {
+        lookups.push(("/podcasts/byfeedurl", ("url", url)));
+    }
+    if lookups.is_empty() {
+        return Err("Feed has neither a <podcast:guid> nor a public URL to look up".to_string());
+    }
+
+    for (path, param) in lookups {
+        let body = index_get(path, &[param]).await?;
+        // Unknown feeds come back as an empty "feed" array
+        if let Ok(feed) = serde_json::from_value::<IndexFeed>(body["feed"].clone()) {
+            if feed.id.is_some() {
+                return Ok(Some(feed));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Save Podcast Index API credentials (empty key removes them)
+#[tauri::command]
+pub fn podcast_index_set_credentials(api_key: String, api_secret: String) -> Result<(), String> {
+    let path = get_credentials_path()?;
+    if api_key.trim().is_empty() {
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| e.to_string())?;
+        }
+        return Ok(());
+    }
+
+    let key = crate::derive_key_from_device()?;
+    let (nonce, ciphertext) = crate::encrypt_nsec(api_secret.trim(), &key)?;
+    let stored = StoredCredentials {
+        api_key: api_key.trim().to_string(),
+        nonce,
+        ciphertext,
+    };
+    let json = serde_json::to_string_pretty(&stored).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| e.to_string())?;
+    crate::set_file_permissions(&path)
+}
+
+/// Whether Podcast Index API credentials are saved
+#[tauri::command]
+pub fn podcast_index_has_credentials() -> Result<bool, String> {
+    Ok(get_credentials_path()?.exists())
+}
+
+/// Check whether Podcast Index has crawled and parsed a feed since it was published.
+/// Uses the given publish time, else when the feed's last publish run finished.
+#[tauri::command]
+pub async fn podcast_index_check_feed(
+    feed_id: String,
+    published_at: Option<u64>,
+) -> Result<AggregatorStatus, String> {
+    let published_at = match published_at {
+        Some(time) => time,
+        None => crate::publish::last_published_at(&feed_id)?
+            .ok_or("Feed has no completed publish run; pass the publish time")?,
+    };
+
+    let feed = crate::load_feed_local(feed_id.clone())?;
+    let doc = parse_rss(&feed.xml)?;
+    let channel = doc.channel();
+    let podcast_guid = channel.and_then(|c| c.child_text("podcast:guid"));
+    let feed_url = channel.and_then(|c| {
+        c.children_named("atom:link")
+            .find(|l| l.attr("rel") == Some("self"))
+            .and_then(|l| l.attr("href"))
+    });
+
+    let found = lookup_feed(podcast_guid, feed_url).await?;
+    let indexed = found.is_some();
+    let found = found.unwrap_or_default();
+    let since_publish = |time: Option<u64>| time.is_some_and(|t| t >= published_at);
+
+    Ok(AggregatorStatus {
+        feed_id,
+        published_at,
+        indexed,
+        podcast_index_id: found.id,
+        indexed_url: found.url,
+        last_update_time: found.last_update_time,
+        crawled_since_publish: since_publish(found.last_crawl_time),
+        picked_up: since_publish(found.last_parse_time),
+        last_crawl_time: found.last_crawl_time,
+        last_parse_time: found.last_parse_time,
+    })
+}
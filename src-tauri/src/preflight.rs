@@ -0,0 +1,349 @@
+// Pre-flight checks against relay (NIP-11) and Blossom (BUD-06) policies before publishing
+
+use crate::formatting::format_size;
+use crate::validation::{Issues, ValidationIssue};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::State;
+
+// Relay info and HEAD requests should not hold up publishing for long
+const PREFLIGHT_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct RelayLimits {
+    pub relay: String,
+    pub reachable: bool,
+    pub max_message_length: Option<u64>,
+    pub max_content_length: Option<u64>,
+    pub max_event_tags: Option<u64>,
+    pub min_pow_difficulty: Option<u64>,
+    pub auth_required: bool,
+    pub payment_required: bool,
+    pub restricted_writes: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BlobPreflight {
+    pub server_url: String,
+    pub file_path: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PreflightReport {
+    pub ok: bool,
+    pub event_size: usize,
+    pub relays: Vec<RelayLimits>,
+    pub issues: Vec<ValidationIssue>,
+}
+
+/// Fetch a relay's NIP-11 information document
+async fn fetch_relay_limits(client: &reqwest::Client, relay: &str) -> RelayLimits {
+    let mut limits = RelayLimits {
+        relay: relay.to_string(),
+        ..Default::default()
+    };
+
+    let http_url = relay
+        .replacen("wss://", "https://", 1)
+        .replacen("ws://", "http://", 1);
+    let info: Option<serde_json::Value> = match client
+        .get(&http_url)
+        .header("Accept", "application/nostr+json")
+        .send()
+        .await
+    {
+        Ok(response) if response.status().is_success() => response.json().await.ok(),
+        _ => None,
+    };
+
+    let Some(info) = info else {
+        return limits;
+    };
+    limits.reachable = true;
+
+    if let Some(limitation) = info.get("limitation") {
+        let number = |key: &str| limitation.get(key).and_then(|v| v.as_u64());
+        let flag = |key: &str| limitation.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+        limits.max_message_length = number("max_message_length");
+        limits.max_content_length = number("max_content_length");
+        limits.max_event_tags = number("max_event_tags");
+        limits.min_pow_difficulty = number("min_pow_difficulty").filter(|d| *d > 0);
+        limits.auth_required = flag("auth_required");
+        limits.payment_required = flag("payment_required");
+        limits.restricted_writes = flag("restricted_writes");
+    }
+    limits
+}
+
+/// Size of the ["EVENT", ...] message a signed event would produce
+fn estimate_event_message(kind: u16, content: &str, tags: &[Vec<String>]) -> Result<usize, String> {
+    let event = serde_json::json!({
+        "id": "0".repeat(64),
+        "pubkey": "0".repeat(64),
+        "created_at": crate::get_current_timestamp()?,
+        "kind": kind,
+        "tags": tags,
+        "content": content,
+        "sig": "0".repeat(128),
+    });
+    let message = serde_json::to_string(&serde_json::json!(["EVENT", event])).map_err(|e| e.to_string())?;
+    Ok(message.len())
+}
+
+/// Compare an event against one relay's published limits
+fn check_relay(limits: &RelayLimits, content_len: u64, tag_count: u64, message_len: u64, issues: &mut Issues) {
+    let relay = &limits.relay;
+    if !limits.reachable {
+        issues.warning(
+            "relay-info-unavailable",
+            format!("Relay {} did not return NIP-11 info; its limits could not be checked", relay),
+        );
+        return;
+    }
+
+    if let Some(max) = limits.max_content_length {
+        if content_len > max {
+            issues.error(
+                "content-too-long",
+                format!(
+                    "Relay {} max {} content; this event has {}. Shorten the description or notes.",
+                    relay,
+                    format_size(max),
+                    format_size(content_len)
+                ),
+            );
+        }
+    }
+    if let Some(max) = limits.max_message_length {
+        if message_len > max {
+            issues.error(
+                "event-too-large",
+                format!(
+                    "Relay {} accepts messages up to {}; this event is {}. Remove tags or shorten the content.",
+                    relay,
+                    format_size(max),
+                    format_size(message_len)
+                ),
+            );
+        }
+    }
+    if let Some(max) = limits.max_event_tags {
+        if tag_count > max {
+            issues.error(
+                "too-many-tags",
+                format!("Relay {} allows {} tags per event; this event has {}.", relay, max, tag_count),
+            );
+        }
+    }
+    if let Some(difficulty) = limits.min_pow_difficulty {
+        issues.warning(
+            "pow-required",
+            format!("Relay {} requires proof of work (difficulty {}); the event may be rejected.", relay, difficulty),
+        );
+    }
+    if limits.payment_required {
+        issues.warning(
+            "payment-required",
+            format!("Relay {} is a paid relay; publishing fails unless your pubkey is registered.", relay),
+        );
+    } else if limits.restricted_writes {
+        issues.warning(
+            "restricted-writes",
+            format!("Relay {} restricts who can write; the event may be rejected.", relay),
+        );
+    }
+    if limits.auth_required {
+        issues.warning(
+            "auth-required",
+            format!("Relay {} requires NIP-42 authentication before accepting events.", relay),
+        );
+    }
+}
+
+/// A Blossom server's answer to a BUD-06 upload requirements check
+pub enum UploadRequirements {
+    Accepted,
+    Unsupported, // the server predates BUD-06
+    Unknown(String), // unreachable, overloaded, or rate limiting; the upload decides
+    Rejected { status: reqwest::StatusCode, reason: Option<String> },
+}
+
+/// Send the BUD-06 `HEAD /upload` check with the blob's hash, size, and type (and
+/// upload auth when logged in). Network errors, 5xx, and 429 are Unknown rather than
+/// rejections, since they say nothing about the blob.
+pub async fn upload_requirements(
+    client: &reqwest::Client,
+    server_url: &str,
+    sha256: &str,
+    size: u64,
+    mime_type: &str,
+    keys: Option<&nostr_sdk::Keys>,
+) -> Result<UploadRequirements, String> {
+    let server = crate::normalize_server_url(server_url);
+    let mut request = client
+        .head(format!("{}/upload", server))
+        .header("X-SHA-256", sha256)
+        .header("X-Content-Length", size)
+        .header("X-Content-Type", mime_type);
+    if let Some(keys) = keys {
+        let auth = crate::create_blossom_auth(keys, sha256, "upload", 300)?;
+        request = request.header("Authorization", crate::nostr_auth_header(&auth)?);
+    }
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => return Ok(UploadRequirements::Unknown(e.to_string())),
+    };
+    let status = response.status();
+    Ok(match status.as_u16() {
+        _ if status.is_success() => UploadRequirements::Accepted,
+        404 | 405 | 501 => UploadRequirements::Unsupported,
+        429 => UploadRequirements::Unknown(format!("rate limited ({})", status)),
+        _ if status.is_server_error() => UploadRequirements::Unknown(format!("server error ({})", status)),
+        _ => UploadRequirements::Rejected {
+            status,
+            reason: response
+                .headers()
+                .get("X-Reason")
+                .and_then(|r| r.to_str().ok())
+                .map(str::to_string),
+        },
+    })
+}
+
+/// What to tell the user when a server rejects a blob without saying why
+fn default_rejection_reason(status: reqwest::StatusCode) -> &'static str {
+    match status.as_u16() {
+        401 | 403 => "log in or check your account on this server",
+        413 => "file exceeds the server's size limit; compress it or pick another server",
+        415 => "convert the file or pick another server",
+        _ => "no reason given",
+    }
+}
+
+/// Check a blob against the server's upload requirements right before uploading,
+/// so a rejected file fails without transferring it. Servers that don't answer, are
+/// failing or rate limiting, or predate BUD-06 are left to the upload itself.
+pub async fn ensure_upload_accepted(
+    server_url: &str,
+    sha256: &str,
+    size: u64,
+    mime_type: &str,
+    keys: &nostr_sdk::Keys,
+) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(PREFLIGHT_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| e.to_string())?;
+    match upload_requirements(&client, server_url, sha256, size, mime_type, Some(keys)).await {
+        Ok(UploadRequirements::Rejected { status, reason }) => Err(format!(
+            "{} rejected the upload ({}, {}, {}): {}",
+            crate::normalize_server_url(server_url),
+            mime_type,
+            format_size(size),
+            status,
+            reason.as_deref().unwrap_or(default_rejection_reason(status))
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Ask a Blossom server whether it would accept a blob (BUD-06)
+async fn check_blob(
+    client: &reqwest::Client,
+    blob: &BlobPreflight,
+    keys: Option<&nostr_sdk::Keys>,
+    issues: &mut Issues,
+) -> Result<(), String> {
+    let path = std::path::PathBuf::from(&blob.file_path);
+    let (sha256, size) = tokio::task::spawn_blocking(move || crate::hash_file_cached(&path))
+        .await
+        .map_err(|e| e.to_string())??;
+    let mime_type = crate::guess_mime_type(&blob.file_path);
+    let server = crate::normalize_server_url(&blob.server_url);
+    let file_name = std::path::Path::new(&blob.file_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| blob.file_path.clone());
+
+    let requirements = upload_requirements(client, server, &sha256, size, mime_type, keys).await;
+    let (status, reason) = match requirements {
+        Ok(UploadRequirements::Accepted) => return Ok(()),
+        // Server predates BUD-06; nothing to check
+        Ok(UploadRequirements::Unsupported) => {
+            issues.warning(
+                "blossom-preflight-unsupported",
+                format!("{} does not support upload pre-checks; {} will be checked on upload", server, file_name),
+            );
+            return Ok(());
+        }
+        Ok(UploadRequirements::Rejected { status, reason }) => (status, reason),
+        Ok(UploadRequirements::Unknown(e)) | Err(e) => {
+            issues.warning(
+                "blossom-unreachable",
+                format!("Could not check {} with {}: {}; it will be checked on upload", file_name, server, e),
+            );
+            return Ok(());
+        }
+    };
+
+    let detail = reason.as_deref().unwrap_or(default_rejection_reason(status));
+    match status.as_u16() {
+        401 | 403 => issues.error(
+            "blossom-not-authorized",
+            format!("{} will not accept {}: {}", server, file_name, detail),
+        ),
+        413 => issues.error(
+            "blob-too-large",
+            format!("{} rejects {} ({}): {}", server, file_name, format_size(size), detail),
+        ),
+        415 => issues.error(
+            "blob-type-rejected",
+            format!("{} does not accept {} files ({}): {}", server, mime_type, file_name, detail),
+        ),
+        _ => issues.error(
+            "blob-rejected",
+            format!("{} rejected {} ({}): {}", server, file_name, status, detail),
+        ),
+    }
+    Ok(())
+}
+
+/// Check an event against relay NIP-11 limits and blobs against Blossom BUD-06
+/// requirements before publishing, so rejections come back as actionable messages
+#[tauri::command]
+pub async fn publish_preflight(
+    kind: u16,
+    content: String,
+    tags: Vec<Vec<String>>,
+    relays: Option<Vec<String>>,
+    blobs: Option<Vec<BlobPreflight>>,
+    state: State<'_, crate::NostrState>,
+) -> Result<PreflightReport, String> {
+    let keys = state.signing_keys().ok();
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(PREFLIGHT_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let relays = relays.unwrap_or_else(|| crate::write_relay_urls(&state));
+    let event_size = estimate_event_message(kind, &content, &tags)?;
+    let mut issues = Issues::default();
+
+    let relay_limits = futures_util::future::join_all(relays.iter().map(|r| fetch_relay_limits(&client, r))).await;
+    for limits in &relay_limits {
+        check_relay(limits, content.len() as u64, tags.len() as u64, event_size as u64, &mut issues);
+    }
+
+    for blob in blobs.unwrap_or_default() {
+        check_blob(&client, &blob, keys.as_ref(), &mut issues).await?;
+    }
+
+    let issues = issues.into_vec();
+    Ok(PreflightReport {
+        ok: !issues.iter().any(|i| i.severity == "error"),
+        event_size,
+        relays: relay_limits,
+        issues,
+    })
+}
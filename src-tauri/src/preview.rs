@@ -0,0 +1,236 @@
+// Feed previews that approximate how common podcast apps display a feed: which tags
+// they honor, how artwork falls back, and where descriptions get cut off
+
+use crate::feed_model::parse_feed_model;
+use crate::feed_xml::parse_rss;
+use serde::{Deserialize, Serialize};
+
+/// Display rules for one app, based on its published feed documentation
+struct AppProfile {
+    id: &'static str,
+    name: &'static str,
+    description_limit: usize,
+    track_description_limit: usize,
+    track_art: bool,
+    music_medium: bool,
+    honored: &'static [&'static str],
+}
+
+// Namespace tags worth reporting on when present in a feed
+const FEATURE_TAGS: &[&str] = &[
+    "podcast:value",
+    "podcast:person",
+    "podcast:transcript",
+    "podcast:chapters",
+    "podcast:funding",
+    "podcast:remoteItem",
+    "podcast:soundbite",
+    "podcast:txt",
+];
+
+const PROFILES: &[AppProfile] = &[
+    AppProfile {
+        id: "apple",
+        name: "Apple Podcasts",
+        description_limit: 4000,
+        track_description_limit: 4000,
+        track_art: true,
+        music_medium: false,
+        honored: &["podcast:transcript", "podcast:txt"],
+    },
+    AppProfile {
+        id: "spotify",
+        name: "Spotify",
+        description_limit: 4000,
+        track_description_limit: 4000,
+        track_art: false,
+        music_medium: false,
+        honored: &[],
+    },
+    AppProfile {
+        id: "fountain",
+        name: "Fountain",
+        description_limit: 10000,
+        track_description_limit: 10000,
+        track_art: true,
+        music_medium: true,
+        honored: &[
+            "podcast:value",
+            "podcast:person",
+            "podcast:transcript",
+            "podcast:chapters",
+            "podcast:funding",
+            "podcast:remoteItem",
+            "podcast:soundbite",
+        ],
+    },
+    AppProfile {
+        id: "podverse",
+        name: "Podverse",
+        description_limit: 10000,
+        track_description_limit: 10000,
+        track_art: true,
+        music_medium: true,
+        honored: &[
+            "podcast:value",
+            "podcast:person",
+            "podcast:transcript",
+            "podcast:chapters",
+            "podcast:funding",
+            "podcast:remoteItem",
+            "podcast:soundbite",
+            "podcast:txt",
+        ],
+    },
+];
+
+#[derive(Serialize, Deserialize)]
+pub struct TrackPreview {
+    pub title: String,
+    pub artist: String,
+    pub artwork_url: Option<String>,
+    pub artwork_source: String, // "track", "album", or "none"
+    pub description: String,
+    pub description_truncated: bool,
+    pub duration: String,
+    pub playable: bool,
+    pub value_enabled: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FeedPreview {
+    pub profile: String,
+    pub app_name: String,
+    pub title: String,
+    pub author: String,
+    pub artwork_url: Option<String>,
+    pub description: String,
+    pub description_truncated: bool,
+    pub tracks: Vec<TrackPreview>,
+    pub honored_tags: Vec<String>,
+    pub ignored_tags: Vec<String>,
+    pub notes: Vec<String>,
+}
+
+/// Strip markup and collapse whitespace, as list views show descriptions as plain text
+fn plain_text(html: &str) -> String {
+    let mut out = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                out.push(' ');
+            }
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Cut text to `limit` characters with an ellipsis, reporting whether it was cut
+fn truncate(text: &str, limit: usize) -> (String, bool) {
+    if text.chars().count() <= limit {
+        return (text.to_string(), false);
+    }
+    let cut: String = text.chars().take(limit.saturating_sub(1)).collect();
+    (format!("{}…", cut.trim_end()), true)
+}
+
+/// Preview a library feed as a given app would show it ("apple", "spotify",
+/// "fountain", or "podverse")
+#[tauri::command]
+pub fn feed_preview_render(feed_id: String, profile: String) -> Result<FeedPreview, String> {
+    let app = PROFILES
+        .iter()
+        .find(|p| p.id == profile)
+        .ok_or_else(|| format!("Unknown preview profile: {}", profile))?;
+
+    let feed = crate::load_feed_local(feed_id)?;
+    let doc = parse_rss(&feed.xml)?;
+    let model = parse_feed_model(&feed.xml)?;
+
+    let mut notes = Vec::new();
+    let medium = if model.medium.is_empty() { "podcast" } else { model.medium.as_str() };
+    if medium == "music" && !app.music_medium {
+        notes.push(format!(
+            "{} does not list music-only feeds; the feed may be rejected or shown as a podcast",
+            app.name
+        ));
+    }
+
+    let album_art = Some(model.image_url.clone()).filter(|u| !u.is_empty());
+    if album_art.is_none() {
+        notes.push(format!("{} will show a placeholder because the feed has no artwork", app.name));
+    }
+
+    let (description, description_truncated) =
+        truncate(&plain_text(&model.description), app.description_limit);
+    if description_truncated {
+        notes.push(format!(
+            "The album description is cut off after {} characters",
+            app.description_limit
+        ));
+    }
+
+    let honors_value = app.honored.contains(&"podcast:value");
+    let has_value = !model.value.recipients.is_empty();
+    let tracks = model
+        .tracks
+        .iter()
+        .map(|track| {
+            let (artwork_url, artwork_source) = match (&track.track_art_url, &album_art) {
+                (Some(art), _) if app.track_art && !art.is_empty() => (Some(art.clone()), "track"),
+                (_, Some(art)) => (Some(art.clone()), "album"),
+                _ => (None, "none"),
+            };
+            let (description, description_truncated) =
+                truncate(&plain_text(&track.description), app.track_description_limit);
+            TrackPreview {
+                title: track.title.clone(),
+                artist: track.author.clone().unwrap_or_else(|| model.author.clone()),
+                artwork_url,
+                artwork_source: artwork_source.to_string(),
+                description,
+                description_truncated,
+                duration: track.duration.clone(),
+                playable: !track.enclosure_url.is_empty() && !track.enclosure_url.starts_with("file://"),
+                value_enabled: honors_value && (has_value || track.override_value),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if tracks.iter().any(|t| !t.playable) {
+        notes.push("Some tracks point at local files and will not play until they are uploaded".to_string());
+    }
+    if !app.track_art && model.tracks.iter().any(|t| t.track_art_url.is_some()) {
+        notes.push(format!("{} shows the album cover instead of per-track artwork", app.name));
+    }
+
+    let present: Vec<&str> = FEATURE_TAGS
+        .iter()
+        .copied()
+        .filter(|tag| !doc.root.descendants_named(tag).is_empty())
+        .collect();
+    let (honored, ignored): (Vec<&str>, Vec<&str>) =
+        present.into_iter().partition(|tag| app.honored.contains(tag));
+    if has_value && !honors_value {
+        notes.push(format!("{} ignores value splits; listeners cannot stream sats there", app.name));
+    }
+
+    Ok(FeedPreview {
+        profile: app.id.to_string(),
+        app_name: app.name.to_string(),
+        title: model.title,
+        author: model.author,
+        artwork_url: album_art,
+        description,
+        description_truncated,
+        tracks,
+        honored_tags: honored.into_iter().map(str::to_string).collect(),
+        ignored_tags: ignored.into_iter().map(str::to_string).collect(),
+        notes,
+    })
+}
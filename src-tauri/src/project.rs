@@ -0,0 +1,419 @@
+// Projects group a publisher feed, its album feeds, shared assets, value templates,
+// and deploy targets so a whole catalog can be managed and moved as one unit.
+// Each project lives in projects/<id>/ with a project.json and an assets/ folder.
+
+use crate::feed_model::{FeedStampOptions, ValueModel};
+use crate::feed_xml::{parse_xml, render_document, XmlNode};
+use crate::import::extract_zip;
+use crate::storage::StorageTarget;
+use crate::workspace::TaskWorkspace;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use uuid::Uuid;
+
+// Format version of exported project archives
+const PROJECT_ARCHIVE_VERSION: u32 = 1;
+
+const PROJECT_FILE: &str = "project.json";
+const ASSETS_DIR: &str = "assets";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ValueTemplate {
+    pub name: String,
+    pub value: ValueModel,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Project {
+    pub id: String,
+    pub name: String,
+    pub publisher_feed_id: Option<String>,
+    pub album_feed_ids: Vec<String>,
+    pub assets: Vec<String>, // file names inside the project's assets folder
+    pub value_templates: Vec<ValueTemplate>,
+    pub deploy_targets: Vec<StorageTarget>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProjectOverview {
+    pub project: Project,
+    pub assets_dir: String,
+    pub feeds: Vec<crate::FeedSummary>,
+    pub missing_feed_ids: Vec<String>,
+}
+
+// Manifest stored as project.json at the root of an exported archive
+#[derive(Serialize, Deserialize)]
+struct ProjectArchive {
+    version: u32,
+    project: Project,
+    assets_dir: String, // where the assets lived, for rewriting feed references
+}
+
+/// Get the directory holding all projects
+fn get_projects_dir() -> Result<PathBuf, String> {
+    let proj_dirs = ProjectDirs::from("com", "podtards", "msp-studio")
+        .ok_or("Could not determine app data directory")?;
+
+    let projects_dir = proj_dirs.data_dir().join("projects");
+    fs::create_dir_all(&projects_dir).map_err(|e| e.to_string())?;
+
+    Ok(projects_dir)
+}
+
+/// Whether a name is a single ordinary path component, safe to join onto a directory
+fn is_plain_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(components.next(), Some(Component::Normal(_)))
+        && components.next().is_none()
+        && !name.contains(['/', '\\'])
+}
+
+/// Reject feed ids and asset names that would resolve outside their folders
+fn check_project_names(project: &Project) -> Result<(), String> {
+    for feed_id in project_feed_ids(project) {
+        if !is_plain_name(&feed_id) {
+            return Err(format!("Invalid feed id in project: {}", feed_id));
+        }
+    }
+    for name in &project.assets {
+        if !is_plain_name(name) {
+            return Err(format!("Invalid asset name in project: {}", name));
+        }
+    }
+    Ok(())
+}
+
+/// Directory of one project (must already exist)
+fn project_dir(project_id: &str) -> Result<PathBuf, String> {
+    let dir = get_projects_dir()?.join(project_id);
+    if !is_plain_name(project_id) || !dir.join(PROJECT_FILE).exists() {
+        return Err(format!("Project not found: {}", project_id));
+    }
+    Ok(dir)
+}
+
+fn load_project(dir: &Path) -> Result<Project, String> {
+    let content = fs::read_to_string(dir.join(PROJECT_FILE)).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid project file: {}", e))
+}
+
+fn write_project(dir: &Path, project: &Project) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(project).map_err(|e| e.to_string())?;
+    fs::write(dir.join(PROJECT_FILE), json).map_err(|e| e.to_string())
+}
+
+/// All feed ids in a project, publisher first
+fn project_feed_ids(project: &Project) -> Vec<String> {
+    project
+        .publisher_feed_id
+        .iter()
+        .chain(&project.album_feed_ids)
+        .cloned()
+        .collect()
+}
+
+/// Copy files into a project's assets folder, returning their names
+fn add_assets(dir: &Path, paths: &[String]) -> Result<Vec<String>, String> {
+    let assets_dir = dir.join(ASSETS_DIR);
+    fs::create_dir_all(&assets_dir).map_err(|e| e.to_string())?;
+
+    let mut names = Vec::new();
+    for path in paths {
+        let source = Path::new(path);
+        let name = source
+            .file_name()
+            .ok_or_else(|| format!("Not a file: {}", path))?
+            .to_string_lossy()
+            .to_string();
+        if assets_dir.join(&name).exists() || names.contains(&name) {
+            return Err(format!("The project already has an asset named {}", name));
+        }
+        fs::copy(source, assets_dir.join(&name)).map_err(|e| format!("Failed to copy {}: {}", path, e))?;
+        names.push(name);
+    }
+    Ok(names)
+}
+
+/// Create a project from existing library feeds, copying the given files in as
+/// shared assets
+#[tauri::command]
+pub fn project_create(
+    name: String,
+    publisher_feed_id: Option<String>,
+    album_feed_ids: Vec<String>,
+    asset_paths: Vec<String>,
+) -> Result<Project, String> {
+    if name.trim().is_empty() {
+        return Err("Project name cannot be empty".to_string());
+    }
+    for feed_id in publisher_feed_id.iter().chain(&album_feed_ids) {
+        crate::load_feed_local(feed_id.clone())?;
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let dir = get_projects_dir()?.join(&id);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let assets = match add_assets(&dir, &asset_paths) {
+        Ok(assets) => assets,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&dir);
+            return Err(e);
+        }
+    };
+
+    let now = crate::get_current_timestamp()?;
+    let project = Project {
+        id,
+        name: name.trim().to_string(),
+        publisher_feed_id,
+        album_feed_ids,
+        assets,
+        value_templates: Vec::new(),
+        deploy_targets: Vec::new(),
+        created_at: now,
+        updated_at: now,
+    };
+    write_project(&dir, &project)?;
+    Ok(project)
+}
+
+/// List projects, most recently updated first
+#[tauri::command]
+pub fn project_list() -> Result<Vec<Project>, String> {
+    let mut projects: Vec<Project> = fs::read_dir(get_projects_dir()?)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .filter_map(|entry| load_project(&entry.path()).ok())
+        .collect();
+    projects.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    Ok(projects)
+}
+
+/// Open a project with summaries of its feeds, reporting feeds no longer in the library
+#[tauri::command]
+pub fn project_open(project_id: String) -> Result<ProjectOverview, String> {
+    let dir = project_dir(&project_id)?;
+    let project = load_project(&dir)?;
+    let mut library = crate::list_feeds_local()?;
+
+    let mut feeds = Vec::new();
+    let mut missing_feed_ids = Vec::new();
+    for feed_id in project_feed_ids(&project) {
+        match library.iter().position(|f| f.id == feed_id) {
+            Some(index) => feeds.push(library.swap_remove(index)),
+            None => missing_feed_ids.push(feed_id),
+        }
+    }
+
+    Ok(ProjectOverview {
+        project,
+        assets_dir: dir.join(ASSETS_DIR).to_string_lossy().to_string(),
+        feeds,
+        missing_feed_ids,
+    })
+}
+
+/// Save edits to a project (feeds, value templates, deploy targets), adding any
+/// new asset files
+#[tauri::command]
+pub fn project_save(mut project: Project, new_asset_paths: Vec<String>) -> Result<Project, String> {
+    let dir = project_dir(&project.id)?;
+    let existing = load_project(&dir)?;
+    check_project_names(&project)?;
+
+    project.assets.extend(add_assets(&dir, &new_asset_paths)?);
+    project.created_at = existing.created_at;
+    project.updated_at = crate::get_current_timestamp()?;
+    write_project(&dir, &project)?;
+    Ok(project)
+}
+
+/// Export a project as a zip with its manifest, feed records, and assets. Deploy
+/// target credentials are left out. Returns the number of files written.
+#[tauri::command]
+pub async fn project_export(project_id: String, path: String) -> Result<usize, String> {
+    tokio::task::spawn_blocking(move || export_project(&project_id, &path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn export_project(project_id: &str, path: &str) -> Result<usize, String> {
+    let dir = project_dir(project_id)?;
+    let mut project = load_project(&dir)?;
+    check_project_names(&project)?;
+    project.deploy_targets = project.deploy_targets.iter().map(|t| t.without_secrets()).collect();
+    let assets_dir = dir.join(ASSETS_DIR);
+
+    let file = fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let deflated = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    // Audio and images are already compressed
+    let stored = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+    let archive = ProjectArchive {
+        version: PROJECT_ARCHIVE_VERSION,
+        project: project.clone(),
+        assets_dir: assets_dir.to_string_lossy().to_string(),
+    };
+    let manifest = serde_json::to_string_pretty(&archive).map_err(|e| e.to_string())?;
+    zip.start_file(PROJECT_FILE, deflated).map_err(|e| e.to_string())?;
+    zip.write_all(manifest.as_bytes()).map_err(|e| e.to_string())?;
+    let mut written = 1;
+
+    for feed_id in project_feed_ids(&project) {
+        let feed = crate::load_feed_local(feed_id.clone())?;
+        zip.start_file(format!("feeds/{}.json", feed_id), deflated)
+            .map_err(|e| e.to_string())?;
+        zip.write_all(crate::serialize_feed_record(&feed)?.as_bytes())
+            .map_err(|e| e.to_string())?;
+        written += 1;
+    }
+
+    for name in &project.assets {
+        let mut asset =
+            fs::File::open(assets_dir.join(name)).map_err(|e| format!("Missing asset {}: {}", name, e))?;
+        zip.start_file(format!("{}/{}", ASSETS_DIR, name), stored)
+            .map_err(|e| e.to_string())?;
+        std::io::copy(&mut asset, &mut zip).map_err(|e| e.to_string())?;
+        written += 1;
+    }
+
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(written)
+}
+
+/// Point references to files in the exported assets folder at the imported one. Both
+/// file:// URLs (compared decoded, so escaped characters match) and plain paths count.
+fn rewrite_asset_references(node: &mut XmlNode, old_assets: &Path, new_assets: &Path) {
+    let values = node.attrs.iter_mut().map(|(_, value)| value).chain([&mut node.text]);
+    for value in values {
+        if let Some(path) = crate::file_url_path(value) {
+            if let Ok(relative) = path.strip_prefix(old_assets) {
+                *value = crate::file_url(&new_assets.join(relative));
+            }
+        } else if let Ok(relative) = Path::new(value.trim()).strip_prefix(old_assets) {
+            *value = new_assets.join(relative).to_string_lossy().to_string();
+        }
+    }
+    for child in &mut node.children {
+        rewrite_asset_references(child, old_assets, new_assets);
+    }
+}
+
+/// Import an exported project as a new project. Its feeds are added to the library
+/// with asset references pointed at the new assets folder. Deploy targets come in
+/// without credentials, which have to be entered again.
+#[tauri::command]
+pub async fn project_import(path: String) -> Result<Project, String> {
+    tokio::task::spawn_blocking(move || import_project(&path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn import_project(path: &str) -> Result<Project, String> {
+    let workspace = TaskWorkspace::create("project-import")?;
+    extract_zip(Path::new(path), workspace.path())?;
+
+    let manifest = fs::read_to_string(workspace.path().join(PROJECT_FILE))
+        .map_err(|_| "Not a project archive (missing project.json)".to_string())?;
+    let archive: ProjectArchive =
+        serde_json::from_str(&manifest).map_err(|e| format!("Invalid project archive: {}", e))?;
+    if archive.version > PROJECT_ARCHIVE_VERSION {
+        return Err(format!(
+            "This project was exported by a newer version (format {}); please update the app",
+            archive.version
+        ));
+    }
+
+    let mut project = archive.project;
+    check_project_names(&project)?;
+    project.deploy_targets = project.deploy_targets.iter().map(|t| t.without_secrets()).collect();
+    project.id = Uuid::new_v4().to_string();
+    let dir = get_projects_dir()?.join(&project.id);
+    let assets_dir = dir.join(ASSETS_DIR);
+
+    // Read and check every feed before saving any, so a collision imports nothing
+    let conn = crate::open_library()?;
+    let mut feeds = Vec::new();
+    for feed_id in project_feed_ids(&project) {
+        let record = fs::read_to_string(workspace.path().join("feeds").join(format!("{}.json", feed_id)))
+            .map_err(|_| format!("Archive is missing feed {}", feed_id))?;
+        let mut feed = crate::parse_feed_record(&record)?;
+        let mut root = parse_xml(&feed.xml).map_err(|e| format!("Invalid feed {} in archive: {}", feed_id, e))?;
+        rewrite_asset_references(&mut root, Path::new(&archive.assets_dir), &assets_dir);
+        feed.xml = render_document(&root);
+        crate::batch::ensure_unique_guids(&conn, None, &feed.xml)?;
+        feeds.push((feed_id, feed));
+    }
+
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let staged_assets = workspace.path().join(ASSETS_DIR);
+    if staged_assets.exists() {
+        workspace.persist(&staged_assets, &assets_dir)?;
+    }
+
+    // Saved feeds get fresh library ids; keep their stored dates as-is
+    let keep_dates = FeedStampOptions {
+        auto: false,
+        ..Default::default()
+    };
+    for (old_id, feed) in feeds {
+        let saved =
+            crate::save_feed_local(None, feed.title, feed.feed_type, feed.xml, Some(keep_dates.clone()))?;
+        if project.publisher_feed_id.as_deref() == Some(old_id.as_str()) {
+            project.publisher_feed_id = Some(saved.id.clone());
+        }
+        for album_id in project.album_feed_ids.iter_mut().filter(|id| **id == old_id) {
+            *album_id = saved.id.clone();
+        }
+    }
+
+    let now = crate::get_current_timestamp()?;
+    project.created_at = now;
+    project.updated_at = now;
+    write_project(&dir, &project)?;
+    Ok(project)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewritten(xml: &str) -> String {
+        let mut root = parse_xml(xml).unwrap();
+        rewrite_asset_references(
+            &mut root,
+            Path::new("/home/me/Projects/old id/assets"),
+            Path::new("/data/projects/new/assets"),
+        );
+        root.to_xml(0)
+    }
+
+    #[test]
+    fn escaped_file_urls_are_rewritten() {
+        let xml = rewritten(r#"<item><enclosure url="file:///home/me/Projects/old%20id/assets/My%20Song.mp3"/></item>"#);
+        assert!(xml.contains("file:///data/projects/new/assets/My%20Song.mp3"), "{}", xml);
+    }
+
+    #[test]
+    fn plain_paths_and_text_are_rewritten() {
+        let xml = rewritten("<image><url>/home/me/Projects/old id/assets/cover.png</url></image>");
+        assert!(xml.contains("/data/projects/new/assets/cover.png"), "{}", xml);
+    }
+
+    #[test]
+    fn references_outside_the_assets_folder_are_kept() {
+        let xml = rewritten(concat!(
+            r#"<item><enclosure url="file:///home/me/Projects/old%20id/assets-old/a.mp3"/>"#,
+            r#"<link>https://example.com/home/me/Projects/old%20id/assets/a.mp3</link></item>"#
+        ));
+        assert!(xml.contains("file:///home/me/Projects/old%20id/assets-old/a.mp3"), "{}", xml);
+        assert!(xml.contains("https://example.com/home/me/Projects/old%20id/assets/a.mp3"), "{}", xml);
+    }
+}
@@ -0,0 +1,457 @@
+// Album publish pipeline: upload local assets, rewrite the feed, publish the feed
+// event, and run the feed's notifiers. Run state is persisted after every step so a failed
+// run resumes where it stopped instead of re-uploading or double-posting. Every run is
+// also kept in a history that can be exported as a JSON report.
+
+use crate::feed_xml::{parse_xml, render_document, XmlNode};
+use crate::notify::{self, NotificationReport, NotificationResult, NotifierConfig};
+use crate::storage::{self, StorageTarget};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, State};
+use uuid::Uuid;
+
+// Pipeline steps, in order
+const STEP_UPLOAD_ASSETS: &str = "upload-assets";
+const STEP_SAVE_FEED: &str = "save-feed";
+const STEP_PUBLISH_FEED: &str = "publish-feed";
+const STEP_NOTIFY: &str = "notify";
+const STEP_DONE: &str = "done";
+
+// Format version of exported run reports
+const PIPELINE_REPORT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PublishAsset {
+    pub source: String, // file:// reference as it appears in the feed
+    pub file_path: String,
+    pub sha256: Option<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StepTiming {
+    pub step: String,
+    pub finished_at: u64,
+    pub duration_ms: Option<u64>, // unknown when the step failed
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PublishRun {
+    pub run_id: String,
+    pub feed_id: String,
+    pub target: StorageTarget,
+    pub step: String,
+    pub assets: Vec<PublishAsset>,
+    pub feed_event_id: Option<String>,
+    pub notifications: Option<NotificationReport>,
+    #[serde(default)]
+    pub announcement_event_id: Option<String>,
+    pub last_error: Option<String>,
+    pub started_at: u64,
+    pub updated_at: u64,
+    #[serde(default)]
+    pub feed_sha256: Option<String>, // hash of the published feed XML
+    #[serde(default)]
+    pub relay_results: Vec<crate::RelayPublishResult>,
+    #[serde(default)]
+    pub step_timings: Vec<StepTiming>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PipelineRunSummary {
+    pub run_id: String,
+    pub feed_id: String,
+    pub step: String,
+    pub completed: bool,
+    pub last_error: Option<String>,
+    pub started_at: u64,
+    pub updated_at: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PipelineReport {
+    pub report_version: u32,
+    pub app_version: String,
+    pub generated_at: u64,
+    pub completed: bool,
+    pub total_duration_ms: u64, // sum of the recorded step durations
+    pub run: PublishRun,
+}
+
+/// Path of the persisted run state for a feed
+fn get_run_path(feed_id: &str) -> Result<PathBuf, String> {
+    let dir = crate::get_appstate_dir()?.join("publish_runs");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(format!("{}.json", feed_id)))
+}
+
+/// Directory holding a copy of every run
+fn get_history_dir() -> Result<PathBuf, String> {
+    let dir = crate::get_appstate_dir()?.join("publish_runs").join("history");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+fn get_history_path(run_id: &str) -> Result<PathBuf, String> {
+    Ok(get_history_dir()?.join(format!("{}.json", run_id)))
+}
+
+fn load_run(feed_id: &str) -> Result<Option<PublishRun>, String> {
+    let path = get_run_path(feed_id)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map(Some).map_err(|e| e.to_string())
+}
+
+/// Persist run state and its history copy (written atomically) and report it as a
+/// `publish://progress` event
+fn checkpoint(run: &mut PublishRun, app: &AppHandle) -> Result<(), String> {
+    run.updated_at = crate::get_current_timestamp()?;
+    let json = serde_json::to_string_pretty(run).map_err(|e| e.to_string())?;
+    for path in [get_run_path(&run.feed_id)?, get_history_path(&run.run_id)?] {
+        crate::write_atomic(&path, json.as_bytes())?;
+    }
+
+    let _ = app.emit("publish://progress", &*run);
+    Ok(())
+}
+
+/// Record how long a step took and move the run on to the next step
+fn finish_step(run: &mut PublishRun, next: &str, started: Instant) -> Result<(), String> {
+    run.step_timings.push(StepTiming {
+        step: run.step.clone(),
+        finished_at: crate::get_current_timestamp()?,
+        duration_ms: Some(started.elapsed().as_millis() as u64),
+        error: None,
+    });
+    run.step = next.to_string();
+    Ok(())
+}
+
+/// Collect distinct file:// references from attributes and text
+pub fn collect_local_refs(node: &XmlNode, refs: &mut Vec<String>) {
+    let values = node.attrs.iter().map(|(_, v)| v.trim()).chain([node.text.trim()]);
+    for value in values {
+        if value.starts_with("file://") && !refs.iter().any(|r| r == value) {
+            refs.push(value.to_string());
+        }
+    }
+    for child in &node.children {
+        collect_local_refs(child, refs);
+    }
+}
+
+/// Replace uploaded file:// references with their hosted URLs
+pub fn rewrite_local_refs(node: &mut XmlNode, urls: &HashMap<&str, &str>) {
+    for (_, value) in node.attrs.iter_mut() {
+        if let Some(url) = urls.get(value.trim()) {
+            *value = url.to_string();
+        }
+    }
+    if let Some(url) = urls.get(node.text.trim()) {
+        node.text = url.to_string();
+    }
+    for child in node.children.iter_mut() {
+        rewrite_local_refs(child, urls);
+    }
+}
+
+/// Add assets referenced by the feed that the run does not know about yet
+fn discover_assets(run: &mut PublishRun, xml: &str) -> Result<(), String> {
+    let mut refs = Vec::new();
+    collect_local_refs(&parse_xml(xml)?, &mut refs);
+    for source in refs {
+        if run.assets.iter().any(|a| a.source == source) {
+            continue;
+        }
+        let file_path = crate::file_url_path(&source).ok_or_else(|| format!("Not a local file: {}", source))?;
+        run.assets.push(PublishAsset {
+            file_path: file_path.to_string_lossy().to_string(),
+            source,
+            sha256: None,
+            url: None,
+        });
+    }
+    Ok(())
+}
+
+/// Run the remaining pipeline steps, checkpointing after each one
+async fn advance(
+    run: &mut PublishRun,
+    announcement: Option<&str>,
+    app: &AppHandle,
+    state: &State<'_, crate::NostrState>,
+) -> Result<(), String> {
+    if run.step == STEP_UPLOAD_ASSETS {
+        let started = Instant::now();
+        let keys = state.signing_keys().ok();
+        for i in 0..run.assets.len() {
+            if run.assets[i].url.is_some() {
+                continue;
+            }
+            let file_path = run.assets[i].file_path.clone();
+            let stored = storage::upload_file(&run.target, &file_path, keys.clone(), Some(app.clone()))
+                .await
+                .map_err(|e| format!("Upload of {} failed: {}", file_path, e))?;
+            run.assets[i].sha256 = Some(stored.sha256);
+            run.assets[i].url = Some(stored.url);
+            checkpoint(run, app)?;
+        }
+        finish_step(run, STEP_SAVE_FEED, started)?;
+        checkpoint(run, app)?;
+    }
+
+    if run.step == STEP_SAVE_FEED {
+        let started = Instant::now();
+        let feed = crate::load_feed_local(run.feed_id.clone())?;
+        let urls: HashMap<&str, &str> = run
+            .assets
+            .iter()
+            .filter_map(|a| Some((a.source.as_str(), a.url.as_deref()?)))
+            .collect();
+        let mut root = parse_xml(&feed.xml)?;
+        rewrite_local_refs(&mut root, &urls);
+        let saved = crate::save_feed_local(
+            Some(feed.id),
+            feed.title,
+            feed.feed_type,
+            render_document(&root),
+            None,
+        )?;
+        run.feed_id = saved.id;
+        finish_step(run, STEP_PUBLISH_FEED, started)?;
+        checkpoint(run, app)?;
+    }
+
+    if run.step == STEP_PUBLISH_FEED {
+        let started = Instant::now();
+        let feed = crate::load_feed_local(run.feed_id.clone())?;
+        let root = parse_xml(&feed.xml)?;
+        let podcast_guid = root
+            .child("channel")
+            .and_then(|c| c.child_text("podcast:guid"))
+            .ok_or("Feed is missing <podcast:guid>")?
+            .to_string();
+        let tags = vec![
+            vec!["d".to_string(), podcast_guid],
+            vec!["title".to_string(), feed.title.clone()],
+            vec!["client".to_string(), "MSP 2.0".to_string()],
+        ];
+        run.feed_sha256 = Some(hex::encode(Sha256::digest(feed.xml.as_bytes())));
+        let published =
+            crate::publish_event_with_results(crate::SYNCED_FEED_KIND, feed.xml, tags, None, state.clone()).await?;
+        // Record what each relay said even when none accepted, so the report shows why
+        let accepted = published.require_accepted();
+        run.relay_results = published.relays;
+        accepted?;
+        run.feed_event_id = Some(published.event_id);
+        finish_step(run, STEP_NOTIFY, started)?;
+        checkpoint(run, app)?;
+    }
+
+    if run.step == STEP_NOTIFY {
+        let started = Instant::now();
+        let mut notifiers = notify::publish_notifiers(&run.feed_id)?;
+        // A one-off announcement replaces the feed's Nostr template rather than posting twice
+        if let Some(text) = announcement.filter(|t| !t.trim().is_empty()) {
+            notifiers.retain(|n| !matches!(n, NotifierConfig::Nostr { .. }));
+            notifiers.push(NotifierConfig::Nostr {
+                template: Some(text.to_string()),
+            });
+        }
+        let notice = notify::publish_notice(&run.feed_id, run.feed_event_id.clone())?;
+
+        // Notifiers that succeeded on an earlier attempt are not run again, so resuming
+        // never posts the announcement twice; each outcome is saved as it comes in
+        let mut results: Vec<NotificationResult> = run
+            .notifications
+            .take()
+            .map(|report| report.results)
+            .unwrap_or_default();
+        results.retain(|r| r.ok);
+        for config in &notifiers {
+            let key = config.key();
+            if results.iter().any(|r| r.key == key) {
+                continue;
+            }
+            let result = notify::run_notifier(config, &notice, state.clone()).await;
+            if result.ok && matches!(config, NotifierConfig::Nostr { .. }) {
+                run.announcement_event_id = Some(result.detail.clone());
+            }
+            results.push(result);
+            run.notifications = Some(notify::notification_report(&run.feed_id, results.clone()));
+            checkpoint(run, app)?;
+        }
+
+        let failed: Vec<String> = results
+            .iter()
+            .filter(|r| !r.ok)
+            .map(|r| format!("{}: {}", r.notifier, r.detail))
+            .collect();
+        run.notifications = Some(notify::notification_report(&run.feed_id, results));
+        if !failed.is_empty() {
+            return Err(format!(
+                "The feed was published, but {} notifier(s) failed ({}). Publish again to retry them.",
+                failed.len(),
+                failed.join("; ")
+            ));
+        }
+        finish_step(run, STEP_DONE, started)?;
+        checkpoint(run, app)?;
+    }
+
+    Ok(())
+}
+
+/// Publish an album: upload its local files, point the feed at the uploads, publish
+/// the feed event, and run the feed's notifiers (plus an optional one-off Nostr
+/// announcement), reporting each notifier's outcome. Uploads go to the given Blossom
+/// server, else the feed's storage target, else the user's preferred Blossom server.
+/// Re-running after a failure resumes the unfinished run for the feed, on the storage
+/// target it started with; asking for another target once files are uploaded is refused.
+#[tauri::command]
+pub async fn publish_album(
+    feed_id: String,
+    server_url: Option<String>,
+    announcement: Option<String>,
+    app: AppHandle,
+    state: State<'_, crate::NostrState>,
+) -> Result<PublishRun, String> {
+    let _operation = crate::shutdown::begin("publish", &feed_id);
+    let target = match server_url {
+        Some(server_url) => StorageTarget::Blossom { server_url },
+        None => storage::resolve_target(Some(&feed_id), &state)?,
+    };
+    let now = crate::get_current_timestamp()?;
+    let mut run = match load_run(&feed_id)? {
+        Some(run) if run.step != STEP_DONE => run,
+        _ => PublishRun {
+            run_id: Uuid::new_v4().to_string(),
+            feed_id: feed_id.clone(),
+            target: target.clone(),
+            step: STEP_UPLOAD_ASSETS.to_string(),
+            assets: Vec::new(),
+            feed_event_id: None,
+            notifications: None,
+            announcement_event_id: None,
+            last_error: None,
+            started_at: now,
+            updated_at: now,
+            feed_sha256: None,
+            relay_results: Vec::new(),
+            step_timings: Vec::new(),
+        },
+    };
+
+    // Uploads already made stay where they are, so a resumed run cannot move hosts
+    // partway; before anything is uploaded it can still switch
+    if run.target.ledger_key() != target.ledger_key() {
+        if run.assets.iter().any(|asset| asset.url.is_some()) {
+            return Err(format!(
+                "An unfinished publish of this feed is uploading to {}; resume it there before publishing to {}",
+                run.target.ledger_key(),
+                target.ledger_key()
+            ));
+        }
+        run.target = target;
+    }
+
+    // Files added since the failed attempt still need uploading
+    run.last_error = None;
+    if run.step == STEP_UPLOAD_ASSETS {
+        let feed = crate::load_feed_local(run.feed_id.clone())?;
+        discover_assets(&mut run, &feed.xml)?;
+    }
+    checkpoint(&mut run, &app)?;
+
+    if let Err(e) = advance(&mut run, announcement.as_deref(), &app, &state).await {
+        run.last_error = Some(e.clone());
+        run.step_timings.push(StepTiming {
+            step: run.step.clone(),
+            finished_at: crate::get_current_timestamp()?,
+            duration_ms: None,
+            error: Some(e.clone()),
+        });
+        checkpoint(&mut run, &app)?;
+        return Err(e);
+    }
+
+    Ok(run)
+}
+
+/// When the feed's last publish run finished, if it has one
+pub fn last_published_at(feed_id: &str) -> Result<Option<u64>, String> {
+    Ok(load_run(feed_id)?
+        .filter(|run| run.step == STEP_DONE)
+        .map(|run| run.updated_at))
+}
+
+/// Get the latest publish run for a feed, if any
+#[tauri::command]
+pub fn publish_album_status(feed_id: String) -> Result<Option<PublishRun>, String> {
+    load_run(&feed_id)
+}
+
+fn load_history_run(path: &std::path::Path) -> Result<PublishRun, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+/// List recorded pipeline runs, newest first, optionally for one feed
+#[tauri::command]
+pub fn pipeline_runs_list(feed_id: Option<String>) -> Result<Vec<PipelineRunSummary>, String> {
+    let mut runs: Vec<PipelineRunSummary> = fs::read_dir(get_history_dir()?)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| load_history_run(&entry.path()).ok())
+        .filter(|run| feed_id.as_ref().map(|id| *id == run.feed_id).unwrap_or(true))
+        .map(|run| PipelineRunSummary {
+            completed: run.step == STEP_DONE,
+            run_id: run.run_id,
+            feed_id: run.feed_id,
+            step: run.step,
+            last_error: run.last_error,
+            started_at: run.started_at,
+            updated_at: run.updated_at,
+        })
+        .collect();
+    runs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    Ok(runs)
+}
+
+/// Export a pipeline run as a JSON report (steps, durations, hashes, relay and
+/// notifier results), also writing it to `path` when given
+#[tauri::command]
+pub fn pipeline_run_export(run_id: String, path: Option<String>) -> Result<String, String> {
+    if run_id.contains(['/', '\\']) {
+        return Err(format!("Pipeline run not found: {}", run_id));
+    }
+    let history_path = get_history_path(&run_id)?;
+    if !history_path.exists() {
+        return Err(format!("Pipeline run not found: {}", run_id));
+    }
+    let run = load_history_run(&history_path)?;
+
+    let report = PipelineReport {
+        report_version: PIPELINE_REPORT_VERSION,
+        app_version: crate::APP_VERSION.to_string(),
+        generated_at: crate::get_current_timestamp()?,
+        completed: run.step == STEP_DONE,
+        total_duration_ms: run.step_timings.iter().filter_map(|t| t.duration_ms).sum(),
+        run,
+    };
+    let json = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+    if let Some(path) = path {
+        fs::write(&path, &json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    }
+    Ok(json)
+}
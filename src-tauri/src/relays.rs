@@ -0,0 +1,186 @@
+// Relay tools: read a relay's NIP-11 information document before adding it, and send
+// raw protocol frames to a single relay for debugging relay-specific behavior.
+
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+// How long a raw request listens for replies by default, and at most
+const RAW_DEFAULT_TIMEOUT_SECS: u64 = 10;
+const RAW_MAX_TIMEOUT_SECS: u64 = 120;
+
+// How long to wait for a relay information document
+const RELAY_INFO_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct RelayLimitation {
+    pub max_message_length: Option<u64>,
+    pub max_subscriptions: Option<u64>,
+    pub max_limit: Option<u64>,
+    pub max_event_tags: Option<u64>,
+    pub max_content_length: Option<u64>,
+    pub min_pow_difficulty: Option<u64>,
+    pub auth_required: bool,
+    pub payment_required: bool,
+    pub restricted_writes: bool,
+    pub created_at_lower_limit: Option<u64>,
+    pub created_at_upper_limit: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct RelayInfo {
+    pub url: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub pubkey: Option<String>,
+    pub contact: Option<String>,
+    pub icon: Option<String>,
+    pub software: Option<String>,
+    pub version: Option<String>,
+    pub supported_nips: Vec<u32>,
+    pub limitation: RelayLimitation,
+    pub payments_url: Option<String>,
+    pub fees: Option<serde_json::Value>,
+    pub raw: serde_json::Value, // the full document, including fields not listed here
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RawRelayFrame {
+    pub request_id: String,
+    pub relay_url: String,
+    pub direction: String, // "sent" or "received"
+    pub json: String,
+    pub received_at_ms: u64, // since the frame was sent
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RawRelayResult {
+    pub request_id: String,
+    pub relay_url: String,
+    pub frames: Vec<RawRelayFrame>,
+    pub completed: bool, // a closing reply (EOSE, OK, CLOSED) arrived before the timeout
+}
+
+/// Whether a reply ends the exchange started by the sent frame
+fn is_final_reply(sent: &ClientMessage, reply: &RelayMessage) -> bool {
+    match (sent, reply) {
+        (ClientMessage::Req { subscription_id, .. }, RelayMessage::EndOfStoredEvents(id)) => subscription_id == id,
+        (ClientMessage::Req { subscription_id, .. }, RelayMessage::Closed { subscription_id: id, .. }) => {
+            subscription_id == id
+        }
+        (ClientMessage::Event(event), RelayMessage::Ok { event_id, .. }) => event.id == *event_id,
+        _ => false,
+    }
+}
+
+/// Send one raw client frame (REQ, EVENT, CLOSE, COUNT, ...) to a single relay over a
+/// throwaway connection. Every frame is emitted as a `nostr://raw` event as it
+/// happens; the reply stream ends at EOSE/OK/CLOSED or the timeout.
+#[tauri::command]
+pub async fn nostr_raw_request(
+    relay: String,
+    json: String,
+    timeout_secs: Option<u64>,
+    app: AppHandle,
+) -> Result<RawRelayResult, String> {
+    let message = ClientMessage::from_json(json.trim()).map_err(|e| format!("Invalid client frame: {}", e))?;
+    let request_id = Uuid::new_v4().to_string();
+    let timeout = Duration::from_secs(
+        timeout_secs
+            .unwrap_or(RAW_DEFAULT_TIMEOUT_SECS)
+            .clamp(1, RAW_MAX_TIMEOUT_SECS),
+    );
+
+    let client = Client::default();
+    client.add_relay(relay.as_str()).await.map_err(|e| e.to_string())?;
+    client
+        .connect_with_timeout(Duration::from_secs(RAW_DEFAULT_TIMEOUT_SECS))
+        .await;
+    let mut notifications = client.notifications();
+
+    let started = std::time::Instant::now();
+    let mut frames = Vec::new();
+    let record = |direction: &str, json: String, frames: &mut Vec<RawRelayFrame>| {
+        let frame = RawRelayFrame {
+            request_id: request_id.clone(),
+            relay_url: relay.clone(),
+            direction: direction.to_string(),
+            json,
+            received_at_ms: started.elapsed().as_millis() as u64,
+        };
+        let _ = app.emit("nostr://raw", &frame);
+        frames.push(frame);
+    };
+
+    if let Err(e) = client.send_msg_to([relay.as_str()], message.clone()).await {
+        let _ = client.disconnect().await;
+        return Err(format!("Failed to send to {}: {}", relay, e));
+    }
+    record("sent", message.as_json(), &mut frames);
+
+    let completed = tokio::time::timeout(timeout, async {
+        loop {
+            match notifications.recv().await {
+                Ok(RelayPoolNotification::Message { message: reply, .. }) => {
+                    let done = is_final_reply(&message, &reply);
+                    record("received", reply.as_json(), &mut frames);
+                    if done {
+                        return true;
+                    }
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return false,
+            }
+        }
+    })
+    .await
+    .unwrap_or(false);
+
+    let _ = client.disconnect().await;
+    Ok(RawRelayResult {
+        request_id,
+        relay_url: relay,
+        frames,
+        completed,
+    })
+}
+
+/// Fetch and parse a relay's NIP-11 information document (supported NIPs, limits,
+/// payment requirements)
+#[tauri::command]
+pub async fn relay_info(url: String) -> Result<RelayInfo, String> {
+    let url = url.trim().to_string();
+    let http_url = if let Some(rest) = url.strip_prefix("wss://") {
+        format!("https://{}", rest)
+    } else if let Some(rest) = url.strip_prefix("ws://") {
+        format!("http://{}", rest)
+    } else {
+        return Err(format!("Not a relay URL: {}", url));
+    };
+
+    let response = reqwest::Client::new()
+        .get(&http_url)
+        .header("Accept", "application/nostr+json")
+        .timeout(Duration::from_secs(RELAY_INFO_TIMEOUT_SECS))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach relay: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Relay returned {}", response.status()));
+    }
+    let raw: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|_| "Relay does not serve a NIP-11 information document".to_string())?;
+
+    let mut info: RelayInfo =
+        serde_json::from_value(raw.clone()).map_err(|e| format!("Invalid relay information document: {}", e))?;
+    info.url = url;
+    info.raw = raw;
+    Ok(info)
+}
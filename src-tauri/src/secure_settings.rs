@@ -0,0 +1,80 @@
+// Encrypted settings store for publishing credentials (S3 keys, SFTP passwords, ...).
+// Each setting is serialized to JSON and sealed with the device key, the same
+// XChaCha20-Poly1305 scheme as passwordless keystore entries, so credentials are
+// unreadable if the settings file is copied to another machine.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use zeroize::Zeroize;
+
+// Held across load, modify, and save so concurrent writers don't drop each other's settings
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize)]
+struct SealedSetting {
+    nonce: String,
+    ciphertext: String,
+}
+
+fn get_store_path() -> Result<PathBuf, String> {
+    Ok(crate::get_appstate_dir()?.join("secure_settings.json"))
+}
+
+fn load_store() -> Result<BTreeMap<String, SealedSetting>, String> {
+    let path = get_store_path()?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| format!("Corrupted settings store: {}", e))
+}
+
+fn save_store(store: &BTreeMap<String, SealedSetting>) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    crate::write_atomic(&get_store_path()?, json.as_bytes())
+}
+
+/// Read and decrypt a setting; None when it was never saved
+pub fn get<T: DeserializeOwned>(name: &str) -> Result<Option<T>, String> {
+    let store = load_store()?;
+    let Some(sealed) = store.get(name) else {
+        return Ok(None);
+    };
+    let mut key = crate::derive_key_from_device()?;
+    let opened = crate::decrypt_nsec(&sealed.nonce, &sealed.ciphertext, &key);
+    key.zeroize();
+    let mut json = opened.map_err(|_| format!("Could not decrypt the saved {} settings", name))?;
+    let value = serde_json::from_str(&json).map_err(|e| e.to_string());
+    json.zeroize();
+    value.map(Some)
+}
+
+/// Encrypt and save a setting, replacing any previous value
+pub fn put<T: Serialize>(name: &str, value: &T) -> Result<(), String> {
+    let _operation = crate::shutdown::begin("app-data-write", name);
+    let mut json = serde_json::to_string(value).map_err(|e| e.to_string())?;
+    let mut key = crate::derive_key_from_device()?;
+    let sealed = crate::encrypt_nsec(&json, &key);
+    key.zeroize();
+    json.zeroize();
+    let (nonce, ciphertext) = sealed?;
+
+    let _lock = STORE_LOCK.lock().unwrap();
+    let mut store = load_store()?;
+    store.insert(name.to_string(), SealedSetting { nonce, ciphertext });
+    save_store(&store)
+}
+
+/// Delete a setting
+pub fn remove(name: &str) -> Result<(), String> {
+    let _lock = STORE_LOCK.lock().unwrap();
+    let mut store = load_store()?;
+    if store.remove(name).is_some() {
+        save_store(&store)?;
+    }
+    Ok(())
+}
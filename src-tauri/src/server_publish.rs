@@ -0,0 +1,435 @@
+// Publish a feed to traditional web hosting over SFTP or FTPS: the feed's local files
+// are uploaded next to it (content-addressed, skipping ones already there), the feed
+// is rewritten to their public URLs, and feed.xml is pushed last. Targets and their
+// credentials live in the encrypted settings store. SFTP host keys are pinned on the
+// first connection, and a changed key refuses to connect until the user accepts it.
+// SFTP storage targets (StorageTarget::Sftp) upload through the same client, checking
+// host keys against ~/.ssh/known_hosts instead.
+
+use crate::feed_xml::{parse_xml, render_document};
+use crate::publish::{collect_local_refs, rewrite_local_refs};
+use crate::upload_ledger;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::time::Duration;
+use uuid::Uuid;
+
+// Name of the saved targets in the encrypted settings store
+const SERVER_TARGETS: &str = "server_publish_targets";
+
+const DEFAULT_FEED_FILE: &str = "feed.xml";
+
+// Connect and per-operation timeouts, so a stalled server fails instead of hanging
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const IO_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ServerTarget {
+    pub id: String,
+    pub name: String,
+    pub protocol: String, // "sftp" or "ftps"
+    pub host: String,
+    pub port: Option<u16>, // 22 for SFTP, 21 for FTPS
+    pub user: String,
+    pub password: String, // empty when read back; empty on save keeps the stored one
+    pub private_key_path: Option<String>, // SFTP only; the ssh agent is used when neither is set
+    pub remote_dir: String,
+    pub public_base_url: String, // URL the remote directory is served from
+    pub host_key_sha256: Option<String>, // SFTP host key, pinned on first connection
+}
+
+/// How an SFTP server's host key is checked
+#[derive(Clone, Copy)]
+enum HostKeyCheck {
+    // Against the fingerprint pinned on the target; none yet pins on first use
+    Pinned,
+    // Against the user's ~/.ssh/known_hosts, like the ssh client
+    KnownHosts,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ServerPublishResult {
+    pub feed_id: String,
+    pub feed_url: String,
+    pub uploaded: Vec<String>, // public URLs of files sent this time
+    pub reused: usize,         // files already on the server
+}
+
+impl ServerTarget {
+    fn ledger_key(&self) -> String {
+        format!("{}:{}@{}:{}", self.protocol, self.user, self.host, self.remote_dir)
+    }
+
+    fn public_url(&self, name: &str) -> String {
+        format!("{}/{}", crate::normalize_server_url(&self.public_base_url), name)
+    }
+}
+
+fn load_targets() -> Result<Vec<ServerTarget>, String> {
+    Ok(crate::secure_settings::get(SERVER_TARGETS)?.unwrap_or_default())
+}
+
+/// Open a TCP connection with the connect and I/O timeouts applied
+fn connect_tcp(host: &str, port: u16) -> Result<TcpStream, String> {
+    let addrs = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Could not resolve {}: {}", host, e))?;
+    let mut last_error = format!("Could not resolve {}", host);
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(IO_TIMEOUT)).map_err(|e| e.to_string())?;
+                stream.set_write_timeout(Some(IO_TIMEOUT)).map_err(|e| e.to_string())?;
+                return Ok(stream);
+            }
+            Err(e) => last_error = format!("Could not reach {}: {}", host, e),
+        }
+    }
+    Err(last_error)
+}
+
+/// Check the server's host key against ~/.ssh/known_hosts
+fn check_known_hosts(session: &ssh2::Session, host: &str, port: u16) -> Result<(), String> {
+    let (key, _) = session.host_key().ok_or("Server did not present a host key")?;
+    let mut known_hosts = session.known_hosts().map_err(|e| e.to_string())?;
+    if let Some(dirs) = directories::BaseDirs::new() {
+        let _ = known_hosts.read_file(
+            &dirs.home_dir().join(".ssh").join("known_hosts"),
+            ssh2::KnownHostFileKind::OpenSSH,
+        );
+    }
+    match known_hosts.check_port(host, port, key) {
+        ssh2::CheckResult::Match => Ok(()),
+        ssh2::CheckResult::Mismatch => Err(format!(
+            "The host key of {} does not match the one in ~/.ssh/known_hosts",
+            host
+        )),
+        ssh2::CheckResult::NotFound | ssh2::CheckResult::Failure => Err(format!(
+            "{} is not in ~/.ssh/known_hosts - connect once with ssh to trust its host key",
+            host
+        )),
+    }
+}
+
+/// An open connection to a target
+enum Connection {
+    // The session is kept alive alongside its SFTP channel
+    Sftp(ssh2::Session, ssh2::Sftp),
+    Ftps(Box<suppaftp::NativeTlsFtpStream>),
+}
+
+impl Connection {
+    /// Connect and log in, returning the SFTP host key fingerprint to pin
+    fn open(target: &ServerTarget, host_keys: HostKeyCheck) -> Result<(Connection, Option<String>), String> {
+        match target.protocol.as_str() {
+            "sftp" => {
+                let port = target.port.unwrap_or(22);
+                let tcp = connect_tcp(&target.host, port)?;
+                let mut session = ssh2::Session::new().map_err(|e| e.to_string())?;
+                session.set_timeout(IO_TIMEOUT.as_millis() as u32);
+                session.set_tcp_stream(tcp);
+                session.handshake().map_err(|e| format!("SSH handshake failed: {}", e))?;
+
+                let host_key = session
+                    .host_key_hash(ssh2::HashType::Sha256)
+                    .map(hex::encode)
+                    .ok_or("Server did not present a host key")?;
+                match host_keys {
+                    HostKeyCheck::KnownHosts => check_known_hosts(&session, &target.host, port)?,
+                    HostKeyCheck::Pinned => {
+                        if let Some(pinned) = target.host_key_sha256.as_deref().filter(|p| *p != host_key) {
+                            return Err(format!(
+                                "The host key of {} changed (was {}, now {}). If the server was reinstalled, accept the new key in the target's settings.",
+                                target.host, pinned, host_key
+                            ));
+                        }
+                    }
+                }
+
+                let auth = match (&target.private_key_path, target.password.is_empty()) {
+                    (Some(key_path), _) => {
+                        let passphrase = (!target.password.is_empty()).then_some(target.password.as_str());
+                        session.userauth_pubkey_file(&target.user, None, Path::new(key_path), passphrase)
+                    }
+                    (None, false) => session.userauth_password(&target.user, &target.password),
+                    (None, true) => session.userauth_agent(&target.user),
+                };
+                auth.map_err(|e| format!("SFTP login failed: {}", e))?;
+                let sftp = session.sftp().map_err(|e| e.to_string())?;
+                Ok((Connection::Sftp(session, sftp), Some(host_key)))
+            }
+            "ftps" => {
+                let connector = suppaftp::native_tls::TlsConnector::new().map_err(|e| e.to_string())?;
+                let tcp = connect_tcp(&target.host, target.port.unwrap_or(21))?;
+                let mut ftp = suppaftp::NativeTlsFtpStream::connect_with_stream(tcp)
+                    .map_err(|e| format!("Could not reach {}: {}", target.host, e))?
+                    .into_secure(suppaftp::NativeTlsConnector::from(connector), &target.host)
+                    .map_err(|e| format!("TLS negotiation failed: {}", e))?;
+                ftp.login(&target.user, &target.password)
+                    .map_err(|e| format!("FTPS login failed: {}", e))?;
+                ftp.transfer_type(suppaftp::types::FileType::Binary)
+                    .map_err(|e| e.to_string())?;
+                Ok((Connection::Ftps(Box::new(ftp)), None))
+            }
+            other => Err(format!("Unsupported protocol \"{}\" - use sftp or ftps", other)),
+        }
+    }
+
+    /// Create the remote directory and its parents; existing ones are fine
+    fn create_dir(&mut self, dir: &str) -> Result<(), String> {
+        let mut path = String::new();
+        for part in dir.split('/').filter(|p| !p.is_empty()) {
+            path = if path.is_empty() && !dir.starts_with('/') {
+                part.to_string()
+            } else {
+                format!("{}/{}", path, part)
+            };
+            match self {
+                Connection::Sftp(_, sftp) => {
+                    let _ = sftp.mkdir(Path::new(&path), 0o755);
+                }
+                Connection::Ftps(ftp) => {
+                    let _ = ftp.mkdir(&path);
+                }
+            }
+        }
+        if let Connection::Ftps(ftp) = self {
+            if !dir.is_empty() {
+                ftp.cwd(dir).map_err(|e| format!("Could not open {}: {}", dir, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a file into the remote directory, replacing any file of the same name.
+    /// Over SFTP it is written under a temporary name and renamed, so readers never
+    /// fetch a half-written feed (servers without overwriting renames briefly have
+    /// no copy between the unlink and the rename).
+    fn put(&mut self, dir: &str, name: &str, reader: &mut dyn Read) -> Result<(), String> {
+        match self {
+            Connection::Sftp(_, sftp) => {
+                let dir = dir.trim_end_matches('/');
+                let partial = format!("{}/.{}.partial", dir, name);
+                let final_path = format!("{}/{}", dir, name);
+                let mut remote = sftp.create(Path::new(&partial)).map_err(|e| e.to_string())?;
+                std::io::copy(reader, &mut remote).map_err(|e| format!("Upload of {} failed: {}", name, e))?;
+                drop(remote);
+                let flags = ssh2::RenameFlags::OVERWRITE | ssh2::RenameFlags::ATOMIC | ssh2::RenameFlags::NATIVE;
+                if sftp.rename(Path::new(&partial), Path::new(&final_path), Some(flags)).is_ok() {
+                    return Ok(());
+                }
+                // SFTP v3 servers (OpenSSH) ignore the flags and refuse to rename over an
+                // existing file, so the old copy is removed first
+                let _ = sftp.unlink(Path::new(&final_path));
+                sftp.rename(Path::new(&partial), Path::new(&final_path), None)
+                    .map_err(|e| format!("Could not move {} into place: {}", name, e))
+            }
+            Connection::Ftps(ftp) => ftp
+                .put_file(name, reader)
+                .map(|_| ())
+                .map_err(|e| format!("Upload of {} failed: {}", name, e)),
+        }
+    }
+
+    fn close(self) {
+        if let Connection::Ftps(mut ftp) = self {
+            let _ = ftp.quit();
+        }
+    }
+}
+
+/// Upload the feed's local files and then the rewritten feed. `runtime` runs the
+/// HTTP checks of files the ledger says are already on the server.
+fn publish_blocking(
+    feed_id: String,
+    target: &ServerTarget,
+    file_name: &str,
+    runtime: &tokio::runtime::Handle,
+) -> Result<(ServerPublishResult, Option<String>), String> {
+    let feed = crate::load_feed_local(feed_id)?;
+    let mut root = parse_xml(&feed.xml)?;
+    let mut refs = Vec::new();
+    collect_local_refs(&root, &mut refs);
+
+    let (mut connection, host_key) = Connection::open(target, HostKeyCheck::Pinned)?;
+    connection.create_dir(&target.remote_dir)?;
+
+    let ledger_key = target.ledger_key();
+    let mut urls = HashMap::new();
+    let mut uploaded = Vec::new();
+    let mut reused = 0;
+    for source in &refs {
+        let file_path = crate::file_url_path(source).ok_or_else(|| format!("Not a local file: {}", source))?;
+        let (sha256, size) = crate::hash_file_cached(&file_path)?;
+        if let Some(blob) = runtime.block_on(upload_ledger::verified_on(&sha256, &ledger_key)) {
+            urls.insert(source.as_str(), blob.url);
+            reused += 1;
+            continue;
+        }
+        let name = match file_path.extension() {
+            Some(ext) => format!("{}.{}", sha256, ext.to_string_lossy().to_lowercase()),
+            None => sha256.clone(),
+        };
+        let mut file =
+            fs::File::open(&file_path).map_err(|e| format!("Failed to read {}: {}", file_path.display(), e))?;
+        connection.put(&target.remote_dir, &name, &mut file)?;
+        let url = target.public_url(&name);
+        let _ = upload_ledger::record(&sha256, &url, size, &ledger_key, file_path.to_str());
+        uploaded.push(url.clone());
+        urls.insert(source.as_str(), url);
+    }
+
+    let url_refs: HashMap<&str, &str> = urls.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    rewrite_local_refs(&mut root, &url_refs);
+    let xml = render_document(&root);
+    connection.put(&target.remote_dir, file_name, &mut xml.as_bytes())?;
+    connection.close();
+
+    let feed_id = if refs.is_empty() {
+        feed.id
+    } else {
+        crate::save_feed_local(Some(feed.id), feed.title, feed.feed_type, xml, None)?.id
+    };
+    Ok((
+        ServerPublishResult {
+            feed_id,
+            feed_url: target.public_url(file_name),
+            uploaded,
+            reused,
+        },
+        host_key,
+    ))
+}
+
+/// List saved SFTP/FTPS targets, without their passwords
+#[tauri::command]
+pub fn server_targets_list() -> Result<Vec<ServerTarget>, String> {
+    Ok(load_targets()?
+        .into_iter()
+        .map(|target| ServerTarget {
+            password: String::new(),
+            ..target
+        })
+        .collect())
+}
+
+/// Upload one file into an SFTP directory with the user's ssh agent, checking the host
+/// key against ~/.ssh/known_hosts. Used by SFTP storage targets; blocks, so call it
+/// from a blocking task.
+pub fn sftp_upload_file(host: &str, port: u16, user: &str, remote_dir: &str, name: &str, file_path: &str) -> Result<(), String> {
+    let target = ServerTarget {
+        protocol: "sftp".to_string(),
+        host: host.to_string(),
+        port: Some(port),
+        user: user.to_string(),
+        remote_dir: remote_dir.to_string(),
+        ..Default::default()
+    };
+    let (mut connection, _) = Connection::open(&target, HostKeyCheck::KnownHosts)?;
+    connection.create_dir(remote_dir)?;
+    let mut file = fs::File::open(file_path).map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+    connection.put(remote_dir, name, &mut file)?;
+    connection.close();
+    Ok(())
+}
+
+/// Save an SFTP/FTPS target (encrypted). A new target gets an id; an empty password
+/// keeps the saved one. The pinned host key is kept unless the host or port changed;
+/// a new key is only trusted through `server_target_accept_host_key`.
+#[tauri::command]
+pub fn server_target_save(mut target: ServerTarget) -> Result<ServerTarget, String> {
+    if !matches!(target.protocol.as_str(), "sftp" | "ftps") {
+        return Err("Protocol must be sftp or ftps".to_string());
+    }
+    if target.host.trim().is_empty() || target.user.trim().is_empty() || target.public_base_url.trim().is_empty() {
+        return Err("A host, user, and public URL are required".to_string());
+    }
+
+    let mut targets = load_targets()?;
+    if target.id.is_empty() {
+        target.id = Uuid::new_v4().to_string();
+    }
+    let existing = targets.iter().find(|t| t.id == target.id);
+    if let Some(existing) = existing {
+        if target.password.is_empty() {
+            target.password = existing.password.clone();
+        }
+    }
+    target.host_key_sha256 = existing
+        .filter(|e| e.host.eq_ignore_ascii_case(&target.host) && e.port == target.port)
+        .and_then(|e| e.host_key_sha256.clone());
+    targets.retain(|t| t.id != target.id);
+    targets.push(target.clone());
+    crate::secure_settings::put(SERVER_TARGETS, &targets)?;
+
+    target.password = String::new();
+    Ok(target)
+}
+
+/// Trust a new SFTP host key for a target, after the user has compared the
+/// fingerprint from the "host key changed" error with the server's
+#[tauri::command]
+pub fn server_target_accept_host_key(id: String, host_key_sha256: String) -> Result<(), String> {
+    let fingerprint = host_key_sha256.trim().to_lowercase();
+    if fingerprint.len() != 64 || !fingerprint.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("The host key fingerprint must be a SHA-256 hex string".to_string());
+    }
+    let mut targets = load_targets()?;
+    let target = targets
+        .iter_mut()
+        .find(|t| t.id == id)
+        .ok_or("Publishing target not found")?;
+    target.host_key_sha256 = Some(fingerprint);
+    crate::secure_settings::put(SERVER_TARGETS, &targets)
+}
+
+/// Delete a saved SFTP/FTPS target
+#[tauri::command]
+pub fn server_target_delete(id: String) -> Result<(), String> {
+    let mut targets = load_targets()?;
+    targets.retain(|t| t.id != id);
+    crate::secure_settings::put(SERVER_TARGETS, &targets)
+}
+
+/// Push a library feed and its local files to a saved SFTP/FTPS target. The feed is
+/// written as `file_name` (default feed.xml) in the target's remote directory.
+#[tauri::command]
+pub async fn publish_feed_server(
+    feed_id: String,
+    target_id: String,
+    file_name: Option<String>,
+) -> Result<ServerPublishResult, String> {
+    let target = load_targets()?
+        .into_iter()
+        .find(|t| t.id == target_id)
+        .ok_or("Publishing target not found")?;
+    let file_name = file_name
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_FEED_FILE.to_string());
+    if file_name.contains('/') || file_name.contains('\\') {
+        return Err("The feed file name must not contain path separators".to_string());
+    }
+    let _operation = crate::shutdown::begin("publish", &feed_id);
+
+    let publish_target = target.clone();
+    let runtime = tokio::runtime::Handle::current();
+    let (result, host_key) =
+        tokio::task::spawn_blocking(move || publish_blocking(feed_id, &publish_target, &file_name, &runtime))
+            .await
+            .map_err(|e| e.to_string())??;
+
+    // Pin the host key seen on the first successful connection
+    if target.host_key_sha256.is_none() && host_key.is_some() {
+        let mut targets = load_targets()?;
+        if let Some(saved) = targets.iter_mut().find(|t| t.id == target.id) {
+            saved.host_key_sha256 = host_key;
+        }
+        crate::secure_settings::put(SERVER_TARGETS, &targets)?;
+    }
+    Ok(result)
+}
@@ -0,0 +1,144 @@
+// Auto-lock after inactivity: once the configured idle timeout passes without user
+// activity (any command the frontend invokes), every signing session and the library
+// key are dropped from memory and `session://locked` is emitted so the frontend asks
+// for the password (or OS prompt) again. Watch-only sessions hold no secrets and stay
+// open.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tauri::{AppHandle, Emitter, Manager};
+
+// How often the idle monitor checks the timeout
+const IDLE_CHECK_INTERVAL_SECS: u64 = 15;
+
+// Idle timeout in seconds (0 = never lock) and the time of the last user activity
+static IDLE_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(0);
+static LAST_ACTIVITY: AtomicU64 = AtomicU64::new(0);
+
+// Set when signing sessions are locked, cleared once a key is unlocked again
+static LOCKED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct SessionLockSettings {
+    pub idle_timeout_mins: Option<u64>, // None never locks
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SessionLockedEvent {
+    pub pubkeys: Vec<String>,
+    pub reason: String, // "idle" or "manual"
+}
+
+fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(crate::get_appstate_dir()?.join("session_lock.json"))
+}
+
+fn load_settings() -> SessionLockSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Record user activity, restarting the idle countdown
+pub fn touch() {
+    LAST_ACTIVITY.store(crate::get_current_timestamp().unwrap_or_default(), Ordering::SeqCst);
+}
+
+/// Whether sessions were locked and no key has been unlocked since
+pub fn is_locked(state: &crate::NostrState) -> bool {
+    if LOCKED.load(Ordering::SeqCst) && state.signing_keys().is_ok() {
+        LOCKED.store(false, Ordering::SeqCst);
+    }
+    LOCKED.load(Ordering::SeqCst)
+}
+
+/// Wrap the command handler so every command the frontend invokes counts as activity
+pub fn touch_on_invoke<R: tauri::Runtime>(
+    handler: impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        touch();
+        handler(invoke)
+    }
+}
+
+/// Drop every session that holds keys, returning the hex pubkeys that were locked
+async fn lock_sessions(state: &crate::NostrState) -> Vec<String> {
+    let locked = state.sign_out(|_, has_keys| has_keys);
+    let pubkeys = locked.iter().map(|session| session.public_key.to_hex()).collect();
+    for session in locked {
+        let _ = session.client.disconnect().await;
+    }
+    pubkeys
+}
+
+async fn lock_and_notify(app: &AppHandle, reason: &str) -> Vec<String> {
+    let pubkeys = lock_sessions(&app.state::<crate::NostrState>()).await;
+    crate::library_crypto::lock();
+    if !pubkeys.is_empty() {
+        LOCKED.store(true, Ordering::SeqCst);
+        let _ = app.emit(
+            "session://locked",
+            SessionLockedEvent {
+                pubkeys: pubkeys.clone(),
+                reason: reason.to_string(),
+            },
+        );
+    }
+    pubkeys
+}
+
+/// Load the idle timeout and lock signing sessions once it passes
+pub fn spawn_idle_monitor(app: AppHandle) {
+    let timeout_mins = load_settings().idle_timeout_mins.unwrap_or(0);
+    IDLE_TIMEOUT_SECS.store(timeout_mins * 60, Ordering::SeqCst);
+    touch();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(IDLE_CHECK_INTERVAL_SECS)).await;
+
+            let timeout = IDLE_TIMEOUT_SECS.load(Ordering::SeqCst);
+            let now = crate::get_current_timestamp().unwrap_or_default();
+            if timeout > 0 && now.saturating_sub(LAST_ACTIVITY.load(Ordering::SeqCst)) >= timeout {
+                lock_and_notify(&app, "idle").await;
+                touch();
+            }
+        }
+    });
+}
+
+/// Get the auto-lock settings
+#[tauri::command]
+pub fn session_lock_get_settings() -> SessionLockSettings {
+    load_settings()
+}
+
+/// Set the idle timeout in minutes; None or 0 turns auto-lock off
+#[tauri::command]
+pub fn session_lock_set_settings(idle_timeout_mins: Option<u64>) -> Result<SessionLockSettings, String> {
+    let idle_timeout_mins = idle_timeout_mins.filter(|m| *m > 0);
+    let settings = SessionLockSettings { idle_timeout_mins };
+    let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    fs::write(get_settings_path()?, json).map_err(|e| e.to_string())?;
+
+    IDLE_TIMEOUT_SECS.store(idle_timeout_mins.unwrap_or(0) * 60, Ordering::SeqCst);
+    touch();
+    Ok(settings)
+}
+
+/// Report user activity from the frontend (input, navigation)
+#[tauri::command]
+pub fn session_touch() {
+    touch();
+}
+
+/// Lock all signing sessions now. Returns the pubkeys that were locked.
+#[tauri::command]
+pub async fn session_lock_now(app: AppHandle) -> Vec<String> {
+    lock_and_notify(&app, "manual").await
+}
@@ -0,0 +1,227 @@
+// First-run setup: report what already exists and whether the default relays and a
+// suggested Blossom server are reachable, then create or import a key and record
+// the chosen defaults. The onboarding wizard drives these commands step by step.
+
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+// Blossom server offered when the user has none
+const SUGGESTED_BLOSSOM_SERVER: &str = "https://blossom.primal.net";
+
+// How long each connectivity test may take
+const SETUP_TEST_TIMEOUT_SECS: u64 = 5;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SetupDefaults {
+    pub relays: Vec<String>,
+    pub blossom_server: Option<String>,
+    pub completed_at: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EndpointCheck {
+    pub url: String,
+    pub reachable: bool,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SetupStatus {
+    pub completed: bool,
+    pub defaults: Option<SetupDefaults>,
+    pub stored_key_count: usize,
+    pub feed_count: usize,
+    pub relays: Vec<EndpointCheck>,
+    pub blossom_server: Option<EndpointCheck>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum SetupKey {
+    Generate {
+        password: Option<String>,
+        label: Option<String>,
+    },
+    Import {
+        nsec: String,
+        password: Option<String>,
+        label: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SetupResult {
+    pub pubkey: Option<String>,
+    pub npub: Option<String>,
+    pub generated_nsec: Option<String>, // shown once so the user can back it up
+    pub defaults: SetupDefaults,
+}
+
+fn get_setup_path() -> Result<PathBuf, String> {
+    Ok(crate::get_appstate_dir()?.join("setup.json"))
+}
+
+fn load_setup_defaults() -> Option<SetupDefaults> {
+    let content = fs::read_to_string(get_setup_path().ok()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Relays to connect to on login: the ones chosen during setup, else the defaults
+pub fn bootstrap_relays() -> Vec<String> {
+    load_setup_defaults()
+        .map(|d| d.relays)
+        .filter(|relays| !relays.is_empty())
+        .unwrap_or_else(|| crate::DEFAULT_RELAYS.iter().map(|r| r.to_string()).collect())
+}
+
+/// Blossom server chosen during setup, used until the user publishes a server list
+pub fn default_blossom_server() -> Option<String> {
+    load_setup_defaults().and_then(|d| d.blossom_server)
+}
+
+/// Try connecting to each relay with a throwaway client
+async fn check_relays(relays: &[String]) -> Vec<EndpointCheck> {
+    let client = Client::default();
+    for relay in relays {
+        let _ = client.add_relay(relay.as_str()).await;
+    }
+    client
+        .connect_with_timeout(Duration::from_secs(SETUP_TEST_TIMEOUT_SECS))
+        .await;
+
+    let statuses = crate::collect_relay_status(&client).await;
+    let _ = client.disconnect().await;
+
+    relays
+        .iter()
+        .map(|url| {
+            let status = statuses
+                .iter()
+                .find(|s| s.url.trim_end_matches('/') == url.trim_end_matches('/'));
+            EndpointCheck {
+                url: url.clone(),
+                reachable: status.map(|s| s.connected).unwrap_or(false),
+                latency_ms: status.and_then(|s| s.latency_ms),
+                error: match status {
+                    Some(s) if !s.connected => Some(format!("Relay is {}", s.status)),
+                    Some(_) => None,
+                    None => Some("Invalid relay URL".to_string()),
+                },
+            }
+        })
+        .collect()
+}
+
+/// Check that a Blossom server answers HTTP requests
+async fn check_blossom_server(server_url: &str) -> EndpointCheck {
+    let started = Instant::now();
+    let result = reqwest::Client::new()
+        .get(crate::normalize_server_url(server_url))
+        .timeout(Duration::from_secs(SETUP_TEST_TIMEOUT_SECS))
+        .send()
+        .await;
+
+    let (reachable, error) = match result {
+        Ok(response) if !response.status().is_server_error() => (true, None),
+        Ok(response) => (false, Some(format!("Server error {}", response.status()))),
+        Err(e) => (false, Some(e.to_string())),
+    };
+    EndpointCheck {
+        url: server_url.to_string(),
+        reachable,
+        latency_ms: reachable.then(|| started.elapsed().as_millis() as u64),
+        error,
+    }
+}
+
+/// Report existing keys and feeds, and test the relays and Blossom server the
+/// wizard will suggest (skipped when `test_network` is false)
+#[tauri::command]
+pub async fn setup_status(test_network: bool) -> Result<SetupStatus, String> {
+    let defaults = load_setup_defaults();
+    let stored_key_count = crate::load_keystore()?.keys.len();
+    let feed_count = crate::list_feeds_local()?.len();
+
+    let (relays, blossom_server) = if test_network {
+        let blossom = defaults
+            .as_ref()
+            .and_then(|d| d.blossom_server.clone())
+            .unwrap_or_else(|| SUGGESTED_BLOSSOM_SERVER.to_string());
+        (
+            check_relays(&bootstrap_relays()).await,
+            Some(check_blossom_server(&blossom).await),
+        )
+    } else {
+        (Vec::new(), None)
+    };
+
+    Ok(SetupStatus {
+        completed: defaults.is_some(),
+        defaults,
+        stored_key_count,
+        feed_count,
+        relays,
+        blossom_server,
+    })
+}
+
+/// Finish setup: optionally create or import a key into the keystore (password or
+/// device protected), and record the chosen relays and Blossom server
+#[tauri::command]
+pub fn setup_apply(
+    key: Option<SetupKey>,
+    relays: Vec<String>,
+    blossom_server: Option<String>,
+) -> Result<SetupResult, String> {
+    for relay in &relays {
+        if !relay.starts_with("wss://") && !relay.starts_with("ws://") {
+            return Err(format!("Not a relay URL: {}", relay));
+        }
+    }
+    if let Some(server) = &blossom_server {
+        if !server.starts_with("https://") && !server.starts_with("http://") {
+            return Err(format!("Not a Blossom server URL: {}", server));
+        }
+    }
+
+    let mut result = SetupResult {
+        pubkey: None,
+        npub: None,
+        generated_nsec: None,
+        defaults: SetupDefaults {
+            relays,
+            blossom_server,
+            completed_at: crate::get_current_timestamp()?,
+        },
+    };
+
+    if let Some(key) = key {
+        let (nsec, password, label, generated) = match key {
+            SetupKey::Generate { password, label } => {
+                let keys = Keys::generate();
+                let nsec = keys.secret_key().to_bech32().map_err(|e| e.to_string())?;
+                (nsec, password, label, true)
+            }
+            SetupKey::Import { nsec, password, label } => (nsec.trim().to_string(), password, label, false),
+        };
+
+        let keys = Keys::new(SecretKey::from_bech32(&nsec).map_err(|e| e.to_string())?);
+        match password.filter(|p| !p.is_empty()) {
+            Some(password) => crate::store_key_with_password(nsec.clone(), password, label)?,
+            None => crate::store_key_without_password(nsec.clone(), label)?,
+        }
+        result.pubkey = Some(keys.public_key().to_hex());
+        result.npub = Some(keys.public_key().to_bech32().map_err(|e| e.to_string())?);
+        if generated {
+            result.generated_nsec = Some(nsec);
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&result.defaults).map_err(|e| e.to_string())?;
+    fs::write(get_setup_path()?, json).map_err(|e| e.to_string())?;
+    Ok(result)
+}
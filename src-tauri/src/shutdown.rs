@@ -0,0 +1,167 @@
+// Graceful shutdown: uploads, publishes, and keystore/feed writes register themselves
+// while they run. Closing the window or quitting is deferred: the frontend gets
+// `app://shutdown-pending` and flushes its autosaves, and the exit waits for the last
+// in-flight operation (publish runs checkpoint each step), up to SHUTDOWN_MAX_WAIT.
+// Then the upload queue saves what is left, the app emits `app://safe-to-close`, and exits.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+// How long the frontend gets to flush its autosaves before the exit goes ahead anyway
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+// How long in-flight operations get before the app quits without them
+const SHUTDOWN_MAX_WAIT: Duration = Duration::from_secs(120);
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+static OPERATIONS: Mutex<BTreeMap<u64, InFlightOperation>> = Mutex::new(BTreeMap::new());
+static NEXT_OPERATION_ID: AtomicU64 = AtomicU64::new(1);
+static CLOSING: AtomicBool = AtomicBool::new(false);
+static FLUSHED: AtomicBool = AtomicBool::new(false);
+static EXITING: AtomicBool = AtomicBool::new(false);
+static CLOSE_REQUEST: AtomicU64 = AtomicU64::new(0); // so a cancelled close's timers expire
+static DEADLINE: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InFlightOperation {
+    pub kind: String, // "upload", "publish", "keystore-write", "feed-write", "app-data-write"
+    pub label: String,
+    pub started_at: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ShutdownStatus {
+    pub closing: bool,
+    pub operations: Vec<InFlightOperation>,
+    pub deadline: Option<u64>, // unix seconds; the app quits then even with operations left
+}
+
+/// Keeps an operation registered until dropped
+pub struct OperationGuard {
+    id: u64,
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        OPERATIONS.lock().unwrap().remove(&self.id);
+        finish_if_ready();
+    }
+}
+
+/// Remember the app handle so a deferred shutdown can complete
+pub fn init(app: AppHandle) {
+    let _ = APP.set(app);
+}
+
+/// Register an in-flight operation for as long as the returned guard lives
+pub fn begin(kind: &str, label: impl Into<String>) -> OperationGuard {
+    let id = NEXT_OPERATION_ID.fetch_add(1, Ordering::SeqCst);
+    OPERATIONS.lock().unwrap().insert(
+        id,
+        InFlightOperation {
+            kind: kind.to_string(),
+            label: label.into(),
+            started_at: crate::get_current_timestamp().unwrap_or_default(),
+        },
+    );
+    OperationGuard { id }
+}
+
+fn status() -> ShutdownStatus {
+    let closing = CLOSING.load(Ordering::SeqCst);
+    ShutdownStatus {
+        closing,
+        operations: OPERATIONS.lock().unwrap().values().cloned().collect(),
+        deadline: closing.then(|| DEADLINE.load(Ordering::SeqCst)),
+    }
+}
+
+fn close_request_current(request: u64) -> bool {
+    CLOSING.load(Ordering::SeqCst) && CLOSE_REQUEST.load(Ordering::SeqCst) == request
+}
+
+/// Exit once a close was requested, the frontend has flushed, and nothing is in flight
+fn finish_if_ready() {
+    let ready = CLOSING.load(Ordering::SeqCst)
+        && FLUSHED.load(Ordering::SeqCst)
+        && OPERATIONS.lock().unwrap().is_empty();
+    if ready {
+        if let Some(app) = APP.get() {
+            finish(app);
+        }
+    }
+}
+
+/// Save the upload queue, signal `app://safe-to-close`, and exit (only once)
+fn finish(app: &AppHandle) {
+    if EXITING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    crate::upload_queue::flush();
+    let _ = app.emit("app://safe-to-close", ());
+    app.exit(0);
+}
+
+/// Called when the window is closing or the app is quitting. Returns true when the
+/// exit must wait: the frontend gets `app://shutdown-pending` to flush its autosaves,
+/// and the app exits once that and every in-flight operation are done, or at the deadline.
+pub fn defer_exit() -> bool {
+    let Some(app) = APP.get() else {
+        return false;
+    };
+    if EXITING.load(Ordering::SeqCst) {
+        return false;
+    }
+    if CLOSING.swap(true, Ordering::SeqCst) {
+        return true;
+    }
+    FLUSHED.store(false, Ordering::SeqCst);
+    let request = CLOSE_REQUEST.fetch_add(1, Ordering::SeqCst) + 1;
+    let now = crate::get_current_timestamp().unwrap_or_default();
+    DEADLINE.store(now + SHUTDOWN_MAX_WAIT.as_secs(), Ordering::SeqCst);
+    let _ = app.emit("app://shutdown-pending", status());
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(FLUSH_TIMEOUT).await;
+        if close_request_current(request) && !FLUSHED.swap(true, Ordering::SeqCst) {
+            finish_if_ready();
+        }
+        tokio::time::sleep(SHUTDOWN_MAX_WAIT - FLUSH_TIMEOUT).await;
+        if close_request_current(request) {
+            finish(&app);
+        }
+    });
+    true
+}
+
+/// Get in-flight operations and whether a shutdown is waiting on them
+#[tauri::command]
+pub fn app_shutdown_status() -> ShutdownStatus {
+    status()
+}
+
+/// Tell the backend the frontend has saved its pending edits during a deferred close
+#[tauri::command]
+pub fn app_shutdown_flushed() {
+    if CLOSING.load(Ordering::SeqCst) {
+        FLUSHED.store(true, Ordering::SeqCst);
+        finish_if_ready();
+    }
+}
+
+/// Keep the app open after a deferred close (operations continue normally)
+#[tauri::command]
+pub fn app_cancel_shutdown() {
+    CLOSING.store(false, Ordering::SeqCst);
+}
+
+/// Quit now without waiting for in-flight operations. Interrupted publish runs
+/// resume from their last checkpoint next time, and queued uploads are restored.
+#[tauri::command]
+pub fn app_force_quit(app: AppHandle) {
+    finish(&app);
+}
@@ -0,0 +1,470 @@
+// Directory submission helpers: check a feed against a directory's own requirements
+// (Apple Podcasts is the strictest), gather the details its submission form asks for,
+// and track where each feed has been submitted in the library database.
+
+use crate::feed_xml::{parse_rss, RssDocument, XmlNode};
+use crate::validation::{Issues, ValidationReport};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+
+// Submission states a feed can be in for one directory
+const SUBMISSION_STATUSES: &[&str] = &["draft", "submitted", "approved", "rejected", "removed"];
+
+// Enclosure types Apple Podcasts accepts
+const APPLE_ENCLOSURE_TYPES: &[&str] = &[
+    "audio/mpeg",
+    "audio/x-m4a",
+    "audio/mp4",
+    "video/mp4",
+    "video/quicktime",
+    "video/x-m4v",
+    "application/pdf",
+];
+
+// Artwork sizes Apple accepts (square, in pixels)
+const APPLE_ARTWORK_PX: std::ops::RangeInclusive<u32> = 1400..=3000;
+// Most of a remote image read while looking for its dimensions in the header
+const ARTWORK_PROBE_BYTES: usize = 1024 * 1024;
+
+#[derive(Serialize, Clone)]
+pub struct DirectoryInfo {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub submit_url: &'static str,
+}
+
+// Directories with submission support, in the order the UI offers them
+const DIRECTORIES: &[DirectoryInfo] = &[
+    DirectoryInfo {
+        id: "apple",
+        name: "Apple Podcasts",
+        submit_url: "https://podcastsconnect.apple.com/my-podcasts/new-feed",
+    },
+    DirectoryInfo {
+        id: "spotify",
+        name: "Spotify",
+        submit_url: "https://creators.spotify.com/pod/dashboard/podcast/add",
+    },
+    DirectoryInfo {
+        id: "podcastindex",
+        name: "Podcast Index",
+        submit_url: "https://podcastindex.org/add",
+    },
+    DirectoryInfo {
+        id: "pocketcasts",
+        name: "Pocket Casts",
+        submit_url: "https://pocketcasts.com/submit/",
+    },
+];
+
+#[derive(Serialize)]
+pub struct SubmissionPacket {
+    pub directory: DirectoryInfo,
+    pub feed_id: String,
+    pub feed_url: Option<String>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub owner_email: Option<String>,
+    pub language: Option<String>,
+    pub categories: Vec<String>,
+    pub artwork_url: Option<String>,
+    pub explicit: Option<String>,
+    pub report: ValidationReport,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SubmissionRecord {
+    pub feed_id: String,
+    pub directory: String,
+    pub status: String,
+    pub listing_url: Option<String>,
+    pub notes: Option<String>,
+    pub updated_at: u64,
+}
+
+fn find_directory(directory: &str) -> Result<&'static DirectoryInfo, String> {
+    DIRECTORIES
+        .iter()
+        .find(|d| d.id == directory)
+        .ok_or_else(|| format!("Unknown directory: {}", directory))
+}
+
+/// The feed's public URL from <atom:link rel="self">
+fn self_url(channel: &XmlNode) -> Option<&str> {
+    channel
+        .children_named("atom:link")
+        .find(|l| l.attr("rel") == Some("self"))
+        .and_then(|l| l.attr("href"))
+}
+
+/// Top-level <itunes:category> names, with any subcategory as "Parent > Child"
+fn categories(channel: &XmlNode) -> Vec<String> {
+    channel
+        .children_named("itunes:category")
+        .filter_map(|c| {
+            let name = c.attr("text")?;
+            Some(
+                match c.child("itunes:category").and_then(|s| s.attr("text")) {
+                    Some(sub) => format!("{} > {}", name, sub),
+                    None => name.to_string(),
+                },
+            )
+        })
+        .collect()
+}
+
+/// Rules every directory needs: a public https feed URL, a title, and episodes
+fn check_directory_common(doc: &RssDocument, feed_type: &str, issues: &mut Issues) {
+    let Some(channel) = doc.channel() else {
+        issues.error("parse-error", "Feed has no <channel>");
+        return;
+    };
+
+    if feed_type == "publisher" {
+        issues.error(
+            "publisher-not-listable",
+            "Publisher feeds only list albums; submit the album feeds instead",
+        );
+    }
+    match self_url(channel) {
+        Some(url) if url.starts_with("https://") => {}
+        Some(url) => issues.error(
+            "feed-url-not-https",
+            format!("Feed URL must use https: {}", url),
+        ),
+        None => issues.error(
+            "missing-feed-url",
+            "Feed has no public URL (<atom:link rel=\"self\">); publish it before submitting",
+        ),
+    }
+    if channel.child_text("title").is_none() {
+        issues.error("missing-title", "Channel is missing a <title>");
+    }
+    if doc.items().is_empty() && feed_type != "publisher" {
+        issues.error("no-items", "Directories reject feeds without episodes");
+    }
+    let local = doc
+        .items()
+        .iter()
+        .filter_map(|item| item.child("enclosure")?.attr("url"))
+        .any(|url| url.starts_with("file://"));
+    if local {
+        issues.error(
+            "local-enclosure",
+            "Some enclosures still point at local files; upload them first",
+        );
+    }
+}
+
+/// Pixel size of remote artwork, read from the start of the image. None when it
+/// cannot be fetched or decoded.
+async fn artwork_dimensions(url: &str) -> Option<(u32, u32)> {
+    let (_, response) = crate::import::fetch_public(url).await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    let mut data = Vec::new();
+    let mut body = response.bytes_stream();
+    while let Some(chunk) = body.next().await {
+        data.extend_from_slice(&chunk.ok()?);
+        if let Ok(dimensions) = crate::artwork::dimensions(&data) {
+            return Some(dimensions);
+        }
+        if data.len() >= ARTWORK_PROBE_BYTES {
+            return None;
+        }
+    }
+    None
+}
+
+/// Apple Podcasts requirements on top of the common rules. `artwork` is the channel
+/// artwork's measured size, if it could be read.
+fn check_apple(doc: &RssDocument, artwork: Option<(u32, u32)>, issues: &mut Issues) {
+    let Some(channel) = doc.channel() else {
+        return;
+    };
+
+    if channel.child_text("description").is_none() && channel.child_text("itunes:summary").is_none()
+    {
+        issues.error(
+            "missing-description",
+            "Apple requires a channel <description>",
+        );
+    }
+    if channel.child_text("language").is_none() {
+        issues.error("missing-language", "Apple requires a channel <language>");
+    }
+    if categories(channel).is_empty() {
+        issues.error(
+            "missing-category",
+            "Apple requires at least one <itunes:category>",
+        );
+    }
+    match channel.child_text("itunes:explicit") {
+        Some("true") | Some("false") => {}
+        Some(other) => issues.error(
+            "invalid-explicit",
+            format!(
+                "<itunes:explicit> must be \"true\" or \"false\", found \"{}\"",
+                other
+            ),
+        ),
+        None => issues.error("missing-explicit", "Apple requires <itunes:explicit>"),
+    }
+    match channel.child("itunes:image").and_then(|i| i.attr("href")) {
+        Some(href) => {
+            let lower = href.to_lowercase();
+            if !lower.starts_with("https://") {
+                issues.error(
+                    "artwork-not-https",
+                    "Artwork must be hosted at an https URL",
+                );
+            }
+            if !lower.ends_with(".jpg") && !lower.ends_with(".jpeg") && !lower.ends_with(".png") {
+                issues.warning("artwork-format", "Apple only accepts JPEG or PNG artwork");
+            }
+            match artwork {
+                Some((width, height))
+                    if width == height && APPLE_ARTWORK_PX.contains(&width) => {}
+                Some((width, height)) => issues.warning(
+                    "artwork-size",
+                    format!(
+                        "Apple requires square artwork between 1400x1400 and 3000x3000 pixels; this is {}x{}",
+                        width, height
+                    ),
+                ),
+                None if lower.starts_with("https://") => issues.warning(
+                    "artwork-unreadable",
+                    "Could not download the artwork to check its size",
+                ),
+                None => {}
+            }
+        }
+        None => issues.error(
+            "missing-image",
+            "Apple requires channel artwork (<itunes:image>)",
+        ),
+    }
+    if channel.child_text("itunes:author").is_none() {
+        issues.warning(
+            "missing-author",
+            "Missing <itunes:author>; Apple shows it under the title",
+        );
+    }
+    if channel
+        .child("itunes:owner")
+        .and_then(|o| o.child_text("itunes:email"))
+        .is_none()
+    {
+        issues.warning(
+            "missing-owner-email",
+            "Missing <itunes:owner><itunes:email>; Apple uses it to verify ownership",
+        );
+    }
+    if channel.child_text("podcast:medium") == Some("music") {
+        issues.warning(
+            "music-medium",
+            "Apple Podcasts does not list music-only feeds; it may reject this album",
+        );
+    }
+
+    for (i, item) in doc.items().iter().enumerate() {
+        let context = format!(
+            "Track {} (\"{}\")",
+            i + 1,
+            item.child_text("title").unwrap_or("untitled")
+        );
+        if item.child_text("title").is_none() {
+            issues.error("missing-item-title", format!("{} has no <title>", context));
+        }
+        if item.child_text("guid").is_none() {
+            issues.error("missing-item-guid", format!("{} has no <guid>", context));
+        }
+        if item.child_text("pubDate").is_none() {
+            issues.warning("missing-pubdate", format!("{} has no <pubDate>", context));
+        }
+        let Some(enclosure) = item.child("enclosure") else {
+            issues.error(
+                "missing-enclosure",
+                format!("{} has no <enclosure>", context),
+            );
+            continue;
+        };
+        let mime = enclosure.attr("type").unwrap_or_default();
+        if !APPLE_ENCLOSURE_TYPES.contains(&mime) {
+            issues.error(
+                "unsupported-enclosure-type",
+                format!(
+                    "{}: Apple does not accept enclosure type \"{}\"",
+                    context, mime
+                ),
+            );
+        }
+        if !enclosure
+            .attr("url")
+            .unwrap_or_default()
+            .starts_with("https://")
+        {
+            issues.error(
+                "enclosure-not-https",
+                format!("{}: enclosure must use https", context),
+            );
+        }
+        if enclosure
+            .attr("length")
+            .and_then(|l| l.parse::<u64>().ok())
+            .unwrap_or(0)
+            == 0
+        {
+            issues.warning(
+                "missing-enclosure-length",
+                format!(
+                    "{}: enclosure length should be the file size in bytes",
+                    context
+                ),
+            );
+        }
+    }
+}
+
+/// Podcast Index keys feeds on their podcast:guid
+fn check_podcastindex(doc: &RssDocument, issues: &mut Issues) {
+    if doc
+        .channel()
+        .and_then(|c| c.child_text("podcast:guid"))
+        .is_none()
+    {
+        issues.warning(
+            "missing-guid",
+            "Missing <podcast:guid>; Podcast Index will assign one from the feed URL",
+        );
+    }
+}
+
+/// List directories with submission support
+#[tauri::command]
+pub fn submission_list_directories() -> Vec<DirectoryInfo> {
+    DIRECTORIES.to_vec()
+}
+
+/// Check a library feed against a directory's requirements and gather the details its
+/// submission form asks for
+#[tauri::command]
+pub async fn submission_prepare(feed_id: String, directory: String) -> Result<SubmissionPacket, String> {
+    let info = find_directory(&directory)?;
+    let feed = crate::load_feed_local(feed_id)?;
+    let doc = parse_rss(&feed.xml)?;
+    let artwork_url = doc
+        .channel()
+        .and_then(|c| c.child("itunes:image"))
+        .and_then(|i| i.attr("href"))
+        .map(str::to_string);
+
+    let mut issues = Issues::default();
+    check_directory_common(&doc, &feed.feed_type, &mut issues);
+    match info.id {
+        "apple" => {
+            let artwork = match artwork_url.as_deref() {
+                Some(url) if url.to_lowercase().starts_with("https://") => artwork_dimensions(url).await,
+                _ => None,
+            };
+            check_apple(&doc, artwork, &mut issues)
+        }
+        "podcastindex" => check_podcastindex(&doc, &mut issues),
+        _ => {}
+    }
+    let issues = issues.into_vec();
+    let report = ValidationReport {
+        valid: !issues.iter().any(|i| i.severity == "error"),
+        feed_type: feed.feed_type.clone(),
+        issues,
+    };
+
+    let channel = doc.channel();
+    let text = |name: &str| channel.and_then(|c| c.child_text(name)).map(str::to_string);
+    Ok(SubmissionPacket {
+        directory: info.clone(),
+        feed_id: feed.id,
+        feed_url: channel.and_then(self_url).map(str::to_string),
+        title: text("title"),
+        author: text("itunes:author"),
+        owner_email: channel
+            .and_then(|c| c.child("itunes:owner"))
+            .and_then(|o| o.child_text("itunes:email"))
+            .map(str::to_string),
+        language: text("language"),
+        categories: channel.map(categories).unwrap_or_default(),
+        artwork_url,
+        explicit: text("itunes:explicit"),
+        report,
+    })
+}
+
+/// Record a feed's submission status for a directory
+#[tauri::command]
+pub fn submission_set_status(
+    feed_id: String,
+    directory: String,
+    status: String,
+    listing_url: Option<String>,
+    notes: Option<String>,
+) -> Result<SubmissionRecord, String> {
+    find_directory(&directory)?;
+    if !SUBMISSION_STATUSES.contains(&status.as_str()) {
+        return Err(format!("Unknown submission status: {}", status));
+    }
+    crate::load_feed_local(feed_id.clone())?;
+
+    let record = SubmissionRecord {
+        feed_id,
+        directory,
+        status,
+        listing_url: listing_url.filter(|u| !u.trim().is_empty()),
+        notes: notes.filter(|n| !n.trim().is_empty()),
+        updated_at: crate::get_current_timestamp()?,
+    };
+    let conn = crate::open_library()?;
+    conn.execute(
+        "INSERT OR REPLACE INTO directory_submissions
+         (feed_id, directory, status, listing_url, notes, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![
+            record.feed_id,
+            record.directory,
+            record.status,
+            record.listing_url,
+            record.notes,
+            record.updated_at
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(record)
+}
+
+/// List submission records, for one feed or the whole library
+#[tauri::command]
+pub fn submission_list(feed_id: Option<String>) -> Result<Vec<SubmissionRecord>, String> {
+    let conn = crate::open_library()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT feed_id, directory, status, listing_url, notes, updated_at
+             FROM directory_submissions
+             WHERE ?1 IS NULL OR feed_id = ?1
+             ORDER BY updated_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([&feed_id], |row| {
+            Ok(SubmissionRecord {
+                feed_id: row.get(0)?,
+                directory: row.get(1)?,
+                status: row.get(2)?,
+                listing_url: row.get(3)?,
+                notes: row.get(4)?,
+                updated_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,291 @@
+// Per-track timeline shared by chapters, transcript cues, and value time splits
+
+use crate::feed_xml::{parse_rss, XmlNode};
+use crate::validation::{Issues, ValidationIssue};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TimelineCue {
+    pub kind: String, // "chapter", "transcript", or "value-split"
+    pub start_ms: u64,
+    pub end_ms: Option<u64>,
+    pub label: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TrackTimeline {
+    pub guid: Option<String>,
+    pub title: String,
+    pub duration_ms: Option<u64>,
+    pub cues: Vec<TimelineCue>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TimelineReport {
+    pub timeline: TrackTimeline,
+    pub issues: Vec<ValidationIssue>,
+}
+
+/// Parse a timestamp into milliseconds. Accepts plain seconds ("90.5"),
+/// MM:SS, HH:MM:SS, and SRT/VTT fractions ("00:01:30,500" / "00:01:30.500").
+pub fn parse_timestamp(value: &str) -> Option<u64> {
+    let value = value.trim().replace(',', ".");
+    let parts: Vec<&str> = value.split(':').collect();
+    if parts.is_empty() || parts.len() > 3 {
+        return None;
+    }
+
+    let mut secs = 0.0;
+    for part in &parts {
+        let n = part.parse::<f64>().ok()?;
+        if !n.is_finite() || n < 0.0 {
+            return None;
+        }
+        secs = secs * 60.0 + n;
+    }
+    Some((secs * 1000.0).round() as u64)
+}
+
+/// Format milliseconds as HH:MM:SS.mmm
+pub fn format_timestamp(ms: u64) -> String {
+    let secs = ms / 1000;
+    format!("{:02}:{:02}:{:02}.{:03}", secs / 3600, (secs % 3600) / 60, secs % 60, ms % 1000)
+}
+
+/// Value time splits declared inside an item's <podcast:value>
+fn value_split_cues(item: &XmlNode, issues: &mut Issues) -> Vec<TimelineCue> {
+    let mut cues = Vec::new();
+    for split in item.descendants_named("podcast:valueTimeSplit") {
+        let Some(start_ms) = split.attr("startTime").and_then(parse_timestamp) else {
+            issues.error("invalid-timestamp", "A <podcast:valueTimeSplit> has a missing or invalid startTime");
+            continue;
+        };
+        let Some(length_ms) = split.attr("duration").and_then(parse_timestamp) else {
+            issues.error(
+                "invalid-timestamp",
+                format!("Value split at {} has a missing or invalid duration", format_timestamp(start_ms)),
+            );
+            continue;
+        };
+
+        let label = split
+            .child("podcast:remoteItem")
+            .and_then(|r| r.attr("itemGuid").or(r.attr("feedGuid")))
+            .or_else(|| split.child("podcast:valueRecipient").and_then(|r| r.attr("name")))
+            .unwrap_or("value split")
+            .to_string();
+
+        cues.push(TimelineCue {
+            kind: "value-split".to_string(),
+            start_ms,
+            end_ms: Some(start_ms + length_ms),
+            label,
+        });
+    }
+    cues
+}
+
+/// Chapters from a podcast namespace chapters JSON document
+fn chapter_cues(json: &str, issues: &mut Issues) -> Result<Vec<TimelineCue>, String> {
+    let doc: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| format!("Invalid chapters JSON: {}", e))?;
+    let chapters = doc
+        .get("chapters")
+        .and_then(|c| c.as_array())
+        .ok_or("Chapters JSON is missing a \"chapters\" array")?;
+
+    let mut cues = Vec::new();
+    for (i, chapter) in chapters.iter().enumerate() {
+        let time = |key: &str| match chapter.get(key) {
+            Some(serde_json::Value::Number(n)) => {
+                n.as_f64().filter(|s| *s >= 0.0).map(|s| (s * 1000.0).round() as u64)
+            }
+            Some(serde_json::Value::String(s)) => parse_timestamp(s),
+            _ => None,
+        };
+
+        let Some(start_ms) = time("startTime") else {
+            issues.error("invalid-timestamp", format!("Chapter {} has a missing or invalid startTime", i + 1));
+            continue;
+        };
+        cues.push(TimelineCue {
+            kind: "chapter".to_string(),
+            start_ms,
+            end_ms: time("endTime"),
+            label: chapter
+                .get("title")
+                .and_then(|t| t.as_str())
+                .unwrap_or_default()
+                .to_string(),
+        });
+    }
+    Ok(cues)
+}
+
+/// Transcript cues from SRT, WebVTT, or podcast namespace JSON transcripts
+fn transcript_cues(text: &str, issues: &mut Issues) -> Vec<TimelineCue> {
+    if let Ok(doc) = serde_json::from_str::<serde_json::Value>(text) {
+        let segments = doc.get("segments").and_then(|s| s.as_array()).cloned().unwrap_or_default();
+        return segments
+            .iter()
+            .filter_map(|segment| {
+                let start = segment.get("startTime")?.as_f64()?;
+                let end = segment.get("endTime").and_then(|e| e.as_f64());
+                Some(TimelineCue {
+                    kind: "transcript".to_string(),
+                    start_ms: (start.max(0.0) * 1000.0).round() as u64,
+                    end_ms: end.map(|e| (e.max(0.0) * 1000.0).round() as u64),
+                    label: segment.get("body").and_then(|b| b.as_str()).unwrap_or_default().to_string(),
+                })
+            })
+            .collect();
+    }
+
+    let lines: Vec<&str> = text.lines().collect();
+    let mut cues = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let Some((start, rest)) = line.split_once("-->") else {
+            continue;
+        };
+        // VTT cue settings may follow the end time
+        let end = rest.split_whitespace().next().unwrap_or_default();
+        let (Some(start_ms), Some(end_ms)) = (parse_timestamp(start), parse_timestamp(end)) else {
+            issues.error("invalid-timestamp", format!("Transcript line {} has an invalid cue timing", i + 1));
+            continue;
+        };
+        cues.push(TimelineCue {
+            kind: "transcript".to_string(),
+            start_ms,
+            end_ms: Some(end_ms),
+            label: lines.get(i + 1).map(|l| l.trim().to_string()).unwrap_or_default(),
+        });
+    }
+    cues
+}
+
+/// Build a track's timeline from its <item>, plus optional chapters and transcript content
+pub fn build_item_timeline(
+    item: &XmlNode,
+    chapters_json: Option<&str>,
+    transcript: Option<&str>,
+    issues: &mut Issues,
+) -> Result<TrackTimeline, String> {
+    let mut cues = value_split_cues(item, issues);
+    if let Some(json) = chapters_json {
+        cues.extend(chapter_cues(json, issues)?);
+    }
+    if let Some(text) = transcript {
+        cues.extend(transcript_cues(text, issues));
+    }
+
+    Ok(TrackTimeline {
+        guid: item.child_text("guid").map(str::to_string),
+        title: item.child_text("title").unwrap_or("untitled").to_string(),
+        duration_ms: item.child_text("itunes:duration").and_then(parse_timestamp),
+        cues,
+    })
+}
+
+/// Flag cues that fall outside the track, run backwards, or overlap where they must not
+pub fn check_timeline(timeline: &TrackTimeline, issues: &mut Issues) {
+    let track = &timeline.title;
+
+    if timeline.duration_ms.is_none() && !timeline.cues.is_empty() {
+        issues.warning(
+            "missing-duration",
+            format!("Track \"{}\" has no <itunes:duration>, so its cues cannot be checked", track),
+        );
+    }
+
+    for cue in &timeline.cues {
+        if let Some(end_ms) = cue.end_ms {
+            if end_ms < cue.start_ms {
+                issues.error(
+                    "cue-ends-before-start",
+                    format!("Track \"{}\": {} \"{}\" ends before it starts", track, cue.kind, cue.label),
+                );
+            }
+        }
+
+        let Some(duration_ms) = timeline.duration_ms else {
+            continue;
+        };
+        if cue.start_ms >= duration_ms {
+            issues.error(
+                "cue-beyond-duration",
+                format!(
+                    "Track \"{}\": {} \"{}\" starts at {}, after the track ends ({})",
+                    track,
+                    cue.kind,
+                    cue.label,
+                    format_timestamp(cue.start_ms),
+                    format_timestamp(duration_ms)
+                ),
+            );
+        } else if cue.end_ms.is_some_and(|end| end > duration_ms) {
+            issues.warning(
+                "cue-exceeds-duration",
+                format!(
+                    "Track \"{}\": {} \"{}\" runs past the end of the track ({})",
+                    track,
+                    cue.kind,
+                    cue.label,
+                    format_timestamp(duration_ms)
+                ),
+            );
+        }
+    }
+
+    // Value splits redirect payments, so overlapping ranges are ambiguous
+    let mut splits: Vec<&TimelineCue> = timeline.cues.iter().filter(|c| c.kind == "value-split").collect();
+    splits.sort_by_key(|c| c.start_ms);
+    for pair in splits.windows(2) {
+        if pair[0].end_ms.is_some_and(|end| end > pair[1].start_ms) {
+            issues.error(
+                "overlapping-value-splits",
+                format!(
+                    "Track \"{}\": value splits at {} and {} overlap",
+                    track,
+                    format_timestamp(pair[0].start_ms),
+                    format_timestamp(pair[1].start_ms)
+                ),
+            );
+        }
+    }
+
+    let chapter_starts: Vec<u64> = timeline
+        .cues
+        .iter()
+        .filter(|c| c.kind == "chapter")
+        .map(|c| c.start_ms)
+        .collect();
+    if chapter_starts.windows(2).any(|w| w[1] < w[0]) {
+        issues.warning("unsorted-chapters", format!("Track \"{}\": chapters are not in time order", track));
+    }
+}
+
+/// Build and check the timeline of one track (by item guid) against its chapters and transcript
+#[tauri::command]
+pub fn track_timeline_check(
+    xml: String,
+    item_guid: String,
+    chapters_json: Option<String>,
+    transcript: Option<String>,
+) -> Result<TimelineReport, String> {
+    let doc = parse_rss(&xml)?;
+    let item = doc
+        .items()
+        .into_iter()
+        .find(|item| item.child_text("guid") == Some(item_guid.as_str()))
+        .ok_or_else(|| format!("Track not found: {}", item_guid))?;
+
+    let mut issues = Issues::default();
+    let mut timeline = build_item_timeline(item, chapters_json.as_deref(), transcript.as_deref(), &mut issues)?;
+    check_timeline(&timeline, &mut issues);
+    timeline.cues.sort_by_key(|c| c.start_ms);
+
+    Ok(TimelineReport {
+        timeline,
+        issues: issues.into_vec(),
+    })
+}
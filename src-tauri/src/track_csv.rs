@@ -0,0 +1,239 @@
+// CSV round-trip of per-track metadata
+//
+// Column schema (header row required, column order free):
+//   guid      - item GUID; used to match rows to existing tracks (optional)
+//   title     - track title
+//   file      - enclosure URL
+//   isrc      - ISRC code, stored as <podcast:txt purpose="isrc">
+//   duration  - HH:MM:SS, MM:SS, or plain seconds
+//   splits    - item value recipients as "name|address|split|type" joined by ";"
+//               (type is "node" or "lnaddress"); empty keeps the existing block
+
+use crate::feed_xml::{parse_rss, render_document, XmlNode};
+use crate::formatting::{format_itunes_duration, parse_duration};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Default)]
+struct TrackRow {
+    #[serde(default)]
+    guid: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    file: String,
+    #[serde(default)]
+    isrc: String,
+    #[serde(default)]
+    duration: String,
+    #[serde(default)]
+    splits: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CsvImportResult {
+    pub updated: usize,
+    pub added: usize,
+    pub feed_id: String,
+}
+
+/// Serialize an item's value recipients into the splits column format
+fn splits_to_string(item: &XmlNode) -> String {
+    item.child("podcast:value")
+        .map(|value| {
+            value
+                .children_named("podcast:valueRecipient")
+                .map(|r| {
+                    format!(
+                        "{}|{}|{}|{}",
+                        r.attr("name").unwrap_or_default(),
+                        r.attr("address").unwrap_or_default(),
+                        r.attr("split").unwrap_or_default(),
+                        r.attr("type").unwrap_or("node")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(";")
+        })
+        .unwrap_or_default()
+}
+
+/// Build a <podcast:value> block from the splits column format
+fn splits_to_value(splits: &str) -> Result<XmlNode, String> {
+    let mut recipients = Vec::new();
+    for entry in splits.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        let fields: Vec<&str> = entry.split('|').map(str::trim).collect();
+        if fields.len() < 3 {
+            return Err(format!("Invalid split \"{}\" - expected name|address|split|type", entry));
+        }
+        fields[2]
+            .parse::<u32>()
+            .map_err(|_| format!("Invalid split percentage in \"{}\"", entry))?;
+        recipients.push(
+            XmlNode::new("podcast:valueRecipient")
+                .with_attr("name", fields[0])
+                .with_attr("address", fields[1])
+                .with_attr("split", fields[2])
+                .with_attr("type", fields.get(3).copied().unwrap_or("node")),
+        );
+    }
+
+    let method = if recipients.iter().any(|r| r.attr("type") == Some("lnaddress")) {
+        "lnaddress"
+    } else {
+        "keysend"
+    };
+    let mut value = XmlNode::new("podcast:value")
+        .with_attr("type", "lightning")
+        .with_attr("method", method);
+    value.children = recipients;
+    Ok(value)
+}
+
+/// Get an item's ISRC from <podcast:txt purpose="isrc">
+fn item_isrc(item: &XmlNode) -> String {
+    item.children_named("podcast:txt")
+        .find(|t| t.attr("purpose") == Some("isrc"))
+        .map(|t| t.text.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Apply a CSV row onto an item
+fn apply_row(item: &mut XmlNode, row: &TrackRow) -> Result<(), String> {
+    if !row.title.trim().is_empty() {
+        item.set_child_text("title", row.title.trim());
+    }
+
+    if !row.file.trim().is_empty() {
+        let url = row.file.trim();
+        match item.child_mut("enclosure") {
+            Some(enclosure) => {
+                enclosure.attrs.retain(|(k, _)| k != "url");
+                enclosure.attrs.insert(0, ("url".to_string(), url.to_string()));
+            }
+            None => item.children.push(
+                XmlNode::new("enclosure")
+                    .with_attr("url", url)
+                    .with_attr("length", "0")
+                    .with_attr("type", crate::guess_mime_type(url)),
+            ),
+        }
+    }
+
+    if !row.isrc.trim().is_empty() {
+        item.children
+            .retain(|c| !(c.name == "podcast:txt" && c.attr("purpose") == Some("isrc")));
+        item.children.push(
+            XmlNode::new("podcast:txt")
+                .with_attr("purpose", "isrc")
+                .with_text(row.isrc.trim()),
+        );
+    }
+
+    if !row.duration.trim().is_empty() {
+        let secs = parse_duration(&row.duration)
+            .ok_or_else(|| format!("Invalid duration \"{}\"", row.duration))?;
+        item.set_child_text("itunes:duration", &format_itunes_duration(secs));
+    }
+
+    if !row.splits.trim().is_empty() {
+        let value = splits_to_value(&row.splits)?;
+        item.remove_children("podcast:value");
+        item.children.push(value);
+    }
+
+    Ok(())
+}
+
+/// Import track metadata from a CSV file into a local feed. Rows with a guid update
+/// the matching track; rows without one update tracks by position, and extra rows
+/// become new tracks.
+#[tauri::command]
+pub fn feed_import_tracks_csv(feed_id: String, path: String) -> Result<CsvImportResult, String> {
+    let feed = crate::load_feed_local(feed_id)?;
+    let mut doc = parse_rss(&feed.xml)?;
+
+    let mut reader = csv::Reader::from_path(&path).map_err(|e| format!("Failed to read CSV: {}", e))?;
+    let rows: Vec<TrackRow> = reader
+        .deserialize()
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Invalid CSV: {}", e))?;
+
+    let channel = doc.root.child_mut("channel").ok_or("Missing <channel> element")?;
+    let mut updated = 0;
+    let mut added = 0;
+
+    for (position, row) in rows.iter().enumerate() {
+        let guid = row.guid.trim();
+        let target = if guid.is_empty() {
+            channel
+                .children
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| c.name == "item")
+                .nth(position)
+                .map(|(i, _)| i)
+        } else {
+            channel
+                .children
+                .iter()
+                .position(|c| c.name == "item" && c.child_text("guid") == Some(guid))
+        };
+
+        match target {
+            Some(index) => {
+                apply_row(&mut channel.children[index], row)?;
+                updated += 1;
+            }
+            None => {
+                let item_guid = if guid.is_empty() { Uuid::new_v4().to_string() } else { guid.to_string() };
+                let mut item = XmlNode::new("item").with_child(
+                    XmlNode::new("guid")
+                        .with_attr("isPermaLink", "false")
+                        .with_text(&item_guid),
+                );
+                apply_row(&mut item, row)?;
+                channel.children.push(item);
+                added += 1;
+            }
+        }
+    }
+
+    let xml = render_document(&doc.root);
+    let saved = crate::save_feed_local(Some(feed.id), feed.title, feed.feed_type, xml, None)?;
+
+    Ok(CsvImportResult {
+        updated,
+        added,
+        feed_id: saved.id,
+    })
+}
+
+/// Export a local feed's track metadata to a CSV file
+#[tauri::command]
+pub fn feed_export_tracks_csv(feed_id: String, path: String) -> Result<usize, String> {
+    let feed = crate::load_feed_local(feed_id)?;
+    let doc = parse_rss(&feed.xml)?;
+
+    let mut writer = csv::Writer::from_path(&path).map_err(|e| format!("Failed to write CSV: {}", e))?;
+    let items = doc.items();
+    for item in &items {
+        writer
+            .serialize(TrackRow {
+                guid: item.child_text("guid").unwrap_or_default().to_string(),
+                title: item.child_text("title").unwrap_or_default().to_string(),
+                file: item
+                    .child("enclosure")
+                    .and_then(|e| e.attr("url"))
+                    .unwrap_or_default()
+                    .to_string(),
+                isrc: item_isrc(item),
+                duration: item.child_text("itunes:duration").unwrap_or_default().to_string(),
+                splits: splits_to_string(item),
+            })
+            .map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+
+    Ok(items.len())
+}
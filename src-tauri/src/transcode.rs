@@ -0,0 +1,296 @@
+// Audio transcoding through ffmpeg: turn a WAV/FLAC master into the MP3, Opus, AAC, or
+// FLAC file used for an enclosure, optionally normalized to a loudness target. ffmpeg is
+// bundled with the app (tauri.conf.json externalBin, installed next to the executable);
+// PATH is only a fallback for development builds. Encoding runs in a task workspace
+// and the finished file is moved into place, so a failed or interrupted encode never
+// leaves a partial file at the destination.
+
+use crate::audio::read_audio_metadata;
+use crate::disk_space::{ensure_space, TaskError};
+use crate::loudness;
+use crate::workspace::TaskWorkspace;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+
+// Range accepted for a normalization target
+const MIN_TARGET_LUFS: f64 = -30.0;
+const MAX_TARGET_LUFS: f64 = -5.0;
+
+struct OutputFormat {
+    id: &'static str,
+    extension: &'static str,
+    codec: &'static str, // ffmpeg encoder
+    mime_type: &'static str,
+    default_kbps: Option<u32>, // None for lossless
+    min_kbps: u32,
+    max_kbps: u32,
+    max_sample_rate: Option<u32>, // None when the encoder always picks its own rate
+}
+
+// Formats transcode_audio can produce
+const FORMATS: &[OutputFormat] = &[
+    OutputFormat {
+        id: "mp3",
+        extension: "mp3",
+        codec: "libmp3lame",
+        mime_type: "audio/mpeg",
+        default_kbps: Some(256),
+        min_kbps: 32,
+        max_kbps: 320,
+        max_sample_rate: Some(48_000),
+    },
+    OutputFormat {
+        id: "opus",
+        extension: "opus",
+        codec: "libopus",
+        mime_type: "audio/opus",
+        default_kbps: Some(160),
+        min_kbps: 6,
+        max_kbps: 510,
+        max_sample_rate: None,
+    },
+    OutputFormat {
+        id: "aac",
+        extension: "m4a",
+        codec: "aac",
+        mime_type: "audio/mp4",
+        default_kbps: Some(256),
+        min_kbps: 32,
+        max_kbps: 512,
+        max_sample_rate: Some(48_000),
+    },
+    OutputFormat {
+        id: "flac",
+        extension: "flac",
+        codec: "flac",
+        mime_type: "audio/flac",
+        default_kbps: None,
+        min_kbps: 0,
+        max_kbps: 0,
+        max_sample_rate: Some(655_350),
+    },
+];
+
+#[derive(Serialize, Clone)]
+pub struct TranscodeProgress {
+    pub input: String,
+    pub format: String,
+    pub percent: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TranscodeResult {
+    pub input: String,
+    pub output_path: String,
+    pub format: String,
+    pub bitrate_kbps: Option<u32>, // None for lossless output
+    pub mime_type: String,
+    pub length: u64,
+    pub duration_secs: f64,
+}
+
+/// The ffmpeg binary: a bundled copy next to the executable, else the one on PATH
+pub fn ffmpeg_path() -> PathBuf {
+    let name = if cfg!(windows) { "ffmpeg.exe" } else { "ffmpeg" };
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(name)))
+        .filter(|path| path.is_file())
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
+/// Where the output goes when no path is given: next to the input, never over it
+/// or any other existing file (a numbered name is used instead)
+fn default_output(input: &Path, extension: &str, bitrate_kbps: Option<u32>) -> PathBuf {
+    let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+    let mut output = input.with_extension(extension);
+    if output == input {
+        let suffix = bitrate_kbps.map(|kbps| format!("-{}k", kbps)).unwrap_or_else(|| "-transcoded".to_string());
+        output = input.with_file_name(format!("{}{}.{}", stem, suffix, extension));
+    }
+    let base = output.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let mut n = 1;
+    while output.exists() {
+        n += 1;
+        output = input.with_file_name(format!("{} ({}).{}", base, n, extension));
+    }
+    output
+}
+
+/// Move the finished file into place. Across volumes it is copied to a temporary
+/// file next to the destination and renamed, so the destination is never half written.
+fn move_into_place(staged: &Path, dest: &Path) -> Result<(), String> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    if fs::rename(staged, dest).is_ok() {
+        return Ok(());
+    }
+    let mut partial = dest.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    let copied = fs::copy(staged, &partial)
+        .and_then(|_| fs::File::open(&partial)?.sync_all())
+        .and_then(|_| fs::rename(&partial, dest));
+    if let Err(e) = copied {
+        let _ = fs::remove_file(&partial);
+        return Err(format!("Failed to write {}: {}", dest.display(), e));
+    }
+    Ok(())
+}
+
+/// Run ffmpeg, emitting `transcode://progress` from its -progress output
+async fn run_ffmpeg(
+    args: Vec<String>,
+    duration_secs: f64,
+    progress: TranscodeProgress,
+    app: &AppHandle,
+) -> Result<(), String> {
+    let mut child = tokio::process::Command::new(ffmpeg_path())
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to run ffmpeg (is it installed?): {}", e))?;
+
+    // Drain stderr alongside stdout so a chatty encoder cannot block on a full pipe
+    let mut stderr = child.stderr.take().ok_or("ffmpeg stderr unavailable")?;
+    let stderr_task = tokio::spawn(async move {
+        let mut text = String::new();
+        let _ = stderr.read_to_string(&mut text).await;
+        text
+    });
+
+    let stdout = child.stdout.take().ok_or("ffmpeg stdout unavailable")?;
+    let mut lines = BufReader::new(stdout).lines();
+    while let Some(line) = lines.next_line().await.map_err(|e| e.to_string())? {
+        // out_time_ms is in microseconds despite its name
+        let Some(micros) = line
+            .strip_prefix("out_time_us=")
+            .or_else(|| line.strip_prefix("out_time_ms="))
+            .and_then(|v| v.trim().parse::<f64>().ok())
+        else {
+            continue;
+        };
+        if duration_secs > 0.0 {
+            let percent = (micros / 1_000_000.0 / duration_secs * 100.0).clamp(0.0, 100.0);
+            let _ = app.emit("transcode://progress", TranscodeProgress { percent, ..progress.clone() });
+        }
+    }
+
+    let status = child.wait().await.map_err(|e| e.to_string())?;
+    let stderr = stderr_task.await.unwrap_or_default();
+    if !status.success() {
+        let detail = stderr.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or("unknown error");
+        return Err(format!("ffmpeg failed: {}", detail.trim()));
+    }
+    let _ = app.emit("transcode://progress", TranscodeProgress { percent: 100.0, ..progress });
+    Ok(())
+}
+
+/// Encode an audio file to "mp3", "opus", "aac", or "flac" at the given bitrate (kbps;
+/// ignored for FLAC), emitting `transcode://progress`. The output defaults to the
+/// input's folder with the new extension, numbered when that name is taken; an
+/// explicit `output` is replaced. Tags are carried over. With
+/// `normalize_lufs`, the audio is first measured and then normalized to that
+/// integrated loudness.
+#[tauri::command]
+pub async fn transcode_audio(
+    input: String,
+    format: String,
+    bitrate: Option<u32>,
+    output: Option<String>,
+    normalize_lufs: Option<f64>,
+    app: AppHandle,
+) -> Result<TranscodeResult, TaskError> {
+    let spec = FORMATS
+        .iter()
+        .find(|f| f.id == format)
+        .ok_or_else(|| format!("Unsupported output format: {}", format))?;
+    let bitrate_kbps = spec.default_kbps.map(|default| bitrate.unwrap_or(default));
+    if let Some(kbps) = bitrate_kbps {
+        if !(spec.min_kbps..=spec.max_kbps).contains(&kbps) {
+            return Err(format!(
+                "{} bitrate must be between {} and {} kbps",
+                spec.id, spec.min_kbps, spec.max_kbps
+            )
+            .into());
+        }
+    }
+
+    if let Some(target) = normalize_lufs {
+        if !(MIN_TARGET_LUFS..=MAX_TARGET_LUFS).contains(&target) {
+            return Err(format!(
+                "Loudness target must be between {} and {} LUFS",
+                MIN_TARGET_LUFS, MAX_TARGET_LUFS
+            )
+            .into());
+        }
+    }
+
+    let input_path = Path::new(&input);
+    let source = read_audio_metadata(input_path)?;
+    let output_path = match output {
+        Some(output) => PathBuf::from(output),
+        None => default_output(input_path, spec.extension, bitrate_kbps),
+    };
+    if output_path == input_path {
+        return Err("Output would overwrite the input file".into());
+    }
+
+    // Lossless output is at most about the size of the master
+    let estimate = match bitrate_kbps {
+        Some(kbps) => (source.duration_secs * f64::from(kbps) * 1000.0 / 8.0) as u64,
+        None => source.file_size,
+    };
+    let workspace = TaskWorkspace::create("transcode")?;
+    ensure_space(workspace.path(), estimate)?;
+    ensure_space(&output_path, estimate)?;
+
+    let staged = workspace.path().join(format!("output.{}", spec.extension));
+    let mut args: Vec<String> = ["-hide_banner", "-nostdin", "-loglevel", "error", "-y", "-i"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    args.push(input.clone());
+    args.extend(["-vn", "-map_metadata", "0", "-c:a", spec.codec].iter().map(|s| s.to_string()));
+    if let Some(kbps) = bitrate_kbps {
+        args.extend(["-b:a".to_string(), format!("{}k", kbps)]);
+    }
+    if let Some(target) = normalize_lufs {
+        let measured = loudness::measure(&input, target).await?;
+        args.extend(["-af".to_string(), loudness::normalize_filter(&measured, target)]);
+        // loudnorm resamples to 192 kHz internally; keep the master's rate where the
+        // encoder supports it (MP3 and AAC stop at 48 kHz, Opus is always 48 kHz)
+        if let (Some(rate), Some(max_rate)) = (source.sample_rate, spec.max_sample_rate) {
+            args.extend(["-ar".to_string(), rate.min(max_rate).to_string()]);
+        }
+    }
+    args.extend(["-progress", "pipe:1", "-nostats"].iter().map(|s| s.to_string()));
+    args.push(staged.to_string_lossy().to_string());
+
+    let progress = TranscodeProgress {
+        input: input.clone(),
+        format: spec.id.to_string(),
+        percent: 0.0,
+    };
+    run_ffmpeg(args, source.duration_secs, progress, &app).await?;
+
+    move_into_place(&staged, &output_path)?;
+    let length = fs::metadata(&output_path).map_err(|e| e.to_string())?.len();
+    Ok(TranscodeResult {
+        input,
+        output_path: output_path.to_string_lossy().to_string(),
+        format: spec.id.to_string(),
+        bitrate_kbps,
+        mime_type: spec.mime_type.to_string(),
+        length,
+        duration_secs: source.duration_secs,
+    })
+}
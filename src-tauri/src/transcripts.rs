@@ -0,0 +1,255 @@
+// Transcript and lyrics files: check SRT, WebVTT, and LRC (synced lyrics) files,
+// upload them to Blossom, and point the track's <podcast:transcript> at them.
+
+use crate::feed_xml::{parse_rss, render_document, XmlNode};
+use crate::storage::{self, StorageTarget};
+use crate::timeline::{format_timestamp, parse_timestamp};
+use crate::validation::{Issues, ValidationIssue};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+// Largest transcript file accepted; lyrics and captions are small text files
+const MAX_TRANSCRIPT_BYTES: u64 = 5 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize)]
+pub struct TranscriptCheck {
+    pub format: String, // "srt", "vtt", or "lrc"
+    pub mime_type: String,
+    pub cue_count: usize,
+    pub last_cue_ms: Option<u64>,
+    pub issues: Vec<ValidationIssue>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TranscriptAttachment {
+    pub feed_id: String,
+    pub item_guid: String,
+    pub url: String,
+    pub mime_type: String,
+}
+
+/// Format and MIME type from the file extension
+fn transcript_format(path: &Path) -> Result<(&'static str, &'static str), String> {
+    match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+        Some("srt") => Ok(("srt", "application/srt")),
+        Some("vtt") => Ok(("vtt", "text/vtt")),
+        Some("lrc") => Ok(("lrc", "text/x-lrc")),
+        _ => Err("Transcript must be an .srt, .vtt, or .lrc file".to_string()),
+    }
+}
+
+/// Cue start times from SRT or WebVTT "start --> end" lines
+fn timed_text_cues(text: &str, issues: &mut Issues) -> Vec<u64> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut starts = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let Some((start, rest)) = line.split_once("-->") else {
+            continue;
+        };
+        let end = rest.split_whitespace().next().unwrap_or_default();
+        let (Some(start_ms), Some(end_ms)) = (parse_timestamp(start), parse_timestamp(end)) else {
+            issues.error("invalid-timestamp", format!("Line {}: invalid cue timing", i + 1));
+            continue;
+        };
+        if end_ms < start_ms {
+            issues.error("cue-ends-before-start", format!("Line {}: cue ends before it starts", i + 1));
+        }
+        if lines.get(i + 1).map(|l| l.trim().is_empty()).unwrap_or(true) {
+            issues.warning("empty-cue", format!("Line {}: cue has no text", i + 1));
+        }
+        starts.push(start_ms);
+    }
+    starts
+}
+
+/// Line start times from LRC "[mm:ss.xx]lyric" lines; "[ar:...]"-style tags are metadata
+fn lrc_cues(text: &str, issues: &mut Issues) -> Vec<u64> {
+    let mut starts = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let mut rest = line.trim();
+        // A line may carry several timestamps when a lyric repeats
+        while let Some((tag, after)) = rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+            rest = after;
+            if tag.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                continue;
+            }
+            match parse_timestamp(tag) {
+                Some(start_ms) => starts.push(start_ms),
+                None => issues.error("invalid-timestamp", format!("Line {}: invalid timestamp [{}]", i + 1, tag)),
+            }
+        }
+    }
+    starts.sort_unstable();
+    starts
+}
+
+/// Check transcript text in the given format
+fn check_transcript(text: &str, format: &str) -> (Vec<u64>, Vec<ValidationIssue>) {
+    let mut issues = Issues::default();
+    if format == "vtt" && !text.trim_start_matches('\u{feff}').starts_with("WEBVTT") {
+        issues.error("invalid-vtt", "WebVTT files must start with \"WEBVTT\"");
+    }
+    let starts = if format == "lrc" { lrc_cues(text, &mut issues) } else { timed_text_cues(text, &mut issues) };
+    if starts.is_empty() {
+        issues.error("no-cues", "File has no timed cues or lyric lines");
+    } else if format != "lrc" && starts.windows(2).any(|w| w[1] < w[0]) {
+        issues.warning("unsorted-cues", "Cues are not in time order");
+    }
+    (starts, issues.into_vec())
+}
+
+fn read_transcript(file_path: &str) -> Result<(String, &'static str, &'static str), String> {
+    let path = Path::new(file_path);
+    let (format, mime_type) = transcript_format(path)?;
+    let size = fs::metadata(path).map_err(|e| e.to_string())?.len();
+    if size > MAX_TRANSCRIPT_BYTES {
+        return Err(format!("{} is too large for a transcript ({} bytes)", file_path, size));
+    }
+    let text = fs::read_to_string(path).map_err(|_| format!("{} is not a UTF-8 text file", file_path))?;
+    Ok((text, format, mime_type))
+}
+
+/// Check an SRT, WebVTT, or LRC file before attaching it
+#[tauri::command]
+pub fn transcript_validate(file_path: String) -> Result<TranscriptCheck, String> {
+    let (text, format, mime_type) = read_transcript(&file_path)?;
+    let (starts, issues) = check_transcript(&text, format);
+    Ok(TranscriptCheck {
+        format: format.to_string(),
+        mime_type: mime_type.to_string(),
+        cue_count: starts.len(),
+        last_cue_ms: starts.iter().max().copied(),
+        issues,
+    })
+}
+
+/// Upload a transcript or lyrics file and point a track's <podcast:transcript> at it.
+/// Uploads go to the given Blossom server, else the feed's storage target.
+#[tauri::command]
+pub async fn transcript_attach(
+    feed_id: String,
+    item_guid: String,
+    file_path: String,
+    language: Option<String>,
+    server_url: Option<String>,
+    state: State<'_, crate::NostrState>,
+) -> Result<TranscriptAttachment, String> {
+    let (text, format, mime_type) = read_transcript(&file_path)?;
+    let (starts, issues) = check_transcript(&text, format);
+    if let Some(issue) = issues.into_iter().find(|i| i.severity == "error") {
+        return Err(issue.message);
+    }
+
+    let feed = crate::load_feed_local(feed_id)?;
+    let mut doc = parse_rss(&feed.xml)?;
+    let item = doc
+        .root
+        .child_mut("channel")
+        .and_then(|c| {
+            c.children
+                .iter_mut()
+                .find(|c| c.name == "item" && c.child_text("guid") == Some(item_guid.as_str()))
+        })
+        .ok_or_else(|| format!("Track not found: {}", item_guid))?;
+    let duration_ms = item.child_text("itunes:duration").and_then(parse_timestamp);
+    if let (Some(duration_ms), Some(last)) = (duration_ms, starts.iter().max()) {
+        if *last >= duration_ms {
+            return Err(format!(
+                "Transcript has a cue at {}, after the track ends ({})",
+                format_timestamp(*last),
+                format_timestamp(duration_ms)
+            ));
+        }
+    }
+
+    let target = match server_url {
+        Some(server_url) => StorageTarget::Blossom { server_url },
+        None => storage::resolve_target(Some(&feed.id), &state)?,
+    };
+    let file_name = Path::new(&file_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| format!("transcript.{}", format));
+    let _operation = crate::shutdown::begin("upload", &file_path);
+    // Upload the text that was checked, not whatever is on disk by now
+    let uploaded = storage::upload_bytes(&target, text.into_bytes(), &file_name, mime_type, state.signing_keys().ok()).await?;
+
+    // Only a transcript in the same format and language is replaced; others stay
+    let language = language.filter(|l| !l.is_empty());
+    let existing = item.children.iter().position(|c| {
+        c.name == "podcast:transcript" && c.attr("type") == Some(mime_type) && c.attr("language") == language.as_deref()
+    });
+    let mut node = XmlNode::new("podcast:transcript")
+        .with_attr("url", &uploaded.url)
+        .with_attr("type", mime_type);
+    if let Some(language) = language.as_deref() {
+        node = node.with_attr("language", language);
+    }
+    if format != "lrc" {
+        node = node.with_attr("rel", "captions");
+    }
+    match existing {
+        Some(index) => item.children[index] = node,
+        None => item.children.push(node),
+    }
+
+    let saved = crate::save_feed_local(Some(feed.id), feed.title, feed.feed_type, render_document(&doc.root), None)?;
+    Ok(TranscriptAttachment {
+        feed_id: saved.id,
+        item_guid,
+        url: uploaded.url,
+        mime_type: mime_type.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(issues: &[ValidationIssue]) -> Vec<&str> {
+        issues.iter().map(|issue| issue.code.as_str()).collect()
+    }
+
+    #[test]
+    fn format_comes_from_the_extension() {
+        assert_eq!(transcript_format(Path::new("/tmp/Song.SRT")).unwrap(), ("srt", "application/srt"));
+        assert_eq!(transcript_format(Path::new("lyrics.lrc")).unwrap().1, "text/x-lrc");
+        assert!(transcript_format(Path::new("notes.txt")).is_err());
+    }
+
+    #[test]
+    fn srt_cues_are_read() {
+        let srt = "1\n00:00:01,000 --> 00:00:04,500\nHello\n\n2\n00:00:05,000 --> 00:00:07,000\nAgain\n";
+        let (starts, issues) = check_transcript(srt, "srt");
+        assert_eq!(starts, vec![1000, 5000]);
+        assert!(issues.is_empty(), "{:?}", codes(&issues));
+    }
+
+    #[test]
+    fn vtt_needs_its_header_and_flags_bad_cues() {
+        let vtt = "00:00:05.000 --> 00:00:02.000\nBackwards\n\n00:00:01.000 --> 00:00:03.000\n";
+        let (starts, issues) = check_transcript(vtt, "vtt");
+        assert_eq!(starts, vec![5000, 1000]);
+        assert_eq!(
+            codes(&issues),
+            vec!["invalid-vtt", "cue-ends-before-start", "empty-cue", "unsorted-cues"]
+        );
+    }
+
+    #[test]
+    fn lrc_lines_may_repeat_and_metadata_is_skipped() {
+        let lrc = "[ar:Artist]\n[00:12.50][01:02.00]Chorus\n[00:05.00]Verse\n[0x:1y]Broken\n";
+        let (starts, issues) = check_transcript(lrc, "lrc");
+        assert_eq!(starts, vec![5000, 12500, 62000]);
+        assert_eq!(codes(&issues), vec!["invalid-timestamp"]);
+    }
+
+    #[test]
+    fn files_without_cues_are_errors() {
+        let (starts, issues) = check_transcript("WEBVTT\n\nJust text\n", "vtt");
+        assert!(starts.is_empty());
+        assert_eq!(codes(&issues), vec!["no-cues"]);
+    }
+}
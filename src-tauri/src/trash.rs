@@ -0,0 +1,291 @@
+// Trash for deleted feeds: deleting moves a feed into the library's trashed_feeds
+// table (its revision history and submissions stay with it) so it can be restored.
+// Trashed feeds are purged for good after the retention period, or on request.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+// How long trashed feeds are kept when no retention is configured
+const DEFAULT_RETENTION_DAYS: u32 = 30;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrashSettings {
+    pub retention_days: u32, // 0 keeps trashed feeds until purged by hand
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        TrashSettings {
+            retention_days: DEFAULT_RETENTION_DAYS,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TrashedFeed {
+    pub id: String,
+    pub title: String,
+    pub feed_type: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub deleted_at: u64,
+    pub purge_at: Option<u64>, // None when retention is off
+}
+
+fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(crate::get_appstate_dir()?.join("trash.json"))
+}
+
+fn load_settings() -> TrashSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Move a feed into the trash. Fails if the feed does not exist.
+pub fn move_to_trash(conn: &mut rusqlite::Connection, id: &str) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let moved = tx
+        .execute(
+            "INSERT INTO trashed_feeds (id, title, feed_type, xml, created_at, updated_at, app_version, podcast_guid, deleted_at)
+             SELECT id, title, feed_type, xml, created_at, updated_at, app_version, podcast_guid, ?2 FROM feeds WHERE id = ?1",
+            rusqlite::params![id, crate::get_current_timestamp()?],
+        )
+        .map_err(|e| e.to_string())?;
+    if moved == 0 {
+        return Err(format!("Feed not found: {}", id));
+    }
+    tx.execute("DELETE FROM feeds WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Permanently delete trashed feeds with their history, submission records,
+/// waveforms, and indexed GUIDs. Ids not in the trash are skipped, so a live feed's
+/// records are untouched.
+fn purge(conn: &mut rusqlite::Connection, ids: &[String]) -> Result<usize, String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut purged = 0;
+    for id in ids {
+        let removed = tx
+            .execute("DELETE FROM trashed_feeds WHERE id = ?1", [id])
+            .map_err(|e| e.to_string())?;
+        if removed == 0 {
+            continue;
+        }
+        purged += removed;
+        tx.execute("DELETE FROM feed_versions WHERE feed_id = ?1", [id])
+            .map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM directory_submissions WHERE feed_id = ?1", [id])
+            .map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM track_waveforms WHERE feed_id = ?1", [id])
+            .map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM item_guids WHERE feed_id = ?1", [id])
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(purged)
+}
+
+/// Ids of trashed feeds, optionally only those deleted before `before`
+fn trashed_ids(conn: &rusqlite::Connection, before: Option<u64>) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id FROM trashed_feeds WHERE deleted_at < ?1")
+        .map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map([before.map_or(i64::MAX, |b| b as i64)], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(ids)
+}
+
+/// Purge trashed feeds older than the retention period. Returns how many were purged.
+pub fn purge_expired() -> Result<usize, String> {
+    let retention_days = load_settings().retention_days;
+    if retention_days == 0 {
+        return Ok(0);
+    }
+    let cutoff = crate::get_current_timestamp()?.saturating_sub(u64::from(retention_days) * 86400);
+    let mut conn = crate::open_library()?;
+    let ids = trashed_ids(&conn, Some(cutoff))?;
+    purge(&mut conn, &ids)
+}
+
+/// List trashed feeds, most recently deleted first
+#[tauri::command]
+pub fn list_trashed_feeds() -> Result<Vec<TrashedFeed>, String> {
+    let retention_secs = u64::from(load_settings().retention_days) * 86400;
+    let conn = crate::open_library()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, feed_type, created_at, updated_at, deleted_at FROM trashed_feeds
+             ORDER BY deleted_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let feeds = stmt
+        .query_map([], |row| {
+            let deleted_at: u64 = row.get(5)?;
+            Ok(TrashedFeed {
+                id: row.get(0)?,
+                title: crate::library_crypto::open_title(row.get(1)?),
+                feed_type: row.get(2)?,
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+                deleted_at,
+                purge_at: (retention_secs > 0).then_some(deleted_at + retention_secs),
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(feeds)
+}
+
+/// Move a trashed feed back into the library under its original id, unless its
+/// <podcast:guid> has since been taken by another feed
+fn restore(conn: &mut rusqlite::Connection, id: &str) -> Result<crate::LocalFeed, String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    if crate::get_library_feed(&tx, id)?.is_some() {
+        return Err(format!("A feed with id {} already exists", id));
+    }
+    let restored = tx
+        .execute(
+            "INSERT INTO feeds (id, title, feed_type, xml, created_at, updated_at, app_version, podcast_guid)
+             SELECT id, title, feed_type, xml, created_at, updated_at, app_version, podcast_guid FROM trashed_feeds WHERE id = ?1",
+            [id],
+        )
+        .map_err(|e| e.to_string())?;
+    if restored == 0 {
+        return Err(format!("Feed not in trash: {}", id));
+    }
+    tx.execute("DELETE FROM trashed_feeds WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    let feed = crate::get_library_feed(&tx, id)?.ok_or_else(|| format!("Feed not found: {}", id))?;
+    crate::batch::ensure_unique_guids(&tx, Some(id), &feed.xml)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(feed)
+}
+
+/// Move a trashed feed back into the library under its original id
+#[tauri::command]
+pub fn restore_feed(id: String) -> Result<crate::LocalFeed, String> {
+    let mut conn = crate::open_library()?;
+    restore(&mut conn, &id)
+}
+
+/// Permanently delete one trashed feed, or the whole trash when no id is given.
+/// Returns how many feeds were purged.
+#[tauri::command]
+pub fn purge_trash(id: Option<String>) -> Result<usize, String> {
+    let mut conn = crate::open_library()?;
+    let ids = match id {
+        Some(id) => vec![id],
+        None => trashed_ids(&conn, None)?,
+    };
+    purge(&mut conn, &ids)
+}
+
+/// Get the trash retention settings
+#[tauri::command]
+pub fn get_trash_settings() -> TrashSettings {
+    load_settings()
+}
+
+/// Set how many days trashed feeds are kept (0 keeps them until purged by hand),
+/// purging anything already past the new period
+#[tauri::command]
+pub fn set_trash_settings(retention_days: u32) -> Result<TrashSettings, String> {
+    let settings = TrashSettings { retention_days };
+    let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    crate::write_atomic(&get_settings_path()?, json.as_bytes())?;
+    purge_expired()?;
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library() -> rusqlite::Connection {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        for sql in crate::LIBRARY_MIGRATIONS {
+            conn.execute_batch(sql).unwrap();
+        }
+        conn
+    }
+
+    fn add_feed(conn: &rusqlite::Connection, id: &str, podcast_guid: &str) {
+        let xml = format!(
+            "<rss><channel><title>{}</title><podcast:guid>{}</podcast:guid></channel></rss>",
+            id, podcast_guid
+        );
+        conn.execute(
+            "INSERT INTO feeds (id, title, feed_type, xml, created_at, updated_at) VALUES (?1, ?1, 'album', ?2, 1, 2)",
+            rusqlite::params![id, xml],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO feed_versions (feed_id, version, title, feed_type, xml, saved_at) VALUES (?1, 1, ?1, 'album', ?2, 1)",
+            rusqlite::params![id, xml],
+        )
+        .unwrap();
+    }
+
+    fn count(conn: &rusqlite::Connection, table: &str, column: &str, id: &str) -> i64 {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {} WHERE {} = ?1", table, column), [id], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn purge_removes_trashed_feed_and_history() {
+        let mut conn = library();
+        add_feed(&conn, "gone", "guid-gone");
+        move_to_trash(&mut conn, "gone").unwrap();
+
+        assert_eq!(purge(&mut conn, &["gone".to_string()]).unwrap(), 1);
+        assert_eq!(count(&conn, "trashed_feeds", "id", "gone"), 0);
+        assert_eq!(count(&conn, "feed_versions", "feed_id", "gone"), 0);
+    }
+
+    #[test]
+    fn purge_leaves_live_feeds_alone() {
+        let mut conn = library();
+        add_feed(&conn, "live", "guid-live");
+
+        assert_eq!(purge(&mut conn, &["live".to_string()]).unwrap(), 0);
+        assert_eq!(count(&conn, "feeds", "id", "live"), 1);
+        assert_eq!(count(&conn, "feed_versions", "feed_id", "live"), 1);
+    }
+
+    #[test]
+    fn restore_brings_back_feed_with_history() {
+        let mut conn = library();
+        add_feed(&conn, "album", "guid-album");
+        move_to_trash(&mut conn, "album").unwrap();
+        assert_eq!(count(&conn, "feeds", "id", "album"), 0);
+
+        let feed = restore(&mut conn, "album").unwrap();
+        assert_eq!(feed.id, "album");
+        assert_eq!(count(&conn, "trashed_feeds", "id", "album"), 0);
+        assert_eq!(count(&conn, "feed_versions", "feed_id", "album"), 1);
+    }
+
+    #[test]
+    fn restore_refuses_podcast_guid_taken_since() {
+        let mut conn = library();
+        add_feed(&conn, "old", "shared-guid");
+        move_to_trash(&mut conn, "old").unwrap();
+        add_feed(&conn, "new", "shared-guid");
+
+        assert!(restore(&mut conn, "old").is_err());
+        assert_eq!(count(&conn, "trashed_feeds", "id", "old"), 1);
+        assert_eq!(count(&conn, "feeds", "id", "old"), 0);
+    }
+}
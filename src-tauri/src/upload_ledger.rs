@@ -0,0 +1,124 @@
+// Upload ledger: which content (by sha256) is hosted where. Uploads consult it to skip
+// files a target already holds (after checking the URL still serves them), and the
+// feed builder can reuse a hosted URL when the same master appears in another album.
+// Targets are keyed like "blossom:<server url>" (see StorageTarget::ledger_key). The
+// local path is remembered so a lost blob can be uploaded again from disk.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UploadedBlob {
+    pub sha256: String,
+    pub url: String,
+    pub size: u64,
+    pub target: String,
+    pub local_path: Option<String>,
+    pub uploaded_at: u64,
+}
+
+/// Ledger key for a Blossom server
+pub fn blossom_key(server_url: &str) -> String {
+    format!("blossom:{}", crate::normalize_server_url(server_url))
+}
+
+/// Record that a blob is hosted at `url` on `target`
+pub fn record(sha256: &str, url: &str, size: u64, target: &str, local_path: Option<&str>) -> Result<(), String> {
+    let conn = crate::open_library()?;
+    conn.execute(
+        "INSERT INTO upload_ledger (sha256, target, url, size, local_path, uploaded_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT (sha256, target) DO UPDATE SET
+             url = excluded.url,
+             size = excluded.size,
+             local_path = COALESCE(excluded.local_path, upload_ledger.local_path),
+             uploaded_at = excluded.uploaded_at",
+        rusqlite::params![sha256, target, url, size, local_path, crate::get_current_timestamp()?],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Ledger rows matching a column, newest first
+fn query(column: &str, value: &str) -> Result<Vec<UploadedBlob>, String> {
+    let conn = crate::open_library()?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT sha256, url, size, target, local_path, uploaded_at FROM upload_ledger
+             WHERE {} = ?1 ORDER BY uploaded_at DESC",
+            column
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([value], |row| {
+            Ok(UploadedBlob {
+                sha256: row.get(0)?,
+                url: row.get(1)?,
+                size: row.get(2)?,
+                target: row.get(3)?,
+                local_path: row.get(4)?,
+                uploaded_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+/// Every place a blob is known to be hosted, newest first
+pub fn lookup(sha256: &str) -> Result<Vec<UploadedBlob>, String> {
+    query("sha256", &sha256.to_lowercase())
+}
+
+/// The ledger entries for a hosted URL
+pub fn lookup_url(url: &str) -> Result<Vec<UploadedBlob>, String> {
+    query("url", url)
+}
+
+/// Drop ledger entries for a URL that no longer resolves, so the next upload sends
+/// the file again instead of reusing the dead URL
+pub fn forget_url(url: &str) -> Result<(), String> {
+    let conn = crate::open_library()?;
+    conn.execute("DELETE FROM upload_ledger WHERE url = ?1", [url])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Drop the ledger entry for a blob on one target, e.g. after deleting it there
+pub fn forget(sha256: &str, target: &str) -> Result<(), String> {
+    let conn = crate::open_library()?;
+    conn.execute(
+        "DELETE FROM upload_ledger WHERE sha256 = ?1 AND target = ?2",
+        rusqlite::params![sha256.to_lowercase(), target],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The ledger entry for a blob on `target`. Lookup failures count as a miss so
+/// uploads still go ahead.
+fn hosted_on(sha256: &str, target: &str) -> Option<UploadedBlob> {
+    lookup(sha256).ok()?.into_iter().find(|blob| blob.target == target)
+}
+
+/// The ledger entry for a blob on `target`, if its URL still answers a HEAD request.
+/// Entries whose URL is gone are forgotten; any failure counts as a miss, so the
+/// caller uploads the file again rather than handing out a dead URL.
+pub async fn verified_on(sha256: &str, target: &str) -> Option<UploadedBlob> {
+    let blob = hosted_on(sha256, target)?;
+    let response = reqwest::Client::new().head(&blob.url).send().await.ok()?;
+    if response.status().is_success() {
+        return Some(blob);
+    }
+    if matches!(response.status().as_u16(), 404 | 410) {
+        let _ = forget(sha256, target);
+    }
+    None
+}
+
+/// Find where a file with this sha256 has already been uploaded, so its URL can be
+/// reused instead of uploading it again
+#[tauri::command]
+pub fn lookup_uploaded(sha256: String) -> Result<Vec<UploadedBlob>, String> {
+    lookup(&sha256)
+}
@@ -0,0 +1,317 @@
+// Upload queue: files are enqueued with their storage target and uploaded N at a time,
+// each retried with backoff before it is marked failed. Pausing stops new uploads from
+// starting (uploads in flight finish); cancelling aborts an upload in flight. Every
+// state change is emitted as `uploads://item`, and the whole queue can be queried.
+// Unfinished items are saved on shutdown and restored, paused, on the next launch.
+
+use crate::storage::{self, StorageTarget};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use uuid::Uuid;
+
+const DEFAULT_CONCURRENCY: usize = 3;
+const MAX_CONCURRENCY: usize = 8;
+// Secure settings entry (targets can carry credentials) holding unfinished uploads
+const PENDING_SETTING: &str = "upload-queue";
+
+static QUEUE: Mutex<UploadQueue> = Mutex::new(UploadQueue {
+    items: Vec::new(),
+    paused: false,
+    concurrency: DEFAULT_CONCURRENCY,
+    running: None,
+});
+
+struct UploadQueue {
+    items: Vec<QueueItem>,
+    paused: bool,
+    concurrency: usize,
+    running: Option<HashMap<String, tauri::async_runtime::JoinHandle<()>>>, // by item id
+}
+
+#[derive(Serialize, Clone)]
+pub struct QueueItem {
+    pub id: String,
+    pub file_path: String,
+    pub feed_id: Option<String>,
+    pub status: String, // "queued", "uploading", "done", "failed", or "cancelled"
+    pub attempts: u32,
+    pub url: Option<String>,
+    pub sha256: Option<String>,
+    pub error: Option<String>,
+    #[serde(skip)]
+    target: StorageTarget,
+}
+
+// An unfinished upload saved across restarts
+#[derive(Serialize, Deserialize)]
+struct PendingUpload {
+    file_path: String,
+    feed_id: Option<String>,
+    target: StorageTarget,
+}
+
+#[derive(Serialize)]
+pub struct QueueCounts {
+    pub queued: usize,
+    pub uploading: usize,
+    pub done: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+}
+
+#[derive(Serialize)]
+pub struct QueueState {
+    pub paused: bool,
+    pub concurrency: usize,
+    pub counts: QueueCounts,
+    pub items: Vec<QueueItem>,
+}
+
+fn queue_state(queue: &UploadQueue) -> QueueState {
+    let count = |status: &str| queue.items.iter().filter(|i| i.status == status).count();
+    QueueState {
+        paused: queue.paused,
+        concurrency: queue.concurrency,
+        counts: QueueCounts {
+            queued: count("queued"),
+            uploading: count("uploading"),
+            done: count("done"),
+            failed: count("failed"),
+            cancelled: count("cancelled"),
+        },
+        items: queue.items.clone(),
+    }
+}
+
+/// Save queued, in-flight, and failed uploads so the next launch can restore them
+pub fn flush() {
+    let pending: Vec<PendingUpload> = QUEUE
+        .lock()
+        .unwrap()
+        .items
+        .iter()
+        .filter(|i| matches!(i.status.as_str(), "queued" | "uploading" | "failed"))
+        .map(|i| PendingUpload {
+            file_path: i.file_path.clone(),
+            feed_id: i.feed_id.clone(),
+            target: i.target.clone(),
+        })
+        .collect();
+    let saved = if pending.is_empty() {
+        crate::secure_settings::remove(PENDING_SETTING)
+    } else {
+        crate::secure_settings::put(PENDING_SETTING, &pending)
+    };
+    if let Err(e) = saved {
+        eprintln!("Could not save pending uploads: {}", e);
+    }
+}
+
+/// Queue the uploads saved at the last shutdown. The queue starts paused so nothing
+/// uploads before the user is signed in; resuming picks them up.
+pub fn restore() {
+    let pending: Vec<PendingUpload> = match crate::secure_settings::get(PENDING_SETTING) {
+        Ok(pending) => pending.unwrap_or_default(),
+        Err(e) => {
+            eprintln!("Could not restore pending uploads: {}", e);
+            return;
+        }
+    };
+    if pending.is_empty() {
+        return;
+    }
+    let mut queue = QUEUE.lock().unwrap();
+    queue.paused = true;
+    queue.items.extend(pending.into_iter().map(|p| QueueItem {
+        id: Uuid::new_v4().to_string(),
+        file_path: p.file_path,
+        feed_id: p.feed_id,
+        status: "queued".to_string(),
+        attempts: 0,
+        url: None,
+        sha256: None,
+        error: None,
+        target: p.target,
+    }));
+}
+
+/// Update an in-flight item and emit its new state. Cancelled items are left alone.
+fn update_item(app: &AppHandle, id: &str, f: impl FnOnce(&mut QueueItem)) {
+    let mut queue = QUEUE.lock().unwrap();
+    if let Some(item) = queue.items.iter_mut().find(|i| i.id == id && i.status == "uploading") {
+        f(item);
+        let _ = app.emit("uploads://item", item.clone());
+    }
+}
+
+/// Upload one item, retrying transient failures with backoff, then start the next queued item
+async fn run_item(app: AppHandle, item: QueueItem) {
+    let mut attempt = 0;
+    loop {
+        update_item(&app, &item.id, |i| i.attempts = attempt + 1);
+        // Keys are read when the attempt starts, so a logout or lock since enqueueing applies
+        let keys = app.state::<crate::NostrState>().signing_keys().ok();
+        match storage::upload_file(&item.target, &item.file_path, keys, Some(app.clone())).await {
+            Ok(stored) => {
+                update_item(&app, &item.id, |i| {
+                    i.status = "done".to_string();
+                    i.url = Some(stored.url);
+                    i.sha256 = Some(stored.sha256);
+                    i.error = None;
+                });
+                break;
+            }
+            Err(e) if attempt >= crate::UPLOAD_MAX_RETRIES || !e.is_retryable() => {
+                update_item(&app, &item.id, |i| {
+                    i.status = "failed".to_string();
+                    i.error = Some(e.to_string());
+                });
+                break;
+            }
+            Err(e) => {
+                update_item(&app, &item.id, |i| i.error = Some(e.to_string()));
+                attempt += 1;
+                tokio::time::sleep(crate::backoff_delay(attempt)).await;
+            }
+        }
+    }
+
+    if let Some(running) = QUEUE.lock().unwrap().running.as_mut() {
+        running.remove(&item.id);
+    }
+    pump(&app);
+}
+
+/// Start queued uploads until the concurrency limit is reached
+fn pump(app: &AppHandle) {
+    let mut queue = QUEUE.lock().unwrap();
+    if queue.paused {
+        return;
+    }
+    let running = queue.running.get_or_insert_with(HashMap::new).len();
+    let free = queue.concurrency.saturating_sub(running);
+
+    let mut started = Vec::new();
+    for item in queue.items.iter_mut().filter(|i| i.status == "queued").take(free) {
+        item.status = "uploading".to_string();
+        let _ = app.emit("uploads://item", item.clone());
+        started.push(item.clone());
+    }
+    for item in started {
+        let id = item.id.clone();
+        let handle = tauri::async_runtime::spawn(run_item(app.clone(), item));
+        queue.running.get_or_insert_with(HashMap::new).insert(id, handle);
+    }
+}
+
+/// Add files to the upload queue. They go to the given target, else the feed's
+/// storage target, else the user's preferred Blossom server. Returns the new items.
+#[tauri::command]
+pub fn upload_queue_enqueue(
+    file_paths: Vec<String>,
+    feed_id: Option<String>,
+    target: Option<StorageTarget>,
+    app: AppHandle,
+    state: State<'_, crate::NostrState>,
+) -> Result<Vec<QueueItem>, String> {
+    let target = match target {
+        Some(target) => target,
+        None => storage::resolve_target(feed_id.as_deref(), &state)?,
+    };
+
+    let items: Vec<QueueItem> = file_paths
+        .into_iter()
+        .map(|file_path| QueueItem {
+            id: Uuid::new_v4().to_string(),
+            file_path,
+            feed_id: feed_id.clone(),
+            status: "queued".to_string(),
+            attempts: 0,
+            url: None,
+            sha256: None,
+            error: None,
+            target: target.clone(),
+        })
+        .collect();
+    QUEUE.lock().unwrap().items.extend(items.iter().cloned());
+    pump(&app);
+    Ok(items)
+}
+
+/// Get every item in the queue with per-status counts
+#[tauri::command]
+pub fn upload_queue_state() -> QueueState {
+    queue_state(&QUEUE.lock().unwrap())
+}
+
+/// Stop starting new uploads; uploads already in flight finish
+#[tauri::command]
+pub fn upload_queue_pause() -> QueueState {
+    let mut queue = QUEUE.lock().unwrap();
+    queue.paused = true;
+    queue_state(&queue)
+}
+
+/// Resume starting queued uploads
+#[tauri::command]
+pub fn upload_queue_resume(app: AppHandle) -> QueueState {
+    QUEUE.lock().unwrap().paused = false;
+    pump(&app);
+    upload_queue_state()
+}
+
+/// Set how many uploads run at once (1-8)
+#[tauri::command]
+pub fn upload_queue_set_concurrency(concurrency: usize, app: AppHandle) -> QueueState {
+    QUEUE.lock().unwrap().concurrency = concurrency.clamp(1, MAX_CONCURRENCY);
+    pump(&app);
+    upload_queue_state()
+}
+
+/// Cancel one item, or every queued and in-flight item when `id` is None.
+/// In-flight uploads are aborted.
+#[tauri::command]
+pub fn upload_queue_cancel(id: Option<String>, app: AppHandle) -> QueueState {
+    {
+        let mut queue = QUEUE.lock().unwrap();
+        let mut aborted = Vec::new();
+        for item in queue.items.iter_mut() {
+            let selected = id.as_deref().map(|id| item.id == id).unwrap_or(true);
+            if selected && (item.status == "queued" || item.status == "uploading") {
+                item.status = "cancelled".to_string();
+                let _ = app.emit("uploads://item", item.clone());
+                aborted.push(item.id.clone());
+            }
+        }
+        if let Some(running) = queue.running.as_mut() {
+            for id in aborted {
+                if let Some(handle) = running.remove(&id) {
+                    handle.abort();
+                }
+            }
+        }
+    }
+    pump(&app);
+    upload_queue_state()
+}
+
+/// Remove finished, failed, and cancelled items. With `retry_failed`, failed items
+/// are queued again instead of removed.
+#[tauri::command]
+pub fn upload_queue_clear(retry_failed: Option<bool>, app: AppHandle) -> QueueState {
+    {
+        let mut queue = QUEUE.lock().unwrap();
+        if retry_failed.unwrap_or(false) {
+            for item in queue.items.iter_mut().filter(|i| i.status == "failed") {
+                item.status = "queued".to_string();
+                item.attempts = 0;
+                item.error = None;
+            }
+        }
+        queue.items.retain(|i| i.status == "queued" || i.status == "uploading");
+    }
+    pump(&app);
+    upload_queue_state()
+}
@@ -0,0 +1,353 @@
+// Feed validation rules, selected by feed type
+
+use crate::feed_xml::{parse_rss, RssDocument, XmlNode};
+use crate::timeline::{build_item_timeline, check_timeline};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ValidationIssue {
+    pub severity: String, // "error" or "warning"
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ValidationReport {
+    pub feed_type: String,
+    pub valid: bool,
+    pub issues: Vec<ValidationIssue>,
+}
+
+/// Collects issues while rules run
+#[derive(Default)]
+pub struct Issues(Vec<ValidationIssue>);
+
+impl Issues {
+    pub fn error(&mut self, code: &str, message: impl Into<String>) {
+        self.0.push(ValidationIssue {
+            severity: "error".to_string(),
+            code: code.to_string(),
+            message: message.into(),
+        });
+    }
+
+    pub fn warning(&mut self, code: &str, message: impl Into<String>) {
+        self.0.push(ValidationIssue {
+            severity: "warning".to_string(),
+            code: code.to_string(),
+            message: message.into(),
+        });
+    }
+
+    pub fn into_vec(self) -> Vec<ValidationIssue> {
+        self.0
+    }
+}
+
+/// Get the <podcast:medium> value of a channel
+fn channel_medium(channel: &XmlNode) -> Option<&str> {
+    channel.child_text("podcast:medium")
+}
+
+/// Rules shared by every feed type
+fn check_common(doc: &RssDocument, issues: &mut Issues) {
+    let Some(channel) = doc.channel() else {
+        return;
+    };
+
+    if channel.child_text("title").is_none() {
+        issues.error("missing-title", "Channel is missing a <title>");
+    }
+}
+
+/// Check a <podcast:value> block: recipients need an address (a node pubkey for
+/// keysend) and a numeric split
+fn check_value_block(value: &XmlNode, context: &str, issues: &mut Issues) {
+    if value.attr("type").is_none() || value.attr("method").is_none() {
+        issues.error(
+            "invalid-value",
+            format!("{}: <podcast:value> needs both type and method attributes", context),
+        );
+    }
+
+    let recipients: Vec<&XmlNode> = value.children_named("podcast:valueRecipient").collect();
+    if recipients.is_empty() {
+        issues.error("invalid-value", format!("{}: <podcast:value> has no recipients", context));
+        return;
+    }
+
+    let mut total: u64 = 0;
+    for recipient in &recipients {
+        let name = recipient.attr("name").unwrap_or("unnamed");
+        if recipient.attr("address").map(str::trim).unwrap_or_default().is_empty() {
+            issues.error("invalid-value-split", format!("{}: recipient \"{}\" has no address", context, name));
+        } else if recipient.attr("type").unwrap_or("node") == "node"
+            && !crate::value_block::is_node_pubkey(recipient.attr("address").unwrap_or_default().trim())
+        {
+            issues.error(
+                "invalid-node-pubkey",
+                format!("{}: recipient \"{}\" address is not a 66-character hex node pubkey", context, name),
+            );
+        }
+        match recipient.attr("split").map(|s| s.trim().parse::<u64>()) {
+            Some(Ok(split)) => total += split,
+            _ => issues.error(
+                "invalid-value-split",
+                format!("{}: recipient \"{}\" needs a whole-number split", context, name),
+            ),
+        }
+    }
+
+    if total == 0 {
+        issues.error("invalid-value-split", format!("{}: value splits add up to zero", context));
+    } else if total != 100 {
+        issues.warning(
+            "value-split-total",
+            format!("{}: value splits add up to {}, not 100; apps will treat them as shares", context, total),
+        );
+    }
+}
+
+/// Podcast namespace rules shared by album and video feeds: guid, artwork,
+/// enclosures, value splits, and track timelines
+fn check_namespace(doc: &RssDocument, issues: &mut Issues) {
+    let Some(channel) = doc.channel() else {
+        return;
+    };
+
+    if channel.child_text("podcast:guid").is_none() {
+        issues.error("missing-guid", "Channel is missing <podcast:guid>");
+    }
+    if channel.child("itunes:image").and_then(|i| i.attr("href")).is_none() {
+        issues.warning("missing-image", "Channel is missing <itunes:image>; most apps will show no artwork");
+    }
+    if let Some(value) = channel.child("podcast:value") {
+        check_value_block(value, "Channel", issues);
+    }
+
+    for (i, item) in doc.items().iter().enumerate() {
+        let context = format!("Track {} (\"{}\")", i + 1, item.child_text("title").unwrap_or("untitled"));
+
+        if let Some(enclosure) = item.child("enclosure") {
+            let url = enclosure.attr("url").unwrap_or_default();
+            let mime = enclosure.attr("type").unwrap_or_default();
+            if url.is_empty() {
+                issues.error("invalid-enclosure", format!("{}: <enclosure> has no url", context));
+            }
+            if !mime.starts_with("audio/") && !mime.starts_with("video/") && mime != "application/x-mpegURL" {
+                issues.error(
+                    "bad-enclosure-type",
+                    format!("{}: enclosure type \"{}\" is not an audio or video MIME type", context, mime),
+                );
+            } else {
+                let expected = crate::guess_mime_type(url);
+                if expected != "application/octet-stream" && expected != mime {
+                    issues.warning(
+                        "enclosure-type-mismatch",
+                        format!("{}: enclosure type \"{}\" does not match its file ({})", context, mime, expected),
+                    );
+                }
+            }
+        }
+
+        for value in item.children_named("podcast:value") {
+            check_value_block(value, &context, issues);
+        }
+
+        if let Ok(timeline) = build_item_timeline(item, None, None, issues) {
+            check_timeline(&timeline, issues);
+        }
+    }
+}
+
+/// Rules for album (music) feeds
+fn check_album(doc: &RssDocument, issues: &mut Issues) {
+    let Some(channel) = doc.channel() else {
+        return;
+    };
+
+    match channel_medium(channel) {
+        Some("music") => {}
+        Some(other) => issues.error(
+            "wrong-medium",
+            format!("Album feeds must use <podcast:medium>music</podcast:medium>, found \"{}\"", other),
+        ),
+        None => issues.warning("missing-medium", "Missing <podcast:medium>; apps will assume a podcast"),
+    }
+
+    let items = doc.items();
+    if items.is_empty() {
+        issues.error("no-items", "Album has no tracks");
+    }
+    for (i, item) in items.iter().enumerate() {
+        if item.child("enclosure").is_none() {
+            let title = item.child_text("title").unwrap_or("untitled");
+            issues.error(
+                "missing-enclosure",
+                format!("Track {} (\"{}\") has no <enclosure>", i + 1, title),
+            );
+        }
+    }
+}
+
+/// Rules for video feeds
+fn check_video(doc: &RssDocument, issues: &mut Issues) {
+    let Some(channel) = doc.channel() else {
+        return;
+    };
+
+    if channel_medium(channel) != Some("video") {
+        issues.error("wrong-medium", "Video feeds must use <podcast:medium>video</podcast:medium>");
+    }
+
+    for item in doc.items() {
+        if let Some(enclosure) = item.child("enclosure") {
+            let mime = enclosure.attr("type").unwrap_or_default();
+            if !mime.starts_with("video/") && !mime.starts_with("application/x-mpegURL") {
+                issues.warning(
+                    "non-video-enclosure",
+                    format!("Enclosure type \"{}\" is not a video type", mime),
+                );
+            }
+        }
+    }
+}
+
+/// Rules for publisher feeds: medium=publisher, only remoteItems, resolvable backlinks
+fn check_publisher(doc: &RssDocument, issues: &mut Issues) {
+    let Some(channel) = doc.channel() else {
+        return;
+    };
+
+    if channel_medium(channel) != Some("publisher") {
+        issues.error(
+            "wrong-medium",
+            "Publisher feeds must use <podcast:medium>publisher</podcast:medium>",
+        );
+    }
+
+    let publisher_guid = channel.child_text("podcast:guid");
+    if publisher_guid.is_none() {
+        issues.error("missing-guid", "Publisher feed is missing <podcast:guid>, so albums cannot link back to it");
+    }
+
+    let items = doc.items();
+    if !items.is_empty() {
+        issues.error(
+            "publisher-has-items",
+            format!("Publisher feeds should only list <podcast:remoteItem>s, found {} <item>(s)", items.len()),
+        );
+    }
+    if items.iter().any(|item| item.child("enclosure").is_some()) {
+        issues.error("publisher-has-enclosures", "Publisher feeds must not contain enclosures");
+    }
+
+    let remote_items: Vec<&XmlNode> = channel.children_named("podcast:remoteItem").collect();
+    if remote_items.is_empty() {
+        issues.warning("no-remote-items", "Publisher feed does not list any albums");
+    }
+
+    // Index local feeds by podcast:guid so backlinks can be resolved
+    let local_feeds: Vec<RssDocument> = crate::load_all_local_feed_xml()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(_, xml)| parse_rss(&xml).ok())
+        .collect();
+
+    for remote in remote_items {
+        let Some(feed_guid) = remote.attr("feedGuid") else {
+            issues.error("remote-item-missing-guid", "A <podcast:remoteItem> is missing its feedGuid");
+            continue;
+        };
+
+        if let Some(medium) = remote.attr("medium") {
+            if medium != "music" && medium != "video" {
+                issues.warning(
+                    "remote-item-medium",
+                    format!("remoteItem {} has unexpected medium \"{}\"", feed_guid, medium),
+                );
+            }
+        }
+
+        let album = local_feeds.iter().find(|d| {
+            d.channel().and_then(|c| c.child_text("podcast:guid")) == Some(feed_guid)
+        });
+        let Some(album) = album else {
+            issues.warning(
+                "unresolved-remote-item",
+                format!("Album {} is not in the local library, so its backlink could not be checked", feed_guid),
+            );
+            continue;
+        };
+
+        let links_back = publisher_guid.is_some_and(|guid| {
+            album
+                .channel()
+                .and_then(|c| c.child("podcast:publisher"))
+                .map(|p| p.children_named("podcast:remoteItem").any(|r| r.attr("feedGuid") == Some(guid)))
+                .unwrap_or(false)
+        });
+        if !links_back {
+            let title = album.channel().and_then(|c| c.child_text("title")).unwrap_or(feed_guid);
+            issues.error(
+                "missing-backlink",
+                format!("Album \"{}\" does not link back to this publisher via <podcast:publisher>", title),
+            );
+        }
+    }
+}
+
+/// Run the rule set for the given feed type against parsed XML
+pub fn validate_document(doc: &RssDocument, feed_type: &str) -> Vec<ValidationIssue> {
+    let mut issues = Issues::default();
+    check_common(doc, &mut issues);
+    match feed_type {
+        "publisher" => check_publisher(doc, &mut issues),
+        "video" => {
+            check_video(doc, &mut issues);
+            check_namespace(doc, &mut issues);
+        }
+        _ => {
+            check_album(doc, &mut issues);
+            check_namespace(doc, &mut issues);
+        }
+    }
+    issues.0
+}
+
+/// Validate feed XML with the rule set for its type (detected when not given). With
+/// a library feed id, also warn if the edit changes the feed's pinned <podcast:guid>.
+#[tauri::command]
+pub fn feed_validate(
+    xml: String,
+    feed_type: Option<String>,
+    feed_id: Option<String>,
+) -> Result<ValidationReport, String> {
+    let feed_type = feed_type.unwrap_or_else(|| crate::detect_feed_type(&xml));
+
+    let mut issues = match parse_rss(&xml) {
+        Ok(doc) => validate_document(&doc, &feed_type),
+        Err(e) => {
+            let mut issues = Issues::default();
+            issues.error("parse-error", e);
+            issues.0
+        }
+    };
+    if let Some(feed_id) = feed_id {
+        let mut guid_issues = Issues::default();
+        crate::podcast_guid::check_guid_stability(&feed_id, &xml, &mut guid_issues)?;
+        issues.extend(guid_issues.0);
+    }
+
+    Ok(ValidationReport {
+        valid: !issues.iter().any(|i| i.severity == "error"),
+        feed_type,
+        issues,
+    })
+}
+
+/// Validate feed XML against the podcast namespace rules, detecting the feed type
+#[tauri::command]
+pub fn validate_feed_xml(xml: String) -> Result<ValidationReport, String> {
+    feed_validate(xml, None, None)
+}
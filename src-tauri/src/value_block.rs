@@ -0,0 +1,182 @@
+// podcast:value block builder: check a set of Lightning recipients (keysend nodes or
+// Lightning addresses) before it goes into a feed, then render the block and put it
+// on the channel or on one track of existing feed XML.
+
+use crate::feed_model::{value_node, ValueModel, ValueRecipientModel, PODCAST_NS};
+use crate::feed_xml::{parse_xml, render_document};
+use crate::validation::{Issues, ValidationIssue};
+use std::collections::HashSet;
+
+/// Whether an address is a compressed secp256k1 node pubkey (66 hex characters)
+pub fn is_node_pubkey(address: &str) -> bool {
+    address.len() == 66
+        && (address.starts_with("02") || address.starts_with("03"))
+        && address.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Whether an address looks like a Lightning address (user@domain.tld)
+fn is_lightning_address(address: &str) -> bool {
+    match address.split_once('@') {
+        Some((user, domain)) => {
+            !user.is_empty() && domain.contains('.') && !domain.starts_with('.') && !domain.ends_with('.')
+        }
+        None => false,
+    }
+}
+
+fn check_recipient(recipient: &ValueRecipientModel, issues: &mut Issues) {
+    let name = if recipient.name.trim().is_empty() { "unnamed" } else { recipient.name.trim() };
+    let address = recipient.address.trim();
+
+    match recipient.recipient_type.as_str() {
+        "" | "node" => {
+            if !is_node_pubkey(address) {
+                issues.error(
+                    "invalid-node-pubkey",
+                    format!("Recipient \"{}\": node address must be a 66-character hex pubkey", name),
+                );
+            }
+        }
+        "lnaddress" => {
+            if !is_lightning_address(address) {
+                issues.error(
+                    "invalid-lnaddress",
+                    format!("Recipient \"{}\": \"{}\" is not a Lightning address", name, address),
+                );
+            }
+            if recipient.custom_key.as_deref().is_some_and(|k| !k.is_empty()) {
+                issues.warning(
+                    "lnaddress-custom-key",
+                    format!("Recipient \"{}\": customKey only applies to keysend recipients", name),
+                );
+            }
+        }
+        other => issues.error(
+            "invalid-recipient-type",
+            format!("Recipient \"{}\": type must be \"node\" or \"lnaddress\", not \"{}\"", name, other),
+        ),
+    }
+
+    let custom_key = recipient.custom_key.as_deref().filter(|k| !k.is_empty());
+    let custom_value = recipient.custom_value.as_deref().filter(|v| !v.is_empty());
+    match (custom_key, custom_value) {
+        (Some(key), _) if key.trim().parse::<u64>().is_err() => issues.error(
+            "invalid-custom-key",
+            format!("Recipient \"{}\": customKey must be a numeric TLV record type", name),
+        ),
+        (Some(_), None) | (None, Some(_)) => issues.error(
+            "invalid-custom-key",
+            format!("Recipient \"{}\": customKey and customValue must be set together", name),
+        ),
+        _ => {}
+    }
+
+    if recipient.split == 0 {
+        issues.warning("zero-split", format!("Recipient \"{}\" has a split of 0 and will receive nothing", name));
+    }
+}
+
+/// Check a value block: addresses, custom records, and that fee and share splits add up
+pub fn check_value(value: &ValueModel) -> Vec<ValidationIssue> {
+    let mut issues = Issues::default();
+    if value.recipients.is_empty() {
+        issues.error("invalid-value", "A value block needs at least one recipient");
+        return issues.into_vec();
+    }
+
+    if let Some(suggested) = value.suggested.as_deref().filter(|s| !s.is_empty()) {
+        if !suggested.trim().parse::<f64>().is_ok_and(|s| s > 0.0) {
+            issues.error("invalid-suggested", format!("Suggested amount \"{}\" is not a positive number", suggested));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    for recipient in &value.recipients {
+        check_recipient(recipient, &mut issues);
+        let key = (recipient.address.trim(), recipient.custom_key.as_deref(), recipient.custom_value.as_deref());
+        if !seen.insert(key) {
+            issues.warning(
+                "duplicate-recipient",
+                format!("Recipient \"{}\" is listed more than once", recipient.address.trim()),
+            );
+        }
+    }
+
+    // Fee splits are percentages off the top; the rest are shares of what remains
+    let fee_total: u64 = value.recipients.iter().filter(|r| r.fee).map(|r| u64::from(r.split)).sum();
+    let share_total: u64 = value.recipients.iter().filter(|r| !r.fee).map(|r| u64::from(r.split)).sum();
+    if fee_total >= 100 {
+        issues.error("invalid-fee", format!("Fee splits add up to {}%, leaving nothing for the other recipients", fee_total));
+    }
+    if share_total == 0 {
+        issues.error("invalid-value-split", "Non-fee recipients' splits add up to zero");
+    } else if share_total != 100 {
+        issues.warning(
+            "value-split-total",
+            format!("Splits add up to {}, not 100; apps will treat them as shares", share_total),
+        );
+    }
+
+    let types: HashSet<&str> = value
+        .recipients
+        .iter()
+        .map(|r| if r.recipient_type.is_empty() { "node" } else { r.recipient_type.as_str() })
+        .collect();
+    if types.contains("node") && types.contains("lnaddress") {
+        issues.warning(
+            "mixed-value-methods",
+            "Keysend nodes and Lightning addresses are mixed; the block is marked lnaddress and some apps only pay one kind",
+        );
+    }
+    issues.into_vec()
+}
+
+/// Fail with the first error, if the block has any
+fn require_valid(value: &ValueModel) -> Result<(), String> {
+    match check_value(value).into_iter().find(|i| i.severity == "error") {
+        Some(issue) => Err(issue.message),
+        None => Ok(()),
+    }
+}
+
+/// Check a value block, returning errors and warnings
+#[tauri::command]
+pub fn value_block_validate(value: ValueModel) -> Vec<ValidationIssue> {
+    check_value(&value)
+}
+
+/// Render a valid value block as a <podcast:value> XML fragment
+#[tauri::command]
+pub fn value_block_build(value: ValueModel) -> Result<String, String> {
+    require_valid(&value)?;
+    let node = value_node(&value).ok_or("A value block needs at least one recipient")?;
+    Ok(node.to_xml(0).trim_end().to_string())
+}
+
+/// Put a valid value block into feed XML, replacing any existing one: on the channel,
+/// or on the track with `item_guid` when given
+#[tauri::command]
+pub fn value_block_inject(xml: String, value: ValueModel, item_guid: Option<String>) -> Result<String, String> {
+    require_valid(&value)?;
+    let node = value_node(&value).ok_or("A value block needs at least one recipient")?;
+
+    let mut root = parse_xml(&xml)?;
+    let channel = root.child_mut("channel").ok_or("Missing <channel> element")?;
+    let target = match item_guid.as_deref() {
+        Some(guid) => channel
+            .children
+            .iter_mut()
+            .find(|c| c.name == "item" && c.child_text("guid") == Some(guid))
+            .ok_or_else(|| format!("Track not found: {}", guid))?,
+        None => channel,
+    };
+    target.remove_children("podcast:value");
+    // Keep the block ahead of the channel's items
+    let position = target.children.iter().position(|c| c.name == "item").unwrap_or(target.children.len());
+    target.children.insert(position, node);
+
+    if root.attr("xmlns:podcast").is_none() {
+        root.attrs.push(("xmlns:podcast".to_string(), PODCAST_NS.to_string()));
+    }
+    Ok(render_document(&root))
+}
@@ -0,0 +1,340 @@
+// Nostr Wallet Connect (NIP-47) client: pair with a wallet from its
+// nostr+walletconnect:// URI, then pay invoices, read the balance, and list
+// transactions so test boosts and zaps can be paid from inside the app. The URI
+// holds the secret that spends from the wallet, so it is kept in the encrypted
+// settings store, and payments wait while the session is locked.
+
+use nostr_sdk::nips::nip04;
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::State;
+use tokio::sync::broadcast::error::RecvError;
+
+const NWC_REQUEST_KIND: u16 = 23194;
+const NWC_RESPONSE_KIND: u16 = 23195;
+
+// How long to wait for the wallet to answer a request
+const WALLET_TIMEOUT_SECS: u64 = 30;
+
+// Secure settings entry holding the wallet pairing
+const WALLET_SETTINGS: &str = "wallet_connection";
+
+/// Parsed connection URI (the secret never leaves the backend)
+struct WalletConnection {
+    wallet_pubkey: PublicKey,
+    relay: String,
+    secret: SecretKey,
+    lud16: Option<String>,
+}
+
+// Pairing as kept in the encrypted settings store
+#[derive(Serialize, Deserialize)]
+struct StoredWallet {
+    wallet_pubkey: String,
+    relay: String,
+    lud16: Option<String>,
+    uri: String,
+    connected_at: u64,
+}
+
+// Pairing as written to wallet.json before it moved to the settings store
+#[derive(Deserialize)]
+struct LegacyStoredWallet {
+    wallet_pubkey: String,
+    relay: String,
+    lud16: Option<String>,
+    nonce: String,
+    ciphertext: String,
+    connected_at: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WalletInfo {
+    pub wallet_pubkey: String,
+    pub relay: String,
+    pub lud16: Option<String>,
+    pub connected_at: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WalletPayment {
+    pub preimage: String,
+    pub fees_paid_msats: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WalletBalance {
+    pub balance_msats: u64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct WalletTransaction {
+    #[serde(rename = "type")]
+    pub transaction_type: Option<String>, // "incoming" or "outgoing"
+    pub invoice: Option<String>,
+    pub description: Option<String>,
+    pub payment_hash: Option<String>,
+    pub preimage: Option<String>,
+    pub amount: u64, // msats
+    pub fees_paid: u64,
+    pub created_at: u64,
+    pub settled_at: Option<u64>,
+}
+
+/// Parse a nostr+walletconnect://<wallet pubkey>?relay=...&secret=... URI
+fn parse_connection_uri(uri: &str) -> Result<WalletConnection, String> {
+    let url =
+        reqwest::Url::parse(uri.trim()).map_err(|_| "Invalid wallet connection URI".to_string())?;
+    if url.scheme() != "nostr+walletconnect" && url.scheme() != "nostrwalletconnect" {
+        return Err("Not a nostr+walletconnect:// URI".to_string());
+    }
+    let wallet_pubkey = url
+        .host_str()
+        .ok_or("Wallet connection URI has no wallet pubkey")
+        .and_then(|h| PublicKey::parse(h).map_err(|_| "Invalid wallet pubkey"))?;
+
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    let relay = params
+        .get("relay")
+        .cloned()
+        .ok_or("Wallet connection URI has no relay")?;
+    let secret = params
+        .get("secret")
+        .ok_or("Wallet connection URI has no secret")
+        .and_then(|s| SecretKey::parse(s).map_err(|_| "Invalid wallet secret"))?;
+
+    Ok(WalletConnection {
+        wallet_pubkey,
+        relay,
+        secret,
+        lud16: params.get("lud16").cloned(),
+    })
+}
+
+/// Path of the pairing from before it moved to the encrypted settings store
+fn get_legacy_wallet_path() -> Result<PathBuf, String> {
+    Ok(crate::get_appstate_dir()?.join("wallet.json"))
+}
+
+fn load_stored_wallet() -> Result<Option<StoredWallet>, String> {
+    if let Some(stored) = crate::secure_settings::get(WALLET_SETTINGS)? {
+        return Ok(Some(stored));
+    }
+
+    // Move a pairing from wallet.json into the encrypted store
+    let path = get_legacy_wallet_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let legacy: LegacyStoredWallet = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    let key = crate::derive_key_from_device()?;
+    let stored = StoredWallet {
+        wallet_pubkey: legacy.wallet_pubkey,
+        relay: legacy.relay,
+        lud16: legacy.lud16,
+        uri: crate::decrypt_nsec(&legacy.nonce, &legacy.ciphertext, &key)?,
+        connected_at: legacy.connected_at,
+    };
+    crate::secure_settings::put(WALLET_SETTINGS, &stored)?;
+    fs::remove_file(&path).map_err(|e| e.to_string())?;
+    Ok(Some(stored))
+}
+
+/// The paired wallet's connection
+fn load_connection() -> Result<WalletConnection, String> {
+    let stored = load_stored_wallet()?.ok_or("No wallet connected")?;
+    parse_connection_uri(&stored.uri)
+}
+
+/// Send one NIP-47 request and wait for the wallet's response, returning its result
+async fn wallet_request(
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let connection = load_connection()?;
+    let keys = Keys::new(connection.secret.clone());
+
+    let payload = serde_json::json!({ "method": method, "params": params }).to_string();
+    let encrypted = nip04::encrypt(keys.secret_key(), &connection.wallet_pubkey, payload)
+        .map_err(|e| e.to_string())?;
+    let request = EventBuilder::new(Kind::from(NWC_REQUEST_KIND), encrypted)
+        .tag(Tag::public_key(connection.wallet_pubkey))
+        .sign_with_keys(&keys)
+        .map_err(|e| e.to_string())?;
+
+    let client = Client::new(keys.clone());
+    client
+        .add_relay(connection.relay.as_str())
+        .await
+        .map_err(|e| e.to_string())?;
+    client.connect().await;
+
+    // Subscribe before sending so a fast reply is not missed
+    let mut notifications = client.notifications();
+    let filter = crate::EventFilterSpec {
+        kinds: Some(vec![NWC_RESPONSE_KIND]),
+        authors: Some(vec![connection.wallet_pubkey.to_hex()]),
+        tags: HashMap::from([("e".to_string(), vec![request.id.to_hex()])]),
+        ..Default::default()
+    }
+    .to_filter()?;
+    let subscribed = client.subscribe(vec![filter], None).await;
+    let sent = client.send_event(request.clone()).await;
+
+    let response = match (subscribed, sent) {
+        (Ok(_), Ok(_)) => tokio::time::timeout(Duration::from_secs(WALLET_TIMEOUT_SECS), async {
+            loop {
+                match notifications.recv().await {
+                    Ok(RelayPoolNotification::Event { event, .. })
+                        if event.kind == Kind::from(NWC_RESPONSE_KIND)
+                            && event.pubkey == connection.wallet_pubkey =>
+                    {
+                        return Ok(*event)
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => {
+                        return Err("Wallet relay connection closed".to_string())
+                    }
+                }
+            }
+        })
+        .await
+        .unwrap_or_else(|_| Err("Wallet did not respond in time".to_string())),
+        (Err(e), _) | (_, Err(e)) => Err(format!("Failed to reach wallet relay: {}", e)),
+    };
+    let _ = client.disconnect().await;
+    let response = response?;
+
+    let decrypted = nip04::decrypt(
+        keys.secret_key(),
+        &connection.wallet_pubkey,
+        &response.content,
+    )
+    .map_err(|e| format!("Could not decrypt wallet response: {}", e))?;
+    let body: serde_json::Value =
+        serde_json::from_str(&decrypted).map_err(|e| format!("Invalid wallet response: {}", e))?;
+    if let Some(error) = body.get("error").filter(|e| !e.is_null()) {
+        return Err(format!(
+            "Wallet error {}: {}",
+            error["code"].as_str().unwrap_or("UNKNOWN"),
+            error["message"].as_str().unwrap_or("request failed")
+        ));
+    }
+    Ok(body["result"].clone())
+}
+
+/// Pair with a wallet from its connection URI, replacing any previous pairing.
+/// The wallet must answer a get_info request before the pairing is saved.
+#[tauri::command]
+pub async fn wallet_connect(uri: String) -> Result<WalletInfo, String> {
+    let connection = parse_connection_uri(&uri)?;
+
+    let stored = StoredWallet {
+        wallet_pubkey: connection.wallet_pubkey.to_hex(),
+        relay: connection.relay,
+        lud16: connection.lud16,
+        uri: uri.trim().to_string(),
+        connected_at: crate::get_current_timestamp()?,
+    };
+    let previous = load_stored_wallet()?;
+    crate::secure_settings::put(WALLET_SETTINGS, &stored)?;
+
+    if let Err(e) = wallet_request("get_info", serde_json::json!({})).await {
+        // Keep the old pairing rather than a wallet that does not answer
+        match previous {
+            Some(previous) => crate::secure_settings::put(WALLET_SETTINGS, &previous)?,
+            None => crate::secure_settings::remove(WALLET_SETTINGS)?,
+        }
+        return Err(e);
+    }
+
+    Ok(WalletInfo {
+        wallet_pubkey: stored.wallet_pubkey,
+        relay: stored.relay,
+        lud16: stored.lud16,
+        connected_at: stored.connected_at,
+    })
+}
+
+/// Get the paired wallet, if any
+#[tauri::command]
+pub fn wallet_status() -> Result<Option<WalletInfo>, String> {
+    Ok(load_stored_wallet()?.map(|stored| WalletInfo {
+        wallet_pubkey: stored.wallet_pubkey,
+        relay: stored.relay,
+        lud16: stored.lud16,
+        connected_at: stored.connected_at,
+    }))
+}
+
+/// Forget the paired wallet
+#[tauri::command]
+pub fn wallet_disconnect() -> Result<(), String> {
+    crate::secure_settings::remove(WALLET_SETTINGS)?;
+    let path = get_legacy_wallet_path()?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Pay a BOLT-11 invoice (amount only for zero-amount invoices). Refused while the
+/// session is locked, so an unattended app cannot spend from the wallet.
+#[tauri::command]
+pub async fn wallet_pay_invoice(
+    invoice: String,
+    amount_msats: Option<u64>,
+    state: State<'_, crate::NostrState>,
+) -> Result<WalletPayment, String> {
+    if crate::session_lock::is_locked(&state) {
+        return Err("The session is locked. Unlock your key to pay from the wallet.".to_string());
+    }
+
+    let mut params = serde_json::json!({ "invoice": invoice.trim() });
+    if let Some(amount) = amount_msats {
+        params["amount"] = amount.into();
+    }
+    let result = wallet_request("pay_invoice", params).await?;
+    Ok(WalletPayment {
+        preimage: result["preimage"]
+            .as_str()
+            .ok_or("Wallet did not return a preimage")?
+            .to_string(),
+        fees_paid_msats: result["fees_paid"].as_u64(),
+    })
+}
+
+/// Get the wallet balance
+#[tauri::command]
+pub async fn wallet_get_balance() -> Result<WalletBalance, String> {
+    let result = wallet_request("get_balance", serde_json::json!({})).await?;
+    Ok(WalletBalance {
+        balance_msats: result["balance"]
+            .as_u64()
+            .ok_or("Wallet did not return a balance")?,
+    })
+}
+
+/// List wallet transactions, newest first
+#[tauri::command]
+pub async fn wallet_list_transactions(
+    from: Option<u64>,
+    until: Option<u64>,
+    limit: Option<u64>,
+) -> Result<Vec<WalletTransaction>, String> {
+    let mut params = serde_json::json!({});
+    for (name, value) in [("from", from), ("until", until), ("limit", limit)] {
+        if let Some(value) = value {
+            params[name] = value.into();
+        }
+    }
+    let result = wallet_request("list_transactions", params).await?;
+    serde_json::from_value(result["transactions"].clone())
+        .map_err(|e| format!("Invalid transaction list: {}", e))
+}
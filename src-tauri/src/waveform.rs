@@ -0,0 +1,210 @@
+// Waveform peaks: decode a track once with ffmpeg and keep a downsampled array of
+// peak amplitudes (0.0-1.0) in the library next to the feed, so players and the
+// feed preview can draw waveforms without decoding audio in the webview.
+
+use crate::feed_xml::parse_rss;
+use crate::transcode::ffmpeg_path;
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use tokio::io::AsyncReadExt;
+
+// Protocols ffmpeg may open: local files and plain or TLS web streams
+const FFMPEG_PROTOCOLS: &str = "file,http,https,tcp,tls";
+
+// Decode rate and block size: one block peak per 10 ms of audio
+const DECODE_SAMPLE_RATE: u32 = 8000;
+const BLOCK_SAMPLES: usize = 80;
+
+// Number of peaks stored per track unless asked otherwise, and the most allowed
+const DEFAULT_POINTS: usize = 1000;
+const MAX_POINTS: usize = 10000;
+
+#[derive(Serialize, Deserialize)]
+pub struct TrackWaveform {
+    pub feed_id: String,
+    pub item_guid: String,
+    pub source_url: String,
+    pub duration_secs: f64,
+    pub peaks: Vec<f32>,
+    pub generated_at: u64,
+}
+
+/// Decode audio to mono and collect the peak of every block
+async fn block_peaks(source: &str) -> Result<Vec<f32>, String> {
+    let mut child = tokio::process::Command::new(ffmpeg_path())
+        .args(["-hide_banner", "-nostdin", "-loglevel", "error", "-protocol_whitelist", FFMPEG_PROTOCOLS])
+        .args(["-i", source, "-vn", "-ac", "1", "-ar"])
+        .arg(DECODE_SAMPLE_RATE.to_string())
+        .args(["-f", "f32le", "-"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to run ffmpeg (is it installed?): {}", e))?;
+
+    let mut stderr = child.stderr.take().ok_or("ffmpeg stderr unavailable")?;
+    let stderr_task = tokio::spawn(async move {
+        let mut text = String::new();
+        let _ = stderr.read_to_string(&mut text).await;
+        text
+    });
+
+    let mut stdout = child.stdout.take().ok_or("ffmpeg stdout unavailable")?;
+    let mut buf = vec![0u8; BLOCK_SAMPLES * 4 * 256];
+    let mut pending = Vec::new(); // bytes of a sample split across reads
+    let mut peaks = Vec::new();
+    let (mut block_peak, mut block_len) = (0f32, 0);
+    loop {
+        let read = stdout.read(&mut buf).await.map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        pending.extend_from_slice(&buf[..read]);
+        let whole = pending.len() / 4 * 4;
+        for sample in pending[..whole].chunks_exact(4) {
+            let value = f32::from_le_bytes([sample[0], sample[1], sample[2], sample[3]]).abs();
+            block_peak = block_peak.max(value);
+            block_len += 1;
+            if block_len == BLOCK_SAMPLES {
+                peaks.push(block_peak);
+                (block_peak, block_len) = (0.0, 0);
+            }
+        }
+        pending.drain(..whole);
+    }
+    if block_len > 0 {
+        peaks.push(block_peak);
+    }
+
+    let status = child.wait().await.map_err(|e| e.to_string())?;
+    let stderr = stderr_task.await.unwrap_or_default();
+    if !status.success() {
+        let detail = stderr.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or("unknown error");
+        return Err(format!("Failed to decode {}: {}", source, detail.trim()));
+    }
+    if peaks.is_empty() {
+        return Err(format!("{} has no audio", source));
+    }
+    Ok(peaks)
+}
+
+/// Reduce block peaks to `points` values, keeping the loudest block in each range
+fn downsample(blocks: &[f32], points: usize) -> Vec<f32> {
+    let points = points.min(blocks.len());
+    (0..points)
+        .map(|i| {
+            let range = &blocks[i * blocks.len() / points..(i + 1) * blocks.len() / points];
+            let peak = range.iter().fold(0f32, |max, v| max.max(*v)).min(1.0);
+            (peak * 1000.0).round() / 1000.0
+        })
+        .collect()
+}
+
+/// Where to decode a track from: the local file for file:// enclosures, or the
+/// http(s) URL. Anything else is refused rather than handed to ffmpeg.
+fn enclosure_source(url: &str) -> Result<String, String> {
+    if let Some(path) = crate::file_url_path(url) {
+        return Ok(path.to_string_lossy().to_string());
+    }
+    match reqwest::Url::parse(url.trim()) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(parsed.to_string()),
+        _ => Err(format!("Unsupported enclosure URL: {}", url)),
+    }
+}
+
+fn save_waveform(waveform: &TrackWaveform) -> Result<(), String> {
+    let conn = crate::open_library()?;
+    let peaks = serde_json::to_string(&waveform.peaks).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO track_waveforms
+         (feed_id, item_guid, source_url, duration_secs, peaks, generated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![
+            waveform.feed_id,
+            waveform.item_guid,
+            waveform.source_url,
+            waveform.duration_secs,
+            peaks,
+            waveform.generated_at
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Generate and store waveform peaks for one track of a library feed, or for every
+/// track when no item guid is given. `points` is the number of peaks per track.
+#[tauri::command]
+pub async fn waveform_generate(
+    feed_id: String,
+    item_guid: Option<String>,
+    points: Option<usize>,
+) -> Result<Vec<TrackWaveform>, String> {
+    let points = points.unwrap_or(DEFAULT_POINTS);
+    if points == 0 || points > MAX_POINTS {
+        return Err(format!("Points must be between 1 and {}", MAX_POINTS));
+    }
+
+    let feed = crate::load_feed_local(feed_id)?;
+    let tracks: Vec<(String, String)> = parse_rss(&feed.xml)?
+        .items()
+        .iter()
+        .filter_map(|item| {
+            let guid = item.child_text("guid")?;
+            let url = item.child("enclosure")?.attr("url")?;
+            Some((guid.to_string(), url.to_string()))
+        })
+        .filter(|(guid, _)| item_guid.as_ref().map(|g| g == guid).unwrap_or(true))
+        .collect();
+    if tracks.is_empty() {
+        return Err(match item_guid {
+            Some(guid) => format!("Track not found or has no enclosure: {}", guid),
+            None => "Feed has no tracks with enclosures".to_string(),
+        });
+    }
+
+    let mut waveforms = Vec::new();
+    for (guid, url) in tracks {
+        let blocks = block_peaks(&enclosure_source(&url)?).await?;
+        let waveform = TrackWaveform {
+            feed_id: feed.id.clone(),
+            item_guid: guid,
+            duration_secs: (blocks.len() * BLOCK_SAMPLES) as f64 / f64::from(DECODE_SAMPLE_RATE),
+            peaks: downsample(&blocks, points),
+            source_url: url,
+            generated_at: crate::get_current_timestamp()?,
+        };
+        save_waveform(&waveform)?;
+        waveforms.push(waveform);
+    }
+    Ok(waveforms)
+}
+
+/// Get the stored waveforms of a feed's tracks, optionally for one track only
+#[tauri::command]
+pub fn waveform_get(feed_id: String, item_guid: Option<String>) -> Result<Vec<TrackWaveform>, String> {
+    let conn = crate::open_library()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT feed_id, item_guid, source_url, duration_secs, peaks, generated_at FROM track_waveforms
+             WHERE feed_id = ?1 AND (?2 IS NULL OR item_guid = ?2)",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![feed_id, item_guid], |row| {
+            let peaks: String = row.get(4)?;
+            Ok(TrackWaveform {
+                feed_id: row.get(0)?,
+                item_guid: row.get(1)?,
+                source_url: row.get(2)?,
+                duration_secs: row.get(3)?,
+                peaks: serde_json::from_str(&peaks).unwrap_or_default(),
+                generated_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
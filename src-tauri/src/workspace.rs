@@ -0,0 +1,217 @@
+// Managed scratch space for intermediate files (zip extractions, downloads, transcodes).
+// Each task gets its own directory that is removed when the task finishes; anything
+// left behind by a crash is swept on the next start.
+
+use crate::feed_xml::{parse_xml, XmlNode};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+// Marker holding the pid of the process that owns a workspace
+const OWNER_MARKER: &str = ".owner";
+
+// Every library table that stores feed XML; imports referenced from any of them are kept
+const FEED_XML_TABLES: &[&str] = &["feeds", "trashed_feeds", "feed_versions"];
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct WorkspaceCleanReport {
+    pub removed_entries: usize,
+    pub reclaimed_bytes: u64,
+}
+
+/// Get the root directory that holds task workspaces
+fn get_workspace_root() -> Result<PathBuf, String> {
+    let proj_dirs = ProjectDirs::from("com", "podtards", "msp-studio")
+        .ok_or("Could not determine app data directory")?;
+
+    let root = proj_dirs.data_dir().join("workspace");
+    fs::create_dir_all(&root).map_err(|e| e.to_string())?;
+
+    Ok(root)
+}
+
+/// A per-task scratch directory, deleted on drop
+pub struct TaskWorkspace {
+    path: PathBuf,
+}
+
+impl TaskWorkspace {
+    /// Create a fresh workspace for one task ("import", "download", ...)
+    pub fn create(task: &str) -> Result<Self, String> {
+        let path = get_workspace_root()?.join(format!("{}-{}", task, Uuid::new_v4()));
+        fs::create_dir_all(&path).map_err(|e| e.to_string())?;
+        fs::write(path.join(OWNER_MARKER), std::process::id().to_string()).map_err(|e| e.to_string())?;
+        Ok(TaskWorkspace { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Move a finished result out of the workspace. The workspace lives on the
+    /// app data volume, so this is a rename rather than a copy.
+    pub fn persist(&self, staged: &Path, dest: &Path) -> Result<(), String> {
+        if !staged.starts_with(&self.path) {
+            return Err(format!("{} is not inside this workspace", staged.display()));
+        }
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::rename(staged, dest).map_err(|e| format!("Failed to move {}: {}", staged.display(), e))
+    }
+}
+
+impl Drop for TaskWorkspace {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Total size of a file or directory tree
+fn entry_size(path: &Path) -> u64 {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|e| entry_size(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+/// Remove a file or directory, counting it in the report
+fn remove_entry(path: &Path, report: &mut WorkspaceCleanReport) {
+    let size = entry_size(path);
+    let removed = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    if removed.is_ok() {
+        report.removed_entries += 1;
+        report.reclaimed_bytes += size;
+    }
+}
+
+/// Remove workspaces not owned by this process (left behind by a crash or kill)
+pub fn clean_stale_workspaces(report: &mut WorkspaceCleanReport) -> Result<(), String> {
+    let current = std::process::id().to_string();
+    for entry in fs::read_dir(get_workspace_root()?).map_err(|e| e.to_string())?.flatten() {
+        let path = entry.path();
+        let owner = fs::read_to_string(path.join(OWNER_MARKER)).unwrap_or_default();
+        if owner.trim() != current {
+            remove_entry(&path, report);
+        }
+    }
+    Ok(())
+}
+
+/// Remove extracted imports that no saved or trashed feed references any more
+fn clean_orphaned_imports(report: &mut WorkspaceCleanReport) -> Result<(), String> {
+    let proj_dirs = ProjectDirs::from("com", "podtards", "msp-studio")
+        .ok_or("Could not determine app data directory")?;
+    let imports_dir = proj_dirs.data_dir().join("imports");
+    if !imports_dir.exists() {
+        return Ok(());
+    }
+
+    // Sealed feeds can't be searched while the library is locked, so nothing is provably orphaned
+    if crate::library_crypto::is_locked()? {
+        return Ok(());
+    }
+
+    let conn = crate::open_library()?;
+    let mut references = Vec::new();
+    for table in FEED_XML_TABLES {
+        let mut stmt = conn
+            .prepare(&format!("SELECT xml FROM {}", table))
+            .map_err(|e| e.to_string())?;
+        let stored = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        for xml in stored {
+            // A feed that can't be read might use any import, so none is removed
+            let Ok(root) = parse_xml(&crate::library_crypto::open(xml)?) else {
+                return Ok(());
+            };
+            collect_local_references(&root, &mut references);
+        }
+    }
+    for entry in fs::read_dir(&imports_dir).map_err(|e| e.to_string())?.flatten() {
+        let path = entry.path();
+        if !is_referenced(&path, &references) {
+            remove_entry(&path, report);
+        }
+    }
+    Ok(())
+}
+
+/// Collect the local paths of file:// URLs in a feed's attributes and text
+fn collect_local_references(node: &XmlNode, references: &mut Vec<PathBuf>) {
+    let values = node.attrs.iter().map(|(_, value)| value.as_str()).chain([node.text.as_str()]);
+    references.extend(values.filter_map(crate::file_url_path));
+    for child in &node.children {
+        collect_local_references(child, references);
+    }
+}
+
+/// Whether any referenced file is `path` itself or lies inside it
+fn is_referenced(path: &Path, references: &[PathBuf]) -> bool {
+    references.iter().any(|reference| reference.starts_with(path))
+}
+
+/// Delete leftover intermediate files (stale task workspaces and imports no feed
+/// uses) and report how much space was reclaimed
+#[tauri::command]
+pub fn workspace_clean() -> Result<WorkspaceCleanReport, String> {
+    let mut report = WorkspaceCleanReport::default();
+    clean_stale_workspaces(&mut report)?;
+    clean_orphaned_imports(&mut report)?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn references_in(xml: &str) -> Vec<PathBuf> {
+        let mut references = Vec::new();
+        collect_local_references(&parse_xml(xml).unwrap(), &mut references);
+        references
+    }
+
+    #[test]
+    fn imports_in_folders_with_spaces_are_referenced() {
+        let import = std::env::temp_dir().join("Application Support").join("imports").join("abc");
+        let track = import.join("01 First Track.mp3");
+        let xml = format!(
+            "<rss><channel><item><enclosure url=\"{}\" type=\"audio/mpeg\"/></item></channel></rss>",
+            crate::file_url(&track)
+        );
+        assert!(crate::file_url(&track).contains("%20"));
+
+        let references = references_in(&xml);
+        assert!(is_referenced(&import, &references));
+        assert!(!is_referenced(&import.with_file_name("other"), &references));
+    }
+
+    #[test]
+    fn file_urls_in_text_are_referenced() {
+        let import = std::env::temp_dir().join("imports").join("with space");
+        let cover = import.join("cover.jpg");
+        let xml = format!("<rss><channel><image><url>{}</url></image></channel></rss>", crate::file_url(&cover));
+
+        assert!(is_referenced(&import, &references_in(&xml)));
+    }
+
+    #[test]
+    fn remote_urls_are_not_local_references() {
+        let xml = "<rss><channel><item><enclosure url=\"https://example.com/a.mp3\"/></item></channel></rss>";
+        assert!(references_in(xml).is_empty());
+    }
+}
@@ -0,0 +1,422 @@
+// Zaps (NIP-57): resolve an artist's LNURL-pay endpoint, create zap requests that
+// return a payable invoice, and total the zap receipts their releases received.
+
+use bech32::primitives::decode::CheckedHrpstring;
+use bech32::Bech32;
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
+use tauri::State;
+
+const ZAP_REQUEST_KIND: u16 = 9734;
+const ZAP_RECEIPT_KIND: u16 = 9735;
+
+#[derive(Serialize, Deserialize)]
+pub struct LnurlPayInfo {
+    pub pay_url: String,
+    pub callback: String,
+    pub min_sendable_msats: u64,
+    pub max_sendable_msats: u64,
+    pub allows_nostr: bool,
+    pub nostr_pubkey: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ZapInvoice {
+    pub invoice: String,
+    pub zap_request: crate::SignedEvent,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ZapReceipt {
+    pub id: String,
+    pub sender: Option<String>,
+    pub amount_msats: u64,
+    pub target_event_id: Option<String>,
+    pub target_coordinate: Option<String>,
+    pub comment: String,
+    pub created_at: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ZapTotal {
+    pub target: String, // event id or coordinate, "profile" for zaps to the artist
+    pub count: usize,
+    pub total_msats: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ZapSummary {
+    pub total_msats: u64,
+    pub receipts: Vec<ZapReceipt>,
+    pub per_target: Vec<ZapTotal>,
+}
+
+/// Decode a bech32 "lnurl1..." string to the URL it wraps
+fn decode_lnurl(lnurl: &str) -> Result<String, String> {
+    let lower = lnurl.trim().to_lowercase();
+    let lower = lower.strip_prefix("lightning:").unwrap_or(&lower);
+    let (hrp, bytes) = bech32::decode(lower).map_err(|e| format!("Invalid LNURL: {}", e))?;
+    if hrp.as_str() != "lnurl" {
+        return Err("Invalid LNURL: expected an lnurl1... string".to_string());
+    }
+    String::from_utf8(bytes).map_err(|_| "Invalid LNURL".to_string())
+}
+
+/// LNURL-pay endpoint for a lightning address (lud16), bech32 LNURL, or https URL
+fn lnurl_pay_url(address: &str) -> Result<String, String> {
+    let address = address.trim();
+    if let Some((name, domain)) = address.split_once('@') {
+        return Ok(format!("https://{}/.well-known/lnurlp/{}", domain, name));
+    }
+    if address.starts_with("https://") {
+        return Ok(address.to_string());
+    }
+    decode_lnurl(address)
+}
+
+/// Fetch the LNURL-pay parameters for an address
+async fn fetch_pay_info(address: &str) -> Result<LnurlPayInfo, String> {
+    let pay_url = lnurl_pay_url(address)?;
+    let doc: serde_json::Value = reqwest::get(&pay_url)
+        .await
+        .map_err(|e| format!("LNURL lookup failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid LNURL response: {}", e))?;
+
+    if doc["status"].as_str() == Some("ERROR") {
+        return Err(format!("LNURL error: {}", doc["reason"].as_str().unwrap_or("unknown")));
+    }
+    let callback = doc["callback"]
+        .as_str()
+        .ok_or("LNURL response has no callback")?
+        .to_string();
+
+    // metadata is a JSON-encoded list of [mime, content] pairs
+    let description = doc["metadata"]
+        .as_str()
+        .and_then(|m| serde_json::from_str::<Vec<Vec<String>>>(m).ok())
+        .and_then(|entries| {
+            entries
+                .into_iter()
+                .find(|e| e.first().map(String::as_str) == Some("text/plain"))
+                .and_then(|e| e.get(1).cloned())
+        });
+
+    Ok(LnurlPayInfo {
+        pay_url,
+        callback,
+        min_sendable_msats: doc["minSendable"].as_u64().unwrap_or(1000),
+        max_sendable_msats: doc["maxSendable"].as_u64().unwrap_or(u64::MAX),
+        allows_nostr: doc["allowsNostr"].as_bool().unwrap_or(false),
+        nostr_pubkey: doc["nostrPubkey"].as_str().map(str::to_string),
+        description,
+    })
+}
+
+/// Amount encoded in a BOLT-11 invoice's human-readable part, in millisats. The
+/// invoice's checksum must be valid.
+fn bolt11_amount_msats(invoice: &str) -> Option<u64> {
+    let invoice = invoice.trim().to_lowercase();
+    let checked = CheckedHrpstring::new::<Bech32>(&invoice).ok()?;
+    let hrp = checked.hrp().to_lowercase();
+    let amount = hrp.strip_prefix("ln")?.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+    let digits_end = amount.find(|c: char| !c.is_ascii_digit()).unwrap_or(amount.len());
+    let value: u64 = amount[..digits_end].parse().ok()?;
+
+    // 1 BTC = 100_000_000_000 msats
+    match &amount[digits_end..] {
+        "" => value.checked_mul(100_000_000_000),
+        "m" => value.checked_mul(100_000_000),
+        "u" => value.checked_mul(100_000),
+        "n" => value.checked_mul(100),
+        "p" => Some(value / 10),
+        _ => None,
+    }
+}
+
+/// First value of a tag on an event
+fn tag_value(event: &Event, name: &str) -> Option<String> {
+    event.tags.iter().find_map(|t| {
+        let parts = t.as_slice();
+        if parts.len() >= 2 && parts[0] == name {
+            Some(parts[1].clone())
+        } else {
+            None
+        }
+    })
+}
+
+/// Read a kind 9735 receipt, or None when it is not a valid NIP-57 receipt: it must
+/// be signed by the recipient's LNURL server (`signers`), embed a signed zap request
+/// for the same recipient, and carry an invoice whose amount is the one requested
+fn parse_receipt(event: &Event, signers: &HashMap<PublicKey, PublicKey>) -> Option<ZapReceipt> {
+    let recipient = tag_value(event, "p").and_then(|p| PublicKey::from_hex(&p).ok())?;
+    if signers.get(&recipient) != Some(&event.pubkey) {
+        return None;
+    }
+
+    let request = tag_value(event, "description").and_then(|d| Event::from_json(d).ok())?;
+    if request.kind != Kind::from(ZAP_REQUEST_KIND)
+        || request.verify().is_err()
+        || tag_value(&request, "p").as_deref() != Some(recipient.to_hex().as_str())
+    {
+        return None;
+    }
+
+    // The invoice is what was paid; a request asking for a different amount is forged
+    let amount_msats = tag_value(event, "bolt11").and_then(|b| bolt11_amount_msats(&b))?;
+    if let Some(requested) = tag_value(&request, "amount") {
+        if requested.parse::<u64>().ok() != Some(amount_msats) {
+            return None;
+        }
+    }
+
+    Some(ZapReceipt {
+        id: event.id.to_hex(),
+        sender: Some(request.pubkey.to_hex()),
+        amount_msats,
+        target_event_id: tag_value(event, "e"),
+        target_coordinate: tag_value(event, "a"),
+        comment: request.content.clone(),
+        created_at: event.created_at.as_u64(),
+    })
+}
+
+/// The pubkey each recipient's LNURL server signs zap receipts with, looked up from
+/// the lightning address in their profile. Recipients without one are left out.
+async fn receipt_signers(client: &Client, recipients: Vec<PublicKey>) -> HashMap<PublicKey, PublicKey> {
+    let mut signers = HashMap::new();
+    if recipients.is_empty() {
+        return signers;
+    }
+    let filter = Filter::new().authors(recipients).kind(Kind::Metadata);
+    let Ok(profiles) = client.fetch_events(vec![filter], Some(Duration::from_secs(10))).await else {
+        return signers;
+    };
+
+    let mut newest: HashMap<PublicKey, &Event> = HashMap::new();
+    for profile in profiles.iter() {
+        let entry = newest.entry(profile.pubkey).or_insert(profile);
+        if profile.created_at > entry.created_at {
+            *entry = profile;
+        }
+    }
+    for (recipient, profile) in newest {
+        let metadata: serde_json::Value = serde_json::from_str(&profile.content).unwrap_or_default();
+        let Some(address) = metadata["lud16"]
+            .as_str()
+            .or_else(|| metadata["lud06"].as_str())
+            .filter(|a| !a.is_empty())
+        else {
+            continue;
+        };
+        let signer = fetch_pay_info(address)
+            .await
+            .ok()
+            .and_then(|info| info.nostr_pubkey)
+            .and_then(|pk| PublicKey::parse(&pk).ok());
+        if let Some(signer) = signer {
+            signers.insert(recipient, signer);
+        }
+    }
+    signers
+}
+
+/// Look up the LNURL-pay endpoint for a lightning address or LNURL and whether it
+/// accepts zaps
+#[tauri::command]
+pub async fn zap_resolve_lnurl(address: String) -> Result<LnurlPayInfo, String> {
+    fetch_pay_info(&address).await
+}
+
+/// Create and sign a zap request (kind 9734) for a pubkey, optionally targeting an
+/// event or addressable coordinate, and return the invoice to pay for it
+#[tauri::command]
+pub async fn zap_create_request(
+    address: String,
+    recipient_pubkey: String,
+    amount_msats: u64,
+    event_id: Option<String>,
+    coordinate: Option<String>,
+    comment: Option<String>,
+    state: State<'_, crate::NostrState>,
+) -> Result<ZapInvoice, String> {
+    let keys = state.signing_keys()?;
+    let info = fetch_pay_info(&address).await?;
+    if !info.allows_nostr {
+        return Err("This lightning address does not support zaps".to_string());
+    }
+    if amount_msats < info.min_sendable_msats || amount_msats > info.max_sendable_msats {
+        return Err(format!(
+            "Amount must be between {} and {} sats",
+            info.min_sendable_msats / 1000,
+            info.max_sendable_msats / 1000
+        ));
+    }
+
+    let recipient = PublicKey::parse(recipient_pubkey.trim()).map_err(|e| e.to_string())?;
+    let mut relays = vec!["relays".to_string()];
+    relays.extend(crate::write_relay_urls(&state));
+
+    let mut builder = EventBuilder::new(Kind::from(ZAP_REQUEST_KIND), comment.unwrap_or_default())
+        .tag(Tag::parse(relays).map_err(|e| e.to_string())?)
+        .tag(Tag::parse(["amount".to_string(), amount_msats.to_string()]).map_err(|e| e.to_string())?)
+        .tag(Tag::public_key(recipient));
+    if address.trim().to_lowercase().starts_with("lnurl") {
+        builder = builder.tag(Tag::parse(["lnurl", address.trim()]).map_err(|e| e.to_string())?);
+    }
+    if let Some(event_id) = event_id {
+        builder = builder.tag(Tag::event(EventId::parse(event_id.trim()).map_err(|e| e.to_string())?));
+    }
+    if let Some(coordinate) = coordinate {
+        builder = builder.tag(Tag::parse(["a", coordinate.trim()]).map_err(|e| e.to_string())?);
+    }
+    let zap_request = builder.sign_with_keys(&keys).map_err(|e| e.to_string())?;
+
+    let response: serde_json::Value = reqwest::Client::new()
+        .get(&info.callback)
+        .query(&[("amount", amount_msats.to_string()), ("nostr", zap_request.as_json())])
+        .send()
+        .await
+        .map_err(|e| format!("Zap callback failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid zap callback response: {}", e))?;
+    let invoice = response["pr"].as_str().ok_or_else(|| {
+        let reason = response["reason"].as_str().unwrap_or("no invoice returned");
+        format!("Zap callback failed: {}", reason)
+    })?;
+    // NIP-57: the invoice must be for exactly the requested amount
+    match bolt11_amount_msats(invoice) {
+        Some(invoiced) if invoiced == amount_msats => {}
+        Some(invoiced) => {
+            return Err(format!(
+                "Zap invoice is for {} msats, not the {} msats requested",
+                invoiced, amount_msats
+            ))
+        }
+        None => return Err("Zap callback returned an invoice without a valid amount".to_string()),
+    }
+
+    Ok(ZapInvoice {
+        invoice: invoice.to_string(),
+        zap_request: crate::event_to_signed_event(&zap_request),
+    })
+}
+
+/// Fetch zap receipts for the given events/coordinates, or for every zap to the
+/// logged-in artist when none are given, with totals per zapped release. Receipts
+/// that fail NIP-57 validation are not counted.
+#[tauri::command]
+pub async fn zap_fetch_receipts(
+    event_ids: Option<Vec<String>>,
+    coordinates: Option<Vec<String>>,
+    since: Option<u64>,
+    state: State<'_, crate::NostrState>,
+) -> Result<ZapSummary, String> {
+    let client = state.client.lock().unwrap().clone().ok_or("Client not initialized")?;
+    let pubkey = state.active_pubkey();
+
+    // Separate filters, since tag conditions within one filter must all match
+    let mut targets = vec![
+        ("e", event_ids.unwrap_or_default()),
+        ("a", coordinates.unwrap_or_default()),
+    ];
+    targets.retain(|(_, values)| !values.is_empty());
+    if targets.is_empty() {
+        targets.push(("p", vec![pubkey.ok_or("Not logged in")?]));
+    }
+    let filters = targets
+        .into_iter()
+        .map(|(tag, values)| {
+            crate::EventFilterSpec {
+                kinds: Some(vec![ZAP_RECEIPT_KIND]),
+                tags: HashMap::from([(tag.to_string(), values)]),
+                since,
+                ..Default::default()
+            }
+            .to_filter()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let events = client
+        .fetch_events(filters, Some(Duration::from_secs(10)))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let recipients: HashSet<PublicKey> = events
+        .iter()
+        .filter_map(|e| tag_value(e, "p").and_then(|p| PublicKey::from_hex(&p).ok()))
+        .collect();
+    let signers = receipt_signers(&client, recipients.into_iter().collect()).await;
+    let mut receipts: Vec<ZapReceipt> = events.iter().filter_map(|e| parse_receipt(e, &signers)).collect();
+    receipts.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+    let mut totals: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+    for receipt in &receipts {
+        let target = receipt
+            .target_coordinate
+            .clone()
+            .or_else(|| receipt.target_event_id.clone())
+            .unwrap_or_else(|| "profile".to_string());
+        let entry = totals.entry(target).or_default();
+        entry.0 += 1;
+        entry.1 += receipt.amount_msats;
+    }
+    let mut per_target: Vec<ZapTotal> = totals
+        .into_iter()
+        .map(|(target, (count, total_msats))| ZapTotal {
+            target,
+            count,
+            total_msats,
+        })
+        .collect();
+    per_target.sort_by(|a, b| b.total_msats.cmp(&a.total_msats));
+
+    Ok(ZapSummary {
+        total_msats: receipts.iter().map(|r| r.amount_msats).sum(),
+        receipts,
+        per_target,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invoice(hrp: &str) -> String {
+        bech32::encode::<Bech32>(bech32::Hrp::parse(hrp).unwrap(), &[0u8; 32]).unwrap()
+    }
+
+    #[test]
+    fn amounts_follow_the_multiplier() {
+        assert_eq!(bolt11_amount_msats(&invoice("lnbc2500u")), Some(250_000_000));
+        assert_eq!(bolt11_amount_msats(&invoice("lnbc20m")), Some(2_000_000_000));
+        assert_eq!(bolt11_amount_msats(&invoice("lnbc210n")), Some(21_000));
+        assert_eq!(bolt11_amount_msats(&invoice("lnbc10p")), Some(1));
+        assert_eq!(bolt11_amount_msats(&invoice("lnbc1")), Some(100_000_000_000));
+        assert_eq!(bolt11_amount_msats(&invoice("lntb1500n")), Some(150_000));
+    }
+
+    #[test]
+    fn invoices_are_read_case_insensitively() {
+        assert_eq!(bolt11_amount_msats(&invoice("lnbc2500u").to_uppercase()), Some(250_000_000));
+    }
+
+    #[test]
+    fn invoices_without_an_amount_are_rejected() {
+        assert_eq!(bolt11_amount_msats(&invoice("lnbc")), None);
+        assert_eq!(bolt11_amount_msats(&invoice("lnbc25x")), None);
+    }
+
+    #[test]
+    fn invoices_with_a_bad_checksum_are_rejected() {
+        let mut tampered = invoice("lnbc2500u");
+        tampered.replace_range(4..8, "9000");
+        assert_eq!(bolt11_amount_msats(&tampered), None);
+    }
+}
@@ -18,6 +18,7 @@ import { InfoModal } from './components/modals/InfoModal';
 import { NostrConnectModal } from './components/modals/NostrConnectModal';
 import { ConfirmModal } from './components/modals/ConfirmModal';
 import { UpdateModal } from './components/modals/UpdateModal';
+import { ShutdownModal } from './components/modals/ShutdownModal';
 import { KeyStorageModal } from './components/modals/KeyStorageModal';
 import { Editor } from './components/Editor/Editor';
 import { checkForUpdate, isTauri, getAppVersion } from './utils/updater';
@@ -516,6 +517,9 @@ function AppContent() {
         />
       )}
 
+      {/* Deferred close waiting on in-flight work (desktop only) */}
+      <ShutdownModal />
+
       {/* Auto-unlock modal for stored keys (desktop only) */}
       <KeyStorageModal
         isOpen={showUnlockModal}
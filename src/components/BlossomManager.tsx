@@ -10,6 +10,7 @@ import {
   blossomUpload,
   blossomList,
   blossomDelete,
+  blossomGetServerList,
   DEFAULT_BLOSSOM_SERVERS,
   checkBlossomServer,
   type BlossomBlob,
@@ -25,6 +26,7 @@ interface BlossomManagerProps {
 }
 
 export function BlossomManager({ feedXml, feedTitle, onUploadComplete }: BlossomManagerProps) {
+  const [servers, setServers] = useState(DEFAULT_BLOSSOM_SERVERS);
   const [serverUrl, setServerUrl] = useState(DEFAULT_BLOSSOM_SERVERS[0]);
   const [customServer, setCustomServer] = useState('');
   const [useCustom, setUseCustom] = useState(false);
@@ -37,6 +39,18 @@ export function BlossomManager({ feedXml, feedTitle, onUploadComplete }: Blossom
 
   const activeServer = useCustom ? customServer : serverUrl;
 
+  // Offer the user's own server list (kind 10063) when they have one
+  useEffect(() => {
+    if (!isTauri()) return;
+    blossomGetServerList()
+      .then((list) => {
+        if (list.length === 0) return;
+        setServers(list);
+        setServerUrl(list[0]);
+      })
+      .catch(() => {});
+  }, []);
+
   // Check server status when it changes
   useEffect(() => {
     if (!activeServer) {
@@ -123,7 +137,7 @@ export function BlossomManager({ feedXml, feedTitle, onUploadComplete }: Blossom
             checked={!useCustom}
             onChange={() => setUseCustom(false)}
           />
-          Popular servers
+          {servers === DEFAULT_BLOSSOM_SERVERS ? 'Popular servers' : 'Your servers'}
         </label>
         
         {!useCustom && (
@@ -131,7 +145,7 @@ export function BlossomManager({ feedXml, feedTitle, onUploadComplete }: Blossom
             value={serverUrl}
             onChange={(e) => setServerUrl(e.target.value)}
           >
-            {DEFAULT_BLOSSOM_SERVERS.map((server: string) => (
+            {servers.map((server: string) => (
               <option key={server} value={server}>{server}</option>
             ))}
           </select>
@@ -25,6 +25,7 @@ import { saveFeedLocal, hasLocalStorage } from '../../utils/localFeedStorage';
 import { useNostr } from '../../store/nostrStore';
 import { ModalWrapper } from './ModalWrapper';
 import { apiFetch, isTauri } from '../../utils/api';
+import { blossomGetServerList } from '../../utils/tauriBlossom';
 
 const DEFAULT_BLOSSOM_SERVER = 'https://blossom.primal.net/';
 
@@ -88,6 +89,16 @@ export function SaveModal({ onClose, album, publisherFeed, feedType = 'album', i
   const [message, setMessage] = useState<{ type: 'success' | 'error'; text: string } | null>(null);
   const [progress, setProgress] = useState<PublishProgress | null>(null);
   const [blossomServer, setBlossomServer] = useState(DEFAULT_BLOSSOM_SERVER);
+
+  // Default to the user's preferred Blossom server (kind 10063) when they have one
+  useEffect(() => {
+    if (!isTauri()) return;
+    blossomGetServerList()
+      .then((list) => {
+        if (list.length > 0) setBlossomServer(list[0]);
+      })
+      .catch(() => {});
+  }, []);
   const [feedUrl, setFeedUrl] = useState<string | null>(null);
   const [stableUrl, setStableUrl] = useState<string | null>(null);
   const [hostedInfo, setHostedInfo] = useState<HostedFeedInfo | null>(null);
@@ -0,0 +1,80 @@
+import { useEffect, useState } from 'react';
+import { ModalWrapper } from './ModalWrapper';
+import {
+  onShutdownPending,
+  getShutdownStatus,
+  cancelShutdown,
+  forceQuit,
+  type ShutdownStatus,
+} from '../../utils/shutdown';
+
+const STATUS_POLL_MS = 1000;
+
+// Shown while a desktop close waits on uploads, publishes, or writes still in flight
+export function ShutdownModal() {
+  const [status, setStatus] = useState<ShutdownStatus | null>(null);
+  const [now, setNow] = useState(() => Math.floor(Date.now() / 1000));
+
+  useEffect(() => onShutdownPending(setStatus), []);
+
+  // Track operations finishing while the modal is open; the app exits on its own
+  // once the last one is done
+  const isOpen = !!status?.closing && status.operations.length > 0;
+  useEffect(() => {
+    if (!isOpen) return;
+    const timer = setInterval(async () => {
+      setNow(Math.floor(Date.now() / 1000));
+      try {
+        setStatus(await getShutdownStatus());
+      } catch (e) {
+        console.error('[shutdown] Failed to get status:', e);
+      }
+    }, STATUS_POLL_MS);
+    return () => clearInterval(timer);
+  }, [isOpen]);
+
+  const handleKeepOpen = async () => {
+    await cancelShutdown();
+    setStatus(null);
+  };
+
+  if (!isOpen || !status) return null;
+
+  const secondsLeft = status.deadline ? Math.max(0, status.deadline - now) : null;
+
+  return (
+    <ModalWrapper
+      isOpen={isOpen}
+      onClose={handleKeepOpen}
+      title="Finishing up before closing"
+      className="confirm-modal"
+      footer={
+        <div className="confirm-modal-footer">
+          <button className="btn btn-warning" onClick={() => forceQuit()}>
+            Quit Now
+          </button>
+          <div style={{ flex: 1 }} />
+          <button className="btn btn-secondary" onClick={handleKeepOpen}>
+            Keep Open
+          </button>
+        </div>
+      }
+    >
+      <div className="confirm-modal-content confirm-modal-warning">
+        <div className="confirm-modal-icon">⏳</div>
+        <p>
+          The app will close when these finish
+          {secondsLeft !== null && ` (or in ${secondsLeft}s)`}. Quitting now
+          interrupts them; publishes resume from their last step next time.
+        </p>
+        <ul>
+          {status.operations.map((op, i) => (
+            <li key={`${op.kind}-${op.started_at}-${i}`}>
+              {op.kind}: {op.label}
+            </li>
+          ))}
+        </ul>
+      </div>
+    </ModalWrapper>
+  );
+}
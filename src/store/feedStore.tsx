@@ -5,6 +5,7 @@ import type { Album, Track, Person, PersonRole, ValueRecipient, Funding, Publish
 import { createEmptyAlbum, createEmptyTrack, createEmptyPerson, createEmptyPersonRole, createEmptyRecipient, createEmptyFunding, createEmptyPublisherFeed, createEmptyRemoteItem, createEmptyVideoAlbum, createSupportRecipients, isCommunitySupport, hasUserRecipients } from '../types/feed';
 import { albumStorage, videoStorage, publisherStorage, feedTypeStorage } from '../utils/storage';
 import { saveToDesktop, loadFromDesktop, DESKTOP_KEYS } from '../utils/desktopStorage';
+import { onShutdownPending, acknowledgeShutdownFlush } from '../utils/shutdown';
 import { isTauri } from '../utils/api';
 import { hydrateHostedCredentials } from '../utils/hostedFeed';
 import { hydrateNostrUser } from '../utils/nostr';
@@ -776,6 +777,31 @@ export function FeedProvider({ children }: { children: ReactNode }) {
     saveToDesktop(DESKTOP_KEYS.FEED_TYPE, state.feedType);
   }, [state.feedType, hydrated]);
 
+  // On a deferred desktop close, write the latest feeds to disk right away instead
+  // of waiting for the debounce, then let the backend finish closing.
+  const hydratedRef = useRef(hydrated);
+  useEffect(() => {
+    hydratedRef.current = hydrated;
+  }, [hydrated]);
+
+  useEffect(() => {
+    return onShutdownPending(async () => {
+      const current = latestStateRef.current;
+      albumStorage.save(current.album);
+      if (current.videoFeed) videoStorage.save(current.videoFeed);
+      if (current.publisherFeed) publisherStorage.save(current.publisherFeed);
+      if (hydratedRef.current) {
+        await Promise.all([
+          saveToDesktop(DESKTOP_KEYS.ALBUM_DATA, current.album),
+          current.videoFeed && saveToDesktop(DESKTOP_KEYS.VIDEO_DATA, current.videoFeed),
+          current.publisherFeed && saveToDesktop(DESKTOP_KEYS.PUBLISHER_DATA, current.publisherFeed),
+          saveToDesktop(DESKTOP_KEYS.FEED_TYPE, current.feedType),
+        ]);
+      }
+      await acknowledgeShutdownFlush();
+    });
+  }, []);
+
   return (
     <FeedContext.Provider value={{ state, dispatch }}>
       {children}
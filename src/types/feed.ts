@@ -145,6 +145,8 @@ export interface Track {
   podcastImages?: PodcastImage[];
   transcriptUrl?: string;
   transcriptType?: string;
+  transcriptLanguage?: string;
+  transcriptRel?: string;
   overridePersons: boolean;
   persons: Person[];
   overrideValue: boolean;
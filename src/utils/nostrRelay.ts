@@ -1,5 +1,14 @@
 // Nostr relay connection and publishing utilities
+import { invoke } from '@tauri-apps/api/core';
 import type { NostrEvent } from '../types/nostr';
+import { isTauri } from './api';
+import { hasSigner, signEventWithTimeout } from './nostrSigner';
+
+// Kind 22242 for NIP-42 relay authentication
+const AUTH_KIND = 22242;
+
+// How long to wait for an AUTH challenge after a relay says auth-required
+const AUTH_CHALLENGE_TIMEOUT = 3000;
 
 // Default relays to use
 export const DEFAULT_RELAYS = [
@@ -125,6 +134,108 @@ export function collectEvents(
   });
 }
 
+/**
+ * Track the latest NIP-42 AUTH challenge a relay sends on this connection
+ */
+function trackAuthChallenge(ws: WebSocket): (timeout: number) => Promise<string | null> {
+  let challenge: string | null = null;
+  ws.addEventListener('message', (event: MessageEvent) => {
+    try {
+      const data = JSON.parse(event.data);
+      if (data[0] === 'AUTH' && typeof data[1] === 'string') {
+        challenge = data[1];
+      }
+    } catch {
+      // Ignore parse errors
+    }
+  });
+
+  return async (timeout: number) => {
+    const deadline = Date.now() + timeout;
+    while (challenge === null && Date.now() < deadline) {
+      await new Promise(resolve => setTimeout(resolve, 100));
+    }
+    return challenge;
+  };
+}
+
+/**
+ * Send an EVENT or AUTH message and wait for the relay's OK for that event
+ */
+function sendAndWaitForOk(
+  ws: WebSocket,
+  type: 'EVENT' | 'AUTH',
+  event: NostrEvent,
+  timeout = 10000
+): Promise<{ accepted: boolean; message: string }> {
+  return new Promise((resolve, reject) => {
+    const timer = setTimeout(() => {
+      ws.removeEventListener('message', handler);
+      reject(new Error('Request timed out'));
+    }, timeout);
+
+    const handler = (msg: MessageEvent) => {
+      try {
+        const data = JSON.parse(msg.data);
+        if (data[0] === 'OK' && data[1] === event.id) {
+          clearTimeout(timer);
+          ws.removeEventListener('message', handler);
+          resolve({ accepted: data[2] === true, message: String(data[3] ?? '') });
+        }
+      } catch {
+        // Ignore parse errors, wait for valid response
+      }
+    };
+
+    ws.addEventListener('message', handler);
+    ws.send(JSON.stringify([type, event]));
+  });
+}
+
+/**
+ * Answer a NIP-42 AUTH challenge with a signed kind 22242 event
+ */
+async function authenticate(ws: WebSocket, relayUrl: string, challenge: string): Promise<void> {
+  const authEvent = await signEventWithTimeout({
+    kind: AUTH_KIND,
+    created_at: Math.floor(Date.now() / 1000),
+    tags: [
+      ['relay', relayUrl],
+      ['challenge', challenge],
+    ],
+    content: '',
+  });
+  const { accepted, message } = await sendAndWaitForOk(ws, 'AUTH', authEvent as NostrEvent);
+  if (!accepted) {
+    throw new Error(`Relay authentication failed: ${message}`);
+  }
+}
+
+/**
+ * Publish to one open relay connection. A relay that answers "auth-required" gets
+ * a NIP-42 AUTH response (when a signer is available) and the event is sent again.
+ */
+async function publishToRelay(ws: WebSocket, relayUrl: string, signedEvent: NostrEvent): Promise<RelayPublishResult> {
+  const waitForChallenge = trackAuthChallenge(ws);
+  let ok = await sendAndWaitForOk(ws, 'EVENT', signedEvent);
+
+  if (!ok.accepted && ok.message.startsWith('auth-required:') && hasSigner()) {
+    const challenge = await waitForChallenge(AUTH_CHALLENGE_TIMEOUT);
+    if (!challenge) {
+      throw new Error(`Relay requires authentication but sent no challenge: ${ok.message}`);
+    }
+    await authenticate(ws, relayUrl, challenge);
+    ok = await sendAndWaitForOk(ws, 'EVENT', signedEvent);
+  }
+
+  return {
+    relay: relayUrl,
+    success: ok.accepted,
+    response: ['OK', signedEvent.id, ok.accepted, ok.message],
+    error: ok.accepted ? undefined : ok.message || 'Event rejected',
+  };
+}
+
 /**
  * Result of publishing to a single relay
  */
@@ -135,20 +246,49 @@ export interface RelayPublishResult {
   error?: string;
 }
 
+// Entry in the logged-in user's relay list (kind 10002), as held by the backend
+interface RelayListEntry {
+  url: string;
+  read: boolean;
+  write: boolean;
+}
+
+/**
+ * Add the logged-in user's write relays to a relay set, deduplicated
+ * Falls back to the given relays in web mode or when the list is unavailable
+ */
+async function withWriteRelays(relays: string[]): Promise<string[]> {
+  if (!isTauri()) return relays;
+  let writeRelays: string[] = [];
+  try {
+    const list = await invoke<RelayListEntry[]>('nostr_get_relay_list');
+    writeRelays = list.filter(entry => entry.write).map(entry => entry.url);
+  } catch {
+    // Not logged in through the backend; publish to the given relays only
+  }
+  const seen = new Set<string>();
+  return [...relays, ...writeRelays].filter(url => {
+    const key = url.replace(/\/+$/, '');
+    if (seen.has(key)) return false;
+    seen.add(key);
+    return true;
+  });
+}
+
 /**
- * Publish an event to multiple relays
- * Returns results from all relay attempts
+ * Publish an event to multiple relays and the user's write relays, answering NIP-42 AUTH challenges
+ * Returns results from all relay attempts; a relay succeeds only when it accepts the event
  */
 export async function publishEventToRelays(
   signedEvent: NostrEvent,
-  relays = DEFAULT_RELAYS
+  targetRelays = DEFAULT_RELAYS
 ): Promise<{ successCount: number; results: RelayPublishResult[] }> {
+  const relays = await withWriteRelays(targetRelays);
   const results = await Promise.allSettled(
     relays.map(async (relayUrl) => {
       const ws = await connectRelay(relayUrl);
       try {
-        const response = await sendAndWait(ws, ['EVENT', signedEvent]);
-        return { relay: relayUrl, success: true, response } as RelayPublishResult;
+        return await publishToRelay(ws, relayUrl, signedEvent);
       } finally {
         ws.close();
       }
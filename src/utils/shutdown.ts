@@ -0,0 +1,68 @@
+/**
+ * Shutdown - Deferred close handshake with the Tauri backend
+ *
+ * Closing the window emits `app://shutdown-pending`. The feed store flushes its
+ * pending autosaves and acknowledges, and the app shows what is still in flight
+ * with options to quit now or keep the app open. No-ops in web mode.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { isTauri } from './api';
+
+export interface InFlightOperation {
+  kind: string;
+  label: string;
+  started_at: number;
+}
+
+export interface ShutdownStatus {
+  closing: boolean;
+  operations: InFlightOperation[];
+  deadline: number | null; // unix seconds; the app quits then regardless
+}
+
+/**
+ * Subscribe to deferred close requests
+ * Returns an unsubscribe function
+ */
+export function onShutdownPending(handler: (status: ShutdownStatus) => void): () => void {
+  if (!isTauri()) return () => {};
+
+  const unlisten = listen<ShutdownStatus>('app://shutdown-pending', event => handler(event.payload));
+  return () => {
+    unlisten.then(fn => fn());
+  };
+}
+
+/**
+ * Get the in-flight operations and whether a close is waiting on them
+ */
+export async function getShutdownStatus(): Promise<ShutdownStatus | null> {
+  if (!isTauri()) return null;
+  return invoke<ShutdownStatus>('app_shutdown_status');
+}
+
+/**
+ * Tell the backend pending edits are saved so the close can go ahead
+ */
+export async function acknowledgeShutdownFlush(): Promise<void> {
+  if (!isTauri()) return;
+  await invoke('app_shutdown_flushed');
+}
+
+/**
+ * Keep the app open; in-flight operations continue normally
+ */
+export async function cancelShutdown(): Promise<void> {
+  if (!isTauri()) return;
+  await invoke('app_cancel_shutdown');
+}
+
+/**
+ * Quit without waiting for in-flight operations
+ */
+export async function forceQuit(): Promise<void> {
+  if (!isTauri()) return;
+  await invoke('app_force_quit');
+}
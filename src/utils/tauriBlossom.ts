@@ -35,6 +35,14 @@ export const DEFAULT_BLOSSOM_SERVERS = [
   'https://blossom.band',
 ];
 
+/**
+ * Get the logged-in user's preferred Blossom servers (kind 10063), first preferred.
+ * Empty when they have not published a list.
+ */
+export async function blossomGetServerList(): Promise<string[]> {
+  return await invoke<string[]>('blossom_get_server_list');
+}
+
 /**
  * Upload content (like XML feeds) to a Blossom server.
  * Requires Nostr login for authentication.